@@ -259,10 +259,11 @@ app.delete('/api/events', async (req, res) => {
 // ── Tickets ──────────────────────────────────────────────────────────
 
 app.post('/api/tickets/buy', async (req, res) => {
-  const { eventId, eventPubkey, wallet, tier, quantity } = req.body ?? {};
+  const { eventId, eventPubkey, wallet, tier, tierIndex, quantity } = req.body ?? {};
   if (!wallet) return res.status(400).json({ error: 'Missing wallet' });
 
   const qty = Math.min(Math.max(1, parseInt(quantity, 10) || 1), 20);
+  const tierIdx = parseInt(tierIndex, 10) || 0;
 
   // Resolve eventPubkey from various sources
   let eventPk = eventPubkey ?? null;
@@ -291,10 +292,10 @@ app.post('/api/tickets/buy', async (req, res) => {
   if (eventPk) {
     try {
       if (qty === 1) {
-        const transaction = await buildBuyTicketTransaction(eventPk, wallet);
+        const transaction = await buildBuyTicketTransaction(eventPk, wallet, tierIdx);
         return res.json({ transaction, message: 'Sign and submit this transaction in your wallet' });
       }
-      const { transaction, ticketMints } = await buildBuyTicketsTransaction(eventPk, wallet, qty);
+      const { transaction, ticketMints } = await buildBuyTicketsTransaction(eventPk, wallet, qty, tierIdx);
       return res.json({ transaction, ticketMints, message: `Sign to buy ${qty} tickets` });
     } catch (e) {
       console.error('buy_ticket build failed', e);
@@ -586,12 +587,12 @@ app.post('/api/listings/confirm', async (req, res) => {
 });
 
 app.post('/api/listings/buy', async (req, res) => {
-  const { buyerWallet, ticketMint } = req.body ?? {};
+  const { buyerWallet, ticketMint, maxLamports } = req.body ?? {};
   if (!buyerWallet || !ticketMint) {
     return res.status(400).json({ error: 'Missing required fields: buyerWallet, ticketMint' });
   }
   try {
-    const transaction = await buildBuyResaleTransaction(buyerWallet, ticketMint);
+    const transaction = await buildBuyResaleTransaction(buyerWallet, ticketMint, maxLamports);
     res.json({ transaction, message: 'Sign and submit to buy the resale ticket' });
     triggerSync();
   } catch (e) {
@@ -19,12 +19,17 @@ const TOKEN_PROGRAM_ID = new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ
 const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey('ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL');
 const SYSTEM_PROGRAM_ID = new PublicKey('11111111111111111111111111111111');
 
-// Platform wallet receives 20% of resale proceeds
-const PLATFORM_WALLET = new PublicKey(process.env.PLATFORM_WALLET ?? 'GFxY452qfw5nwA4N9KQ28zZTmJL9CD1eenydHY9kEE32');
+// Platform wallet receives the platform share of resale proceeds (the program's PLATFORM_WALLET)
+const PLATFORM_WALLET = new PublicKey('GFxY452qfw5nwA4N9KQ28zZTmJL9CD1eenydHY9kEE32');
+
+// Singleton PlatformConfig PDA; create_event and update_event read the price ceiling from it,
+// buy_resale counts the platform share on it
+const [PLATFORM_CONFIG] = PublicKey.findProgramAddressSync([Buffer.from('platform_config')], PROGRAM_ID);
 
 // Account discriminators (first 8 bytes of sha256("account:<Name>"))
 const EVENT_DISCRIMINATOR = Buffer.from([125, 192, 125, 158, 9, 115, 152, 233]);
 const LISTING_DISCRIMINATOR = Buffer.from([218, 32, 50, 73, 43, 134, 26, 58]);
+const RECEIPT_DISCRIMINATOR = Buffer.from([39, 154, 73, 106, 80, 102, 145, 153]);
 
 function getConnection() {
   const rpc = process.env.SOLANA_RPC_URL ?? 'https://api.devnet.solana.com';
@@ -84,9 +89,47 @@ function findPda(seeds, programId) {
 }
 
 /**
- * Build unsigned buy_ticket transaction. Returns base64 serialized tx.
+ * Accounts for buy_ticket of ticket `index`. The vault is passed once it exists
+ * (events with refund windows or vesting escrow their proceeds); the buyer record
+ * is always passed, and only checked for events with a per-wallet limit.
  */
-export async function buildBuyTicketTransaction(eventPubkey, buyerPubkey) {
+async function buyTicketAccounts(connection, eventPk, eventData, buyerPk, index) {
+  const indexBuf = Buffer.alloc(4);
+  indexBuf.writeUInt32LE(index, 0);
+
+  const ticketAuthority = findPda([Buffer.from('ticket_authority'), eventPk.toBuffer(), indexBuf], PROGRAM_ID);
+  const ticketMint = findPda([Buffer.from('ticket_mint'), eventPk.toBuffer(), indexBuf], PROGRAM_ID);
+  const vault = findPda([Buffer.from('vault'), eventPk.toBuffer()], PROGRAM_ID);
+  const vaultExists = (await connection.getAccountInfo(vault)) != null;
+
+  return {
+    ticketMint,
+    accounts: {
+      buyer: buyerPk,
+      organizer: eventData.organizer,
+      event: eventPk,
+      ticketAuthority,
+      ticketMint,
+      buyerTokenAccount: getAssociatedTokenAddressSync(ticketMint, buyerPk),
+      vault: vaultExists ? vault : null,
+      receipt: findPda([Buffer.from('receipt'), buyerPk.toBuffer(), ticketMint.toBuffer()], PROGRAM_ID),
+      idempotencyKey: findPda([Buffer.from('idem'), buyerPk.toBuffer(), eventPk.toBuffer(), indexBuf], PROGRAM_ID),
+      rateLimit: findPda([Buffer.from('rate_limit'), buyerPk.toBuffer()], PROGRAM_ID),
+      buyerRecord: findPda([Buffer.from('buyer'), eventPk.toBuffer(), buyerPk.toBuffer()], PROGRAM_ID),
+      organizerBan: findPda([Buffer.from('org_ban'), eventData.organizer.toBuffer(), buyerPk.toBuffer()], PROGRAM_ID),
+      nameOverride: findPda([Buffer.from('name_override'), eventPk.toBuffer(), indexBuf], PROGRAM_ID),
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SYSTEM_PROGRAM_ID,
+    },
+  };
+}
+
+/**
+ * Build unsigned buy_ticket transaction for a ticket in tier `tierIndex`.
+ * Returns base64 serialized tx.
+ */
+export async function buildBuyTicketTransaction(eventPubkey, buyerPubkey, tierIndex = 0) {
   const connection = getConnection();
   const eventPk = new PublicKey(eventPubkey);
   const buyerPk = new PublicKey(buyerPubkey);
@@ -95,34 +138,12 @@ export async function buildBuyTicketTransaction(eventPubkey, buyerPubkey) {
   if (!eventData) throw new Error('Event account not found');
   if (eventData.sold >= eventData.supply) throw new Error('Event is sold out');
 
-  const soldBuf = Buffer.alloc(4);
-  soldBuf.writeUInt32LE(eventData.sold, 0);
-
-  const ticketAuthority = findPda(
-    [Buffer.from('ticket_authority'), eventPk.toBuffer(), soldBuf],
-    PROGRAM_ID
-  );
-  const ticketMint = findPda(
-    [Buffer.from('ticket_mint'), eventPk.toBuffer(), soldBuf],
-    PROGRAM_ID
-  );
-
-  const buyerAta = getAssociatedTokenAddressSync(ticketMint, buyerPk);
+  const { accounts } = await buyTicketAccounts(connection, eventPk, eventData, buyerPk, eventData.sold);
 
   const program = getProgram(connection);
   const tx = await program.methods
-    .buyTicket()
-    .accounts({
-      buyer: buyerPk,
-      organizer: eventData.organizer,
-      event: eventPk,
-      ticketAuthority,
-      ticketMint,
-      buyerTokenAccount: buyerAta,
-      tokenProgram: TOKEN_PROGRAM_ID,
-      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-      systemProgram: SYSTEM_PROGRAM_ID,
-    })
+    .buyTicket(eventData.sold, tierIndex, null, new BN(0), false, null)
+    .accounts(accounts)
     .transaction();
 
   tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
@@ -136,7 +157,7 @@ export async function buildBuyTicketTransaction(eventPubkey, buyerPubkey) {
  * Build unsigned transaction to buy multiple tickets (N × buy_ticket in one tx).
  * Returns { transaction: base64, ticketMints: string[] }.
  */
-export async function buildBuyTicketsTransaction(eventPubkey, buyerPubkey, quantity) {
+export async function buildBuyTicketsTransaction(eventPubkey, buyerPubkey, quantity, tierIndex = 0) {
   const connection = getConnection();
   const eventPk = new PublicKey(eventPubkey);
   const buyerPk = new PublicKey(buyerPubkey);
@@ -158,34 +179,12 @@ export async function buildBuyTicketsTransaction(eventPubkey, buyerPubkey, quant
 
   for (let i = 0; i < quantity; i++) {
     const soldIndex = eventData.sold + i;
-    const soldBuf = Buffer.alloc(4);
-    soldBuf.writeUInt32LE(soldIndex, 0);
-
-    const ticketAuthority = findPda(
-      [Buffer.from('ticket_authority'), eventPk.toBuffer(), soldBuf],
-      PROGRAM_ID
-    );
-    const ticketMint = findPda(
-      [Buffer.from('ticket_mint'), eventPk.toBuffer(), soldBuf],
-      PROGRAM_ID
-    );
+    const { ticketMint, accounts } = await buyTicketAccounts(connection, eventPk, eventData, buyerPk, soldIndex);
     ticketMints.push(ticketMint.toBase58());
 
-    const buyerAta = getAssociatedTokenAddressSync(ticketMint, buyerPk);
-
     const ix = await program.methods
-      .buyTicket()
-      .accounts({
-        buyer: buyerPk,
-        organizer: eventData.organizer,
-        event: eventPk,
-        ticketAuthority,
-        ticketMint,
-        buyerTokenAccount: buyerAta,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-        systemProgram: SYSTEM_PROGRAM_ID,
-      })
+      .buyTicket(soldIndex, tierIndex, null, new BN(0), false, null)
+      .accounts(accounts)
       .instruction();
 
     tx.add(ix);
@@ -216,20 +215,16 @@ export async function buildCreateEventTransaction(organizerPubkey, args) {
 
   const artistPct = args.artistPct != null ? args.artistPct : 40;
 
+  // A single tier; no sale window, no per-wallet limit, no Token Metadata
+  const tiers = [{ name: args.tierName, priceLamports: new BN(args.priceLamports), supply: args.supply }];
+
   const tx = await program.methods
-    .createEvent(
-      nonce,
-      args.title,
-      args.venue,
-      new BN(args.dateTs),
-      args.tierName,
-      new BN(args.priceLamports),
-      args.supply,
-      artistPct
-    )
+    .createEvent(nonce, args.title, args.venue, new BN(args.dateTs), tiers, artistPct, null, null, 0, '')
     .accounts({
       organizer: organizerPk,
       event: eventPda,
+      platformConfig: PLATFORM_CONFIG,
+      splitTemplate: null,
       systemProgram: SYSTEM_PROGRAM_ID,
     })
     .transaction();
@@ -253,12 +248,16 @@ export async function buildCloseEventTransaction(organizerPubkey, eventPubkey) {
   const program = getProgram(connection);
   const organizerPk = new PublicKey(organizerPubkey);
   const eventPk = new PublicKey(eventPubkey);
+  // The vault, if the event has one, is closed with it
+  const vault = findPda([Buffer.from('vault'), eventPk.toBuffer()], PROGRAM_ID);
+  const vaultExists = (await connection.getAccountInfo(vault)) != null;
 
   const tx = await program.methods
     .closeEvent()
     .accounts({
       organizer: organizerPk,
       event: eventPk,
+      vault: vaultExists ? vault : null,
       systemProgram: SYSTEM_PROGRAM_ID,
     })
     .transaction();
@@ -291,16 +290,21 @@ export async function buildListForResaleTransaction(sellerPubkey, eventPubkey, t
     PROGRAM_ID
   );
   const sellerAta = getAssociatedTokenAddressSync(ticketMintPk, sellerPk);
+  // Names the ticket's tier, which multi-tier events price resale caps and floors by
+  const purchaseReceipt = await findTicketReceipt(connection, ticketMintPk);
+  const ticketIndex = await findTicketIndex(connection, eventPk, ticketMintPk);
 
   const tx = await program.methods
-    .listForResale(new BN(priceLamports))
+    .listForResale(ticketIndex, new BN(priceLamports))
     .accounts({
       seller: sellerPk,
       event: eventPk,
       ticketMint: ticketMintPk,
+      ticketFlag: findPda([Buffer.from('ticket_flag'), ticketMintPk.toBuffer()], PROGRAM_ID),
       listing: listingPda,
       sellerTokenAccount: sellerAta,
       escrowTokenAccount: escrowPda,
+      purchaseReceipt,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SYSTEM_PROGRAM_ID,
@@ -346,16 +350,20 @@ export async function buildListForResaleTransactions(sellerPubkey, items) {
       PROGRAM_ID
     );
     const sellerAta = getAssociatedTokenAddressSync(ticketMintPk, sellerPk);
+    const purchaseReceipt = await findTicketReceipt(connection, ticketMintPk);
+    const ticketIndex = await findTicketIndex(connection, eventPk, ticketMintPk);
 
     const ix = await program.methods
-      .listForResale(new BN(priceLamports))
+      .listForResale(ticketIndex, new BN(priceLamports))
       .accounts({
         seller: sellerPk,
         event: eventPk,
         ticketMint: ticketMintPk,
+        ticketFlag: findPda([Buffer.from('ticket_flag'), ticketMintPk.toBuffer()], PROGRAM_ID),
         listing: listingPda,
         sellerTokenAccount: sellerAta,
         escrowTokenAccount: escrowPda,
+        purchaseReceipt,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SYSTEM_PROGRAM_ID,
@@ -371,9 +379,12 @@ export async function buildListForResaleTransactions(sellerPubkey, items) {
 
 /**
  * Build buy_resale transaction. Buyer purchases a listed resale ticket.
- * SOL is split 40/40/20 (organizer / seller / platform) on-chain.
+ * SOL is split on-chain by the event's artist_bps / seller_bps / platform_bps
+ * (organizer / seller / platform).
+ * maxLamports caps what the buyer pays; it defaults to the listed price, and is
+ * required for USD listings, whose lamport price is only known at purchase time.
  */
-export async function buildBuyResaleTransaction(buyerPubkey, ticketMintPubkey) {
+export async function buildBuyResaleTransaction(buyerPubkey, ticketMintPubkey, maxLamports) {
   const connection = getConnection();
   const program = getProgram(connection);
   const buyerPk = new PublicKey(buyerPubkey);
@@ -395,25 +406,39 @@ export async function buildBuyResaleTransaction(buyerPubkey, ticketMintPubkey) {
   const listingData = listingInfo.data;
   const seller = new PublicKey(listingData.slice(8, 40));
   const eventPk = new PublicKey(listingData.slice(40, 72));
+  const priceLamports = listingData.readBigUInt64LE(104);
+  const usdPriceCents = listingData.readBigUInt64LE(130);
+  const priceFeed = usdPriceCents > 0n ? new PublicKey(listingData.slice(138, 170)) : null;
+  if (maxLamports == null && priceFeed) {
+    throw new Error('maxLamports is required for USD-priced listings');
+  }
 
   // Fetch event to get organizer
   const eventData = await fetchEvent(connection, eventPk);
   if (!eventData) throw new Error('Event account not found for this listing');
 
   const buyerAta = getAssociatedTokenAddressSync(ticketMintPk, buyerPk);
+  // Settles the ticket's attendance deposit, if the event takes one
+  const depositReceipt = await findTicketReceipt(connection, ticketMintPk);
 
   const tx = await program.methods
-    .buyResale()
+    .buyResale(new BN((maxLamports ?? priceLamports).toString()))
     .accounts({
       buyer: buyerPk,
       seller,
       organizer: eventData.organizer,
       platform: PLATFORM_WALLET,
+      platformConfig: PLATFORM_CONFIG,
       event: eventPk,
       ticketMint: ticketMintPk,
+      ticketFlag: findPda([Buffer.from('ticket_flag'), ticketMintPk.toBuffer()], PROGRAM_ID),
       listing: listingPda,
       escrowTokenAccount: escrowPda,
       buyerTokenAccount: buyerAta,
+      priceFeed,
+      depositReceipt,
+      ticketStats: findPda([Buffer.from('ticket_stats'), eventPk.toBuffer()], PROGRAM_ID),
+      floorMonitor: findPda([Buffer.from('floor_monitor'), eventPk.toBuffer()], PROGRAM_ID),
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SYSTEM_PROGRAM_ID,
@@ -561,7 +586,14 @@ export async function fetchAllListings() {
     const event = new PublicKey(data.slice(40, 72));
     const ticketMint = new PublicKey(data.slice(72, 104));
     const priceLamports = data.readBigUInt64LE(104);
-    const bump = data[112];
+    const anomalyFlag = data[112] === 1;
+    const createdAt = Number(data.readBigInt64LE(113));
+    const lastPriceUpdateAt = Number(data.readBigInt64LE(121));
+    const visible = data[129] === 1;
+    const usdPriceCents = Number(data.readBigUInt64LE(130)); // 0 = priced in lamports
+    const priceFeed = new PublicKey(data.slice(138, 170));
+    const tierIndex = data[170];
+    const bump = data[171];
     return {
       pubkey: pubkey.toBase58(),
       seller: seller.toBase58(),
@@ -569,7 +601,74 @@ export async function fetchAllListings() {
       ticketMint: ticketMint.toBase58(),
       priceLamports: Number(priceLamports),
       priceSol: Number(priceLamports) / 1e9,
+      anomalyFlag,
+      createdAt,
+      lastPriceUpdateAt,
+      visible,
+      usdPriceCents,
+      priceFeed: usdPriceCents > 0 ? priceFeed.toBase58() : null,
+      tierIndex,
       bump,
     };
+  }).filter((listing) => listing.visible); // organizers can hide listings from the market
+}
+
+/**
+ * Index of a ticket mint within its event, which list_for_resale takes to tie the
+ * mint to the event. Ticket mints are PDAs over (event, index) for indices below
+ * `sold`.
+ */
+async function findTicketIndex(connection, eventPk, ticketMintPk) {
+  const eventData = await fetchEvent(connection, eventPk);
+  if (!eventData) throw new Error('Event not found');
+  for (let index = 0; index < eventData.sold; index++) {
+    const indexBuf = Buffer.alloc(4);
+    indexBuf.writeUInt32LE(index, 0);
+    const mint = findPda([Buffer.from('ticket_mint'), eventPk.toBuffer(), indexBuf], PROGRAM_ID);
+    if (mint.equals(ticketMintPk)) return index;
+  }
+  throw new Error('Ticket does not belong to this event');
+}
+
+/**
+ * Find a ticket's primary purchase Receipt, whoever bought it. Returns null for
+ * tickets minted without one.
+ */
+async function findTicketReceipt(connection, ticketMintPk) {
+  const accounts = await connection.getProgramAccounts(PROGRAM_ID, {
+    dataSlice: { offset: 0, length: 0 },
+    filters: [
+      { memcmp: { offset: 0, bytes: RECEIPT_DISCRIMINATOR.toString('base64'), encoding: 'base64' } },
+      // Receipt layout: discriminator (8) | buyer (32) | event (32) | ticket_mint (32) | ...
+      { memcmp: { offset: 72, bytes: ticketMintPk.toBase58() } },
+    ],
+  });
+  return accounts.length > 0 ? accounts[0].pubkey : null;
+}
+
+/**
+ * Fetch all Receipt PDAs for an event and build the remaining_accounts list for
+ * audit_royalties. Returns batches of `batchSize` read-only account metas, in the
+ * ascending address order the program requires across the whole audit.
+ */
+export async function getAllReceiptsForEvent(eventPubkey, batchSize = 20) {
+  const connection = getConnection();
+  const eventPk = new PublicKey(eventPubkey);
+  const accounts = await connection.getProgramAccounts(PROGRAM_ID, {
+    filters: [
+      { memcmp: { offset: 0, bytes: RECEIPT_DISCRIMINATOR.toString('base64'), encoding: 'base64' } },
+      // Receipt layout: discriminator (8) | buyer (32) | event (32) | ...
+      { memcmp: { offset: 40, bytes: eventPk.toBase58() } },
+    ],
   });
+
+  const metas = accounts
+    .map(({ pubkey }) => pubkey)
+    .sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()))
+    .map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }));
+  const batches = [];
+  for (let i = 0; i < metas.length; i += batchSize) {
+    batches.push(metas.slice(i, i + batchSize));
+  }
+  return batches;
 }
@@ -0,0 +1,287 @@
+//! Example TicketChain consumer: a PDA treasury that buys tickets via CPI.
+//!
+//! Shows the account wiring and signer seeds another program needs to compose with
+//! TicketChain through its `cpi` feature. Supports events without tax, donation,
+//! vault, concession, fan token, per-wallet limit or ticket metadata configuration,
+//! and lamport-priced resale listings. Every spend is signed by the authority named
+//! in the TreasuryConfig.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::TokenInterface;
+use ticketchain::program::Ticketchain;
+
+declare_id!("CwARJqdfCUmEuKu7Y6w3JHaRkba71CRZUf1wbj7X441j");
+
+#[program]
+pub mod ticketchain_treasury {
+    use super::*;
+
+    /// Create the TreasuryConfig naming who may spend treasury funds. Upgrade
+    /// authority only.
+    pub fn init_treasury(ctx: Context<InitTreasury>, authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = authority;
+        config.bump = ctx.bumps.config;
+        Ok(())
+    }
+
+    /// Buy the next ticket of an event with the treasury PDA as buyer. The treasury is
+    /// a system-owned PDA funded by plain transfers; it signs the CPI with its seeds.
+    pub fn buy_for_treasury(ctx: Context<BuyForTreasury>, ticket_index: u32, tier_index: u8) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[ctx.bumps.treasury]]];
+        let accounts = ticketchain::cpi::accounts::BuyTicket {
+            buyer: ctx.accounts.treasury.to_account_info(),
+            organizer: ctx.accounts.organizer.to_account_info(),
+            event: ctx.accounts.event.to_account_info(),
+            ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+            ticket_mint: ctx.accounts.ticket_mint.to_account_info(),
+            buyer_token_account: ctx.accounts.treasury_token_account.to_account_info(),
+            tax_wallet: None,
+            donation_wallet: None,
+            vault: None,
+            instructions: None,
+            platform_config: None,
+            fan_token_mint: None,
+            fan_token_authority: None,
+            buyer_fan_token_account: None,
+            payment_mint: None,
+            buyer_payment_account: None,
+            payment_destination: None,
+            receipt: ctx.accounts.receipt.to_account_info(),
+            idempotency_key: ctx.accounts.idempotency_key.to_account_info(),
+            ticket_metadata: None,
+            ticket_edition: None,
+            ticket_token_record: None,
+            token_metadata_program: None,
+            rate_limit: ctx.accounts.rate_limit.to_account_info(),
+            deposit_escrow: None,
+            personhood_credential: None,
+            person_purchase: None,
+            buyer_record: None,
+            identity_purchase: None,
+            organizer_ban: ctx.accounts.organizer_ban.to_account_info(),
+            name_override: ctx.accounts.name_override.to_account_info(),
+            accessible_approval: None,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        ticketchain::cpi::buy_ticket(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticketchain_program.to_account_info(),
+                accounts,
+                signer_seeds,
+            ),
+            ticket_index,
+            tier_index,
+            None,
+            0,
+            false,
+            None,
+        )
+    }
+
+    /// Buy a resale listing with the treasury PDA as buyer, paying at most
+    /// `max_lamports`.
+    pub fn buy_resale_for_treasury(ctx: Context<BuyResaleForTreasury>, max_lamports: u64) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[ctx.bumps.treasury]]];
+        let accounts = ticketchain::cpi::accounts::BuyResale {
+            buyer: ctx.accounts.treasury.to_account_info(),
+            seller: ctx.accounts.seller.to_account_info(),
+            organizer: ctx.accounts.organizer.to_account_info(),
+            platform: ctx.accounts.platform.to_account_info(),
+            platform_config: ctx.accounts.platform_config.to_account_info(),
+            fee_day: None,
+            event: ctx.accounts.event.to_account_info(),
+            royalty_escrow: None,
+            ticket_mint: ctx.accounts.ticket_mint.to_account_info(),
+            ticket_flag: ctx.accounts.ticket_flag.to_account_info(),
+            listing: ctx.accounts.listing.to_account_info(),
+            price_feed: None,
+            ask_book: None,
+            deposit_receipt: ctx.accounts.deposit_receipt.as_ref().map(|r| r.to_account_info()),
+            escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+            buyer_token_account: ctx.accounts.treasury_token_account.to_account_info(),
+            ticket_stats: ctx.accounts.ticket_stats.to_account_info(),
+            floor_monitor: ctx.accounts.floor_monitor.to_account_info(),
+            ticket_metadata: None,
+            ticket_edition: None,
+            seller_token_account: None,
+            seller_token_record: None,
+            escrow_token_record: None,
+            buyer_token_record: None,
+            instructions: None,
+            token_metadata_program: None,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        ticketchain::cpi::buy_resale(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticketchain_program.to_account_info(),
+                accounts,
+                signer_seeds,
+            ),
+            max_lamports,
+        )
+    }
+}
+
+#[account]
+pub struct TreasuryConfig {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 1,
+        seeds = [b"treasury_config"],
+        bump,
+    )]
+    pub config: Account<'info, TreasuryConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::TicketchainTreasury>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyForTreasury<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"treasury_config"], bump = config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, TreasuryConfig>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    /// CHECK: Validated by TicketChain against event.organizer.
+    #[account(mut)]
+    pub organizer: AccountInfo<'info>,
+
+    /// CHECK: TicketChain Event account; validated by TicketChain.
+    #[account(mut)]
+    pub event: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["ticket_authority", event, ticket_index]` PDA.
+    pub ticket_authority: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["ticket_mint", event, ticket_index]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub ticket_mint: AccountInfo<'info>,
+
+    /// CHECK: Treasury's associated token account for the ticket mint; created by TicketChain.
+    #[account(mut)]
+    pub treasury_token_account: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["receipt", treasury, ticket_mint]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub receipt: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["idem", treasury, event, ticket_index]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub idempotency_key: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["rate_limit", treasury]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub rate_limit: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["org_ban", organizer, treasury]` PDA; must not exist.
+    pub organizer_ban: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["name_override", event, ticket_index]` PDA; may not exist.
+    #[account(mut)]
+    pub name_override: AccountInfo<'info>,
+
+    pub ticketchain_program: Program<'info, Ticketchain>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyResaleForTreasury<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"treasury_config"], bump = config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, TreasuryConfig>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    /// CHECK: Validated by TicketChain against listing.seller.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+
+    /// CHECK: Validated by TicketChain against event.organizer.
+    #[account(mut)]
+    pub organizer: AccountInfo<'info>,
+
+    /// CHECK: TicketChain platform wallet; validated by TicketChain.
+    #[account(mut)]
+    pub platform: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["platform_config"]` PDA; counts the platform share.
+    #[account(mut)]
+    pub platform_config: AccountInfo<'info>,
+
+    /// CHECK: TicketChain Event account; validated by TicketChain.
+    #[account(mut)]
+    pub event: AccountInfo<'info>,
+
+    /// CHECK: Ticket mint named by the listing; validated by TicketChain.
+    pub ticket_mint: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["ticket_flag", ticket_mint]` PDA; must not exist.
+    pub ticket_flag: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["listing", ticket_mint]` PDA; closed by TicketChain.
+    #[account(mut)]
+    pub listing: AccountInfo<'info>,
+
+    /// CHECK: The ticket's TicketChain Receipt; validated by TicketChain. Required when
+    /// the event takes attendance deposits.
+    pub deposit_receipt: Option<AccountInfo<'info>>,
+
+    /// CHECK: TicketChain `["escrow", ticket_mint]` token account; closed by TicketChain.
+    #[account(mut)]
+    pub escrow_token_account: AccountInfo<'info>,
+
+    /// CHECK: Treasury's associated token account for the ticket mint; created by TicketChain.
+    #[account(mut)]
+    pub treasury_token_account: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["ticket_stats", event]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub ticket_stats: AccountInfo<'info>,
+
+    /// CHECK: TicketChain `["floor_monitor", event]` PDA; created by TicketChain.
+    #[account(mut)]
+    pub floor_monitor: AccountInfo<'info>,
+
+    pub ticketchain_program: Program<'info, Ticketchain>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Signer is not the treasury authority")]
+    Unauthorized,
+}
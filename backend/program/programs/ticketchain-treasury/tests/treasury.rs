@@ -0,0 +1,189 @@
+#[path = "../../ticketchain/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use ticketchain::PLATFORM_WALLET;
+use ticketchain_treasury::ErrorCode;
+
+fn treasury_ix(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction { program_id: ticketchain_treasury::ID, accounts: accounts.to_account_metas(None), data: args.data() }
+}
+
+fn treasury_pda(seed: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[seed], &ticketchain_treasury::ID).0
+}
+
+/// TicketChain runtime plus the treasury program, upgradeable by `upgrader`, with a
+/// funded treasury PDA and an initialized TreasuryConfig naming `authority`.
+fn treasury_setup() -> (Svm, Pubkey) {
+    let mut svm = setup();
+    svm.add_program(ticketchain_treasury::ID, ticketchain_treasury::entry);
+    let upgrader = svm.wallet(SOL);
+    install_program_data(&mut svm, upgrader);
+    let authority = svm.wallet(SOL);
+    assert_ok(svm.send(init_ix(&upgrader, authority), &[upgrader]));
+    svm.airdrop(&treasury_pda(b"treasury"), 10 * SOL);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    (svm, authority)
+}
+
+fn program_data_pda() -> Pubkey {
+    Pubkey::find_program_address(&[ticketchain_treasury::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Points the treasury program account at a ProgramData account whose upgrade
+/// authority is `upgrader`.
+fn install_program_data(svm: &mut Svm, upgrader: Pubkey) {
+    let program_data = program_data_pda();
+    let mut program = svm.account(&ticketchain_treasury::ID).unwrap().clone();
+    program.data = bincode::serialize(&UpgradeableLoaderState::Program { programdata_address: program_data }).unwrap();
+    svm.set_account(ticketchain_treasury::ID, program);
+    let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(upgrader),
+    })
+    .unwrap();
+    let lamports = anchor_lang::solana_program::rent::Rent::default().minimum_balance(data.len());
+    svm.set_account(
+        program_data,
+        Account { lamports, data, owner: bpf_loader_upgradeable::ID, executable: false },
+    );
+}
+
+fn init_ix(payer: &Pubkey, authority: Pubkey) -> Instruction {
+    treasury_ix(
+        ticketchain_treasury::accounts::InitTreasury {
+            payer: *payer,
+            config: treasury_pda(b"treasury_config"),
+            program: ticketchain_treasury::ID,
+            program_data: program_data_pda(),
+            system_program: system_program::ID,
+        },
+        ticketchain_treasury::instruction::InitTreasury { authority },
+    )
+}
+
+fn buy_ix(fixture: &EventFixture, authority: &Pubkey, index: u32) -> Instruction {
+    let treasury = treasury_pda(b"treasury");
+    let ticket = buy_ticket_accounts(fixture, &treasury, index);
+    treasury_ix(
+        ticketchain_treasury::accounts::BuyForTreasury {
+            authority: *authority,
+            config: treasury_pda(b"treasury_config"),
+            treasury,
+            organizer: ticket.organizer,
+            event: ticket.event,
+            ticket_authority: ticket.ticket_authority,
+            ticket_mint: ticket.ticket_mint,
+            treasury_token_account: ticket.buyer_token_account,
+            receipt: ticket.receipt,
+            idempotency_key: ticket.idempotency_key,
+            rate_limit: ticket.rate_limit,
+            organizer_ban: ticket.organizer_ban,
+            name_override: ticket.name_override,
+            ticketchain_program: ticketchain::ID,
+            token_program: TOKEN_PROGRAM,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain_treasury::instruction::BuyForTreasury { ticket_index: index, tier_index: 0 },
+    )
+}
+
+fn buy_resale_ix(fixture: &EventFixture, authority: &Pubkey, seller: &Pubkey, mint: &Pubkey, max_lamports: u64) -> Instruction {
+    let treasury = treasury_pda(b"treasury");
+    let event = fixture.event;
+    treasury_ix(
+        ticketchain_treasury::accounts::BuyResaleForTreasury {
+            authority: *authority,
+            config: treasury_pda(b"treasury_config"),
+            treasury,
+            seller: *seller,
+            organizer: fixture.organizer,
+            platform: PLATFORM_WALLET,
+            platform_config: platform_config_pda(),
+            event,
+            ticket_mint: *mint,
+            ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+            listing: listing_pda(mint),
+            deposit_receipt: None,
+            escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+            treasury_token_account: ata(&treasury, mint),
+            ticket_stats: pda(&[b"ticket_stats", event.as_ref()]),
+            floor_monitor: pda(&[b"floor_monitor", event.as_ref()]),
+            ticketchain_program: ticketchain::ID,
+            token_program: TOKEN_PROGRAM,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain_treasury::instruction::BuyResaleForTreasury { max_lamports },
+    )
+}
+
+#[test]
+fn only_the_upgrade_authority_initializes_the_treasury() {
+    let mut svm = setup();
+    svm.add_program(ticketchain_treasury::ID, ticketchain_treasury::entry);
+    let upgrader = svm.wallet(SOL);
+    install_program_data(&mut svm, upgrader);
+    let stranger = svm.wallet(SOL);
+
+    assert_err(svm.send(init_ix(&stranger, stranger), &[stranger]), ErrorCode::Unauthorized);
+    assert_ok(svm.send(init_ix(&upgrader, stranger), &[upgrader]));
+    let config: ticketchain_treasury::TreasuryConfig = svm.get(&treasury_pda(b"treasury_config"));
+    assert_eq!(config.authority, stranger);
+}
+
+#[test]
+fn authority_buys_a_primary_ticket_into_the_treasury() {
+    let (mut svm, authority) = treasury_setup();
+    let fixture = create_event(&mut svm);
+    let treasury = treasury_pda(b"treasury");
+    let before = svm.lamports(&treasury);
+
+    assert_ok(svm.send(buy_ix(&fixture, &authority, 0), &[authority]));
+
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    assert_eq!(token_balance(&svm, &ata(&treasury, &mint)), 1);
+    assert_eq!(event(&svm, &fixture).sold, 1);
+    assert!(svm.lamports(&treasury) <= before - SOL / 10);
+}
+
+#[test]
+fn non_authority_cannot_spend_the_treasury() {
+    let (mut svm, _authority) = treasury_setup();
+    let fixture = create_event(&mut svm);
+    let stranger = svm.wallet(SOL);
+
+    assert_err(svm.send(buy_ix(&fixture, &stranger, 0), &[stranger]), ErrorCode::Unauthorized);
+    assert_eq!(event(&svm, &fixture).sold, 0);
+}
+
+#[test]
+fn authority_buys_a_resale_listing_into_the_treasury() {
+    let (mut svm, authority) = treasury_setup();
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 10));
+    let stranger = svm.wallet(SOL);
+
+    assert_err(
+        svm.send(buy_resale_ix(&fixture, &stranger, &seller, &mint, SOL / 10), &[stranger]),
+        ErrorCode::Unauthorized,
+    );
+    assert_err(
+        svm.send(buy_resale_ix(&fixture, &authority, &seller, &mint, SOL / 10 - 1), &[authority]),
+        ticketchain::ErrorCode::PriceAboveMax,
+    );
+    assert_ok(svm.send(buy_resale_ix(&fixture, &authority, &seller, &mint, SOL / 10), &[authority]));
+
+    let treasury = treasury_pda(b"treasury");
+    assert_eq!(token_balance(&svm, &ata(&treasury, &mint)), 1);
+    assert!(!svm.exists(&listing_pda(&mint)));
+}
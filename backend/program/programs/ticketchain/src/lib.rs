@@ -1,37 +1,247 @@
 //! TicketChain: create events, mint ticket NFTs, and enable on-chain resale on Solana.
 
+// The generated CPI client mirrors create_event's argument list
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke_signed, set_return_data};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
 use anchor_spl::token_interface::{
     Mint, TokenAccount, TokenInterface,
     mint_to, MintTo,
     transfer_checked, TransferChecked,
     close_account, CloseAccount,
+    burn, Burn,
+    initialize_mint2, InitializeMint2,
+    set_authority, SetAuthority,
+    spl_token_2022::state::Mint as SplMint,
 };
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_lang::solana_program::program_pack::Pack;
 
 declare_id!("BxjzLBTGVQYHRAC5NBGvyn9r6V7GfVHWUExFcJbRoCts");
 
+/// Waitlist size, as a multiple of supply, that triggers an automatic supply increase.
+pub const WAITLIST_DEMAND_MULTIPLIER: u32 = 1;
+/// Maximum number of automatic supply increases per event.
+pub const MAX_AUTO_INCREMENTS: u8 = 3;
+/// Maximum number of winners a single raffle can draw.
+pub const MAX_RAFFLE_WINNERS: u8 = 16;
+/// Number of recent resale prices kept on TicketStats.
+pub const PRICE_HISTORY_LEN: usize = 5;
+/// Listing-vs-market deviation (bps) above which a price warning is emitted.
+pub const PRICE_DEVIATION_WARNING_BPS: u64 = 5000;
+/// Maximum number of tranches in an event's release schedule.
+pub const MAX_RELEASE_TRANCHES: usize = 8;
+/// Delay after the event start before holders can claim a no-show attestation.
+pub const NO_SHOW_DELAY_SECONDS: i64 = 2 * 60 * 60;
+/// Maximum number of resting orders on each side of an event's order book.
+pub const MAX_BOOK_ORDERS: usize = 20;
+/// Maximum length in bytes of a gift note message.
+pub const MAX_GIFT_MESSAGE_LEN: usize = 200;
+/// Share (bps) of recovered rent paid to the cranker by cleanup_accounts and sweep_expired_holds.
+pub const CLEANUP_TIP_BPS: u64 = 1000;
+/// Maximum length in bytes of a perk's metadata URI.
+pub const MAX_PERK_URI_LEN: usize = 200;
+/// Lowest non-zero primary ticket price (0.001 SOL); free events use 0.
+pub const MIN_TICKET_PRICE_LAMPORTS: u64 = 1_000_000;
+/// Default ceiling on primary ticket prices (100 SOL), overridable via PlatformConfig.
+pub const MAX_TICKET_PRICE_LAMPORTS: u64 = 100_000_000_000;
+/// Maximum number of events a festival pass bundle can cover.
+pub const MAX_BUNDLE_EVENTS: usize = 8;
+/// Maximum length in bytes of an event's social link.
+pub const MAX_SOCIAL_LINK_LEN: usize = 128;
+/// Maximum number of concession price categories per event.
+pub const MAX_CONCESSIONS: usize = 4;
+/// Maximum length in bytes of a concession category name.
+pub const MAX_CONCESSION_NAME_LEN: usize = 16;
+/// Maximum number of ticket tiers (GA, VIP, ...) per event.
+pub const MAX_TIERS: usize = 16;
+/// Maximum length in bytes of a ticket tier name.
+pub const MAX_TIER_NAME_LEN: usize = 32;
+/// Maximum length in bytes of an event's ticket design CID.
+pub const MAX_DESIGN_CID_LEN: usize = 64;
+/// Tips above this multiple of the ticket price need `allow_large_tip`.
+pub const MAX_TIP_MULTIPLE: u64 = 10;
+/// Maximum length in bytes of a split template name.
+pub const MAX_TEMPLATE_NAME_LEN: usize = 32;
+/// Maximum number of ticket mints pre-allocated in one call.
+pub const MAX_PREALLOCATED_TICKETS: u8 = 10;
+/// Maximum tickets bought in one buy_tickets call; each mints an NFT and a receipt.
+pub const MAX_BATCH_PURCHASE: u8 = 5;
+/// buy_tickets remaining accounts per ticket: authority, mint, ATA, receipt, name override.
+pub const BATCH_ACCOUNTS_PER_TICKET: usize = 5;
+/// Share of votes cast (percent) that must be yes to pass a governance proposal.
+pub const GOVERNANCE_THRESHOLD_PCT: u64 = 60;
+/// Share of sold tickets (percent) that must vote for a proposal's result to count.
+pub const GOVERNANCE_QUORUM_PCT: u64 = 50;
+/// How long ticket holders can vote on a proposal; it can only be applied afterwards.
+pub const GOVERNANCE_VOTING_PERIOD_SECS: i64 = 3 * 24 * 60 * 60;
+/// Governance change types.
+pub const CHANGE_VENUE: u8 = 0;
+pub const CHANGE_DATE: u8 = 1;
+/// Window over which FloorMonitor tracks the high resale price.
+pub const FLOOR_MONITOR_WINDOW_SECONDS: i64 = 3600;
+/// A resale below this share (percent) of the window high pauses resale.
+pub const CIRCUIT_BREAKER_FLOOR_PCT: u64 = 50;
+/// Default cooldown between listing price updates.
+pub const DEFAULT_PRICE_UPDATE_INTERVAL_SECS: i64 = 60;
+/// Purchase attestations older than this many slots (~1 minute) are rejected.
+pub const MAX_ATTESTATION_AGE_SLOTS: u64 = 150;
+/// Size of the unique-buyer bitmap in EventAnalytics (1024 bits).
+pub const ANALYTICS_BITMAP_BYTES: usize = 128;
+/// Metaplex Token Metadata program, which wallets read ticket names and images from.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+/// Token Metadata `CreateMetadataAccountV3` instruction discriminator.
+pub const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+/// Maximum length in bytes of a Token Metadata name and URI.
+pub const MAX_METADATA_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+/// Token Metadata symbol shown for every ticket.
+pub const TICKET_SYMBOL: &str = "TIX";
+/// Sizes of the metadata, master edition and TokenRecord accounts a pNFT ticket adds.
+pub const TOKEN_METADATA_ACCOUNT_LEN: usize = 679;
+pub const MASTER_EDITION_ACCOUNT_LEN: usize = 282;
+pub const TOKEN_RECORD_ACCOUNT_LEN: usize = 80;
+/// Token Metadata instruction discriminators for programmable (pNFT) tickets.
+pub const BURN_V1: u8 = 41;
+pub const CREATE_V1: u8 = 42;
+pub const MINT_V1: u8 = 43;
+pub const DELEGATE_V1: u8 = 44;
+pub const REVOKE_V1: u8 = 45;
+pub const LOCK_V1: u8 = 46;
+pub const UNLOCK_V1: u8 = 47;
+pub const TRANSFER_V1: u8 = 49;
+/// Token Metadata `TokenStandard::ProgrammableNonFungible`.
+pub const PROGRAMMABLE_NON_FUNGIBLE: u8 = 4;
+/// `DelegateArgs` / `RevokeArgs` variant of the locked-transfer delegate a pNFT
+/// listing takes: it can lock the ticket and move it only to the listing.
+pub const LOCKED_TRANSFER_DELEGATE: u8 = 7;
+/// Maximum accepted payment options per event.
+pub const MAX_PAYMENT_OPTIONS: usize = 4;
+/// Paid from the event's MonitorRewardPool to whoever runs an event health check.
+pub const MONITOR_REWARD_LAMPORTS: u64 = 10_000;
+/// Most one monitor can earn from one event's reward pool.
+pub const MONITOR_REWARD_CAP_LAMPORTS: u64 = 100 * MONITOR_REWARD_LAMPORTS;
+/// Minimum time between rewarded health checks of one event.
+pub const MONITOR_INTERVAL_SECONDS: i64 = 10 * 60;
+/// HealthCheckFailed reasons.
+pub const HEALTH_ESCROW: u8 = 0;
+pub const HEALTH_SUPPLY: u8 = 1;
+pub const HEALTH_VAULT: u8 = 2;
+/// Maximum primary purchases per wallet per RATE_LIMIT_WINDOW_SECONDS, across events.
+pub const RATE_LIMIT_PER_MINUTE: u8 = 5;
+pub const RATE_LIMIT_WINDOW_SECONDS: i64 = 60;
+/// Deposited with each bid; paid to whoever refunds the bid after it expires.
+pub const BID_REFUND_TIP_LAMPORTS: u64 = 5_000;
+/// Default lock on escrowed royalties after the last deposit (one quarter).
+pub const DEFAULT_ROYALTY_RELEASE_DELAY_SECONDS: u32 = 90 * 24 * 60 * 60;
+/// Maximum length in bytes of a per-index custom ticket name.
+pub const MAX_TICKET_NAME_LEN: usize = 32;
+/// Highest buy_ticket_v2 version this program understands.
+pub const CURRENT_MAX_VERSION: u8 = 4;
+/// Version byte of the ownership attestation message format.
+pub const OWNERSHIP_ATTESTATION_VERSION: u8 = 1;
+/// Pyth Solana receiver program; owns the PriceUpdateV2 accounts USD listings read.
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// Pyth SOL/USD price feed id.
+pub const SOL_USD_FEED_ID: [u8; 32] = [
+    239, 13, 139, 111, 218, 44, 235, 164, 29, 161, 93, 64, 149, 209, 218, 57,
+    42, 13, 47, 142, 208, 198, 199, 188, 15, 76, 250, 200, 194, 128, 181, 109,
+];
+/// USD listings reject price updates older than this or wider than MAX_PRICE_CONF_BPS.
+/// The buyer supplies the update, so the age bounds how far back they can shop for a
+/// favourable price.
+pub const MAX_PRICE_FEED_AGE_SECONDS: i64 = 10;
+pub const MAX_PRICE_CONF_BPS: u64 = 100;
+/// Tier perks bits (Tier.perks_bitmask), shown to door staff at check-in.
+pub const PERK_FAST_LANE: u16 = 1 << 0;
+pub const PERK_BACKSTAGE: u16 = 1 << 1;
+pub const PERK_MERCH_INCLUDED: u16 = 1 << 2;
+/// PlatformConfig.personhood_provider values; see PersonhoodProvider.
+pub const PERSONHOOD_CIVIC: u8 = 0;
+/// Civic gateway program; owns Civic gateway token accounts.
+pub const CIVIC_GATEWAY_PROGRAM_ID: Pubkey = pubkey!("gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs");
+/// Attendance deposits settle this long after `date_ts` (the program records no end time).
+pub const ATTENDANCE_FINALIZE_SECONDS: i64 = 24 * 60 * 60;
+/// Holders of a postponed event can take claim_refund for this long after the move,
+/// or until the new date if that comes sooner.
+pub const POSTPONEMENT_REFUND_SECONDS: i64 = 7 * 24 * 60 * 60;
+/// Ticket notes are frozen this long before `date_ts`.
+pub const TICKET_NOTE_LOCK_SECONDS: i64 = 24 * 60 * 60;
+/// Length of one FeeDay bucket.
+pub const FEE_DAY_SECONDS: i64 = 24 * 60 * 60;
+/// Cheapest lamport asks kept on an event's AskBook.
+pub const ASK_BOOK_SIZE: usize = 8;
+/// PlatformConfig account size, discriminator included. Configs created before the
+/// appended fields are PLATFORM_CONFIG_V1_LEN bytes until migrate_platform_config runs.
+pub const PLATFORM_CONFIG_LEN: usize = PLATFORM_CONFIG_V1_LEN + 33 + 1 + 32 + 8 + 8;
+pub const PLATFORM_CONFIG_V1_LEN: usize = 8 + 32 + 8 + 8 + 32 + 8 + 4 + 1;
+/// Platform wallet; receives the platform share of every resale.
+pub const PLATFORM_WALLET: Pubkey = pubkey!("GFxY452qfw5nwA4N9KQ28zZTmJL9CD1eenydHY9kEE32");
+
 #[program]
 pub mod ticketchain {
     use super::*;
 
     /// Create a new event. The event account is a PDA derived from organizer + nonce.
+    /// `tiers` lists 1 to MAX_TIERS ticket tiers, each with its own price and supply;
+    /// the event's supply is their total. The first tier is the event's headline
+    /// `tier_name` and `price_lamports`. `sale_start_ts` / `sale_end_ts` optionally
+    /// bound when buy_ticket sells (presales, flash sales). `max_per_wallet` caps the
+    /// tickets one wallet can buy (0 = unlimited). A non-empty `metadata_uri` gives
+    /// each ticket Token Metadata at purchase, so wallets show its name and image.
     pub fn create_event(
         ctx: Context<CreateEvent>,
         nonce: u64,
         title: String,
         venue: String,
         date_ts: i64,
-        tier_name: String,
-        price_lamports: u64,
-        supply: u32,
+        tiers: Vec<TierConfig>,
         artist_pct: u8,
+        sale_start_ts: Option<i64>,
+        sale_end_ts: Option<i64>,
+        max_per_wallet: u16,
+        metadata_uri: String,
     ) -> Result<()> {
         require!(title.len() <= 64, ErrorCode::TitleTooLong);
+        require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
         require!(venue.len() <= 64, ErrorCode::VenueTooLong);
-        require!(tier_name.len() <= 32, ErrorCode::TierNameTooLong);
-        require!(supply > 0, ErrorCode::InvalidSupply);
+        validate_sale_window(sale_start_ts, sale_end_ts, date_ts)?;
+        require!(!tiers.is_empty() && tiers.len() <= MAX_TIERS, ErrorCode::InvalidTiers);
+        let max_price = ctx.accounts.platform_config.max_ticket_price;
+        let mut supply: u32 = 0;
+        for tier in &tiers {
+            require!(tier.name.len() <= MAX_TIER_NAME_LEN, ErrorCode::TierNameTooLong);
+            require!(tier.supply > 0, ErrorCode::InvalidSupply);
+            require!(
+                tier.price_lamports == 0 || tier.price_lamports >= MIN_TICKET_PRICE_LAMPORTS,
+                ErrorCode::InvalidPrice
+            );
+            require!(tier.price_lamports <= max_price, ErrorCode::PriceAboveCeiling);
+            supply = supply.checked_add(tier.supply).ok_or(ErrorCode::InvalidSupply)?;
+        }
         require!(artist_pct <= 80, ErrorCode::InvalidSplit);
+        // A split template, when given, replaces the artist_pct / seller / 20% platform default
+        let (artist_bps, seller_bps, platform_bps) = match &ctx.accounts.split_template {
+            Some(template) => (template.artist_bps, template.seller_bps, template.platform_bps),
+            None => {
+                let artist_bps = artist_pct as u16 * 100;
+                (artist_bps, 8000 - artist_bps, 2000)
+            }
+        };
+        validate_split(artist_bps, seller_bps, platform_bps)?;
 
         let event = &mut ctx.accounts.event;
         event.organizer = ctx.accounts.organizer.key();
@@ -39,118 +249,316 @@ pub mod ticketchain {
         event.title = title;
         event.venue = venue;
         event.date_ts = date_ts;
-        event.tier_name = tier_name;
-        event.price_lamports = price_lamports;
+        event.tier_name = tiers[0].name.clone();
+        event.price_lamports = tiers[0].price_lamports;
         event.supply = supply;
         event.sold = 0;
-        event.artist_pct = artist_pct;
+        event.artist_pct = (artist_bps / 100) as u8;
+        event.artist_bps = artist_bps;
+        event.seller_bps = seller_bps;
+        event.platform_bps = platform_bps;
+        event.capacity = supply;
+        event.waitlist_head = 0;
+        event.auto_increase_supply = false;
+        event.demand_increment = 0;
+        event.auto_increments = 0;
+        event.checked_in = 0;
+        event.release_schedule = Vec::new();
+        event.released_early = 0;
+        event.max_listing_multiple = 0;
+        event.held = 0;
+        event.tax_bps = 0;
+        event.tax_wallet = Pubkey::default();
+        event.tax_collected = 0;
+        event.donation_bps = 0;
+        event.donation_wallet = Pubkey::default();
+        event.total_donated = 0;
+        event.resale_paused = false;
+        event.verification_ts = 0;
+        event.require_original_buyer_only = false;
+        event.grace_period_seconds = 0;
+        event.refundable_until = 0;
+        event.vesting_cliff_ts = 0;
+        event.vesting_duration_seconds = 0;
+        event.proceeds_withdrawn = 0;
+        event.fan_token_mint = None;
+        event.fan_tokens_per_purchase = 0;
+        event.pass_reserved = 0;
+        event.social_link = String::new();
+        event.concessions = Vec::new();
+        event.ticket_design_cid = String::new();
+        event.accessible_seating_count = 0;
+        event.accessible_sold = 0;
+        event.requires_attestation = false;
+        event.analytics = EventAnalytics::default();
+        event.use_pnft = false;
+        event.accepted_payments = Vec::new();
+        event.sales_paused = false;
+        event.last_health_check_ts = 0;
+        event.resale_blocked_while_primary_available = false;
+        event.min_resale_lamports = 0;
+        event.dynamic_resale_floor = false;
+        event.royalty_escrow_mode = false;
+        event.one_per_person = false;
+        event.deposit_lamports = 0;
+        event.deposit_forfeit_wallet = Pubkey::default();
+        event.deposits_held = 0;
+        event.notes_survive_transfer = false;
+        event.split_into = [Pubkey::default(); 2];
+        event.split_from = Pubkey::default();
+        event.migrated_out = 0;
+        event.platform_fees_collected = 0;
+        event.resale_blackout_secs = 0;
+        event.platform_fee_override_bps = None;
+        event.identity_required = false;
+        event.identity_at_check_in = false;
+        event.closed_receipt_revenue = 0;
+        event.token_only = false;
+        event.sale_start_ts = sale_start_ts;
+        event.sale_end_ts = sale_end_ts;
+        event.max_per_wallet = max_per_wallet;
+        event.last_updated_ts = Clock::get()?.unix_timestamp;
+        event.cancelled = false;
+        event.refunds_pending = 0;
+        event.metadata_uri = metadata_uri;
+        event.perks_created = 0;
+        event.accessibility_verifier = Pubkey::default();
+        event.holds_expire_at = 0;
+        event.postponement_refund_until = 0;
+        event.pass_refunds_owed = 0;
+        event.tiers = tiers
+            .into_iter()
+            .map(|t| Tier { name: t.name, price_lamports: t.price_lamports, supply: t.supply, sold: 0, perks_bitmask: 0 })
+            .collect();
 
         Ok(())
     }
 
-    /// Buy a ticket: pay SOL to organizer, receive one NFT (new mint, 1 token).
-    pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
-        let event = &ctx.accounts.event;
-        require!(event.sold < event.supply, ErrorCode::SoldOut);
-
-        let buyer = &ctx.accounts.buyer;
-        let organizer = &ctx.accounts.organizer;
-        let lamports = event.price_lamports;
+    /// Configure demand-driven supply increases. Only the organizer can call this.
+    /// When enabled, supply grows by `demand_increment` (up to `capacity`) once the
+    /// waitlist reaches `supply * WAITLIST_DEMAND_MULTIPLIER`.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn configure_auto_supply(
+        ctx: Context<ConfigureAutoSupply>,
+        auto_increase_supply: bool,
+        demand_increment: u32,
+        capacity: u32,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(capacity >= event.supply, ErrorCode::InvalidCapacity);
+        require!(!auto_increase_supply || demand_increment > 0, ErrorCode::InvalidDemandIncrement);
 
-        // Transfer SOL from buyer to organizer
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: buyer.to_account_info(),
-                    to: organizer.to_account_info(),
-                },
-            ),
-            lamports,
-        )?;
+        event.auto_increase_supply = auto_increase_supply;
+        event.demand_increment = demand_increment;
+        event.capacity = capacity;
 
-        // Mint one ticket NFT (decimals 0) to buyer; program PDA signs as mint authority
-        let event_key = event.key();
-        let sold = event.sold;
-        let sold_bytes = sold.to_le_bytes();
-        let bump = ctx.bumps.ticket_authority;
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"ticket_authority",
-            event_key.as_ref(),
-            &sold_bytes,
-            &[bump],
-        ]];
-        mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.ticket_authority.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            1,
-        )?;
+        Ok(())
+    }
 
+    /// Join the waitlist of a sold-out event. If the waitlist has already crossed the
+    /// demand threshold, supply is increased before the new entry is recorded.
+    pub fn join_waitlist(ctx: Context<JoinWaitlist>) -> Result<()> {
         let event = &mut ctx.accounts.event;
-        event.sold = event.sold.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        require!(event.sold >= event.supply, ErrorCode::NotSoldOut);
+
+        // Lazily apply any pending automatic supply increase
+        let threshold = event.supply.saturating_mul(WAITLIST_DEMAND_MULTIPLIER);
+        if event.auto_increase_supply
+            && event.auto_increments < MAX_AUTO_INCREMENTS
+            && event.supply < event.capacity
+            && event.waitlist_head >= threshold
+        {
+            let new_supply = event
+                .supply
+                .saturating_add(event.demand_increment)
+                .min(event.capacity);
+            event.tiers[0].supply =
+                event.tiers[0].supply.checked_add(new_supply - event.supply).ok_or(ErrorCode::Overflow)?;
+            event.supply = new_supply;
+            event.auto_increments += 1;
+
+            emit!(AutoSupplyIncrease {
+                event: event.key(),
+                new_supply,
+                trigger_waitlist_size: event.waitlist_head,
+            });
+        }
+
+        let entry = &mut ctx.accounts.waitlist_entry;
+        entry.event = event.key();
+        entry.wallet = ctx.accounts.wallet.key();
+        entry.position = event.waitlist_head;
+        entry.joined_ts = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.waitlist_entry;
+
+        event.waitlist_head = event.waitlist_head.checked_add(1).ok_or(ErrorCode::Overflow)?;
 
         Ok(())
     }
 
-    /// List a ticket for resale. Transfers the NFT into an escrow account
-    /// owned by the Listing PDA.
-    pub fn list_for_resale(ctx: Context<ListForResale>, price_lamports: u64) -> Result<()> {
-        require!(price_lamports > 0, ErrorCode::InvalidPrice);
+    /// Buy a ticket: pay SOL to organizer, receive one NFT (new mint, 1 token).
+    /// `ticket_index` must equal `event.sold`; replaying a purchase that already minted
+    /// that index returns Ok without charging or minting again.
+    pub fn buy_ticket(
+        ctx: Context<BuyTicket>,
+        ticket_index: u32,
+        tier_index: u8,
+        category: Option<u8>,
+        tip_lamports: u64,
+        allow_large_tip: bool,
+        payment_index: Option<u8>,
+    ) -> Result<()> {
+        purchase_ticket(
+            ctx,
+            ticket_index,
+            tier_index,
+            category,
+            tip_lamports,
+            allow_large_tip,
+            payment_index,
+            None,
+            None,
+            false,
+        )
+    }
 
-        // Transfer NFT from seller to escrow
-        transfer_checked(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.seller_token_account.to_account_info(),
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.escrow_token_account.to_account_info(),
-                    authority: ctx.accounts.seller.to_account_info(),
-                },
-            ),
-            1,
-            0, // decimals
-        )?;
+    /// Buy one of the event's accessible (ADA) seats at the regular price. These seats
+    /// are held back from buy_ticket until sold here or released by the organizer.
+    /// The buyer needs an unused AccessibleApproval from the organizer or the event's
+    /// accessibility verifier; the purchase uses it up.
+    pub fn buy_accessible_ticket(ctx: Context<BuyTicket>, ticket_index: u32) -> Result<()> {
+        purchase_ticket(ctx, ticket_index, 0, None, 0, false, None, None, None, true)
+    }
 
-        let listing = &mut ctx.accounts.listing;
-        listing.seller = ctx.accounts.seller.key();
-        listing.event = ctx.accounts.event.key();
-        listing.ticket_mint = ctx.accounts.ticket_mint.key();
-        listing.price_lamports = price_lamports;
-        listing.bump = ctx.bumps.listing;
+    /// Versioned buy_ticket: new purchase options are added to BuyTicketOptions rather
+    /// than as new instructions. Version 1 behaves exactly like buy_ticket; version 2
+    /// adds the buyer note, version 3 the identity commitment and version 4 the tier.
+    /// The voucher, referrer and offer fields are reserved for later versions and
+    /// rejected if set.
+    pub fn buy_ticket_v2(
+        ctx: Context<BuyTicket>,
+        ticket_index: u32,
+        version: u8,
+        options: BuyTicketOptions,
+    ) -> Result<()> {
+        require!((1..=CURRENT_MAX_VERSION).contains(&version), ErrorCode::UnsupportedVersion);
+        require!(
+            options.voucher_hash.is_none() && options.referrer.is_none() && options.offered_lamports.is_none(),
+            ErrorCode::UnsupportedVersion
+        );
+        require!(version >= 2 || options.note.is_none(), ErrorCode::UnsupportedVersion);
+        require!(version >= 3 || options.identity_commitment.is_none(), ErrorCode::UnsupportedVersion);
+        require!(version >= 4 || options.tier_index == 0, ErrorCode::UnsupportedVersion);
+        purchase_ticket(
+            ctx,
+            ticket_index,
+            options.tier_index,
+            options.category,
+            options.tip_lamports,
+            options.allow_large_tip,
+            options.payment_index,
+            options.note,
+            options.identity_commitment,
+            false,
+        )
+    }
 
-        Ok(())
+    /// List a ticket for resale. Transfers the NFT into an escrow account
+    /// owned by the Listing PDA. `ticket_index` ties the mint to `event`.
+    pub fn list_for_resale(ctx: Context<ListForResale>, _ticket_index: u32, price_lamports: u64) -> Result<()> {
+        create_listing(ctx, price_lamports, 0)
+    }
+
+    /// List a ticket priced in US cents. The lamport price is read from the Pyth SOL/USD
+    /// `price_feed` at purchase time; caps and floors are checked against the converted
+    /// amount, both now and in buy_resale.
+    pub fn list_for_resale_usd(ctx: Context<ListForResale>, _ticket_index: u32, usd_price_cents: u64) -> Result<()> {
+        require!(usd_price_cents > 0, ErrorCode::InvalidPrice);
+        let feed = ctx.accounts.price_feed.as_ref().ok_or(ErrorCode::InvalidPriceFeed)?;
+        let price_lamports =
+            usd_cents_to_lamports(feed, usd_price_cents, Clock::get()?.unix_timestamp)?;
+        create_listing(ctx, price_lamports, usd_price_cents)
     }
 
-    /// Buy a resale ticket. SOL is split per the event's artist_pct / seller / 20% platform.
-    /// NFT is transferred from escrow to buyer. Listing is closed.
-    pub fn buy_resale(ctx: Context<BuyResale>) -> Result<()> {
-        let price = ctx.accounts.listing.price_lamports;
-        let artist_pct = ctx.accounts.event.artist_pct as u64;
-        let artist_share = price * artist_pct / 100;
-        let platform_share = price * 20 / 100;
-        let seller_share = price - artist_share - platform_share;
+    /// Buy a resale ticket. SOL is split per the event's artist / seller / platform bps,
+    /// after the ticket's attendance deposit is paid back to the seller. NFT is
+    /// transferred from escrow to buyer. Listing is closed.
+    pub fn buy_resale(ctx: Context<BuyResale>, max_lamports: u64) -> Result<()> {
+        require!(!ctx.accounts.listing.anomaly_flag, ErrorCode::EscrowAnomaly);
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        require!(Clock::get()?.unix_timestamp < ctx.accounts.event.date_ts, ErrorCode::EventEnded);
+        require!(!ctx.accounts.event.resale_paused, ErrorCode::ResalePaused);
+        require!(
+            !resale_competes_with_primary(&ctx.accounts.event, Clock::get()?.unix_timestamp),
+            ErrorCode::PrimaryStillAvailable
+        );
+        // USD listings convert at this read of the feed; caps and floors use the same amount
+        let listing = &ctx.accounts.listing;
+        let price = if listing.usd_price_cents > 0 {
+            let feed = ctx.accounts.price_feed.as_ref().ok_or(ErrorCode::InvalidPriceFeed)?;
+            let price =
+                usd_cents_to_lamports(feed, listing.usd_price_cents, Clock::get()?.unix_timestamp)?;
+            check_listing_multiple(&ctx.accounts.event, listing.tier_index, price)?;
+            check_resale_floor(&ctx.accounts.event, listing.tier_index, price, Clock::get()?.unix_timestamp)?;
+            price
+        } else {
+            listing.price_lamports
+        };
+        require!(price <= max_lamports, ErrorCode::PriceAboveMax);
+        let deposit = resale_deposit(
+            &ctx.accounts.event,
+            ctx.accounts.deposit_receipt.as_deref().map(|r| &**r),
+            price,
+        )?;
+        // The buyer's ticket account is created below, after the check
+        let buyer_ata = ctx.accounts.buyer_token_account.to_account_info();
+        let ata_rent = if buyer_ata.data_is_empty() {
+            Rent::get()?.minimum_balance(spl_token_2022::state::Account::LEN).saturating_sub(buyer_ata.lamports())
+        } else {
+            0
+        };
+        // A pNFT sale also creates TokenRecords for the escrow hop and the buyer
+        let record_rent = if ctx.accounts.event.use_pnft {
+            2 * Rent::get()?.minimum_balance(TOKEN_RECORD_ACCOUNT_LEN)
+        } else {
+            0
+        };
+        require_funds(
+            &ctx.accounts.buyer.to_account_info(),
+            price.checked_add(ata_rent + record_rent).ok_or(ErrorCode::Overflow)?,
+        )?;
+        anchor_spl::associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: ctx.accounts.buyer.to_account_info(),
+                associated_token: buyer_ata,
+                authority: ctx.accounts.buyer.to_account_info(),
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+        let (artist_share, seller_share, platform_share) = resale_split(&ctx.accounts.event, price - deposit);
 
-        // artist_pct% to organizer (artist)
+        // artist_bps to organizer (artist), or held in the royalty escrow
+        let artist_to = royalty_recipient(
+            &ctx.accounts.event,
+            &ctx.accounts.organizer,
+            ctx.accounts.royalty_escrow.as_deref_mut(),
+            artist_share,
+        )?;
         anchor_lang::system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
                 anchor_lang::system_program::Transfer {
                     from: ctx.accounts.buyer.to_account_info(),
-                    to: ctx.accounts.organizer.to_account_info(),
+                    to: artist_to,
                 },
             ),
             artist_share,
         )?;
 
-        // (80 - artist_pct)% to seller
+        // seller_bps to seller, plus their attendance deposit back
         anchor_lang::system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
@@ -159,10 +567,10 @@ pub mod ticketchain {
                     to: ctx.accounts.seller.to_account_info(),
                 },
             ),
-            seller_share,
+            seller_share + deposit,
         )?;
 
-        // 20% to platform
+        // platform_bps to platform
         anchor_lang::system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
@@ -173,6 +581,12 @@ pub mod ticketchain {
             ),
             platform_share,
         )?;
+        record_platform_fee(
+            &mut ctx.accounts.platform_config,
+            &mut ctx.accounts.event,
+            ctx.accounts.fee_day.as_deref_mut(),
+            platform_share,
+        )?;
 
         // Transfer NFT from escrow to buyer
         let ticket_mint_key = ctx.accounts.ticket_mint.key();
@@ -183,20 +597,68 @@ pub mod ticketchain {
             &[bump],
         ]];
 
-        transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.listing.to_account_info(),
-                },
+        if ctx.accounts.event.use_pnft {
+            // pNFT: the locked-transfer delegate may only move the ticket to the listing,
+            // so it goes through the escrow account on its way to the buyer
+            let mint = ctx.accounts.ticket_mint.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            let token_program = ctx.accounts.token_program.to_account_info();
+            let pnft = PnftAccounts::new(
+                ctx.accounts.token_metadata_program.as_ref(),
+                ctx.accounts.ticket_metadata.as_ref(),
+                ctx.accounts.ticket_edition.as_ref(),
+                ctx.accounts.instructions.as_ref(),
+                &mint,
+                &system_program,
+                &token_program,
+            )?;
+            let (Some(seller_token), Some(seller_record), Some(escrow_record), Some(buyer_record)) = (
+                ctx.accounts.seller_token_account.as_ref(),
+                ctx.accounts.seller_token_record.as_ref(),
+                ctx.accounts.escrow_token_record.as_ref(),
+                ctx.accounts.buyer_token_record.as_ref(),
+            ) else {
+                return err!(ErrorCode::MissingMetadataAccounts);
+            };
+            let seller = ctx.accounts.seller.to_account_info();
+            let buyer = ctx.accounts.buyer.to_account_info();
+            let listing = ctx.accounts.listing.to_account_info();
+            let escrow = ctx.accounts.escrow_token_account.to_account_info();
+            let buyer_token = ctx.accounts.buyer_token_account.to_account_info();
+            let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+            pnft.set_lock(UNLOCK_V1, &seller, seller_token, seller_record, &listing, &buyer, signer_seeds)?;
+            pnft.transfer(
+                (seller_token, &seller, seller_record),
+                (&escrow, &listing, escrow_record),
+                &listing,
+                &buyer,
+                &associated_token_program,
                 signer_seeds,
-            ),
-            1,
-            0,
-        )?;
+            )?;
+            pnft.transfer(
+                (&escrow, &listing, escrow_record),
+                (&buyer_token, &buyer, buyer_record),
+                &listing,
+                &buyer,
+                &associated_token_program,
+                signer_seeds,
+            )?;
+        } else {
+            transfer_checked_exact(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        mint: ctx.accounts.ticket_mint.to_account_info(),
+                        to: ctx.accounts.buyer_token_account.to_account_info(),
+                        authority: ctx.accounts.listing.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                1,
+                0,
+            )?;
+        }
 
         // Close the escrow token account (rent returned to seller)
         close_account(
@@ -211,12 +673,30 @@ pub mod ticketchain {
             ),
         )?;
 
+        record_resale_price(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.ticket_stats,
+            ctx.bumps.ticket_stats,
+            &mut ctx.accounts.floor_monitor,
+            ctx.bumps.floor_monitor,
+            price,
+        )?;
+        let buyer = ctx.accounts.buyer.key();
+        ctx.accounts.event.analytics.record_resale(&buyer, price)?;
+
+        let listing_key = ctx.accounts.listing.key();
+        if let Some(book) = ctx.accounts.ask_book.as_mut() {
+            book.remove(&listing_key);
+        }
+
         // Listing PDA is closed via `close = seller` at end of instruction
         Ok(())
     }
 
     /// Cancel a resale listing. Returns the NFT to the seller and closes the listing.
     pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        ensure_seller_ata(&ctx)?;
+
         let ticket_mint_key = ctx.accounts.ticket_mint.key();
         let bump = ctx.accounts.listing.bump;
         let signer_seeds: &[&[&[u8]]] = &[&[
@@ -225,21 +705,45 @@ pub mod ticketchain {
             &[bump],
         ]];
 
-        // Transfer NFT back to seller
-        transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.seller_token_account.to_account_info(),
-                    authority: ctx.accounts.listing.to_account_info(),
-                },
+        if ctx.accounts.escrow_token_account.amount == 0 {
+            // pNFT listings lock the ticket in the seller's wallet instead
+            let mint = ctx.accounts.ticket_mint.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            let token_program = ctx.accounts.token_program.to_account_info();
+            let pnft = PnftAccounts::new(
+                ctx.accounts.token_metadata_program.as_ref(),
+                ctx.accounts.ticket_metadata.as_ref(),
+                ctx.accounts.ticket_edition.as_ref(),
+                ctx.accounts.instructions.as_ref(),
+                &mint,
+                &system_program,
+                &token_program,
+            )?;
+            let token_record = ctx.accounts.seller_token_record.as_ref().ok_or(ErrorCode::MissingMetadataAccounts)?;
+            pnft.release_listing(
+                &ctx.accounts.seller.to_account_info(),
+                &ctx.accounts.seller_token_account.to_account_info(),
+                token_record,
+                &ctx.accounts.listing.to_account_info(),
                 signer_seeds,
-            ),
-            1,
-            0,
-        )?;
+            )?;
+        } else {
+            // Transfer NFT back to seller
+            transfer_checked_exact(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        mint: ctx.accounts.ticket_mint.to_account_info(),
+                        to: ctx.accounts.seller_token_account.to_account_info(),
+                        authority: ctx.accounts.listing.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                1,
+                0,
+            )?;
+        }
 
         // Close the escrow token account
         close_account(
@@ -254,195 +758,10029 @@ pub mod ticketchain {
             ),
         )?;
 
+        let listing_key = ctx.accounts.listing.key();
+        if let Some(book) = ctx.accounts.ask_book.as_mut() {
+            book.remove(&listing_key);
+        }
+
         // Listing PDA is closed via `close = seller`
         Ok(())
     }
 
     /// Close an event. Only the organizer can call this.
     /// Rent SOL is returned to the organizer. No tickets must have been sold.
-    pub fn close_event(_ctx: Context<CloseEvent>) -> Result<()> {
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn close_event(ctx: Context<CloseEvent>) -> Result<()> {
         // The `close = organizer` constraint on the event account
         // handles closing the account and returning rent.
         Ok(())
     }
-}
 
-// ── Account structs ──────────────────────────────────────────────────
+    /// Check a ticket holder in at the venue. Both the organizer (door staff) and the
+    /// holder sign; the holder must currently own the ticket. Each mint checks in once.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn check_in(ctx: Context<CheckIn>, ticket_index: u32) -> Result<()> {
+        require!(!ctx.accounts.event.identity_at_check_in, ErrorCode::IdentityPreimageRequired);
+        let note = ctx.accounts.receipt.as_ref().and_then(|r| {
+            r.note_for(&ctx.accounts.holder.key(), ctx.accounts.event.notes_survive_transfer)
+        });
+        record_attendance(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.attendance_record,
+            ctx.accounts.ticket_mint.key(),
+            ctx.accounts.holder.key(),
+            ticket_index,
+            ctx.accounts.receipt.as_ref().and_then(|r| r.category),
+            ctx.accounts.receipt.as_ref().map_or(0, |r| r.perks_bitmask),
+            note,
+            ctx.bumps.attendance_record,
+        )
+    }
 
-#[account]
-pub struct Event {
-    pub organizer: Pubkey,
-    pub nonce: u64,
-    pub title: String,
-    pub venue: String,
-    pub date_ts: i64,
-    pub tier_name: String,
-    pub price_lamports: u64,
-    pub supply: u32,
-    pub sold: u32,
-    pub artist_pct: u8,       // 0-80: artist's share of resale (platform always gets 20%)
-}
+    /// Create a post-event raffle among checked-in attendees. Only the organizer can call this.
+    /// `commitment` is `hash(randomness)` for the randomness later revealed in draw_raffle;
+    /// a zero commitment leaves the draw to the lock slot's hash alone. `draw_after_ts`
+    /// must leave ATTENDANCE_FINALIZE_SECONDS after the event for check-ins to finish.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn create_raffle(
+        ctx: Context<CreateRaffle>,
+        prize_count: u8,
+        draw_after_ts: i64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            prize_count > 0 && prize_count <= MAX_RAFFLE_WINNERS,
+            ErrorCode::InvalidPrizeCount
+        );
+        // Check-ins keep arriving after showtime; entrants are only locked once they're final
+        let finalized_ts = ctx
+            .accounts
+            .event
+            .date_ts
+            .checked_add(ATTENDANCE_FINALIZE_SECONDS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(draw_after_ts >= finalized_ts, ErrorCode::InvalidDrawTime);
 
-#[account]
-pub struct Listing {
-    pub seller: Pubkey,        // 32
-    pub event: Pubkey,         // 32
-    pub ticket_mint: Pubkey,   // 32
-    pub price_lamports: u64,   // 8
-    pub bump: u8,              // 1
-}
+        let raffle = &mut ctx.accounts.raffle;
+        raffle.event = ctx.accounts.event.key();
+        raffle.prize_count = prize_count;
+        raffle.draw_after_ts = draw_after_ts;
+        raffle.drawn = false;
+        raffle.seed = [0; 32];
+        raffle.winners = Vec::new();
+        raffle.claimed_mask = 0;
+        raffle.bump = ctx.bumps.raffle;
+        raffle.commitment = commitment;
+        raffle.lock_slot = 0;
+        raffle.entrants = 0;
 
-// ── Instruction contexts ─────────────────────────────────────────────
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(nonce: u64)]
-pub struct CreateEvent<'info> {
-    #[account(mut)]
-    pub organizer: Signer<'info>,
+    /// Lock a raffle's entrants once `draw_after_ts` has passed. Anyone can call this.
+    /// The draw uses the hash of the lock slot, which nobody knows when locking, and
+    /// attendees checked in after the lock can't win. If the lock slot ages out of
+    /// SlotHashes before a draw, anyone can lock again at a new slot, keeping the
+    /// entrants. The organizer missed their reveal, so the commitment is dropped and
+    /// the draw becomes permissionless; withholding a reveal can't stall the raffle.
+    pub fn lock_raffle(ctx: Context<LockRaffle>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(!raffle.drawn, ErrorCode::RaffleAlreadyDrawn);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp >= raffle.draw_after_ts, ErrorCode::RaffleNotReady);
 
-    #[account(
-        init,
-        payer = organizer,
-        space = 8 + 32 + 8 + 68 + 68 + 8 + 36 + 8 + 4 + 4 + 1,
-        seeds = [b"event", organizer.key().as_ref(), &nonce.to_le_bytes()],
-        bump
-    )]
-    pub event: Account<'info, Event>,
+        if raffle.lock_slot == 0 {
+            raffle.entrants = ctx.accounts.event.checked_in;
+        } else {
+            let slot_hashes_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+            require!(
+                slot_hash_at(&slot_hashes_data, raffle.lock_slot).is_none(),
+                ErrorCode::RaffleAlreadyLocked
+            );
+            raffle.commitment = [0; 32];
+        }
+        raffle.lock_slot = clock.slot;
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct BuyTicket<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    /// Draw the raffle winners. Callable once, after lock_raffle and while the lock slot
+    /// is still in SlotHashes (~512 slots). With a commitment, only the organizer can
+    /// draw: `randomness` must match it and is mixed with the lock slot's hash; the
+    /// organizer fixed one and can't see the other in advance, so can't steer the result.
+    /// Without one, anyone can draw and `randomness` is ignored. Winners are drawn from
+    /// attendance indices so only checked-in tickets can win.
+    pub fn draw_raffle(ctx: Context<DrawRaffle>, randomness: [u8; 32]) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(!raffle.drawn, ErrorCode::RaffleAlreadyDrawn);
+        require!(raffle.lock_slot != 0, ErrorCode::RaffleNotLocked);
+        let randomness = if raffle.commitment == [0; 32] {
+            [0; 32]
+        } else {
+            organizer_only(&ctx.accounts.event, &ctx.accounts.caller)?;
+            require!(hashv(&[&randomness]).to_bytes() == raffle.commitment, ErrorCode::RaffleRevealMismatch);
+            randomness
+        };
 
-    #[account(mut, address = event.organizer)]
-    pub organizer: SystemAccount<'info>,
+        let slot_hashes_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+        let slot_hash = slot_hash_at(&slot_hashes_data, raffle.lock_slot)
+            .ok_or(ErrorCode::SlotHashUnavailable)?;
+        let seed = hashv(&[&randomness, &slot_hash]).to_bytes();
 
-    #[account(
-        mut,
-        constraint = event.sold < event.supply @ ErrorCode::SoldOut
-    )]
-    pub event: Account<'info, Event>,
+        raffle.seed = seed;
+        raffle.winners = draw_winners(&seed, raffle.entrants, raffle.prize_count);
+        raffle.drawn = true;
 
-    /// CHECK: PDA used as mint authority for ticket mints.
-    #[account(
-        seeds = [b"ticket_authority", event.key().as_ref(), &event.sold.to_le_bytes()],
-        bump
-    )]
-    pub ticket_authority: AccountInfo<'info>,
+        emit!(RaffleDrawn {
+            event: raffle.event,
+            seed,
+            winners: raffle.winners.clone(),
+        });
 
-    #[account(
-        init,
-        payer = buyer,
-        mint::decimals = 0,
-        mint::authority = ticket_authority.key(),
-        seeds = [b"ticket_mint", event.key().as_ref(), &event.sold.to_le_bytes()],
-        bump
-    )]
-    pub ticket_mint: InterfaceAccount<'info, Mint>,
+        Ok(())
+    }
 
-    #[account(
-        init_if_needed,
-        payer = buyer,
-        associated_token::mint = ticket_mint,
-        associated_token::authority = buyer
-    )]
-    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Claim a raffle win. The caller must be the wallet that checked in the winning ticket.
+    pub fn claim_raffle_win(ctx: Context<ClaimRaffleWin>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        let attendance_index = ctx.accounts.attendance_record.attendance_index;
 
-    pub token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+        let slot = raffle
+            .winners
+            .iter()
+            .position(|w| *w == attendance_index)
+            .ok_or(ErrorCode::NotRaffleWinner)?;
+        let bit = 1u16 << slot;
+        require!(raffle.claimed_mask & bit == 0, ErrorCode::RaffleAlreadyClaimed);
+        raffle.claimed_mask |= bit;
 
-#[derive(Accounts)]
-pub struct ListForResale<'info> {
-    #[account(mut)]
-    pub seller: Signer<'info>,
+        Ok(())
+    }
 
-    pub event: Box<Account<'info, Event>>,
+    /// Create the resale statistics account for an event ahead of time. Only the organizer
+    /// can call this. Optional: `buy_resale` creates the account on first use.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn init_ticket_stats(ctx: Context<InitTicketStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.ticket_stats;
+        stats.event = ctx.accounts.event.key();
+        stats.price_history = [0; PRICE_HISTORY_LEN];
+        stats.sales_count = 0;
+        stats.bump = ctx.bumps.ticket_stats;
 
-    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+        Ok(())
+    }
 
-    #[account(
-        init,
-        payer = seller,
-        space = 8 + 32 + 32 + 32 + 8 + 1,
-        seeds = [b"listing", ticket_mint.key().as_ref()],
-        bump,
-    )]
-    pub listing: Box<Account<'info, Listing>>,
+    /// Compare a listing's price against the median of the last five resales.
+    /// Emits `FairValueEstimate`, plus `PriceDeviationWarning` when the listing is more
+    /// than 50% away from market. Informational only; never blocks a listing.
+    pub fn get_fair_value_estimate(ctx: Context<GetFairValueEstimate>) -> Result<()> {
+        let stats = &ctx.accounts.ticket_stats;
+        require!(
+            stats.sales_count as usize >= PRICE_HISTORY_LEN,
+            ErrorCode::InsufficientPriceHistory
+        );
 
-    #[account(
-        mut,
-        associated_token::mint = ticket_mint,
-        associated_token::authority = seller,
-    )]
-    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        let estimated_fair_value = median_of_five(stats.price_history);
+        let listing_price = ctx.accounts.listing.price_lamports;
+        let deviation_bps = if estimated_fair_value == 0 {
+            0
+        } else {
+            (listing_price.abs_diff(estimated_fair_value) as u128 * 10_000
+                / estimated_fair_value as u128) as u64
+        };
 
-    #[account(
-        init,
-        payer = seller,
-        token::mint = ticket_mint,
-        token::authority = listing,
-        seeds = [b"escrow", ticket_mint.key().as_ref()],
-        bump,
-    )]
-    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        let listing = ctx.accounts.listing.key();
+        emit!(FairValueEstimate {
+            listing,
+            estimated_fair_value,
+            deviation_bps,
+        });
+        if deviation_bps > PRICE_DEVIATION_WARNING_BPS {
+            emit!(PriceDeviationWarning {
+                listing,
+                listing_price,
+                estimated_fair_value,
+                deviation_bps,
+            });
+        }
 
-    pub token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct BuyResale<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    /// Set the tranche release schedule. Only the organizer can call this, and only
+    /// before the first sale. Tranches must be in ascending time order and the
+    /// scheduled counts may not exceed supply; an empty schedule releases everything.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_release_schedule(
+        ctx: Context<SetReleaseSchedule>,
+        release_schedule: Vec<ReleaseTranche>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(
+            release_schedule.len() <= MAX_RELEASE_TRANCHES,
+            ErrorCode::InvalidReleaseSchedule
+        );
 
-    /// CHECK: Seller receives 40%. Validated by listing.seller constraint.
-    #[account(mut, constraint = seller.key() == listing.seller @ ErrorCode::InvalidSeller)]
-    pub seller: AccountInfo<'info>,
+        let mut total: u32 = 0;
+        for (i, tranche) in release_schedule.iter().enumerate() {
+            require!(tranche.count > 0, ErrorCode::InvalidReleaseSchedule);
+            if i > 0 {
+                require!(
+                    tranche.release_ts > release_schedule[i - 1].release_ts,
+                    ErrorCode::InvalidReleaseSchedule
+                );
+            }
+            total = total.checked_add(tranche.count).ok_or(ErrorCode::Overflow)?;
+        }
+        require!(total <= event.supply, ErrorCode::InvalidReleaseSchedule);
 
-    /// CHECK: Organizer (artist) receives 40%. Validated by event.organizer.
-    #[account(mut, constraint = organizer.key() == event.organizer @ ErrorCode::InvalidOrganizer)]
-    pub organizer: AccountInfo<'info>,
+        event.release_schedule = release_schedule;
+        event.released_early = 0;
 
-    /// CHECK: Platform receives 20%.
-    #[account(mut)]
-    pub platform: AccountInfo<'info>,
+        Ok(())
+    }
 
-    pub event: Box<Account<'info, Event>>,
+    /// Release `count` additional tickets ahead of the schedule. Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn release_now(ctx: Context<SetReleaseSchedule>, count: u32) -> Result<()> {
+        require!(count > 0, ErrorCode::InvalidReleaseSchedule);
+        let event = &mut ctx.accounts.event;
+        event.released_early = event.released_early.checked_add(count).ok_or(ErrorCode::Overflow)?;
 
-    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"listing", ticket_mint.key().as_ref()],
-        bump = listing.bump,
-        constraint = listing.event == event.key(),
-        constraint = listing.ticket_mint == ticket_mint.key(),
-        close = seller,
+    /// Cap resale prices at `max_listing_multiple` percent of the primary price
+    /// (e.g. 200 = 2x). Zero removes the cap. Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_max_listing_multiple(
+        ctx: Context<SetMaxListingMultiple>,
+        max_listing_multiple: u16,
+    ) -> Result<()> {
+        ctx.accounts.event.max_listing_multiple = max_listing_multiple;
+        Ok(())
+    }
+
+    /// Change the asking price of an existing listing. The event's resale cap is
+    /// re-checked against its current value, and updates are rate-limited by the
+    /// platform's `min_price_update_interval_secs`.
+    pub fn update_listing_price(ctx: Context<UpdateListingPrice>, price_lamports: u64) -> Result<()> {
+        require!(price_lamports > 0, ErrorCode::InvalidPrice);
+        let tier_index = ctx.accounts.listing.tier_index;
+        check_listing_multiple(&ctx.accounts.event, tier_index, price_lamports)?;
+        check_resale_floor(&ctx.accounts.event, tier_index, price_lamports, Clock::get()?.unix_timestamp)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let listing = &mut ctx.accounts.listing;
+        require!(
+            now >= listing.last_price_update_at + ctx.accounts.platform_config.min_price_update_interval_secs,
+            ErrorCode::PriceUpdateTooFrequent
+        );
+        listing.price_lamports = price_lamports;
+        listing.usd_price_cents = 0;
+        listing.price_feed = Pubkey::default();
+        listing.last_price_update_at = now;
+
+        let listing_key = ctx.accounts.listing.key();
+        if let Some(book) = ctx.accounts.ask_book.as_mut() {
+            book.offer(listing_key, price_lamports);
+        }
+        Ok(())
+    }
+
+    /// Reserve `count` tickets for a sponsor outside the purchasable pool. Only the
+    /// organizer can call this. The beneficiary claims them at `price_lamports` each
+    /// (may be zero) before `expires_at`; afterwards anyone can release the hold.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn create_hold(
+        ctx: Context<CreateHold>,
+        beneficiary: Pubkey,
+        count: u32,
+        price_lamports: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(count > 0, ErrorCode::InvalidHold);
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidHold);
+
+        let event = &mut ctx.accounts.event;
+        require!(!event.use_pnft, ErrorCode::PnftUnsupported);
+        let reserved = event
+            .sold
+            .checked_add(event.held)
+            .and_then(|r| r.checked_add(event.pass_reserved))
+            .and_then(|r| r.checked_add(count))
+            .ok_or(ErrorCode::Overflow)?;
+        require!(reserved <= event.supply && count <= tier_available(event, 0)?, ErrorCode::InsufficientSupply);
+        event.holds_expire_at = if event.held == 0 { expires_at } else { event.holds_expire_at.min(expires_at) };
+        event.held += count;
+
+        let hold = &mut ctx.accounts.hold;
+        hold.event = event.key();
+        hold.beneficiary = beneficiary;
+        hold.remaining = count;
+        hold.price_lamports = price_lamports;
+        hold.expires_at = expires_at;
+        hold.bump = ctx.bumps.hold;
+
+        Ok(())
+    }
+
+    /// Claim one held ticket. The beneficiary pays the hold price to the organizer and
+    /// receives a freshly minted ticket. Rejected once the hold has expired, and, as in
+    /// buy_ticket, while sales are paused or once the event is cancelled, superseded or
+    /// started. Identity mode events need the beneficiary's `identity_commitment`, as in
+    /// buy_ticket.
+    pub fn claim_hold(ctx: Context<ClaimHold>, identity_commitment: Option<[u8; 32]>) -> Result<()> {
+        let hold = &ctx.accounts.hold;
+        let event = &ctx.accounts.event;
+        let now = Clock::get()?.unix_timestamp;
+        require!(hold.remaining > 0, ErrorCode::InvalidHold);
+        require!(!event.sales_paused, ErrorCode::SalesPaused);
+        require!(!event.cancelled, ErrorCode::EventCancelled);
+        require!(!is_superseded(event), ErrorCode::EventSuperseded);
+        require!(now < event.date_ts, ErrorCode::EventEnded);
+        require!(!event.use_pnft, ErrorCode::PnftUnsupported);
+        require!(now < hold.expires_at, ErrorCode::HoldExpired);
+
+        // Transfer the hold price from beneficiary to organizer, or to the vault
+        // while refunds or vesting apply
+        if hold.price_lamports > 0 {
+            let proceeds_to = match (proceeds_escrowed(&ctx.accounts.event), &ctx.accounts.vault) {
+                (false, _) => ctx.accounts.organizer.to_account_info(),
+                (true, Some(vault)) => vault.to_account_info(),
+                (true, None) => return err!(ErrorCode::InvalidVault),
+            };
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.beneficiary.to_account_info(),
+                        to: proceeds_to,
+                    },
+                ),
+                hold.price_lamports,
+            )?;
+        }
+        let beneficiary = ctx.accounts.beneficiary.key();
+        ctx.accounts.event.analytics.record_primary_sale(&beneficiary, hold.price_lamports)?;
+        let identity_commitment = take_identity_commitment(
+            &ctx.accounts.event,
+            identity_commitment,
+            ctx.accounts.identity_purchase.as_ref(),
+            &ctx.accounts.beneficiary.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Mint the held ticket; program PDA signs as mint authority
+        let custom_name = take_name_override(&ctx.accounts.name_override, &ctx.accounts.organizer.to_account_info())?;
+        let event_key = ctx.accounts.event.key();
+        let sold = ctx.accounts.event.sold;
+        let sold_bytes = sold.to_le_bytes();
+        let bump = ctx.bumps.ticket_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"ticket_authority",
+            event_key.as_ref(),
+            &sold_bytes,
+            &[bump],
+        ]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                    authority: ctx.accounts.ticket_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        create_ticket_metadata(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.ticket_metadata.as_ref(),
+            &ctx.accounts.ticket_mint.to_account_info(),
+            &ctx.accounts.ticket_authority.to_account_info(),
+            &ctx.accounts.beneficiary.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.event,
+            sold,
+            custom_name.as_deref(),
+            signer_seeds,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.ticket_mint.to_account_info(),
+            ctx.accounts.ticket_authority.to_account_info(),
+            signer_seeds,
+        )?;
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.buyer = ctx.accounts.beneficiary.key();
+        receipt.event = event_key;
+        receipt.ticket_mint = ctx.accounts.ticket_mint.key();
+        receipt.paid_lamports = ctx.accounts.hold.price_lamports;
+        receipt.purchased_ts = Clock::get()?.unix_timestamp;
+        receipt.refunded = false;
+        receipt.category = None;
+        receipt.payment_index = None;
+        receipt.tip_lamports = 0;
+        receipt.perks_bitmask = ctx.accounts.event.tiers[0].perks_bitmask;
+        receipt.deposit_lamports = 0;
+        receipt.note = [0; 32];
+        receipt.note_holder = ctx.accounts.beneficiary.key();
+        receipt.identity_commitment = identity_commitment;
+        receipt.tier_index = 0;
+        receipt.bump = ctx.bumps.receipt;
+
+        emit!(TicketPurchased {
+            event: event_key,
+            buyer: beneficiary,
+            ticket_mint: receipt.ticket_mint,
+            price_lamports: receipt.paid_lamports,
+            tip_lamports: 0,
+            donation_lamports: 0,
+            custom_name,
+        });
+
+        // Move one ticket from the held pool to sold
+        let hold = &mut ctx.accounts.hold;
+        hold.remaining -= 1;
+        let event = &mut ctx.accounts.event;
+        event.held -= 1;
+        record_tier_sale(event, 0, 1)?;
+        if event.grace_period_seconds > 0 {
+            event.refundable_until = receipt.purchased_ts + event.grace_period_seconds as i64;
+        }
+
+        Ok(())
+    }
+
+    /// Release an expired hold back into the purchasable pool. Permissionless;
+    /// the hold account's rent is returned to the organizer.
+    pub fn release_expired_hold(ctx: Context<ReleaseExpiredHold>) -> Result<()> {
+        let hold = &ctx.accounts.hold;
+        require!(Clock::get()?.unix_timestamp >= hold.expires_at, ErrorCode::HoldNotExpired);
+
+        let event = &mut ctx.accounts.event;
+        event.held = event.held.saturating_sub(hold.remaining);
+
+        // Hold PDA is closed via `close = organizer`
+        Ok(())
+    }
+
+    /// Record an on-chain no-show attestation for a ticket that was never checked in.
+    /// Callable by the current holder once the event started more than two hours ago.
+    /// Does not refund; insurance oracles can read the resulting ClaimNoShow PDA.
+    pub fn claim_no_show(ctx: Context<ClaimNoShow>, _ticket_index: u32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.event.date_ts.saturating_add(NO_SHOW_DELAY_SECONDS),
+            ErrorCode::EventNotOver
+        );
+        require!(
+            ctx.accounts.attendance_record.data_is_empty(),
+            ErrorCode::TicketCheckedIn
+        );
+
+        let no_show = &mut ctx.accounts.no_show;
+        no_show.event = ctx.accounts.event.key();
+        no_show.ticket_mint = ctx.accounts.ticket_mint.key();
+        no_show.holder = ctx.accounts.holder.key();
+        no_show.claimed_ts = now;
+        no_show.bump = ctx.bumps.no_show;
+
+        Ok(())
+    }
+
+    /// Configure sales tax on primary sales: `tax_bps` of every ticket price is sent to
+    /// `tax_wallet` instead of the organizer. Only the organizer can call this, and only
+    /// before the first sale. Tax is remitted immediately and is not clawed back.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_tax_config(ctx: Context<SetTaxConfig>, tax_bps: u16, tax_wallet: Pubkey) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(tax_bps as u32 + event.donation_bps as u32 <= 10_000, ErrorCode::InvalidTaxConfig);
+        require!(tax_bps == 0 || tax_wallet != Pubkey::default(), ErrorCode::InvalidTaxConfig);
+        require!(tax_bps == 0 || !accepts_token_payments(event), ErrorCode::TokenPaymentConflict);
+
+        event.tax_bps = tax_bps;
+        event.tax_wallet = tax_wallet;
+
+        Ok(())
+    }
+
+    /// Create the singleton platform config. Only the program's upgrade authority can call this.
+    pub fn init_platform_config(ctx: Context<InitPlatformConfig>, authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.platform_config;
+        config.authority = authority;
+        config.max_ticket_price = MAX_TICKET_PRICE_LAMPORTS;
+        config.min_price_update_interval_secs = DEFAULT_PRICE_UPDATE_INTERVAL_SECS;
+        config.attestor_key = Pubkey::default();
+        config.monitor_reward_fund = 0;
+        config.royalty_release_delay_seconds = DEFAULT_ROYALTY_RELEASE_DELAY_SECONDS;
+        config.bump = ctx.bumps.platform_config;
+        config.messaging_program = None;
+        config.personhood_provider = PERSONHOOD_CIVIC;
+        config.personhood_network = Pubkey::default();
+        config.resale_fees_collected = 0;
+        config.resale_fee_count = 0;
+
+        Ok(())
+    }
+
+    /// Set or clear a negotiated platform share of this event's resales, at most the
+    /// event's platform_bps; the seller keeps the difference. Needs both the organizer's
+    /// and the platform authority's signatures, and only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_fee_override(ctx: Context<SetFeeOverride>, platform_fee_override_bps: Option<u16>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        if let Some(bps) = platform_fee_override_bps {
+            require!(bps <= event.platform_bps, ErrorCode::FeeOverrideAboveDefault);
+        }
+        event.platform_fee_override_bps = platform_fee_override_bps;
+        Ok(())
+    }
+
+    /// Grow a PlatformConfig created before the appended fields to PLATFORM_CONFIG_LEN.
+    /// The new fields start at their init_platform_config defaults. Only the platform
+    /// authority can call this, and pays the extra rent; a current config is left as is.
+    pub fn migrate_platform_config(ctx: Context<MigratePlatformConfig>) -> Result<()> {
+        let info = ctx.accounts.platform_config.to_account_info();
+        let len = info.data_len();
+        {
+            let data = info.try_borrow_data()?;
+            require!(
+                len >= PLATFORM_CONFIG_V1_LEN && data[..8] == <PlatformConfig as anchor_lang::Discriminator>::DISCRIMINATOR,
+                anchor_lang::error::ErrorCode::AccountDidNotDeserialize
+            );
+            require!(data[8..40] == *ctx.accounts.authority.key.as_ref(), ErrorCode::Unauthorized);
+        }
+        if len >= PLATFORM_CONFIG_LEN {
+            return Ok(());
+        }
+
+        let shortfall = Rent::get()?.minimum_balance(PLATFORM_CONFIG_LEN).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        info.realloc(PLATFORM_CONFIG_LEN, true)?;
+
+        // Zeroed bytes already read as messaging_program = None and a default network
+        let mut data = info.try_borrow_mut_data()?;
+        data[PLATFORM_CONFIG_V1_LEN + 1] = PERSONHOOD_CIVIC;
+        Ok(())
+    }
+
+    /// Mark an event's organizer as verified, recording when. Platform authority only.
+    pub fn verify_organizer(ctx: Context<SetOrganizerVerification>) -> Result<()> {
+        require!(!ctx.accounts.event.social_link.is_empty(), ErrorCode::SocialLinkInvalid);
+        ctx.accounts.event.verification_ts = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Clear an event's organizer verification. Platform authority only.
+    pub fn revoke_organizer_verification(ctx: Context<SetOrganizerVerification>) -> Result<()> {
+        ctx.accounts.event.verification_ts = 0;
+        Ok(())
+    }
+
+    /// Escrow a ticket behind a claim link. `claim_key` is the public half of a one-time
+    /// ed25519 keypair whose secret travels in the link; whoever gets it to sign their
+    /// own wallet before `expires_at` receives the ticket.
+    pub fn create_claim_link(
+        ctx: Context<CreateClaimLink>,
+        claim_key: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+
+        // Transfer NFT from sender to the claim escrow
+        transfer_checked_exact(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.sender_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.sender.to_account_info(),
+                },
+            ),
+            1,
+            0,
+        )?;
+
+        let link = &mut ctx.accounts.claim_link;
+        link.sender = ctx.accounts.sender.key();
+        link.ticket_mint = ctx.accounts.ticket_mint.key();
+        link.claim_key = claim_key;
+        link.expires_at = expires_at;
+        link.bump = ctx.bumps.claim_link;
+
+        Ok(())
+    }
+
+    /// Claim an escrowed ticket. An ed25519 instruction earlier in the transaction must
+    /// carry the link's claim key signing `"claim_link" || ticket_mint || claimer`, so a
+    /// copied claim transaction can't be replayed to another wallet. The ticket is
+    /// delivered to the claimer's ATA and the link is closed (rent to the sender).
+    pub fn claim_via_link(ctx: Context<ClaimViaLink>) -> Result<()> {
+        let link = &ctx.accounts.claim_link;
+        require!(Clock::get()?.unix_timestamp < link.expires_at, ErrorCode::ClaimLinkExpired);
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let message = [
+            b"claim_link".as_ref(),
+            ticket_mint_key.as_ref(),
+            ctx.accounts.claimer.key().as_ref(),
+        ]
+        .concat();
+        verify_ed25519_attestation(&ctx.accounts.instructions, &link.claim_key, &message)
+            .map_err(|_| ErrorCode::InvalidClaimSignature)?;
+
+        let bump = link.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"claim_link",
+            ticket_mint_key.as_ref(),
+            &[bump],
+        ]];
+
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.claimer_token_account.to_account_info(),
+                    authority: ctx.accounts.claim_link.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account (rent returned to sender)
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.sender.to_account_info(),
+                    authority: ctx.accounts.claim_link.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        // ClaimLink PDA is closed via `close = sender`
+        Ok(())
+    }
+
+    /// Return an unclaimed ticket to the sender. The sender can cancel at any time;
+    /// once the link has expired anyone can trigger the return.
+    pub fn cancel_claim_link(ctx: Context<CancelClaimLink>) -> Result<()> {
+        let link = &ctx.accounts.claim_link;
+        require!(
+            ctx.accounts.caller.key() == link.sender
+                || Clock::get()?.unix_timestamp >= link.expires_at,
+            ErrorCode::ClaimLinkActive
+        );
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let bump = link.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"claim_link",
+            ticket_mint_key.as_ref(),
+            &[bump],
+        ]];
+
+        // Transfer NFT back to sender
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.sender_token_account.to_account_info(),
+                    authority: ctx.accounts.claim_link.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.sender.to_account_info(),
+                    authority: ctx.accounts.claim_link.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        // ClaimLink PDA is closed via `close = sender`
+        Ok(())
+    }
+
+    /// Create the resale order book for an event. Permissionless; the caller pays rent.
+    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+        let book = &mut ctx.accounts.order_book;
+        book.event = ctx.accounts.event.key();
+        book.buy_orders = Vec::new();
+        book.sell_orders = Vec::new();
+        book.bump = ctx.bumps.order_book;
+
+        Ok(())
+    }
+
+    /// Place a bid for up to `quantity` tickets at `price` lamports each, valid until
+    /// `expires_at`. The full amount plus a BID_REFUND_TIP_LAMPORTS deposit is escrowed in
+    /// the order book PDA. Bids are kept sorted by price, descending.
+    pub fn place_buy_order(
+        ctx: Context<PlaceBuyOrder>,
+        price: u64,
+        quantity: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidPrice);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+        let book = &mut ctx.accounts.order_book;
+        require!(book.buy_orders.len() < MAX_BOOK_ORDERS, ErrorCode::OrderBookFull);
+
+        let total = price
+            .checked_mul(quantity as u64)
+            .and_then(|t| t.checked_add(BID_REFUND_TIP_LAMPORTS))
+            .ok_or(ErrorCode::Overflow)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.bidder.to_account_info(),
+                    to: book.to_account_info(),
+                },
+            ),
+            total,
+        )?;
+
+        // Insert after all bids at an equal or better price (price-time priority)
+        let pos = book.buy_orders.partition_point(|o| o.price >= price);
+        book.buy_orders.insert(pos, OrderBookEntry {
+            price,
+            quantity,
+            wallet: ctx.accounts.bidder.key(),
+            ticket_mint: Pubkey::default(),
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Offer one ticket (the event's ticket `ticket_index`) at `price` lamports until
+    /// `expires_at`. The ticket is escrowed under the order book PDA. Asks are kept
+    /// sorted by price, ascending.
+    pub fn place_sell_order(
+        ctx: Context<PlaceSellOrder>,
+        price: u64,
+        _ticket_index: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidPrice);
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < ctx.accounts.event.date_ts, ErrorCode::EventEnded);
+        require!(
+            expires_at > now && expires_at <= ctx.accounts.event.date_ts,
+            ErrorCode::InvalidExpiry
+        );
+        let tier_index = ticket_tier(&ctx.accounts.event, ctx.accounts.purchase_receipt.as_deref().map(|r| &**r))?;
+        check_listing_multiple(&ctx.accounts.event, tier_index, price)?;
+        check_resale_floor(&ctx.accounts.event, tier_index, price, Clock::get()?.unix_timestamp)?;
+        check_original_buyer(
+            &ctx.accounts.event,
+            ctx.accounts.receipt.as_deref().map(|r| &**r),
+            &ctx.accounts.seller.key(),
+        )?;
+        check_resale_blackout(
+            &ctx.accounts.event,
+            ctx.accounts.purchase_receipt.as_deref().map(|r| &**r),
+            Clock::get()?.unix_timestamp,
+        )?;
+        require!(
+            ctx.accounts.order_book.sell_orders.len() < MAX_BOOK_ORDERS,
+            ErrorCode::OrderBookFull
+        );
+        require!(!ctx.accounts.event.use_pnft, ErrorCode::PnftUnsupported);
+
+        check_escrow_clean(&ctx.accounts.escrow_token_account, &ctx.accounts.order_book.key())?;
+
+        // Transfer NFT from seller to the order book escrow
+        transfer_checked_exact(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.seller_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            1,
+            0,
+        )?;
+
+        let book = &mut ctx.accounts.order_book;
+        let pos = book.sell_orders.partition_point(|o| o.price <= price);
+        book.sell_orders.insert(pos, OrderBookEntry {
+            price,
+            quantity: 1,
+            wallet: ctx.accounts.seller.key(),
+            ticket_mint: ctx.accounts.ticket_mint.key(),
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Crank: execute the best live bid against the best live ask when they cross.
+    /// Expired orders are skipped, so they can't wedge the book while they wait for
+    /// refund_expired_offer / cancel_sell_order. Fills one ticket (the event's ticket
+    /// `ticket_index`) at the ask price, split like `buy_resale`; the bidder is refunded
+    /// the difference from their escrowed bid.
+    pub fn match_orders(ctx: Context<MatchOrders>, _ticket_index: u32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        require!(!ctx.accounts.event.resale_paused, ErrorCode::ResalePaused);
+        require!(now < ctx.accounts.event.date_ts, ErrorCode::EventEnded);
+        require!(
+            !resale_competes_with_primary(&ctx.accounts.event, now),
+            ErrorCode::PrimaryStillAvailable
+        );
+        let book = &ctx.accounts.order_book;
+        let bid_pos = book.buy_orders.iter().position(|o| now < o.expires_at);
+        let ask_pos = book.sell_orders.iter().position(|o| now < o.expires_at);
+        let (bid_pos, ask_pos, bid, ask) = match (bid_pos, ask_pos) {
+            (Some(b), Some(a)) if book.buy_orders[b].price >= book.sell_orders[a].price => {
+                (b, a, book.buy_orders[b].clone(), book.sell_orders[a].clone())
+            }
+            _ => return err!(ErrorCode::NoCrossingOrders),
+        };
+        require!(ctx.accounts.buyer.key() == bid.wallet, ErrorCode::InvalidBuyer);
+        require!(ctx.accounts.seller.key() == ask.wallet, ErrorCode::InvalidSeller);
+        require!(ctx.accounts.ticket_mint.key() == ask.ticket_mint, ErrorCode::InvalidTicketMint);
+        // Re-checked at fill time: the organizer may have enabled the mode, or tightened
+        // the cap or floor, since the ask rested
+        check_original_buyer(
+            &ctx.accounts.event,
+            ctx.accounts.receipt.as_deref().map(|r| &**r),
+            &ask.wallet,
+        )?;
+        let price = ask.price;
+        let tier_index = ticket_tier(&ctx.accounts.event, ctx.accounts.deposit_receipt.as_deref().map(|r| &**r))?;
+        check_listing_multiple(&ctx.accounts.event, tier_index, price)?;
+        check_resale_floor(&ctx.accounts.event, tier_index, price, now)?;
+
+        let deposit = resale_deposit(
+            &ctx.accounts.event,
+            ctx.accounts.deposit_receipt.as_deref().map(|r| &**r),
+            price,
+        )?;
+        let (artist_share, seller_share, platform_share) = resale_split(&ctx.accounts.event, price - deposit);
+        let refund = bid.price - price;
+
+        // Pay out of the escrowed bid
+        let book_info = ctx.accounts.order_book.to_account_info();
+        let artist_to = royalty_recipient(
+            &ctx.accounts.event,
+            &ctx.accounts.organizer,
+            ctx.accounts.royalty_escrow.as_deref_mut(),
+            artist_share,
+        )?;
+        transfer_lamports_from_pda(&book_info, &artist_to, artist_share)?;
+        transfer_lamports_from_pda(&book_info, &ctx.accounts.seller.to_account_info(), seller_share + deposit)?;
+        transfer_lamports_from_pda(&book_info, &ctx.accounts.platform.to_account_info(), platform_share)?;
+        record_platform_fee(
+            &mut ctx.accounts.platform_config,
+            &mut ctx.accounts.event,
+            ctx.accounts.fee_day.as_deref_mut(),
+            platform_share,
+        )?;
+        transfer_lamports_from_pda(&book_info, &ctx.accounts.buyer.to_account_info(), refund)?;
+
+        // Transfer NFT from the order book escrow to buyer
+        let event_key = ctx.accounts.event.key();
+        let bump = ctx.accounts.order_book.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"orderbook",
+            event_key.as_ref(),
+            &[bump],
+        ]];
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: book_info.clone(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account (rent returned to seller)
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.seller.to_account_info(),
+                    authority: book_info,
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        record_resale_price(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.ticket_stats,
+            ctx.bumps.ticket_stats,
+            &mut ctx.accounts.floor_monitor,
+            ctx.bumps.floor_monitor,
+            price,
+        )?;
+        let buyer = ctx.accounts.buyer.key();
+        ctx.accounts.event.analytics.record_resale(&buyer, price)?;
+
+        let book = &mut ctx.accounts.order_book;
+        book.sell_orders.remove(ask_pos);
+        if bid.quantity > 1 {
+            book.buy_orders[bid_pos].quantity -= 1;
+        } else {
+            // Fully filled: the refund-tip deposit goes back to the bidder
+            book.buy_orders.remove(bid_pos);
+            transfer_lamports_from_pda(
+                &ctx.accounts.order_book.to_account_info(),
+                &ctx.accounts.buyer.to_account_info(),
+                BID_REFUND_TIP_LAMPORTS,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a resting bid and refund its remaining escrow. Only the bidder can call this.
+    pub fn cancel_buy_order(ctx: Context<CancelBuyOrder>, index: u8) -> Result<()> {
+        let book = &mut ctx.accounts.order_book;
+        let order = book.buy_orders.get(index as usize).ok_or(ErrorCode::InvalidOrderIndex)?;
+        require!(order.wallet == ctx.accounts.bidder.key(), ErrorCode::InvalidBuyer);
+
+        let refund = order.price * order.quantity as u64 + BID_REFUND_TIP_LAMPORTS;
+        book.buy_orders.remove(index as usize);
+        transfer_lamports_from_pda(
+            &ctx.accounts.order_book.to_account_info(),
+            &ctx.accounts.bidder.to_account_info(),
+            refund,
+        )?;
+
+        Ok(())
+    }
+
+    /// Permissionless: refund an expired bid's escrow to its bidder and remove it from
+    /// the book. The caller receives the bid's BID_REFUND_TIP_LAMPORTS deposit.
+    pub fn refund_expired_offer(ctx: Context<RefundExpiredOffer>, index: u8) -> Result<()> {
+        let book = &mut ctx.accounts.order_book;
+        let order = book.buy_orders.get(index as usize).ok_or(ErrorCode::InvalidOrderIndex)?;
+        require!(order.wallet == ctx.accounts.bidder.key(), ErrorCode::InvalidBuyer);
+        require!(Clock::get()?.unix_timestamp >= order.expires_at, ErrorCode::OfferNotExpired);
+
+        let refund = order.price * order.quantity as u64;
+        book.buy_orders.remove(index as usize);
+        let book_info = ctx.accounts.order_book.to_account_info();
+        transfer_lamports_from_pda(&book_info, &ctx.accounts.bidder.to_account_info(), refund)?;
+        transfer_lamports_from_pda(&book_info, &ctx.accounts.cranker.to_account_info(), BID_REFUND_TIP_LAMPORTS)?;
+
+        Ok(())
+    }
+
+    /// Cancel a resting ask and return the escrowed ticket to its seller. The seller can
+    /// cancel at any time; once the ask has expired or its ticket is flagged, anyone can.
+    pub fn cancel_sell_order(ctx: Context<CancelSellOrder>) -> Result<()> {
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let index = ctx
+            .accounts
+            .order_book
+            .sell_orders
+            .iter()
+            .position(|o| o.ticket_mint == ticket_mint_key)
+            .ok_or(ErrorCode::InvalidOrderIndex)?;
+        let order = &ctx.accounts.order_book.sell_orders[index];
+        require!(order.wallet == ctx.accounts.seller.key(), ErrorCode::InvalidSeller);
+        require!(
+            ctx.accounts.caller.key() == order.wallet
+                || Clock::get()?.unix_timestamp >= order.expires_at
+                || !ctx.accounts.ticket_flag.data_is_empty(),
+            ErrorCode::OrderStillActive
+        );
+
+        let event_key = ctx.accounts.order_book.event;
+        let bump = ctx.accounts.order_book.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"orderbook",
+            event_key.as_ref(),
+            &[bump],
+        ]];
+
+        // Transfer NFT back to seller
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.seller_token_account.to_account_info(),
+                    authority: ctx.accounts.order_book.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.seller.to_account_info(),
+                    authority: ctx.accounts.order_book.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        ctx.accounts.order_book.sell_orders.remove(index);
+        Ok(())
+    }
+
+    /// Restrict resale listings to original buyers (wallets holding a primary Receipt).
+    /// Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_original_buyer_only(ctx: Context<SetOriginalBuyerOnly>, enabled: bool) -> Result<()> {
+        ctx.accounts.event.require_original_buyer_only = enabled;
+        Ok(())
+    }
+
+    /// Offer a ticket to `recipient`. The ticket is escrowed until the recipient
+    /// accepts, the sender cancels, or the offer expires.
+    /// An optional `message` is stored on a GiftNote PDA (sender pays rent) that the
+    /// recipient can read and later close.
+    pub fn offer_transfer(
+        ctx: Context<OfferTransfer>,
+        recipient: Pubkey,
+        expires_at: i64,
+        message: Option<String>,
+    ) -> Result<()> {
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+        require!(recipient != ctx.accounts.sender.key(), ErrorCode::InvalidRecipient);
+
+        match (message, ctx.accounts.gift_note.as_mut()) {
+            (Some(message), Some(note)) => {
+                require!(message.len() <= MAX_GIFT_MESSAGE_LEN, ErrorCode::GiftMessageTooLong);
+                note.sender = ctx.accounts.sender.key();
+                note.recipient = recipient;
+                note.ticket_mint = ctx.accounts.ticket_mint.key();
+                note.message = message;
+                note.bump = ctx.bumps.gift_note.ok_or(ErrorCode::GiftNoteMismatch)?;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::GiftNoteMismatch),
+        }
+
+        // Transfer NFT from sender to the transfer escrow
+        transfer_checked_exact(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.sender_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.sender.to_account_info(),
+                },
+            ),
+            1,
+            0,
+        )?;
+
+        let offer = &mut ctx.accounts.transfer_offer;
+        offer.sender = ctx.accounts.sender.key();
+        offer.recipient = recipient;
+        offer.ticket_mint = ctx.accounts.ticket_mint.key();
+        offer.expires_at = expires_at;
+        offer.bump = ctx.bumps.transfer_offer;
+
+        Ok(())
+    }
+
+    /// Accept a pending transfer. Only the named recipient can call this, before expiry.
+    pub fn accept_transfer(ctx: Context<AcceptTransfer>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.transfer_offer.expires_at,
+            ErrorCode::TransferOfferExpired
+        );
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let bump = ctx.accounts.transfer_offer.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"transfer_offer",
+            ticket_mint_key.as_ref(),
+            &[bump],
+        ]];
+
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.transfer_offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account (rent returned to sender)
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.sender.to_account_info(),
+                    authority: ctx.accounts.transfer_offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        // TransferOffer PDA is closed via `close = sender`
+        Ok(())
+    }
+
+    /// Return a pending transfer to the sender. The sender can cancel at any time;
+    /// once the offer has expired anyone can sweep it back.
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+        let offer = &ctx.accounts.transfer_offer;
+        require!(
+            ctx.accounts.caller.key() == offer.sender
+                || Clock::get()?.unix_timestamp >= offer.expires_at,
+            ErrorCode::TransferOfferActive
+        );
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let bump = offer.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"transfer_offer",
+            ticket_mint_key.as_ref(),
+            &[bump],
+        ]];
+
+        // Transfer NFT back to sender
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.sender_token_account.to_account_info(),
+                    authority: ctx.accounts.transfer_offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        // Close the escrow token account
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.sender.to_account_info(),
+                    authority: ctx.accounts.transfer_offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        // TransferOffer PDA is closed via `close = sender`
+        Ok(())
+    }
+
+    /// Close a gift note. The recipient (or the sender) can call this; rent goes back
+    /// to the sender who paid it.
+    pub fn close_gift_note(ctx: Context<CloseGiftNote>) -> Result<()> {
+        let note = &ctx.accounts.gift_note;
+        let caller = ctx.accounts.caller.key();
+        require!(caller == note.recipient || caller == note.sender, ErrorCode::Unauthorized);
+
+        // GiftNote PDA is closed via `close = sender`
+        Ok(())
+    }
+
+    /// Set the refund grace period. Only the organizer can call this, and only before
+    /// the first sale so every buyer gets the same window. While non-zero, primary
+    /// proceeds are held in the event vault until the last open window has closed.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_grace_period(ctx: Context<SetGracePeriod>, grace_period_seconds: u32) -> Result<()> {
+        require!(ctx.accounts.event.sold == 0, ErrorCode::EventHasSales);
+
+        let vault = &mut ctx.accounts.vault;
+        vault.event = ctx.accounts.event.key();
+        vault.bump = ctx.bumps.vault;
+
+        ctx.accounts.event.grace_period_seconds = grace_period_seconds;
+        Ok(())
+    }
+
+    /// Refund a primary purchase within the grace period. Burns the ticket and returns
+    /// the price from the vault; sales tax and donations already remitted are final and
+    /// not refunded. Each receipt can be refunded once.
+    pub fn request_grace_refund(ctx: Context<RequestGraceRefund>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let receipt = &ctx.accounts.receipt;
+        require!(!receipt.refunded, ErrorCode::AlreadyRefunded);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            event.grace_period_seconds > 0
+                && now - receipt.purchased_ts <= event.grace_period_seconds as i64,
+            ErrorCode::OutsideGracePeriod
+        );
+        require!(!event.use_pnft, ErrorCode::PnftUnsupported);
+
+        burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        let tax = (receipt.paid_lamports as u128 * event.tax_bps as u128 / 10_000) as u64;
+        let donation = (receipt.paid_lamports as u128 * event.donation_bps as u128 / 10_000) as u64;
+        let refund = receipt.paid_lamports - tax - donation;
+        let payment_mint = receipt
+            .payment_index
+            .and_then(|index| event.accepted_payments.get(index as usize))
+            .and_then(|option| option.mint);
+        if let Some(payment_mint) = payment_mint {
+            // Repay in the mint that was paid, out of the vault's account for that mint
+            let (Some(mint), Some(from), Some(to)) = (
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.vault_payment_account,
+                &ctx.accounts.buyer_payment_account,
+            ) else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+            require_keys_eq!(mint.key(), payment_mint, ErrorCode::InvalidPaymentOption);
+            require!(
+                from.mint == payment_mint && from.owner == ctx.accounts.vault.key(),
+                ErrorCode::InvalidPaymentAccount
+            );
+            let event_key = event.key();
+            let vault_seeds: &[&[&[u8]]] = &[&[b"vault", event_key.as_ref(), &[ctx.accounts.vault.bump]]];
+            transfer_checked_exact(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    vault_seeds,
+                ),
+                refund,
+                mint.decimals,
+            )?;
+        } else {
+            transfer_lamports_from_pda(
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.buyer.to_account_info(),
+                refund,
+            )?;
+            ctx.accounts.event.analytics.record_refund(refund)?;
+        }
+
+        // The attendance deposit comes back in full with the ticket
+        let deposit = ctx.accounts.receipt.deposit_lamports;
+        if deposit > 0 {
+            let escrow = ctx.accounts.deposit_escrow.as_ref().ok_or(ErrorCode::MissingDepositEscrow)?;
+            transfer_lamports_from_pda(
+                &escrow.to_account_info(),
+                &ctx.accounts.buyer.to_account_info(),
+                deposit,
+            )?;
+            ctx.accounts.receipt.deposit_lamports = 0;
+            ctx.accounts.event.deposits_held -= deposit;
+        }
+
+        require!(
+            ctx.accounts.receipt.identity_commitment == [0; 32] || ctx.accounts.identity_purchase.is_some(),
+            ErrorCode::IdentityCommitmentRequired
+        );
+        ctx.accounts.receipt.refunded = true;
+        Ok(())
+    }
+
+    /// Withdraw vaulted primary proceeds to the organizer once every refund window
+    /// has closed, limited to the vested amount when a vesting schedule is set. Only
+    /// the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn withdraw_proceeds(ctx: Context<WithdrawProceeds>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now > ctx.accounts.event.refundable_until, ErrorCode::ProceedsLocked);
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+
+        let vault = ctx.accounts.vault.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(vault.data_len());
+        let escrowed = vault.lamports().saturating_sub(rent_floor);
+        let amount = withdrawable_proceeds(&ctx.accounts.event, escrowed, now)?;
+        transfer_lamports_from_pda(&vault, &ctx.accounts.organizer.to_account_info(), amount)?;
+
+        let event = &mut ctx.accounts.event;
+        event.proceeds_withdrawn = event.proceeds_withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Pay primary proceeds out gradually: nothing before `cliff_ts`, then linearly over
+    /// `duration_seconds`. Only the organizer can call this, and only before the first
+    /// sale. Proceeds are held in the event vault and released by `withdraw_proceeds`.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_vesting_schedule(
+        ctx: Context<SetVestingSchedule>,
+        cliff_ts: i64,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        require!(ctx.accounts.event.sold == 0, ErrorCode::EventHasSales);
+        require!(duration_seconds >= 0, ErrorCode::InvalidVestingSchedule);
+        require!(
+            duration_seconds == 0 || !accepts_token_payments(&ctx.accounts.event),
+            ErrorCode::TokenPaymentConflict
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.event = ctx.accounts.event.key();
+        vault.bump = ctx.bumps.vault;
+
+        let event = &mut ctx.accounts.event;
+        event.vesting_cliff_ts = cliff_ts;
+        event.vesting_duration_seconds = duration_seconds;
+
+        Ok(())
+    }
+
+    /// Permissionless crank that closes empty token accounts and returns their rent.
+    /// `remaining_accounts` is a list of `(token_account, owner)` pairs. Each token
+    /// account must hold zero tokens and have its close authority set to the
+    /// `cleanup_authority` PDA, which owners grant as a standing cleanup approval.
+    /// Program escrows never qualify: their close authority is unset and they are
+    /// closed together with their Listing/ClaimLink/TransferOffer state. Rent goes to
+    /// the owner, less a CLEANUP_TIP_BPS tip for the cranker.
+    pub fn cleanup_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CleanupAccounts<'info>>,
+    ) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
+        require!(!remaining.is_empty() && remaining.len() % 2 == 0, ErrorCode::InvalidCleanupBatch);
+
+        let cleanup_key = ctx.accounts.cleanup_authority.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"cleanup_authority", &[ctx.bumps.cleanup_authority]]];
+
+        for pair in remaining.chunks(2) {
+            let (candidate, owner) = (&pair[0], &pair[1]);
+            require_keys_eq!(*candidate.owner, ctx.accounts.token_program.key(), ErrorCode::InvalidCleanupAccount);
+            let token = TokenAccount::try_deserialize(&mut &candidate.try_borrow_data()?[..])?;
+            require!(token.amount == 0, ErrorCode::InvalidCleanupAccount);
+            require!(token.close_authority == COption::Some(cleanup_key), ErrorCode::InvalidCleanupAccount);
+            require_keys_eq!(token.owner, owner.key(), ErrorCode::InvalidCleanupAccount);
+
+            let rent = candidate.lamports();
+            close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    CloseAccount {
+                        account: candidate.clone(),
+                        destination: ctx.accounts.cranker.to_account_info(),
+                        authority: ctx.accounts.cleanup_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+            )?;
+
+            // Forward the owner's share; the cranker keeps the tip
+            let tip = rent * CLEANUP_TIP_BPS / 10_000;
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.cranker.to_account_info(),
+                        to: owner.clone(),
+                    },
+                ),
+                rent - tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reward primary buyers with `tokens_per_purchase` units of a fan token. Only the
+    /// organizer can call this. The mint's authority must already be the event's
+    /// `fan_token_authority` PDA so the program can mint on every purchase.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn configure_fan_token(ctx: Context<ConfigureFanToken>, tokens_per_purchase: u64) -> Result<()> {
+        require!(
+            ctx.accounts.fan_token_mint.mint_authority == COption::Some(ctx.accounts.fan_token_authority.key()),
+            ErrorCode::InvalidFanTokenMint
+        );
+
+        let config = &mut ctx.accounts.fan_token_config;
+        config.mint = ctx.accounts.fan_token_mint.key();
+        config.authority_bump = ctx.bumps.fan_token_authority;
+        config.tokens_per_purchase = tokens_per_purchase;
+        config.bump = ctx.bumps.fan_token_config;
+
+        let event = &mut ctx.accounts.event;
+        event.fan_token_mint = Some(config.mint);
+        event.fan_tokens_per_purchase = tokens_per_purchase;
+
+        Ok(())
+    }
+
+    /// Announce a perk (merch voucher, bonus NFT) for every ticket holder. Only the
+    /// organizer can call this. Holders mint one perk NFT per ticket until `claim_until`.
+    /// Each perk gets the next `event.perks_created` index in its PDA, so a closed perk's
+    /// address (and its PerkClaimed markers) is never reused.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn create_perk(ctx: Context<CreatePerk>, uri: String, claim_until: i64) -> Result<()> {
+        require!(uri.len() <= MAX_PERK_URI_LEN, ErrorCode::PerkUriTooLong);
+        require!(claim_until > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+
+        let event = &mut ctx.accounts.event;
+        let perk = &mut ctx.accounts.perk;
+        perk.event = event.key();
+        perk.uri = uri;
+        perk.claim_until = claim_until;
+        perk.claimed = 0;
+        perk.bump = ctx.bumps.perk;
+        perk.index = event.perks_created;
+        event.perks_created = event.perks_created.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Mint one perk NFT for a held ticket. The PerkClaimed marker is keyed by the
+    /// ticket mint, so a resold ticket cannot claim again.
+    pub fn claim_perk(ctx: Context<ClaimPerk>, _ticket_index: u32) -> Result<()> {
+        let perk = &ctx.accounts.perk;
+        require!(Clock::get()?.unix_timestamp <= perk.claim_until, ErrorCode::PerkClaimClosed);
+
+        let event_key = ctx.accounts.event.key();
+        let index = perk.index.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"perk", event_key.as_ref(), &index, &[perk.bump]]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.perk_mint.to_account_info(),
+                    to: ctx.accounts.holder_perk_account.to_account_info(),
+                    authority: ctx.accounts.perk.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.perk_mint.to_account_info(),
+            ctx.accounts.perk.to_account_info(),
+            signer_seeds,
+        )?;
+
+        let claimed = &mut ctx.accounts.perk_claimed;
+        claimed.perk = ctx.accounts.perk.key();
+        claimed.ticket_mint = ctx.accounts.ticket_mint.key();
+        claimed.claimer = ctx.accounts.holder.key();
+        claimed.bump = ctx.bumps.perk_claimed;
+
+        let perk = &mut ctx.accounts.perk;
+        perk.claimed = perk.claimed.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Close a perk once its claim window has ended. Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn close_perk(ctx: Context<ClosePerk>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp > ctx.accounts.perk.claim_until,
+            ErrorCode::PerkClaimOpen
+        );
+        // Perk PDA is closed via `close = organizer`
+        Ok(())
+    }
+
+    /// Change the platform-wide ceiling on primary ticket prices. Only the platform
+    /// authority can call this.
+    pub fn set_max_ticket_price(ctx: Context<SetMaxTicketPrice>, max_ticket_price: u64) -> Result<()> {
+        require!(max_ticket_price >= MIN_TICKET_PRICE_LAMPORTS, ErrorCode::InvalidPrice);
+        ctx.accounts.platform_config.max_ticket_price = max_ticket_price;
+        Ok(())
+    }
+
+    /// Set the minimum interval between price updates on a listing. Platform authority only.
+    pub fn set_min_price_update_interval(ctx: Context<SetMinPriceUpdateInterval>, interval_secs: i64) -> Result<()> {
+        require!(interval_secs >= 0, ErrorCode::InvalidPriceUpdateInterval);
+        ctx.accounts.platform_config.min_price_update_interval_secs = interval_secs;
+        Ok(())
+    }
+
+    /// Set how long escrowed royalties stay locked after the last deposit. Platform
+    /// authority only.
+    pub fn set_royalty_release_delay(ctx: Context<SetRoyaltyReleaseDelay>, delay_seconds: u32) -> Result<()> {
+        ctx.accounts.platform_config.royalty_release_delay_seconds = delay_seconds;
+        Ok(())
+    }
+
+    /// Set or rotate the key that signs purchase attestations. Platform authority only.
+    pub fn set_attestor_key(ctx: Context<SetAttestorKey>, attestor_key: Pubkey) -> Result<()> {
+        ctx.accounts.platform_config.attestor_key = attestor_key;
+        Ok(())
+    }
+
+    /// Create a festival pass covering several events. `remaining_accounts` are the
+    /// bundled Event accounts (writable, in `events` order) followed by any co-signing
+    /// organizers; each event must be organized by the creator or a co-signer. Every
+    /// event reserves `supply` seats, and pass revenue is split across organizers by
+    /// `shares_bps` (summing to 10000). Passes sell until `sale_end_ts`;
+    /// `release_pass_seats` then returns the seats of unsold passes to each event.
+    pub fn create_pass_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreatePassBundle<'info>>,
+        nonce: u64,
+        events: Vec<Pubkey>,
+        shares_bps: Vec<u16>,
+        price_lamports: u64,
+        supply: u32,
+        sale_end_ts: i64,
+    ) -> Result<()> {
+        require!(
+            !events.is_empty() && events.len() <= MAX_BUNDLE_EVENTS,
+            ErrorCode::InvalidBundle
+        );
+        require!(shares_bps.len() == events.len(), ErrorCode::InvalidBundle);
+        require!(
+            shares_bps.iter().map(|&s| s as u32).sum::<u32>() == 10_000,
+            ErrorCode::InvalidBundle
+        );
+        require!(supply > 0, ErrorCode::InvalidSupply);
+        require!(sale_end_ts > Clock::get()?.unix_timestamp, ErrorCode::InvalidExpiry);
+        require!(ctx.remaining_accounts.len() >= events.len(), ErrorCode::InvalidBundle);
+
+        let creator = ctx.accounts.creator.key();
+        let mut organizers = Vec::with_capacity(events.len());
+        for (info, key) in ctx.remaining_accounts.iter().zip(events.iter()) {
+            require_keys_eq!(info.key(), *key, ErrorCode::InvalidBundle);
+            let mut event = Account::<Event>::try_from(info)?;
+            require!(!event.use_pnft, ErrorCode::PnftUnsupported);
+            require!(!event.cancelled, ErrorCode::EventCancelled);
+            let consents = event.organizer == creator
+                || ctx
+                    .remaining_accounts
+                    .iter()
+                    .chain(std::iter::once(&ctx.accounts.creator.to_account_info()))
+                    .any(|a| a.is_signer && a.key() == event.organizer);
+            require!(consents, ErrorCode::InvalidOrganizer);
+
+            // Reserve seats so passes can never over-promise
+            let reserved = event
+                .sold
+                .checked_add(event.held)
+                .and_then(|r| r.checked_add(event.pass_reserved))
+                .and_then(|r| r.checked_add(supply))
+                .ok_or(ErrorCode::Overflow)?;
+            require!(reserved <= event.supply && supply <= tier_available(&event, 0)?, ErrorCode::InsufficientSupply);
+            event.pass_reserved += supply;
+            event.exit(&crate::ID)?;
+
+            organizers.push(event.organizer);
+        }
+
+        let bundle = &mut ctx.accounts.bundle;
+        bundle.creator = creator;
+        bundle.nonce = nonce;
+        bundle.events = events;
+        bundle.organizers = organizers;
+        bundle.shares_bps = shares_bps;
+        bundle.price_lamports = price_lamports;
+        bundle.supply = supply;
+        bundle.sold = 0;
+        bundle.bump = ctx.bumps.bundle;
+        bundle.sale_end_ts = sale_end_ts;
+        bundle.reserved = vec![supply; bundle.events.len()];
+        bundle.redeemed = vec![0; bundle.events.len()];
+
+        Ok(())
+    }
+
+    /// Buy a festival pass NFT. `remaining_accounts` are the bundle's organizers
+    /// (writable, in bundle order), each paid their share of the price, then the bundled
+    /// events (writable, in bundle order), each recording its share as owed to pass
+    /// holders should it be cancelled.
+    pub fn buy_pass<'info>(ctx: Context<'_, '_, 'info, 'info, BuyPass<'info>>) -> Result<()> {
+        let bundle = &ctx.accounts.bundle;
+        require!(Clock::get()?.unix_timestamp < bundle.sale_end_ts, ErrorCode::PassSaleClosed);
+        require!(bundle.sold < bundle.supply, ErrorCode::SoldOut);
+        let count = bundle.organizers.len();
+        require!(ctx.remaining_accounts.len() == 2 * count, ErrorCode::InvalidBundle);
+
+        let (organizers, events) = ctx.remaining_accounts.split_at(count);
+        for (i, info) in organizers.iter().enumerate() {
+            require_keys_eq!(info.key(), bundle.organizers[i], ErrorCode::InvalidOrganizer);
+            let share = pass_share(bundle, i);
+            if share > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to: info.clone(),
+                        },
+                    ),
+                    share,
+                )?;
+            }
+        }
+        for (i, info) in events.iter().enumerate() {
+            require_keys_eq!(info.key(), bundle.events[i], ErrorCode::InvalidBundle);
+            let mut event = Account::<Event>::try_from(info)?;
+            require!(!event.cancelled, ErrorCode::EventCancelled);
+            event.pass_refunds_owed = event.pass_refunds_owed.checked_add(pass_share(bundle, i)).ok_or(ErrorCode::Overflow)?;
+            event.exit(&crate::ID)?;
+        }
+
+        // Mint the pass NFT; the bundle PDA signs as mint authority
+        let creator = bundle.creator;
+        let nonce_bytes = bundle.nonce.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"pass_bundle",
+            creator.as_ref(),
+            &nonce_bytes,
+            &[bundle.bump],
+        ]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.pass_mint.to_account_info(),
+                    to: ctx.accounts.buyer_pass_account.to_account_info(),
+                    authority: ctx.accounts.bundle.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.pass_mint.to_account_info(),
+            ctx.accounts.bundle.to_account_info(),
+            signer_seeds,
+        )?;
+
+        let bundle = &mut ctx.accounts.bundle;
+        bundle.sold = bundle.sold.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Redeem a festival pass for a ticket to one of its bundled events, using one of
+    /// the seats reserved at bundle creation. Each pass redeems once per event. The
+    /// ticket gets a zero-price receipt in the holder's name, with their
+    /// `identity_commitment` on identity mode events.
+    pub fn redeem_bundle_ticket(
+        ctx: Context<RedeemBundleTicket>,
+        _pass_index: u32,
+        identity_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let event_key = ctx.accounts.event.key();
+        let position = ctx
+            .accounts
+            .bundle
+            .events
+            .iter()
+            .position(|e| *e == event_key)
+            .ok_or(ErrorCode::InvalidBundle)?;
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        require!(Clock::get()?.unix_timestamp < ctx.accounts.event.date_ts, ErrorCode::EventEnded);
+        require!(!ctx.accounts.event.use_pnft, ErrorCode::PnftUnsupported);
+
+        let custom_name = take_name_override(&ctx.accounts.name_override, &ctx.accounts.organizer.to_account_info())?;
+        let sold = ctx.accounts.event.sold;
+        let sold_bytes = sold.to_le_bytes();
+        let bump = ctx.bumps.ticket_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"ticket_authority",
+            event_key.as_ref(),
+            &sold_bytes,
+            &[bump],
+        ]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.holder_token_account.to_account_info(),
+                    authority: ctx.accounts.ticket_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        create_ticket_metadata(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.ticket_metadata.as_ref(),
+            &ctx.accounts.ticket_mint.to_account_info(),
+            &ctx.accounts.ticket_authority.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.event,
+            sold,
+            custom_name.as_deref(),
+            signer_seeds,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.ticket_mint.to_account_info(),
+            ctx.accounts.ticket_authority.to_account_info(),
+            signer_seeds,
+        )?;
+
+        let redemption = &mut ctx.accounts.redemption;
+        redemption.pass_mint = ctx.accounts.pass_mint.key();
+        redemption.event = event_key;
+        redemption.ticket_mint = ctx.accounts.ticket_mint.key();
+        redemption.bump = ctx.bumps.redemption;
+
+        let identity_commitment = take_identity_commitment(
+            &ctx.accounts.event,
+            identity_commitment,
+            ctx.accounts.identity_purchase.as_ref(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let holder = ctx.accounts.holder.key();
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.buyer = holder;
+        receipt.event = event_key;
+        receipt.ticket_mint = ctx.accounts.ticket_mint.key();
+        receipt.paid_lamports = 0;
+        receipt.purchased_ts = Clock::get()?.unix_timestamp;
+        receipt.refunded = false;
+        receipt.category = None;
+        receipt.payment_index = None;
+        receipt.tip_lamports = 0;
+        receipt.perks_bitmask = ctx.accounts.event.tiers[0].perks_bitmask;
+        receipt.deposit_lamports = 0;
+        receipt.note = [0; 32];
+        receipt.note_holder = holder;
+        receipt.identity_commitment = identity_commitment;
+        receipt.tier_index = 0;
+        receipt.bump = ctx.bumps.receipt;
+
+        emit!(TicketPurchased {
+            event: event_key,
+            buyer: ctx.accounts.holder.key(),
+            ticket_mint: redemption.ticket_mint,
+            price_lamports: 0,
+            tip_lamports: 0,
+            donation_lamports: 0,
+            custom_name,
+        });
+
+        // Move one seat from the pass reserve to sold
+        let bundle = &mut ctx.accounts.bundle;
+        bundle.reserved[position] -= 1;
+        bundle.redeemed[position] += 1;
+        let event = &mut ctx.accounts.event;
+        event.pass_reserved -= 1;
+        record_tier_sale(event, 0, 1)?;
+
+        Ok(())
+    }
+
+    /// Permissionless: return a bundle's reserved seats on one event to general sale.
+    /// Once the pass sale has ended, seats of unsold passes are released; once the event
+    /// is cancelled or has started, every seat still reserved for the bundle is.
+    pub fn release_pass_seats(ctx: Context<ReleasePassSeats>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event_key = ctx.accounts.event.key();
+        let bundle = &mut ctx.accounts.bundle;
+        let position = bundle.events.iter().position(|e| *e == event_key).ok_or(ErrorCode::InvalidBundle)?;
+
+        let event = &mut ctx.accounts.event;
+        let keep = if event.cancelled || now >= event.date_ts {
+            0
+        } else if now >= bundle.sale_end_ts {
+            // Sold passes that haven't redeemed this event keep their seat
+            bundle.sold - bundle.redeemed[position]
+        } else {
+            return err!(ErrorCode::PassSaleOpen);
+        };
+        let released = bundle.reserved[position].saturating_sub(keep);
+        bundle.reserved[position] -= released;
+        event.pass_reserved -= released;
+
+        Ok(())
+    }
+
+    /// Check that a listing's escrow still holds exactly one ticket. Only the platform
+    /// authority can call this. On a mismatch the listing is flagged, which blocks
+    /// `buy_resale`, and an EscrowAnomalyDetected event is emitted.
+    pub fn verify_escrow_health(ctx: Context<VerifyEscrowHealth>) -> Result<()> {
+        let actual_balance = ctx.accounts.escrow_token_account.amount;
+        if actual_balance != 1 {
+            ctx.accounts.listing.anomaly_flag = true;
+            emit!(EscrowAnomalyDetected {
+                listing: ctx.accounts.listing.key(),
+                escrow: ctx.accounts.escrow_token_account.key(),
+                actual_balance,
+            });
+        }
+        Ok(())
+    }
+
+    /// Close a flagged listing. Only the platform authority can call this. Any tokens
+    /// left in escrow go back to the seller, who also receives the rent.
+    pub fn resolve_escrow_anomaly(ctx: Context<ResolveEscrowAnomaly>) -> Result<()> {
+        require!(ctx.accounts.listing.anomaly_flag, ErrorCode::NoEscrowAnomaly);
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let bump = ctx.accounts.listing.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"listing",
+            ticket_mint_key.as_ref(),
+            &[bump],
+        ]];
+
+        let remaining = ctx.accounts.escrow_token_account.amount;
+        if remaining > 0 {
+            transfer_checked_exact(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        mint: ctx.accounts.ticket_mint.to_account_info(),
+                        to: ctx.accounts.seller_token_account.to_account_info(),
+                        authority: ctx.accounts.listing.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                remaining,
+                0,
+            )?;
+        }
+
+        close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.escrow_token_account.to_account_info(),
+                    destination: ctx.accounts.seller.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                signer_seeds,
+            ),
+        )?;
+
+        // Listing PDA is closed via `close = seller`
+        Ok(())
+    }
+
+    /// Set the organizer's social profile or event page link. Only the organizer can
+    /// call this. Changing the link clears any verification so it must be re-verified.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn update_social_link(ctx: Context<UpdateSocialLink>, link: String) -> Result<()> {
+        require!(
+            link.starts_with("https://") && link.len() <= MAX_SOCIAL_LINK_LEN,
+            ErrorCode::SocialLinkInvalid
+        );
+
+        let event = &mut ctx.accounts.event;
+        event.social_link = link;
+        event.verification_ts = 0;
+
+        Ok(())
+    }
+
+    /// Let another wallet present this ticket at the door for `valid_hours` hours.
+    /// The ticket stays with the holder, and only the holder can delegate, so a
+    /// delegate cannot re-delegate.
+    pub fn delegate_entry_rights(
+        ctx: Context<DelegateEntryRights>,
+        delegate: Pubkey,
+        valid_hours: u8,
+    ) -> Result<()> {
+        require!(valid_hours > 0, ErrorCode::InvalidExpiry);
+        require_keys_neq!(delegate, ctx.accounts.holder.key(), ErrorCode::InvalidRecipient);
+
+        let entry = &mut ctx.accounts.entry_delegate;
+        entry.ticket_mint = ctx.accounts.ticket_mint.key();
+        entry.holder = ctx.accounts.holder.key();
+        entry.delegate = delegate;
+        entry.expires_ts = Clock::get()?.unix_timestamp + valid_hours as i64 * 3600;
+        entry.bump = ctx.bumps.entry_delegate;
+
+        Ok(())
+    }
+
+    /// Revoke an entry delegation; the rent returns to the holder.
+    pub fn revoke_entry_delegation(_ctx: Context<RevokeEntryDelegation>) -> Result<()> {
+        // DelegateEntry PDA is closed via `close = holder`
+        Ok(())
+    }
+
+    /// Check a ticket in with the holder's entry delegate signing instead of the
+    /// holder. The attendance record still names the holder.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn check_in_delegate(ctx: Context<CheckInDelegate>, ticket_index: u32) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.entry_delegate.expires_ts,
+            ErrorCode::DelegationExpired
+        );
+        require!(!ctx.accounts.event.identity_at_check_in, ErrorCode::IdentityPreimageRequired);
+        let note = ctx.accounts.receipt.as_ref().and_then(|r| {
+            r.note_for(&ctx.accounts.holder.key(), ctx.accounts.event.notes_survive_transfer)
+        });
+        record_attendance(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.attendance_record,
+            ctx.accounts.ticket_mint.key(),
+            ctx.accounts.holder.key(),
+            ticket_index,
+            ctx.accounts.receipt.as_ref().and_then(|r| r.category),
+            ctx.accounts.receipt.as_ref().map_or(0, |r| r.perks_bitmask),
+            note,
+            ctx.bumps.attendance_record,
+        )
+    }
+
+    /// Set reduced-price concession categories (child, senior, student, ...), each for
+    /// one tier and capped by its supply. Only the organizer can call this, and only
+    /// before the first sale. Verified-only categories need an organizer ed25519
+    /// signature over `"concession" ‖ event ‖ buyer ‖ category` in the instruction
+    /// before `buy_ticket`.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_concessions(ctx: Context<SetConcessions>, concessions: Vec<Concession>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(concessions.len() <= MAX_CONCESSIONS, ErrorCode::InvalidConcession);
+
+        let mut concessions = concessions;
+        for concession in concessions.iter_mut() {
+            require!(
+                !concession.name.is_empty() && concession.name.len() <= MAX_CONCESSION_NAME_LEN,
+                ErrorCode::InvalidConcession
+            );
+            require!(
+                concession.price_lamports == 0 || concession.price_lamports >= MIN_TICKET_PRICE_LAMPORTS,
+                ErrorCode::InvalidPrice
+            );
+            let tier = event.tiers.get(concession.tier_index as usize).ok_or(ErrorCode::InvalidTierIndex)?;
+            require!(
+                concession.max_count > 0 && concession.max_count <= tier.supply,
+                ErrorCode::InvalidConcession
+            );
+            concession.sold = 0;
+        }
+        event.concessions = concessions;
+
+        Ok(())
+    }
+
+    /// Set the IPFS CIDv1 of the ticket template SVG. Only the organizer can call this.
+    /// Tickets minted afterwards take `ticket_image_uri` as their metadata URI.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn update_ticket_design(ctx: Context<UpdateTicketDesign>, new_cid: String) -> Result<()> {
+        require!(
+            new_cid.starts_with("baf") && new_cid.len() <= MAX_DESIGN_CID_LEN,
+            ErrorCode::InvalidCid
+        );
+        ctx.accounts.event.ticket_design_cid = new_cid;
+        Ok(())
+    }
+
+    /// Create a preset resale split organizers can pick at `create_event`. Only the
+    /// platform authority can call this. The bps must sum to exactly 10000.
+    pub fn create_split_template(
+        ctx: Context<CreateSplitTemplate>,
+        name: String,
+        artist_bps: u16,
+        seller_bps: u16,
+        platform_bps: u16,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_TEMPLATE_NAME_LEN,
+            ErrorCode::InvalidSplit
+        );
+        validate_split(artist_bps, seller_bps, platform_bps)?;
+
+        let template = &mut ctx.accounts.split_template;
+        template.name = name;
+        template.artist_bps = artist_bps;
+        template.seller_bps = seller_bps;
+        template.platform_bps = platform_bps;
+        template.bump = ctx.bumps.split_template;
+
+        Ok(())
+    }
+
+    /// Change a split template. Only the platform authority can call this. Events
+    /// created from it keep the values copied at creation.
+    pub fn update_split_template(
+        ctx: Context<UpdateSplitTemplate>,
+        artist_bps: u16,
+        seller_bps: u16,
+        platform_bps: u16,
+    ) -> Result<()> {
+        validate_split(artist_bps, seller_bps, platform_bps)?;
+
+        let template = &mut ctx.accounts.split_template;
+        template.artist_bps = artist_bps;
+        template.seller_bps = seller_bps;
+        template.platform_bps = platform_bps;
+
+        Ok(())
+    }
+
+    /// Pre-create the next `count` ticket mints (up to MAX_PREALLOCATED_TICKETS) so
+    /// drops do not pay mint creation at purchase time. Only the organizer can call
+    /// this. `remaining_accounts` are the writable `ticket_mint` PDAs for indices
+    /// `event.sold..event.sold + count`; `buy_ticket` reuses them as buyers reach
+    /// those indices. Mints that already exist are skipped, so the call can be
+    /// repeated to extend a batch.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn pre_allocate_tickets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PreAllocateTickets<'info>>,
+        count: u8,
+    ) -> Result<()> {
+        require!(count > 0 && count <= MAX_PREALLOCATED_TICKETS, ErrorCode::InvalidQuantity);
+        require!(ctx.remaining_accounts.len() == count as usize, ErrorCode::InvalidQuantity);
+
+        let event_key = ctx.accounts.event.key();
+        let start_index = ctx.accounts.event.sold;
+        let end_index = start_index.checked_add(count as u32).ok_or(ErrorCode::Overflow)?;
+        require!(end_index <= ctx.accounts.event.supply, ErrorCode::InsufficientSupply);
+
+        for (info, index) in ctx.remaining_accounts.iter().zip(start_index..end_index) {
+            let index_bytes = index.to_le_bytes();
+            let (mint_key, mint_bump) = Pubkey::find_program_address(
+                &[b"ticket_mint", event_key.as_ref(), &index_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(info.key(), mint_key, ErrorCode::InvalidTicketMint);
+            if !info.data_is_empty() {
+                continue;
+            }
+            let (authority, _) = Pubkey::find_program_address(
+                &[b"ticket_authority", event_key.as_ref(), &index_bytes],
+                ctx.program_id,
+            );
+
+            let mint_seeds: &[&[&[u8]]] = &[&[
+                b"ticket_mint",
+                event_key.as_ref(),
+                &index_bytes,
+                &[mint_bump],
+            ]];
+            create_pda_account(
+                &ctx.accounts.organizer.to_account_info(),
+                info,
+                &ctx.accounts.system_program.to_account_info(),
+                SplMint::LEN,
+                &ctx.accounts.token_program.key(),
+                mint_seeds,
+            )?;
+            initialize_mint2(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    InitializeMint2 { mint: info.clone() },
+                ),
+                0,
+                &authority,
+                None,
+            )?;
+        }
+
+        let allocation = &mut ctx.accounts.allocation;
+        allocation.event = event_key;
+        allocation.start_index = start_index;
+        allocation.bump = ctx.bumps.allocation;
+
+        Ok(())
+    }
+
+    /// Send `donation_bps` of every primary sale to a charity wallet. Only the organizer
+    /// can call this, and only before the first sale. Tax plus donation may not exceed
+    /// the full price. Donations are remitted immediately and are final.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_donation_config(
+        ctx: Context<SetDonationConfig>,
+        donation_bps: u16,
+        donation_wallet: Pubkey,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(
+            donation_bps as u32 + event.tax_bps as u32 <= 10_000,
+            ErrorCode::InvalidDonationConfig
+        );
+        require!(
+            donation_bps == 0 || donation_wallet != Pubkey::default(),
+            ErrorCode::InvalidDonationConfig
+        );
+        require!(donation_bps == 0 || !accepts_token_payments(event), ErrorCode::TokenPaymentConflict);
+
+        event.donation_bps = donation_bps;
+        event.donation_wallet = donation_wallet;
+
+        Ok(())
+    }
+
+    /// Propose a venue or date change for ticket holders to vote on. Any ticket holder
+    /// can call this. `proposed_value` is the UTF-8 venue (zero-padded) for CHANGE_VENUE
+    /// or the little-endian `date_ts` in the first 8 bytes for CHANGE_DATE. Voting runs
+    /// for GOVERNANCE_VOTING_PERIOD_SECS and must end before the event starts.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        proposal_id: u64,
+        _ticket_index: u32,
+        change_type: u8,
+        proposed_value: [u8; 64],
+    ) -> Result<()> {
+        require!(
+            change_type == CHANGE_VENUE || change_type == CHANGE_DATE,
+            ErrorCode::InvalidProposal
+        );
+        let voting_ends_at = Clock::get()?
+            .unix_timestamp
+            .checked_add(GOVERNANCE_VOTING_PERIOD_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(voting_ends_at < ctx.accounts.event.date_ts, ErrorCode::InvalidProposal);
+        if change_type == CHANGE_DATE {
+            proposed_date(&proposed_value, &ctx.accounts.event, voting_ends_at)?;
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.event = ctx.accounts.event.key();
+        proposal.proposal_id = proposal_id;
+        proposal.change_type = change_type;
+        proposal.proposed_value = proposed_value;
+        proposal.yes_votes = 0;
+        proposal.no_votes = 0;
+        proposal.applied = false;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.voting_ends_at = voting_ends_at;
+
+        Ok(())
+    }
+
+    /// Vote on a proposal with one held ticket. Each ticket mint votes once per
+    /// proposal, even if the ticket changes hands.
+    pub fn cast_vote(ctx: Context<CastVote>, _proposal_id: u64, _ticket_index: u32, yes: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.applied, ErrorCode::ProposalApplied);
+        require!(Clock::get()?.unix_timestamp < proposal.voting_ends_at, ErrorCode::VotingClosed);
+        if yes {
+            proposal.yes_votes = proposal.yes_votes.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        } else {
+            proposal.no_votes = proposal.no_votes.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+
+        let record = &mut ctx.accounts.vote_record;
+        record.proposal = proposal.key();
+        record.ticket_mint = ctx.accounts.ticket_mint.key();
+        record.voter = ctx.accounts.voter.key();
+        record.yes = yes;
+        record.bump = ctx.bumps.vote_record;
+
+        Ok(())
+    }
+
+    /// Apply a proposal after its voting window, if at least GOVERNANCE_QUORUM_PCT of
+    /// sold tickets voted and more than GOVERNANCE_THRESHOLD_PCT of the votes were yes.
+    /// Permissionless, so the organizer cannot sit on a passed proposal.
+    pub fn apply_governance_change(ctx: Context<ApplyGovernanceChange>, _proposal_id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &ctx.accounts.proposal;
+        require!(!proposal.applied, ErrorCode::ProposalApplied);
+        require!(now >= proposal.voting_ends_at, ErrorCode::VotingOpen);
+        let event = &mut ctx.accounts.event;
+        let votes = proposal.yes_votes as u64 + proposal.no_votes as u64;
+        require!(
+            event.sold > 0 && votes * 100 >= event.sold as u64 * GOVERNANCE_QUORUM_PCT,
+            ErrorCode::QuorumNotReached
+        );
+        require!(
+            proposal.yes_votes as u64 * 100 > votes * GOVERNANCE_THRESHOLD_PCT,
+            ErrorCode::ProposalNotPassed
+        );
+
+        match proposal.change_type {
+            CHANGE_VENUE => {
+                let len = proposal.proposed_value.iter().position(|&b| b == 0).unwrap_or(64);
+                let venue = std::str::from_utf8(&proposal.proposed_value[..len])
+                    .map_err(|_| ErrorCode::InvalidProposal)?;
+                event.venue = venue.to_string();
+            }
+            CHANGE_DATE => {
+                event.date_ts = proposed_date(&proposal.proposed_value, event, now)?;
+            }
+            _ => return err!(ErrorCode::InvalidProposal),
+        }
+
+        ctx.accounts.proposal.applied = true;
+        Ok(())
+    }
+
+    /// Batch variant of `release_expired_hold` for keepers. `remaining_accounts` are
+    /// writable Hold accounts of this event; holds that have not expired yet are
+    /// skipped, and the call fails with NothingToCrank if none were ready. Each swept
+    /// hold's rent goes to the organizer, less a CLEANUP_TIP_BPS tip for the cranker.
+    pub fn sweep_expired_holds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpiredHolds<'info>>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event_key = ctx.accounts.event.key();
+        let mut swept = 0u32;
+
+        for info in ctx.remaining_accounts.iter() {
+            let hold = Account::<Hold>::try_from(info)?;
+            require_keys_eq!(hold.event, event_key, ErrorCode::InvalidHold);
+            if now < hold.expires_at {
+                continue;
+            }
+
+            let event = &mut ctx.accounts.event;
+            event.held = event.held.saturating_sub(hold.remaining);
+
+            let tip = info.lamports() * CLEANUP_TIP_BPS / 10_000;
+            transfer_lamports_from_pda(info, &ctx.accounts.cranker.to_account_info(), tip)?;
+            hold.close(ctx.accounts.organizer.to_account_info())?;
+            swept += 1;
+        }
+
+        require!(swept > 0, ErrorCode::NothingToCrank);
+        Ok(())
+    }
+
+    /// Keeper view: emit which deferred work is ready for this event among the Hold
+    /// accounts passed in `remaining_accounts`. Never modifies state.
+    pub fn pending_work<'info>(ctx: Context<'_, '_, 'info, 'info, PendingWorkView<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event_key = ctx.accounts.event.key();
+        let mut expired_holds = 0u32;
+        let mut releasable_seats = 0u32;
+
+        for info in ctx.remaining_accounts.iter() {
+            let hold = Account::<Hold>::try_from(info)?;
+            if hold.event == event_key && now >= hold.expires_at {
+                expired_holds += 1;
+                releasable_seats = releasable_seats.saturating_add(hold.remaining);
+            }
+        }
+
+        emit!(PendingWork {
+            event: event_key,
+            expired_holds,
+            releasable_seats,
+        });
+        Ok(())
+    }
+
+    /// Re-enable resale after the circuit breaker tripped. Callable by the organizer or
+    /// the platform authority.
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>) -> Result<()> {
+        let signer = ctx.accounts.authority.key();
+        let is_platform = ctx
+            .accounts
+            .platform_config
+            .as_ref()
+            .is_some_and(|config| config.authority == signer);
+        require!(
+            signer == ctx.accounts.event.organizer || is_platform,
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.event.resale_paused = false;
+        Ok(())
+    }
+
+    /// Reserve `count` seats for accessible seating. Only the organizer can call this;
+    /// the reserve cannot drop below accessible seats already sold or exceed unsold supply.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_accessible_seating(ctx: Context<ConfigureAccessibleSeating>, count: u32) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(count >= event.accessible_sold, ErrorCode::InvalidAccessibleSeating);
+        let general_sold = event.sold - event.accessible_sold;
+        let committed = general_sold
+            .checked_add(event.held)
+            .and_then(|seats| seats.checked_add(event.pass_reserved))
+            .and_then(|seats| seats.checked_add(count - event.accessible_sold));
+        require!(
+            committed.is_some_and(|seats| seats <= event.supply),
+            ErrorCode::InvalidAccessibleSeating
+        );
+        event.accessible_seating_count = count;
+        Ok(())
+    }
+
+    /// Release up to `count` unsold accessible seats to general buyers. Only the organizer
+    /// can call this, at any time before the event starts, so the seats can still be sold.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn release_accessible_seats(ctx: Context<ConfigureAccessibleSeating>, count: u32) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(Clock::get()?.unix_timestamp < event.date_ts, ErrorCode::EventEnded);
+        require!(
+            count <= event.accessible_seating_count - event.accessible_sold,
+            ErrorCode::AccessibleSeatingUnavailable
+        );
+        event.accessible_seating_count -= count;
+        Ok(())
+    }
+
+    /// Name a wallet (for example, a disability services partner) that may approve
+    /// accessible-seat buyers alongside the organizer. `Pubkey::default()` clears it.
+    /// Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_accessibility_verifier(ctx: Context<SetAccessibilityVerifier>, verifier: Pubkey) -> Result<()> {
+        ctx.accounts.event.accessibility_verifier = verifier;
+        Ok(())
+    }
+
+    /// Approve `buyer` for one accessible seat. Signed by the organizer or the event's
+    /// accessibility verifier, who pays the approval's rent.
+    pub fn approve_accessible_buyer(ctx: Context<ApproveAccessibleBuyer>, buyer: Pubkey) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let approver = ctx.accounts.approver.key();
+        require!(
+            approver == event.organizer
+                || (event.accessibility_verifier != Pubkey::default() && approver == event.accessibility_verifier),
+            ErrorCode::Unauthorized
+        );
+        let approval = &mut ctx.accounts.approval;
+        approval.event = event.key();
+        approval.buyer = buyer;
+        approval.approver = approver;
+        approval.used = false;
+        approval.bump = ctx.bumps.approval;
+        Ok(())
+    }
+
+    /// Require a platform attestation (captcha, device check) on every buy_ticket.
+    /// Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_requires_attestation(ctx: Context<SetRequiresAttestation>, enabled: bool) -> Result<()> {
+        ctx.accounts.event.requires_attestation = enabled;
+        Ok(())
+    }
+
+    /// Mint the event's tickets as Metaplex programmable NFTs (pNFTs), each with a
+    /// TokenRecord tracking its lock and delegate. buy_ticket mints through Token
+    /// Metadata, listings lock the ticket in the seller's wallet instead of escrowing
+    /// it, and buy_resale, cancel_listing and claim_refund move or burn it through
+    /// Token Metadata. Instructions that move tickets with plain token transfers, or
+    /// mint them another way, reject pNFT events. Needs a metadata_uri or design CID.
+    /// Only the organizer can call this, and only before any ticket is sold, held or
+    /// reserved for a pass.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_pnft_mode(ctx: Context<SetPnftMode>, use_pnft: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0 && event.held == 0 && event.pass_reserved == 0, ErrorCode::EventHasSales);
+        require!(!use_pnft || ticket_metadata_uri(event, 0).is_some(), ErrorCode::PnftRequiresMetadata);
+        event.use_pnft = use_pnft;
+        Ok(())
+    }
+
+    /// Hide or unhide a listing from the public market. Only the organizer can call this.
+    /// Display-only: buy_resale still accepts hidden listings by address.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_listing_visibility(ctx: Context<SetListingVisibility>, visible: bool) -> Result<()> {
+        ctx.accounts.listing.visible = visible;
+        Ok(())
+    }
+
+    /// Dashboard view: emit the event's analytics counters. Never modifies state.
+    pub fn get_event_stats(ctx: Context<EventStatsView>) -> Result<()> {
+        let analytics = &ctx.accounts.event.analytics;
+        emit!(EventStats {
+            event: ctx.accounts.event.key(),
+            average_resale_price: analytics.average_resale_price(),
+            analytics: analytics.clone(),
+        });
+        Ok(())
+    }
+
+    /// Accept payment in up to MAX_PAYMENT_OPTIONS alternatives to a tier's SOL price,
+    /// each a tier, a mint (None = SOL) and price. Only the organizer can call this,
+    /// and only before the first sale. Token options cannot be combined with tax,
+    /// donations or vesting.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_accepted_payments(ctx: Context<SetAcceptedPayments>, options: Vec<PaymentOption>) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(options.len() <= MAX_PAYMENT_OPTIONS, ErrorCode::InvalidPaymentOption);
+        require!(
+            !event.token_only || options.iter().any(|option| option.mint.is_some()),
+            ErrorCode::InvalidPaymentOption
+        );
+        for option in options.iter() {
+            require!((option.tier_index as usize) < event.tiers.len(), ErrorCode::InvalidTierIndex);
+            let valid = match option.mint {
+                Some(_) => option.price > 0,
+                None => option.price == 0 || option.price >= MIN_TICKET_PRICE_LAMPORTS,
+            };
+            require!(valid, ErrorCode::InvalidPaymentOption);
+        }
+        event.accepted_payments = options;
+        require!(
+            !accepts_token_payments(event)
+                || (event.tax_bps == 0 && event.donation_bps == 0 && event.vesting_duration_seconds == 0),
+            ErrorCode::TokenPaymentConflict
+        );
+        Ok(())
+    }
+
+    /// Withdraw the vault's whole balance of one payment mint to the organizer once every
+    /// refund window has closed. Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn withdraw_token_proceeds(ctx: Context<WithdrawTokenProceeds>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now > ctx.accounts.event.refundable_until, ErrorCode::ProceedsLocked);
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+
+        let event_key = ctx.accounts.event.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[b"vault", event_key.as_ref(), &[ctx.accounts.vault.bump]]];
+        transfer_checked_exact(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_payment_account.to_account_info(),
+                    mint: ctx.accounts.payment_mint.to_account_info(),
+                    to: ctx.accounts.organizer_payment_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                vault_seeds,
+            ),
+            ctx.accounts.vault_payment_account.amount,
+            ctx.accounts.payment_mint.decimals,
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless health check. `remaining_accounts` are (listing, escrow) pairs for
+    /// this event. Flags listings whose escrow no longer holds exactly one ticket, checks
+    /// the event's seat counters for consistency, and, while proceeds are escrowed,
+    /// checks that the vault covers what it should hold. Any failure pauses primary
+    /// sales and emits HealthCheckFailed. At most once per MONITOR_INTERVAL_SECONDS per
+    /// event, the caller earns MONITOR_REWARD_LAMPORTS from the event's reward pool, up
+    /// to MONITOR_REWARD_CAP_LAMPORTS per monitor.
+    pub fn monitor_event_health<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MonitorEventHealth<'info>>,
+    ) -> Result<()> {
+        require!(ctx.remaining_accounts.len() % 2 == 0, ErrorCode::InvalidHealthCheckBatch);
+        let event_key = ctx.accounts.event.key();
+        let mut failures: Vec<u8> = Vec::new();
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let mut listing = Account::<Listing>::try_from(&pair[0])?;
+            require_keys_eq!(listing.event, event_key, ErrorCode::InvalidHealthCheckBatch);
+            let (escrow_key, _) =
+                Pubkey::find_program_address(&[b"escrow", listing.ticket_mint.as_ref()], &crate::ID);
+            require_keys_eq!(pair[1].key(), escrow_key, ErrorCode::InvalidHealthCheckBatch);
+            let escrow = InterfaceAccount::<TokenAccount>::try_from(&pair[1])?;
+            if escrow.amount != 1 && !listing.anomaly_flag {
+                listing.anomaly_flag = true;
+                listing.exit(&crate::ID)?;
+                emit!(EscrowAnomalyDetected {
+                    listing: listing.key(),
+                    escrow: escrow_key,
+                    actual_balance: escrow.amount,
+                });
+                if !failures.contains(&HEALTH_ESCROW) {
+                    failures.push(HEALTH_ESCROW);
+                }
+            }
+        }
+
+        // Without per-ticket ownership PDAs, minted supply is approximated by the counters
+        let event = &ctx.accounts.event;
+        let concessions_sold: u64 = event.concessions.iter().map(|c| c.sold as u64).sum();
+        let seats_consistent = event.sold as u64 + event.held as u64 + event.pass_reserved as u64
+            <= event.supply as u64
+            && event.checked_in <= event.sold
+            && event.accessible_sold <= event.accessible_seating_count
+            && event.accessible_sold <= event.sold
+            && concessions_sold <= event.sold as u64;
+        if !seats_consistent {
+            failures.push(HEALTH_SUPPLY);
+        }
+
+        // Every escrowed SOL sale went to the vault, less tax, donations, refunds and withdrawals
+        if proceeds_escrowed(event) {
+            let analytics = &event.analytics;
+            let expected = analytics
+                .gross_primary_revenue
+                .saturating_sub(event.tax_collected)
+                .saturating_sub(event.total_donated)
+                .saturating_sub(analytics.refunded_lamports)
+                .saturating_sub(event.proceeds_withdrawn);
+            let info = ctx.accounts.vault.as_ref().ok_or(ErrorCode::InvalidVault)?.to_account_info();
+            let held = info.lamports().saturating_sub(Rent::get()?.minimum_balance(info.data_len()));
+            if held < expected {
+                failures.push(HEALTH_VAULT);
+            }
+        }
+
+        let event = &mut ctx.accounts.event;
+        for reason in failures.iter() {
+            emit!(HealthCheckFailed { event: event_key, reason: *reason });
+        }
+        if !failures.is_empty() {
+            event.sales_paused = true;
+        }
+
+        // Reward the monitor from the event's own pool, rate-limited per event and
+        // capped per monitor so one wallet can't drain it
+        let now = Clock::get()?.unix_timestamp;
+        let claims = &mut ctx.accounts.monitor_claims;
+        claims.event = event_key;
+        claims.monitor = ctx.accounts.cranker.key();
+        claims.bump = ctx.bumps.monitor_claims;
+        if let Some(pool) = ctx.accounts.reward_pool.as_mut() {
+            if now >= event.last_health_check_ts + MONITOR_INTERVAL_SECONDS
+                && pool.balance >= MONITOR_REWARD_LAMPORTS
+                && claims.earned + MONITOR_REWARD_LAMPORTS <= MONITOR_REWARD_CAP_LAMPORTS
+            {
+                pool.balance -= MONITOR_REWARD_LAMPORTS;
+                claims.earned += MONITOR_REWARD_LAMPORTS;
+                transfer_lamports_from_pda(
+                    &pool.to_account_info(),
+                    &ctx.accounts.cranker.to_account_info(),
+                    MONITOR_REWARD_LAMPORTS,
+                )?;
+            }
+        }
+        event.last_health_check_ts = now;
+        Ok(())
+    }
+
+    /// Resume primary sales after a failed health check has been resolved. Platform
+    /// authority only.
+    pub fn reset_health_check(ctx: Context<ResetHealthCheck>) -> Result<()> {
+        ctx.accounts.event.sales_paused = false;
+        Ok(())
+    }
+
+    /// Add lamports to an event's health monitor reward pool, creating it on first use.
+    /// Anyone can contribute; monitors of other events can't draw on it.
+    pub fn fund_monitor_rewards(ctx: Context<FundMonitorRewards>, amount: u64) -> Result<()> {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.reward_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.event = ctx.accounts.event.key();
+        pool.balance = pool.balance.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        pool.bump = ctx.bumps.reward_pool;
+        Ok(())
+    }
+
+    /// Block resale purchases while primary tickets remain on sale. Only the organizer
+    /// can call this. Listing and cancelling stay allowed.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_resale_blocked_while_primary_available(
+        ctx: Context<SetResaleBlocking>,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.event.resale_blocked_while_primary_available = enabled;
+        Ok(())
+    }
+
+    /// Set the resale price floor. With `dynamic` set, listings may not undercut the
+    /// primary price until the event sells out. Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_resale_floor(ctx: Context<SetResaleFloor>, min_resale_lamports: u64, dynamic: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        event.min_resale_lamports = min_resale_lamports;
+        event.dynamic_resale_floor = dynamic;
+        Ok(())
+    }
+
+    /// Auditor check: sum `paid_lamports` over the event's SOL Receipt accounts passed in
+    /// `remaining_accounts` and compare with the recorded gross primary revenue. Run in
+    /// batches with receipts in ascending address order across the whole run; the
+    /// running total is kept on the auditor's RevenueAudit PDA for this event, so no
+    /// batch can be skipped or counted twice. On the `final_batch` a non-zero
+    /// discrepancy also emits AuditDiscrepancyFound and the RevenueAudit is closed.
+    /// Receipts closed by close_ticket_record count through event.closed_receipt_revenue.
+    /// Event state is never modified.
+    pub fn audit_royalties<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditRoyalties<'info>>,
+        final_batch: bool,
+    ) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let event_key = event.key();
+        let audit = &mut ctx.accounts.audit;
+        audit.event = event_key;
+        audit.auditor = ctx.accounts.auditor.key();
+        audit.bump = ctx.bumps.audit;
+        let mut expected_revenue = audit.expected_revenue;
+
+        for info in ctx.remaining_accounts.iter() {
+            // Strictly ascending addresses rule out duplicates within and across batches
+            require!(info.key() > audit.last_receipt, ErrorCode::InvalidAuditBatch);
+            audit.last_receipt = info.key();
+            let receipt = Account::<Receipt>::try_from(info)?;
+            require_keys_eq!(receipt.event, event_key, ErrorCode::InvalidAuditBatch);
+            // Token-paid receipts are not part of the SOL revenue counter
+            let paid_in_token = receipt
+                .payment_index
+                .and_then(|index| event.accepted_payments.get(index as usize))
+                .is_some_and(|option| option.mint.is_some());
+            if !paid_in_token {
+                expected_revenue = expected_revenue
+                    .checked_add(receipt.paid_lamports)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        audit.expected_revenue = expected_revenue;
+        let recorded_revenue = event.analytics.gross_primary_revenue;
+        let discrepancy =
+            recorded_revenue as i128 - expected_revenue as i128 - event.closed_receipt_revenue as i128;
+        emit!(AuditResult {
+            event: event_key,
+            expected_revenue,
+            recorded_revenue,
+            discrepancy: discrepancy as i64,
+        });
+        if final_batch {
+            if discrepancy != 0 {
+                emit!(AuditDiscrepancyFound {
+                    event: event_key,
+                    discrepancy: discrepancy as i64,
+                });
+            }
+            ctx.accounts.audit.close(ctx.accounts.auditor.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Hold resale royalties in the event's RoyaltyEscrow instead of paying them out
+    /// immediately. Only the organizer can call this; the escrow is created on first use.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_royalty_escrow_mode(ctx: Context<SetRoyaltyEscrowMode>, enabled: bool) -> Result<()> {
+        let escrow = &mut ctx.accounts.royalty_escrow;
+        escrow.event = ctx.accounts.event.key();
+        escrow.bump = ctx.bumps.royalty_escrow;
+        ctx.accounts.event.royalty_escrow_mode = enabled;
+        Ok(())
+    }
+
+    /// Withdraw escrowed royalties once the platform's release delay has passed since
+    /// the first deposit of the unclaimed balance; later deposits don't extend the lock.
+    /// Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn claim_escrowed_royalties(ctx: Context<ClaimEscrowedRoyalties>) -> Result<()> {
+        let escrow = &mut ctx.accounts.royalty_escrow;
+        let release_ts = escrow.locked_since_ts + ctx.accounts.platform_config.royalty_release_delay_seconds as i64;
+        require!(Clock::get()?.unix_timestamp >= release_ts, ErrorCode::RoyaltyEscrowLocked);
+
+        let amount = escrow.claimable_amount;
+        escrow.claimable_amount = 0;
+        transfer_lamports_from_pda(
+            &escrow.to_account_info(),
+            &ctx.accounts.organizer.to_account_info(),
+            amount,
+        )?;
+        Ok(())
+    }
+
+    /// Flag a ticket reported as fraudulent or duplicated. Only the organizer can call
+    /// this. A flagged ticket cannot be checked in, listed, bought on resale, offered,
+    /// gifted through a claim link or accepted from one of those escrows; if it is
+    /// currently listed, the listing is cancelled and the ticket returned to the seller.
+    /// Checked-in tickets cannot be flagged.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn flag_ticket(ctx: Context<FlagTicket>, _ticket_index: u32, reason_code: u8) -> Result<()> {
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let mut listing_cancelled = false;
+
+        // Force-cancel an open listing back to the seller
+        let listing_info = ctx.accounts.listing.to_account_info();
+        if !listing_info.data_is_empty() {
+            require!(!ctx.accounts.event.use_pnft, ErrorCode::PnftUnsupported);
+            let listing = Listing::try_deserialize(&mut &listing_info.try_borrow_data()?[..])?;
+            let (Some(escrow), Some(seller), Some(seller_token_account)) = (
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.seller,
+                &ctx.accounts.seller_token_account,
+            ) else {
+                return err!(ErrorCode::MissingListingAccounts);
+            };
+            require_keys_eq!(seller.key(), listing.seller, ErrorCode::InvalidSeller);
+            require_keys_eq!(seller_token_account.owner, listing.seller, ErrorCode::InvalidSeller);
+
+            let signer_seeds: &[&[&[u8]]] = &[&[b"listing", ticket_mint_key.as_ref(), &[listing.bump]]];
+            transfer_checked_exact(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: escrow.to_account_info(),
+                        mint: ctx.accounts.ticket_mint.to_account_info(),
+                        to: seller_token_account.to_account_info(),
+                        authority: listing_info.clone(),
+                    },
+                    signer_seeds,
+                ),
+                1,
+                0,
+            )?;
+            close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: escrow.to_account_info(),
+                    destination: seller.to_account_info(),
+                    authority: listing_info.clone(),
+                },
+                signer_seeds,
+            ))?;
+            close_pda(&listing_info, seller)?;
+            listing_cancelled = true;
+        }
+
+        let flag = &mut ctx.accounts.ticket_flag;
+        flag.event = ctx.accounts.event.key();
+        flag.ticket_mint = ticket_mint_key;
+        flag.reason_code = reason_code;
+        flag.flagged_ts = Clock::get()?.unix_timestamp;
+        flag.bump = ctx.bumps.ticket_flag;
+
+        emit!(TicketFlagRaised {
+            event: flag.event,
+            ticket_mint: ticket_mint_key,
+            reason_code,
+            listing_cancelled,
+        });
+        Ok(())
+    }
+
+    /// Clear a ticket's flag once remediated. Only the organizer can call this; the flag
+    /// PDA's rent returns to the organizer.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn unflag_ticket(ctx: Context<UnflagTicket>) -> Result<()> {
+        emit!(TicketFlagCleared {
+            event: ctx.accounts.event.key(),
+            ticket_mint: ctx.accounts.ticket_flag.ticket_mint,
+        });
+        Ok(())
+    }
+
+    /// Give the ticket at `ticket_index` a custom name (e.g. "Artist Meet & Greet Slot 1").
+    /// Only the organizer can call this, before that index is sold; calling it again
+    /// replaces the name. Whichever instruction mints that index (buy_ticket, buy_tickets,
+    /// claim_hold or redeem_bundle_ticket) consumes the override and closes it.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_ticket_name(ctx: Context<SetTicketName>, ticket_index: u32, name: String) -> Result<()> {
+        require!(ticket_index >= ctx.accounts.event.sold, ErrorCode::InvalidTicketIndex);
+        require!(!name.is_empty() && name.len() <= MAX_TICKET_NAME_LEN, ErrorCode::TicketNameTooLong);
+        let name_override = &mut ctx.accounts.name_override;
+        name_override.custom_name = name;
+        name_override.bump = ctx.bumps.name_override;
+        Ok(())
+    }
+
+    /// Ban a wallet from buying or checking in to any of the signing organizer's events.
+    /// Scoped to this organizer only; `reason_hash` commits to an off-chain reason.
+    pub fn ban_from_organizer_events(
+        ctx: Context<BanFromOrganizerEvents>,
+        wallet: Pubkey,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let ban = &mut ctx.accounts.organizer_ban;
+        ban.organizer = ctx.accounts.organizer.key();
+        ban.wallet = wallet;
+        ban.ban_ts = Clock::get()?.unix_timestamp;
+        ban.reason_hash = reason_hash;
+        ban.bump = ctx.bumps.organizer_ban;
+        Ok(())
+    }
+
+    /// Lift an organizer-scoped ban; the rent returns to the organizer.
+    pub fn lift_organizer_ban(_ctx: Context<LiftOrganizerBan>, _wallet: Pubkey) -> Result<()> {
+        // OrganizerBan PDA is closed via `close = organizer`
+        Ok(())
+    }
+
+    /// Set or clear the messaging program that receives ownership attestations.
+    /// Platform authority only.
+    pub fn set_messaging_program(
+        ctx: Context<SetMessagingProgram>,
+        messaging_program: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.platform_config.messaging_program = messaging_program;
+        Ok(())
+    }
+
+    /// Attest that the signer currently holds a ticket, for partners on other chains.
+    /// Emits OwnershipAttested with the message from ownership_attestation_message.
+    /// Nonces must increase per ticket, so each attestation can only be relayed once.
+    /// When platform_config.messaging_program is set, the message is also sent to it
+    /// by CPI, signed by the `["ownership_attestor"]` PDA, with `remaining_accounts`
+    /// forwarded as the messaging program's accounts.
+    pub fn attest_ownership<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AttestOwnership<'info>>,
+        _ticket_index: u32,
+        nonce: u64,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.attestation_nonce;
+        require!(nonce > record.last_nonce, ErrorCode::AttestationNonceUsed);
+        record.last_nonce = nonce;
+        record.bump = ctx.bumps.attestation_nonce;
+
+        let event_key = ctx.accounts.event.key();
+        let ticket_mint = ctx.accounts.ticket_mint.key();
+        let owner = ctx.accounts.holder.key();
+        let slot = Clock::get()?.slot;
+        let message = ownership_attestation_message(&event_key, &ticket_mint, &owner, slot, nonce);
+
+        let messaging_program = ctx
+            .accounts
+            .platform_config
+            .as_ref()
+            .and_then(|config| config.messaging_program);
+        if let Some(program_id) = messaging_program {
+            let (Some(program), Some(attestor)) =
+                (&ctx.accounts.messaging_program, &ctx.accounts.ownership_attestor)
+            else {
+                return err!(ErrorCode::MissingMessagingAccounts);
+            };
+            require_keys_eq!(program.key(), program_id, ErrorCode::MissingMessagingAccounts);
+            let mut metas = vec![AccountMeta::new_readonly(attestor.key(), true)];
+            let mut infos = vec![attestor.clone()];
+            for info in ctx.remaining_accounts {
+                metas.push(if info.is_writable {
+                    AccountMeta::new(info.key(), info.is_signer)
+                } else {
+                    AccountMeta::new_readonly(info.key(), info.is_signer)
+                });
+                infos.push(info.clone());
+            }
+            infos.push(program.clone());
+            let bump = ctx.bumps.ownership_attestor.ok_or(ErrorCode::MissingMessagingAccounts)?;
+            invoke_signed(
+                &Instruction { program_id, accounts: metas, data: message.clone() },
+                &infos,
+                &[&[b"ownership_attestor", &[bump]]],
+            )?;
+        }
+
+        emit!(OwnershipAttested {
+            version: OWNERSHIP_ATTESTATION_VERSION,
+            event: event_key,
+            ticket_mint,
+            owner,
+            slot,
+            nonce,
+            message,
+        });
+        Ok(())
+    }
+
+    /// Set the PERK_* bits (fast lane, backstage, merch included, ...) granted by tier
+    /// `tier_index`. Only the organizer can call this; tickets keep the bits they were
+    /// minted with.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_tier_perks(ctx: Context<SetTierPerks>, tier_index: u8, perks_bitmask: u16) -> Result<()> {
+        let tier = ctx.accounts.event.tiers.get_mut(tier_index as usize).ok_or(ErrorCode::InvalidTierIndex)?;
+        tier.perks_bitmask = perks_bitmask;
+        Ok(())
+    }
+
+    /// Choose the proof-of-personhood provider and the gatekeeper network (or
+    /// collection) whose credentials one-per-person events accept. Platform authority only.
+    pub fn set_personhood_provider(
+        ctx: Context<SetPersonhoodProvider>,
+        provider: u8,
+        network: Pubkey,
+    ) -> Result<()> {
+        require!(provider == PERSONHOOD_CIVIC, ErrorCode::InvalidPersonhoodCredential);
+        let config = &mut ctx.accounts.platform_config;
+        config.personhood_provider = provider;
+        config.personhood_network = network;
+        Ok(())
+    }
+
+    /// Require a personhood credential on buy_ticket and allow one ticket per person,
+    /// across wallets. Only the organizer can call this, and only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_one_per_person(ctx: Context<SetOnePerPerson>, enabled: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        event.one_per_person = enabled;
+        Ok(())
+    }
+
+    /// Move every event of a festival pass bundle by `delta_seconds`. Only the bundle
+    /// creator can call this. `remaining_accounts` are all of the bundle's Event
+    /// accounts (writable, in `bundle.events` order), then when `delta_seconds > 0`
+    /// their vault PDAs (writable, same order), then any co-signing organizers; each
+    /// event's organizer must sign. Cancelled, superseded and started events cannot
+    /// move, and no event may move into the past. A postponement opens refunds on each
+    /// event, topped up by its organizer (writable when co-signing). All events are
+    /// checked before any is written.
+    pub fn update_series_schedule<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateSeriesSchedule<'info>>,
+        delta_seconds: i64,
+    ) -> Result<()> {
+        let bundle = &ctx.accounts.bundle;
+        let count = bundle.events.len();
+        let postponed = delta_seconds > 0;
+        let expected = if postponed { 2 * count } else { count };
+        require!(ctx.remaining_accounts.len() >= expected, ErrorCode::InvalidBundle);
+        let now = Clock::get()?.unix_timestamp;
+        let creator = ctx.accounts.creator.to_account_info();
+
+        let mut events = Vec::with_capacity(count);
+        for (info, key) in ctx.remaining_accounts.iter().zip(bundle.events.iter()) {
+            require_keys_eq!(info.key(), *key, ErrorCode::InvalidBundle);
+            let event = Account::<Event>::try_from(info)?;
+            let organizer = if event.organizer == creator.key() {
+                &creator
+            } else {
+                ctx.remaining_accounts
+                    .iter()
+                    .find(|a| a.is_signer && a.key() == event.organizer)
+                    .ok_or(ErrorCode::InvalidOrganizer)?
+            };
+            require!(!event.cancelled, ErrorCode::EventCancelled);
+            require!(!is_superseded(&event), ErrorCode::EventSuperseded);
+            require!(now < event.date_ts, ErrorCode::EventAlreadyStarted);
+            let new_date = event.date_ts.checked_add(delta_seconds).ok_or(ErrorCode::Overflow)?;
+            require!(new_date > now, ErrorCode::InvalidEventDate);
+            require!(event.sold == 0 || delta_seconds >= 0, ErrorCode::DateMovedEarlierAfterSales);
+            validate_sale_window(event.sale_start_ts, event.sale_end_ts, new_date)?;
+            events.push((event, organizer, new_date));
+        }
+
+        let vaults = &ctx.remaining_accounts[count..];
+        for (i, (mut event, organizer, new_date)) in events.into_iter().enumerate() {
+            if postponed {
+                let event_key = event.key();
+                open_postponement_refunds(
+                    &mut event,
+                    &event_key,
+                    new_date,
+                    &vaults[i],
+                    organizer,
+                    &ctx.accounts.system_program.to_account_info(),
+                    now,
+                )?;
+            }
+            event.date_ts = new_date;
+            event.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+
+    /// Require a refundable attendance deposit on each ticket of the event's free tiers.
+    /// A resale pays the deposit back to the seller out of the price. No-show deposits go
+    /// to `forfeit_wallet` (the organizer when None). Only the organizer can call this,
+    /// and only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_attendance_deposit(
+        ctx: Context<SetAttendanceDeposit>,
+        deposit_lamports: u64,
+        forfeit_wallet: Option<Pubkey>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(event.tiers.iter().any(|tier| tier.price_lamports == 0), ErrorCode::InvalidDepositConfig);
+        event.deposit_lamports = deposit_lamports;
+        event.deposit_forfeit_wallet = forfeit_wallet.unwrap_or(event.organizer);
+
+        let escrow = &mut ctx.accounts.deposit_escrow;
+        escrow.event = event.key();
+        escrow.bump = ctx.bumps.deposit_escrow;
+        Ok(())
+    }
+
+    /// Return an attendee's deposit once attendance is final (ATTENDANCE_FINALIZE_SECONDS
+    /// after `date_ts`). Signed by the holder recorded at check-in.
+    pub fn reclaim_deposit(ctx: Context<ReclaimDeposit>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.event.date_ts + ATTENDANCE_FINALIZE_SECONDS,
+            ErrorCode::AttendanceNotFinal
+        );
+        let deposit = ctx.accounts.receipt.deposit_lamports;
+        require!(deposit > 0, ErrorCode::NoDepositHeld);
+
+        transfer_lamports_from_pda(
+            &ctx.accounts.deposit_escrow.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            deposit,
+        )?;
+        ctx.accounts.receipt.deposit_lamports = 0;
+        ctx.accounts.event.deposits_held -= deposit;
+        Ok(())
+    }
+
+    /// Forfeit the deposits of tickets that never checked in, once attendance is final.
+    /// Permissionless. `remaining_accounts` are pairs of (writable Receipt, its
+    /// `["attendance", mint]` PDA); checked-in or settled receipts are skipped, and the
+    /// call fails with NothingToCrank if nothing was forfeited. Cancelled events forfeit
+    /// nothing; their deposits go back with the refunds.
+    pub fn sweep_forfeited_deposits<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepForfeitedDeposits<'info>>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        require!(
+            now >= ctx.accounts.event.date_ts + ATTENDANCE_FINALIZE_SECONDS,
+            ErrorCode::AttendanceNotFinal
+        );
+        require!(ctx.remaining_accounts.len() % 2 == 0, ErrorCode::InvalidQuantity);
+
+        let event_key = ctx.accounts.event.key();
+        let mut forfeited = 0u64;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let mut receipt = Account::<Receipt>::try_from(&pair[0])?;
+            require_keys_eq!(receipt.event, event_key, ErrorCode::InvalidTicketMint);
+            let (attendance, _) = Pubkey::find_program_address(
+                &[b"attendance", receipt.ticket_mint.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(pair[1].key(), attendance, ErrorCode::InvalidTicketMint);
+            if receipt.deposit_lamports == 0 || !pair[1].data_is_empty() {
+                continue;
+            }
+            forfeited = forfeited.checked_add(receipt.deposit_lamports).ok_or(ErrorCode::Overflow)?;
+            receipt.deposit_lamports = 0;
+            receipt.exit(&crate::ID)?;
+        }
+        require!(forfeited > 0, ErrorCode::NothingToCrank);
+
+        transfer_lamports_from_pda(
+            &ctx.accounts.deposit_escrow.to_account_info(),
+            &ctx.accounts.forfeit_wallet.to_account_info(),
+            forfeited,
+        )?;
+        ctx.accounts.event.deposits_held -= forfeited;
+        Ok(())
+    }
+
+    /// Write or replace the note on a ticket (merch size, dietary preference, ...). Only
+    /// the current holder can call this, until TICKET_NOTE_LOCK_SECONDS before the event.
+    pub fn set_ticket_note(ctx: Context<SetTicketNote>, note: [u8; 32]) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.event.date_ts - TICKET_NOTE_LOCK_SECONDS,
+            ErrorCode::TicketNoteLocked
+        );
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.note = note;
+        receipt.note_holder = ctx.accounts.holder.key();
+        Ok(())
+    }
+
+    /// Keep ticket notes valid after resale or transfer instead of clearing them. Only
+    /// the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_notes_survive_transfer(ctx: Context<SetNotesSurviveTransfer>, enabled: bool) -> Result<()> {
+        ctx.accounts.event.notes_survive_transfer = enabled;
+        Ok(())
+    }
+
+    /// Split an event into two replacement shows (e.g. early and late). Only the
+    /// organizer of all three can call this, before the original date. The original
+    /// stops selling; holders exchange tickets with migrate_ticket or take
+    /// claim_split_refund. Like cancel_event, the original's vault is topped up, or its
+    /// surplus returned, so it holds exactly the SOL still owed to holders, and it stays
+    /// locked until the original date. Both new events must be unsold, not part of
+    /// another split, and charge the original's tax and donation rates. Events that
+    /// accept token payments cannot be split.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn split_event(ctx: Context<SplitEvent>) -> Result<()> {
+        let original = ctx.accounts.event.key();
+        let (a, b) = (ctx.accounts.event_a.key(), ctx.accounts.event_b.key());
+        require!(a != b && a != original && b != original, ErrorCode::InvalidEventSplit);
+        let event = &ctx.accounts.event;
+        require!(!event.cancelled, ErrorCode::EventCancelled);
+        require!(!is_superseded(event), ErrorCode::EventSuperseded);
+        require!(!accepts_token_payments(event), ErrorCode::InvalidPaymentOption);
+        require!(!event.use_pnft, ErrorCode::PnftUnsupported);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < event.date_ts, ErrorCode::EventAlreadyStarted);
+        for show in [&ctx.accounts.event_a, &ctx.accounts.event_b] {
+            require_keys_eq!(show.organizer, ctx.accounts.organizer.key(), ErrorCode::InvalidOrganizer);
+            require!(
+                show.sold == 0
+                    && show.split_from == Pubkey::default()
+                    && !is_superseded(show)
+                    && !show.cancelled
+                    && show.tax_bps == event.tax_bps
+                    && show.donation_bps == event.donation_bps,
+                ErrorCode::InvalidEventSplit
+            );
+        }
+
+        let owed = refunds_owed(event);
+        let vault = ctx.accounts.vault.to_account_info();
+        let escrowed = vault.lamports().saturating_sub(Rent::get()?.minimum_balance(vault.data_len()));
+        if escrowed < owed {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.organizer.to_account_info(),
+                        to: vault.clone(),
+                    },
+                ),
+                owed - escrowed,
+            )?;
+        } else if escrowed > owed {
+            transfer_lamports_from_pda(&vault, &ctx.accounts.organizer.to_account_info(), escrowed - owed)?;
+        }
+        let vault = &mut ctx.accounts.vault;
+        vault.event = original;
+        vault.bump = ctx.bumps.vault;
+
+        let event = &mut ctx.accounts.event;
+        event.split_into = [a, b];
+        event.refundable_until = event.refundable_until.max(event.date_ts);
+        ctx.accounts.event_a.split_from = original;
+        ctx.accounts.event_b.split_from = original;
+        Ok(())
+    }
+
+    /// Exchange a ticket of a split event for the next ticket of one of its replacement
+    /// shows. The holder picks the show and a tier of it priced at most what the ticket
+    /// cost; both must have a seat left. The old ticket is burned and the new one minted
+    /// with a fixed supply, like a festival pass redemption. The ticket's refundable SOL
+    /// moves from the original's vault to the show's, under a new Receipt for the
+    /// holder, so the show can refund it; any attendance deposit is returned. Identity
+    /// mode shows need the holder's `identity_commitment`, as in buy_ticket.
+    pub fn migrate_ticket(
+        ctx: Context<MigrateTicket>,
+        _ticket_index: u32,
+        tier_index: u8,
+        identity_commitment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let target_key = ctx.accounts.target_event.key();
+        require!(
+            is_superseded(&ctx.accounts.event) && ctx.accounts.event.split_into.contains(&target_key),
+            ErrorCode::InvalidEventSplit
+        );
+        let target = &ctx.accounts.target_event;
+        require!(!target.use_pnft, ErrorCode::PnftUnsupported);
+        let accessible_unsold = target.accessible_seating_count - target.accessible_sold;
+        require!(
+            target.sold + target.held + target.pass_reserved + accessible_unsold < target.supply,
+            ErrorCode::SoldOut
+        );
+        require!(tier_available(target, tier_index)? > 0, ErrorCode::SoldOut);
+        let tier = &target.tiers[tier_index as usize];
+        require!(tier.price_lamports <= ctx.accounts.receipt.paid_lamports, ErrorCode::TierAbovePaidPrice);
+
+        burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    from: ctx.accounts.holder_token_account.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        let sold = target.sold;
+        let sold_bytes = sold.to_le_bytes();
+        let bump = ctx.bumps.target_ticket_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"ticket_authority",
+            target_key.as_ref(),
+            &sold_bytes,
+            &[bump],
+        ]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.target_ticket_mint.to_account_info(),
+                    to: ctx.accounts.holder_target_token_account.to_account_info(),
+                    authority: ctx.accounts.target_ticket_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        create_ticket_metadata(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.target_ticket_metadata.as_ref(),
+            &ctx.accounts.target_ticket_mint.to_account_info(),
+            &ctx.accounts.target_ticket_authority.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            target,
+            sold,
+            None,
+            signer_seeds,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.target_ticket_mint.to_account_info(),
+            ctx.accounts.target_ticket_authority.to_account_info(),
+            signer_seeds,
+        )?;
+
+        // The refundable part of the price follows the ticket; tax and donations were remitted
+        let event = &ctx.accounts.event;
+        let paid = ctx.accounts.receipt.paid_lamports;
+        let tax = (paid as u128 * event.tax_bps as u128 / 10_000) as u64;
+        let donation = (paid as u128 * event.donation_bps as u128 / 10_000) as u64;
+        transfer_lamports_from_pda(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.target_vault.to_account_info(),
+            paid - tax - donation,
+        )?;
+        let target_vault = &mut ctx.accounts.target_vault;
+        target_vault.event = target_key;
+        target_vault.bump = ctx.bumps.target_vault;
+
+        let deposit = ctx.accounts.receipt.deposit_lamports;
+        if deposit > 0 {
+            let escrow = ctx.accounts.deposit_escrow.as_ref().ok_or(ErrorCode::MissingDepositEscrow)?;
+            transfer_lamports_from_pda(
+                &escrow.to_account_info(),
+                &ctx.accounts.holder.to_account_info(),
+                deposit,
+            )?;
+            ctx.accounts.receipt.deposit_lamports = 0;
+            ctx.accounts.event.deposits_held -= deposit;
+        }
+
+        let identity_commitment = take_identity_commitment(
+            &ctx.accounts.target_event,
+            identity_commitment,
+            ctx.accounts.identity_purchase.as_ref(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let holder = ctx.accounts.holder.key();
+        let old = &ctx.accounts.receipt;
+        let receipt = &mut ctx.accounts.target_receipt;
+        receipt.buyer = holder;
+        receipt.event = target_key;
+        receipt.ticket_mint = ctx.accounts.target_ticket_mint.key();
+        receipt.paid_lamports = paid;
+        receipt.purchased_ts = old.purchased_ts;
+        receipt.refunded = false;
+        receipt.category = None;
+        receipt.payment_index = None;
+        receipt.tip_lamports = 0;
+        receipt.perks_bitmask = ctx.accounts.target_event.tiers[tier_index as usize].perks_bitmask;
+        receipt.deposit_lamports = 0;
+        receipt.note = [0; 32];
+        receipt.note_holder = holder;
+        receipt.identity_commitment = identity_commitment;
+        receipt.tier_index = tier_index;
+        receipt.bump = ctx.bumps.target_receipt;
+
+        let target = &mut ctx.accounts.target_event;
+        record_tier_sale(target, tier_index, 1)?;
+        target.tax_collected = target.tax_collected.checked_add(tax).ok_or(ErrorCode::Overflow)?;
+        target.total_donated = target.total_donated.checked_add(donation).ok_or(ErrorCode::Overflow)?;
+        target.analytics.record_primary_sale(&holder, paid)?;
+        let event = &mut ctx.accounts.event;
+        event.migrated_out = event.migrated_out.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        emit!(TicketMigrated {
+            from_event: event.key(),
+            to_event: target_key,
+            old_mint: ctx.accounts.ticket_mint.key(),
+            new_mint: ctx.accounts.target_ticket_mint.key(),
+            holder: ctx.accounts.holder.key(),
+        });
+        Ok(())
+    }
+
+    /// Decline both replacement shows of a split event: burn the ticket and get the SOL
+    /// price back from the reserve in the original's vault, less remitted tax and
+    /// donations (and any attendance deposit back in full). Whoever holds the ticket is
+    /// refunded, not only the original buyer, until the original date.
+    pub fn claim_split_refund(ctx: Context<ClaimSplitRefund>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let receipt = &ctx.accounts.receipt;
+        require!(is_superseded(event), ErrorCode::InvalidEventSplit);
+        require!(!receipt.refunded, ErrorCode::AlreadyRefunded);
+        require!(Clock::get()?.unix_timestamp <= event.date_ts, ErrorCode::EventAlreadyStarted);
+        let paid_in_token = receipt
+            .payment_index
+            .and_then(|index| event.accepted_payments.get(index as usize))
+            .is_some_and(|option| option.mint.is_some());
+        require!(!paid_in_token, ErrorCode::InvalidPaymentOption);
+
+        burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    from: ctx.accounts.holder_token_account.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        let tax = (receipt.paid_lamports as u128 * event.tax_bps as u128 / 10_000) as u64;
+        let donation = (receipt.paid_lamports as u128 * event.donation_bps as u128 / 10_000) as u64;
+        let refund = receipt.paid_lamports - tax - donation;
+        transfer_lamports_from_pda(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            refund,
+        )?;
+        ctx.accounts.event.analytics.record_refund(refund)?;
+
+        let deposit = ctx.accounts.receipt.deposit_lamports;
+        if deposit > 0 {
+            let escrow = ctx.accounts.deposit_escrow.as_ref().ok_or(ErrorCode::MissingDepositEscrow)?;
+            transfer_lamports_from_pda(
+                &escrow.to_account_info(),
+                &ctx.accounts.holder.to_account_info(),
+                deposit,
+            )?;
+            ctx.accounts.receipt.deposit_lamports = 0;
+            ctx.accounts.event.deposits_held -= deposit;
+        }
+
+        require!(
+            ctx.accounts.receipt.identity_commitment == [0; 32] || ctx.accounts.identity_purchase.is_some(),
+            ErrorCode::IdentityCommitmentRequired
+        );
+        ctx.accounts.receipt.refunded = true;
+        Ok(())
+    }
+
+    /// Block resale listings for `blackout_secs` after each ticket's primary purchase
+    /// (0 disables). Only the organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_resale_blackout(ctx: Context<SetResaleBlackout>, blackout_secs: i64) -> Result<()> {
+        require!(blackout_secs >= 0, ErrorCode::InvalidResaleBlackout);
+        ctx.accounts.event.resale_blackout_secs = blackout_secs;
+        Ok(())
+    }
+
+    /// Require an identity commitment (a hashed ID or membership number) on every
+    /// purchase, one ticket per commitment, through buy_ticket_v2 version 3. With
+    /// `verify_at_check_in`, check-in goes through check_in_with_identity and the holder
+    /// must present the commitment's preimage. Only the organizer can call this, and
+    /// only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_identity_mode(
+        ctx: Context<SetIdentityMode>,
+        required: bool,
+        verify_at_check_in: bool,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(required || !verify_at_check_in, ErrorCode::IdentityCommitmentRequired);
+        event.identity_required = required;
+        event.identity_at_check_in = verify_at_check_in;
+        Ok(())
+    }
+
+    /// check_in for events with event.identity_at_check_in: the holder presents the
+    /// preimage of the identity commitment on the ticket's receipt, which must hash
+    /// (SHA-256) to it. Delegated door staff can't check these tickets in.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn check_in_with_identity(
+        ctx: Context<CheckIn>,
+        ticket_index: u32,
+        identity_preimage: [u8; 32],
+    ) -> Result<()> {
+        let receipt = ctx.accounts.receipt.as_ref().ok_or(ErrorCode::IdentityPreimageRequired)?;
+        require!(
+            receipt.identity_commitment != [0; 32]
+                && hashv(&[&identity_preimage]).to_bytes() == receipt.identity_commitment,
+            ErrorCode::IdentityPreimageMismatch
+        );
+        let note = receipt.note_for(&ctx.accounts.holder.key(), ctx.accounts.event.notes_survive_transfer);
+        let (category, perks_bitmask) = (receipt.category, receipt.perks_bitmask);
+        record_attendance(
+            &mut ctx.accounts.event,
+            &mut ctx.accounts.attendance_record,
+            ctx.accounts.ticket_mint.key(),
+            ctx.accounts.holder.key(),
+            ticket_index,
+            category,
+            perks_bitmask,
+            note,
+            ctx.bumps.attendance_record,
+        )
+    }
+
+    /// Create the cheapest-asks book for an event. Permissionless; the caller pays rent.
+    pub fn init_ask_book(ctx: Context<InitAskBook>) -> Result<()> {
+        let book = &mut ctx.accounts.ask_book;
+        book.event = ctx.accounts.event.key();
+        book.asks = Vec::new();
+        book.bump = ctx.bumps.ask_book;
+        Ok(())
+    }
+
+    /// Permissionless crank: re-verify the ask book against the Listing accounts passed
+    /// in `remaining_accounts`. Closed listings on the book are dropped; live listings
+    /// of this event are re-entered at their current price (or dropped if USD-priced,
+    /// flagged or hidden). Anything else is rejected.
+    pub fn repair_ask_book<'info>(ctx: Context<'_, '_, 'info, 'info, RepairAskBook<'info>>) -> Result<()> {
+        let event_key = ctx.accounts.event.key();
+        let book = &mut ctx.accounts.ask_book;
+        for info in ctx.remaining_accounts.iter() {
+            if info.owner != &crate::ID || info.data_is_empty() {
+                require!(
+                    info.data_is_empty() && book.asks.iter().any(|a| a.listing == info.key()),
+                    ErrorCode::InvalidAskBookEntry
+                );
+                book.remove(&info.key());
+                continue;
+            }
+            let listing = Account::<Listing>::try_from(info).map_err(|_| ErrorCode::InvalidAskBookEntry)?;
+            require_keys_eq!(listing.event, event_key, ErrorCode::InvalidAskBookEntry);
+            if listing.usd_price_cents > 0 || listing.anomaly_flag || !listing.visible {
+                book.remove(&info.key());
+            } else {
+                book.offer(info.key(), listing.price_lamports);
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the Receipt of a burned ticket (refunded, migrated or otherwise destroyed)
+    /// and return its rent to the buyer who paid it. Permissionless. The ticket mint's
+    /// supply must be zero and no attendance deposit may still be held against it; the
+    /// receipt's SOL price is carried on the event so audit_royalties still balances.
+    pub fn close_ticket_record(ctx: Context<CloseTicketRecord>) -> Result<()> {
+        require!(ctx.accounts.ticket_mint.supply == 0, ErrorCode::TicketStillLive);
+        let receipt = &ctx.accounts.receipt;
+        require!(receipt.deposit_lamports == 0, ErrorCode::TicketStillLive);
+
+        // A closed event has nothing left to audit
+        let info = &ctx.accounts.event;
+        if info.owner == &crate::ID && !info.data_is_empty() {
+            let mut event = Event::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            let paid_in_token = receipt
+                .payment_index
+                .and_then(|index| event.accepted_payments.get(index as usize))
+                .is_some_and(|option| option.mint.is_some());
+            if !paid_in_token {
+                event.closed_receipt_revenue = event
+                    .closed_receipt_revenue
+                    .checked_add(receipt.paid_lamports)
+                    .ok_or(ErrorCode::Overflow)?;
+                event.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            }
+        }
+
+        // Receipt PDA is closed via `close = buyer`
+        Ok(())
+    }
+
+    /// Fix an event's details without recreating it (the PDA and shared links stay the
+    /// same). Title, venue, date and headline tier name can change until the event
+    /// starts; the headline (first tier) price, and the supply of a single-tier event,
+    /// only before the first sale. `None` leaves a field unchanged. Moving the date
+    /// later opens postponement refunds (see open_postponement_refunds). Only the
+    /// organizer can call this.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn update_event(
+        ctx: Context<UpdateEvent>,
+        title: Option<String>,
+        venue: Option<String>,
+        date_ts: Option<i64>,
+        price_lamports: Option<u64>,
+        tier_name: Option<String>,
+        supply: Option<u32>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < ctx.accounts.event.date_ts, ErrorCode::EventAlreadyStarted);
+        require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+        require!(!is_superseded(&ctx.accounts.event), ErrorCode::EventSuperseded);
+        if let Some(date_ts) = date_ts {
+            require!(date_ts > now, ErrorCode::InvalidEventDate);
+            // Holders bought for the announced date; it can only be postponed
+            require!(
+                ctx.accounts.event.sold == 0 || date_ts >= ctx.accounts.event.date_ts,
+                ErrorCode::DateMovedEarlierAfterSales
+            );
+            if date_ts > ctx.accounts.event.date_ts {
+                let event_key = ctx.accounts.event.key();
+                let (Some(vault), Some(system_program)) = (&ctx.accounts.vault, &ctx.accounts.system_program)
+                else {
+                    return err!(ErrorCode::InvalidVault);
+                };
+                open_postponement_refunds(
+                    &mut ctx.accounts.event,
+                    &event_key,
+                    date_ts,
+                    vault,
+                    &ctx.accounts.organizer.to_account_info(),
+                    &system_program.to_account_info(),
+                    now,
+                )?;
+            }
+        }
+        let event = &mut ctx.accounts.event;
+        if let Some(title) = title {
+            require!(title.len() <= 64, ErrorCode::TitleTooLong);
+            event.title = title;
+        }
+        if let Some(venue) = venue {
+            require!(venue.len() <= 64, ErrorCode::VenueTooLong);
+            event.venue = venue;
+        }
+        if let Some(date_ts) = date_ts {
+            validate_sale_window(event.sale_start_ts, event.sale_end_ts, date_ts)?;
+            event.date_ts = date_ts;
+        }
+        if let Some(price_lamports) = price_lamports {
+            require!(event.sold == 0, ErrorCode::EventHasSales);
+            require!(
+                price_lamports == 0 || price_lamports >= MIN_TICKET_PRICE_LAMPORTS,
+                ErrorCode::InvalidPrice
+            );
+            let max_price = ctx.accounts.platform_config.max_ticket_price;
+            require!(price_lamports <= max_price, ErrorCode::PriceAboveCeiling);
+            event.price_lamports = price_lamports;
+            event.tiers[0].price_lamports = price_lamports;
+        }
+        if let Some(tier_name) = tier_name {
+            require!(tier_name.len() <= MAX_TIER_NAME_LEN, ErrorCode::TierNameTooLong);
+            event.tiers[0].name = tier_name.clone();
+            event.tier_name = tier_name;
+        }
+        if let Some(supply) = supply {
+            require!(event.sold == 0, ErrorCode::EventHasSales);
+            require!(event.tiers.len() == 1, ErrorCode::InvalidTiers);
+            let reserved = event.held + event.pass_reserved + event.accessible_seating_count;
+            let scheduled: u32 = event.release_schedule.iter().map(|t| t.count).sum();
+            require!(
+                supply > 0
+                    && supply >= reserved
+                    && supply >= scheduled
+                    && event.concessions.iter().all(|c| c.max_count <= supply),
+                ErrorCode::InvalidSupply
+            );
+            event.supply = supply;
+            event.tiers[0].supply = supply;
+            event.capacity = event.capacity.max(supply);
+        }
+        event.last_updated_ts = now;
+        Ok(())
+    }
+
+    /// Sell only for tokens: purchases must pick a token option from accepted_payments,
+    /// so buyers aren't exposed to SOL's price (e.g. a USDC-only event). Only the
+    /// organizer can call this, and only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn set_token_only(ctx: Context<SetTokenOnly>, enabled: bool) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        require!(
+            !enabled || event.accepted_payments.iter().any(|option| option.mint.is_some()),
+            ErrorCode::InvalidPaymentOption
+        );
+        event.token_only = enabled;
+        Ok(())
+    }
+
+    /// Move the event's sale window (`None` = open-ended on that side). Only the
+    /// organizer can call this, and only before the first sale.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn update_sale_window(
+        ctx: Context<UpdateSaleWindow>,
+        sale_start_ts: Option<i64>,
+        sale_end_ts: Option<i64>,
+    ) -> Result<()> {
+        let event = &mut ctx.accounts.event;
+        require!(event.sold == 0, ErrorCode::EventHasSales);
+        validate_sale_window(sale_start_ts, sale_end_ts, event.date_ts)?;
+        event.sale_start_ts = sale_start_ts;
+        event.sale_end_ts = sale_end_ts;
+        Ok(())
+    }
+
+    /// Close the buyer's BuyerRecord once the event has started, returning its rent.
+    pub fn close_buyer_record(ctx: Context<CloseBuyerRecord>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.event.date_ts,
+            ErrorCode::EventNotStarted
+        );
+        // BuyerRecord PDA is closed via `close = buyer`
+        Ok(())
+    }
+
+    /// Buy `quantity` tickets (up to MAX_BATCH_PURCHASE) of one tier in one transaction,
+    /// for indices `event.sold..event.sold + quantity`. All are minted or none: asking
+    /// for more than remain fails with `InsufficientSupply`. `remaining_accounts` are,
+    /// per index in order, the writable `(ticket_authority, ticket_mint,
+    /// buyer_token_account, receipt, name_override)` accounts buy_ticket takes for one
+    /// ticket, followed by its `ticket_metadata` when the event has a metadata URI. SOL
+    /// only, at the tier price; events needing per-ticket proofs, a deposit or fan
+    /// tokens must use buy_ticket.
+    pub fn buy_tickets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyTickets<'info>>,
+        quantity: u8,
+        tier_index: u8,
+    ) -> Result<()> {
+        require!(quantity > 0 && quantity <= MAX_BATCH_PURCHASE, ErrorCode::InvalidQuantity);
+        require!(!ctx.accounts.event.use_pnft, ErrorCode::PnftUnsupported);
+        let with_metadata = ticket_metadata_uri(&ctx.accounts.event, 0).is_some();
+        let per_ticket = BATCH_ACCOUNTS_PER_TICKET + with_metadata as usize;
+        require!(
+            ctx.remaining_accounts.len() == quantity as usize * per_ticket,
+            ErrorCode::InvalidQuantity
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let count = quantity as u32;
+
+        // Each ticket counts against the per-minute rate limit
+        let rate_limit = &mut ctx.accounts.rate_limit;
+        if now - rate_limit.window_start_ts > RATE_LIMIT_WINDOW_SECONDS {
+            rate_limit.purchases_last_minute = 0;
+            rate_limit.window_start_ts = now;
+        }
+        require!(
+            rate_limit.purchases_last_minute + quantity <= RATE_LIMIT_PER_MINUTE,
+            ErrorCode::RateLimitExceeded
+        );
+        rate_limit.purchases_last_minute += quantity;
+        rate_limit.bump = ctx.bumps.rate_limit;
+
+        if ctx.accounts.event.max_per_wallet > 0 {
+            let event_key = ctx.accounts.event.key();
+            let limit = ctx.accounts.event.max_per_wallet;
+            let record = ctx.accounts.buyer_record.as_mut().ok_or(ErrorCode::PurchaseLimitReached)?;
+            require!(record.tickets_bought + quantity as u16 <= limit, ErrorCode::PurchaseLimitReached);
+            record.event = event_key;
+            record.buyer = ctx.accounts.buyer.key();
+            record.tickets_bought += quantity as u16;
+            record.bump = ctx.bumps.buyer_record.ok_or(ErrorCode::PurchaseLimitReached)?;
+        }
+
+        let event = &ctx.accounts.event;
+        require!(!event.sales_paused, ErrorCode::SalesPaused);
+        require!(!event.cancelled, ErrorCode::EventCancelled);
+        require!(!is_superseded(event), ErrorCode::EventSuperseded);
+        require!(now < event.date_ts, ErrorCode::EventEnded);
+        require!(!matches!(event.sale_start_ts, Some(start) if now < start), ErrorCode::SaleNotStarted);
+        require!(!matches!(event.sale_end_ts, Some(end) if now >= end), ErrorCode::SaleEnded);
+        require!(
+            !event.requires_attestation
+                && !event.one_per_person
+                && !event.identity_required
+                && !event.token_only
+                && event.deposit_lamports == 0
+                && event.fan_token_mint.is_none(),
+            ErrorCode::BatchPurchaseUnsupported
+        );
+
+        // Check the whole quantity up front so a partial batch never mints
+        let start_index = event.sold;
+        let end_index = start_index.checked_add(count).ok_or(ErrorCode::Overflow)?;
+        let accessible_unsold = event.accessible_seating_count - event.accessible_sold;
+        let reserved = event.held + event.pass_reserved + accessible_unsold;
+        require!(
+            end_index.checked_add(reserved).ok_or(ErrorCode::Overflow)? <= event.supply
+                && end_index <= released_supply(event, now),
+            ErrorCode::InsufficientSupply
+        );
+        require!(count <= tier_available(event, tier_index)?, ErrorCode::InsufficientSupply);
+        let tier = &event.tiers[tier_index as usize];
+
+        let price = tier.price_lamports;
+        let tax = (price as u128 * event.tax_bps as u128 / 10_000) as u64;
+        let donation = (price as u128 * event.donation_bps as u128 / 10_000) as u64;
+        let total_tax = tax.checked_mul(quantity as u64).ok_or(ErrorCode::Overflow)?;
+        let total_donation = donation.checked_mul(quantity as u64).ok_or(ErrorCode::Overflow)?;
+        let total = price.checked_mul(quantity as u64).ok_or(ErrorCode::Overflow)?;
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let receipt_space = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1;
+
+        // Fail early with the shortfall logged: the price plus rent for every account
+        // the loop below creates
+        let rent = Rent::get()?;
+        let mut required = total;
+        for accounts in ctx.remaining_accounts.chunks(per_ticket) {
+            let [_, mint, buyer_token_account, receipt, ..] = accounts else {
+                return err!(ErrorCode::InvalidQuantity);
+            };
+            let mut spaces = vec![
+                (mint, SplMint::LEN),
+                (buyer_token_account, spl_token_2022::state::Account::LEN),
+                (receipt, receipt_space),
+            ];
+            if with_metadata {
+                spaces.push((&accounts[BATCH_ACCOUNTS_PER_TICKET], TOKEN_METADATA_ACCOUNT_LEN));
+            }
+            for (account, space) in spaces {
+                if account.data_is_empty() {
+                    let owed = rent.minimum_balance(space).saturating_sub(account.lamports());
+                    required = required.checked_add(owed).ok_or(ErrorCode::Overflow)?;
+                }
+            }
+        }
+        require_funds(&buyer, required)?;
+
+        if total_tax > 0 {
+            let tax_wallet = ctx.accounts.tax_wallet.as_ref().ok_or(ErrorCode::InvalidTaxWallet)?;
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer { from: buyer.clone(), to: tax_wallet.to_account_info() },
+                ),
+                total_tax,
+            )?;
+        }
+        if total_donation > 0 {
+            let donation_wallet = ctx
+                .accounts
+                .donation_wallet
+                .as_ref()
+                .ok_or(ErrorCode::InvalidDonationWallet)?;
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer { from: buyer.clone(), to: donation_wallet.to_account_info() },
+                ),
+                total_donation,
+            )?;
+        }
+        let proceeds_to = match (proceeds_escrowed(event), &ctx.accounts.vault) {
+            (false, _) => ctx.accounts.organizer.to_account_info(),
+            (true, Some(vault)) => vault.to_account_info(),
+            (true, None) => return err!(ErrorCode::InvalidVault),
+        };
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer { from: buyer.clone(), to: proceeds_to },
+            ),
+            total - total_tax - total_donation,
+        )?;
+
+        let event_key = event.key();
+        let perks_bitmask = tier.perks_bitmask;
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let organizer = ctx.accounts.organizer.to_account_info();
+        for (accounts, index) in ctx
+            .remaining_accounts
+            .chunks(per_ticket)
+            .zip(start_index..end_index)
+        {
+            let [authority, mint, buyer_token_account, receipt, name_override, metadata @ ..] = accounts else {
+                return err!(ErrorCode::InvalidQuantity);
+            };
+            let index_bytes = index.to_le_bytes();
+            let (authority_key, authority_bump) = Pubkey::find_program_address(
+                &[b"ticket_authority", event_key.as_ref(), &index_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(authority.key(), authority_key, ErrorCode::InvalidTicketMint);
+            let (mint_key, mint_bump) = Pubkey::find_program_address(
+                &[b"ticket_mint", event_key.as_ref(), &index_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(mint.key(), mint_key, ErrorCode::InvalidTicketMint);
+
+            create_ticket_mint(&buyer, mint, &system_program, &token_program, &event_key, index, mint_bump, &authority_key)?;
+
+            require_keys_eq!(
+                buyer_token_account.key(),
+                get_associated_token_address_with_program_id(&buyer.key(), &mint_key, &token_program.key()),
+                ErrorCode::InvalidTicketMint
+            );
+            anchor_spl::associated_token::create_idempotent(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: buyer.clone(),
+                    associated_token: buyer_token_account.clone(),
+                    authority: buyer.clone(),
+                    mint: mint.clone(),
+                    system_program: system_program.clone(),
+                    token_program: token_program.clone(),
+                },
+            ))?;
+
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"ticket_authority",
+                event_key.as_ref(),
+                &index_bytes,
+                &[authority_bump],
+            ]];
+            mint_to(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    MintTo { mint: mint.clone(), to: buyer_token_account.clone(), authority: authority.clone() },
+                    signer_seeds,
+                ),
+                1,
+            )?;
+            let (name_override_key, _) = Pubkey::find_program_address(
+                &[b"name_override", event_key.as_ref(), &index_bytes],
+                ctx.program_id,
+            );
+            require_keys_eq!(name_override.key(), name_override_key, ErrorCode::InvalidTicketMint);
+            let custom_name = take_name_override(name_override, &organizer)?;
+            create_ticket_metadata(
+                ctx.accounts.token_metadata_program.as_ref(),
+                metadata.first(),
+                mint,
+                authority,
+                &buyer,
+                &system_program,
+                &ctx.accounts.event,
+                index,
+                custom_name.as_deref(),
+                signer_seeds,
+            )?;
+            revoke_mint_authority(token_program.clone(), mint.clone(), authority.clone(), signer_seeds)?;
+
+            let (receipt_key, receipt_bump) = Pubkey::find_program_address(
+                &[b"receipt", buyer.key().as_ref(), mint_key.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(receipt.key(), receipt_key, ErrorCode::InvalidTicketMint);
+            create_pda_account(
+                &buyer,
+                receipt,
+                &system_program,
+                receipt_space,
+                ctx.program_id,
+                &[&[b"receipt", buyer.key().as_ref(), mint_key.as_ref(), &[receipt_bump]]],
+            )?;
+            let record = Receipt {
+                buyer: buyer.key(),
+                event: event_key,
+                ticket_mint: mint_key,
+                paid_lamports: price,
+                purchased_ts: now,
+                refunded: false,
+                category: None,
+                payment_index: None,
+                tip_lamports: 0,
+                perks_bitmask,
+                deposit_lamports: 0,
+                note: [0; 32],
+                note_holder: buyer.key(),
+                identity_commitment: [0; 32],
+                tier_index,
+                bump: receipt_bump,
+            };
+            record.try_serialize(&mut &mut receipt.try_borrow_mut_data()?[..])?;
+
+            emit!(TicketPurchased {
+                event: event_key,
+                buyer: buyer.key(),
+                ticket_mint: mint_key,
+                price_lamports: price,
+                tip_lamports: 0,
+                donation_lamports: donation,
+                custom_name,
+            });
+        }
+
+        let event = &mut ctx.accounts.event;
+        record_tier_sale(event, tier_index, count)?;
+        event.tax_collected = event.tax_collected.checked_add(total_tax).ok_or(ErrorCode::Overflow)?;
+        event.total_donated = event.total_donated.checked_add(total_donation).ok_or(ErrorCode::Overflow)?;
+        event.analytics.record_primary_sale(&buyer.key(), total)?;
+        if event.grace_period_seconds > 0 {
+            event.refundable_until = now + event.grace_period_seconds as i64;
+        }
+
+        Ok(())
+    }
+
+    /// Cancel an event that has sold tickets. Sales and proceeds withdrawals stop, and
+    /// the event vault is topped up by the organizer, or its surplus returned, so that
+    /// it holds exactly the SOL still owed to holders: primary revenue less tax,
+    /// donations and refunds already paid, plus the event's share of pass sales.
+    /// Holders then burn their tickets for a refund with `claim_refund`. Only the
+    /// organizer can call this. Events that accept token payments or were split cannot
+    /// be cancelled.
+    #[access_control(organizer_only(&ctx.accounts.event, &ctx.accounts.organizer))]
+    pub fn cancel_event(ctx: Context<CancelEvent>) -> Result<()> {
+        let event = &ctx.accounts.event;
+        require!(!event.cancelled, ErrorCode::EventCancelled);
+        require!(!is_superseded(event), ErrorCode::EventSuperseded);
+        require!(!accepts_token_payments(event), ErrorCode::InvalidPaymentOption);
+
+        let owed = refunds_owed(event).checked_add(event.pass_refunds_owed).ok_or(ErrorCode::Overflow)?;
+        let vault = ctx.accounts.vault.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(vault.data_len());
+        let escrowed = vault.lamports().saturating_sub(rent_floor);
+        if escrowed < owed {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.organizer.to_account_info(),
+                        to: vault.clone(),
+                    },
+                ),
+                owed - escrowed,
+            )?;
+        } else if escrowed > owed {
+            transfer_lamports_from_pda(&vault, &ctx.accounts.organizer.to_account_info(), escrowed - owed)?;
+        }
+        let vault = &mut ctx.accounts.vault;
+        vault.event = ctx.accounts.event.key();
+        vault.bump = ctx.bumps.vault;
+
+        let event = &mut ctx.accounts.event;
+        event.cancelled = true;
+        event.refunds_pending = event.sold.saturating_sub(event.analytics.refund_count);
+        Ok(())
+    }
+
+    /// Burn a ticket of a cancelled event, or of a postponed one until
+    /// `postponement_refund_until`, and get its SOL price back from the event vault, less
+    /// remitted tax and donations (and any attendance deposit back in full). Whoever
+    /// holds the ticket is refunded, not only the original buyer.
+    pub fn claim_refund(ctx: Context<ClaimRefund>, _ticket_index: u32) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let receipt = &ctx.accounts.receipt;
+        require!(
+            event.cancelled || Clock::get()?.unix_timestamp <= event.postponement_refund_until,
+            ErrorCode::EventNotCancelled
+        );
+        require!(!receipt.refunded, ErrorCode::AlreadyRefunded);
+
+        if event.use_pnft {
+            let mint = ctx.accounts.ticket_mint.to_account_info();
+            let system_program = ctx
+                .accounts
+                .system_program
+                .as_ref()
+                .ok_or(ErrorCode::MissingMetadataAccounts)?
+                .to_account_info();
+            let token_program = ctx.accounts.token_program.to_account_info();
+            let pnft = PnftAccounts::new(
+                ctx.accounts.token_metadata_program.as_ref(),
+                ctx.accounts.ticket_metadata.as_ref(),
+                ctx.accounts.ticket_edition.as_ref(),
+                ctx.accounts.instructions.as_ref(),
+                &mint,
+                &system_program,
+                &token_program,
+            )?;
+            let token_record = ctx.accounts.holder_token_record.as_ref().ok_or(ErrorCode::MissingMetadataAccounts)?;
+            pnft.burn(
+                &ctx.accounts.holder.to_account_info(),
+                &ctx.accounts.holder_token_account.to_account_info(),
+                token_record,
+            )?;
+        } else {
+            burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.ticket_mint.to_account_info(),
+                        from: ctx.accounts.holder_token_account.to_account_info(),
+                        authority: ctx.accounts.holder.to_account_info(),
+                    },
+                ),
+                1,
+            )?;
+        }
+
+        let tax = (receipt.paid_lamports as u128 * event.tax_bps as u128 / 10_000) as u64;
+        let donation = (receipt.paid_lamports as u128 * event.donation_bps as u128 / 10_000) as u64;
+        let refund = receipt.paid_lamports - tax - donation;
+        transfer_lamports_from_pda(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            refund,
+        )?;
+        ctx.accounts.event.analytics.record_refund(refund)?;
+
+        let deposit = ctx.accounts.receipt.deposit_lamports;
+        if deposit > 0 {
+            let escrow = ctx.accounts.deposit_escrow.as_ref().ok_or(ErrorCode::MissingDepositEscrow)?;
+            transfer_lamports_from_pda(
+                &escrow.to_account_info(),
+                &ctx.accounts.holder.to_account_info(),
+                deposit,
+            )?;
+            ctx.accounts.receipt.deposit_lamports = 0;
+            ctx.accounts.event.deposits_held -= deposit;
+        }
+
+        require!(
+            ctx.accounts.receipt.identity_commitment == [0; 32] || ctx.accounts.identity_purchase.is_some(),
+            ErrorCode::IdentityCommitmentRequired
+        );
+        ctx.accounts.receipt.refunded = true;
+        let event = &mut ctx.accounts.event;
+        event.refunds_pending = event.refunds_pending.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Refund a festival pass holder the event's share of the pass price once the event
+    /// is cancelled, from the event vault. Each pass claims once per event, whether or
+    /// not it was redeemed there; the pass itself stays valid for the other events.
+    pub fn claim_pass_refund(ctx: Context<ClaimPassRefund>, _pass_index: u32) -> Result<()> {
+        require!(ctx.accounts.event.cancelled, ErrorCode::EventNotCancelled);
+        let event_key = ctx.accounts.event.key();
+        let bundle = &ctx.accounts.bundle;
+        let position = bundle.events.iter().position(|e| *e == event_key).ok_or(ErrorCode::InvalidBundle)?;
+        let share = pass_share(bundle, position);
+        transfer_lamports_from_pda(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            share,
+        )?;
+
+        ctx.accounts.pass_refund.bump = ctx.bumps.pass_refund;
+        let event = &mut ctx.accounts.event;
+        event.pass_refunds_owed = event.pass_refunds_owed.saturating_sub(share);
+        Ok(())
+    }
+
+    /// View: quote what a fresh buy_ticket of a tier costs `buyer` in SOL, including
+    /// rent for the accounts it creates (excluding transaction fees), and the shortfall
+    /// against the buyer's balance. Emits PurchaseQuote. Uses the same charge
+    /// computation as buy_ticket's pre-flight check.
+    pub fn quote_ticket(ctx: Context<QuoteTicket>, tier_index: u8, tip_lamports: u64) -> Result<()> {
+        let event = &ctx.accounts.event;
+        let tier = event.tiers.get(tier_index as usize).ok_or(ErrorCode::InvalidTierIndex)?;
+        let charges_lamports = primary_purchase_lamports(event, tier_index, tier.price_lamports, false, tip_lamports)?;
+        let rent_lamports = fresh_purchase_rent(
+            event,
+            ctx.accounts.rate_limit.data_is_empty(),
+            ctx.accounts.buyer_record.data_is_empty(),
+        )?;
+        let total_lamports = charges_lamports.checked_add(rent_lamports).ok_or(ErrorCode::Overflow)?;
+        emit!(PurchaseQuote {
+            event: event.key(),
+            buyer: ctx.accounts.buyer.key(),
+            price_lamports: tier.price_lamports,
+            charges_lamports,
+            rent_lamports,
+            total_lamports,
+            shortfall_lamports: total_lamports.saturating_sub(ctx.accounts.buyer.lamports()),
+        });
+        Ok(())
+    }
+
+    /// Create the fee bucket for day `day` (unix time / FEE_DAY_SECONDS), for today or
+    /// later. Permissionless; the caller pays rent.
+    pub fn init_fee_day(ctx: Context<InitFeeDay>, day: u32) -> Result<()> {
+        require!(
+            day as i64 >= Clock::get()?.unix_timestamp / FEE_DAY_SECONDS,
+            ErrorCode::FeeDayPassed
+        );
+        let bucket = &mut ctx.accounts.fee_day;
+        bucket.day = day;
+        bucket.resale_fees_collected = 0;
+        bucket.resale_fee_count = 0;
+        bucket.bump = ctx.bumps.fee_day;
+        Ok(())
+    }
+
+    /// View: platform fee totals, returned as a borsh FeeReport in return data, for
+    /// the platform and, when passed, one event and one day bucket. Primary sales pay
+    /// no platform fee and resale fees are paid to the platform as they are collected,
+    /// so everything collected has also been paid out.
+    pub fn get_fee_report(ctx: Context<GetFeeReport>) -> Result<()> {
+        let config = &ctx.accounts.platform_config;
+        let report = FeeReport {
+            resale_fees_collected: config.resale_fees_collected,
+            resale_fee_count: config.resale_fee_count,
+            event_fees_collected: ctx.accounts.event.as_ref().map(|e| e.platform_fees_collected),
+            day: ctx.accounts.fee_day.as_ref().map(|d| (d.day, d.resale_fees_collected, d.resale_fee_count)),
+        };
+        set_return_data(&report.try_to_vec()?);
+        Ok(())
+    }
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────
+
+/// Deterministically pick up to `count` distinct attendance indices in `0..pool` from `seed`.
+pub fn draw_winners(seed: &[u8; 32], pool: u32, count: u8) -> Vec<u32> {
+    let count = (count as u32).min(pool) as usize;
+    let mut winners: Vec<u32> = Vec::with_capacity(count);
+    let mut nonce: u32 = 0;
+    while winners.len() < count {
+        let h = hashv(&[seed, &nonce.to_le_bytes()]).to_bytes();
+        let candidate = (u64::from_le_bytes(h[..8].try_into().unwrap()) % pool as u64) as u32;
+        if !winners.contains(&candidate) {
+            winners.push(candidate);
+        }
+        nonce = nonce.wrapping_add(1);
+    }
+    winners
+}
+
+/// Hash recorded for `slot` in raw SlotHashes data: a u64 length, then
+/// (u64 slot, [u8; 32] hash) entries, newest first.
+pub fn slot_hash_at(slot_hashes: &[u8], slot: u64) -> Option<[u8; 32]> {
+    let len = u64::from_le_bytes(slot_hashes.get(..8)?.try_into().ok()?) as usize;
+    slot_hashes
+        .get(8..)?
+        .chunks_exact(40)
+        .take(len)
+        .find(|entry| entry[..8] == slot.to_le_bytes())
+        .map(|entry| entry[8..].try_into().unwrap())
+}
+
+/// Median of five prices using a 9-comparator sorting network.
+pub fn median_of_five(mut prices: [u64; 5]) -> u64 {
+    const NETWORK: [(usize, usize); 9] =
+        [(0, 1), (3, 4), (2, 4), (2, 3), (0, 3), (0, 2), (1, 4), (1, 3), (1, 2)];
+    for (i, j) in NETWORK {
+        if prices[i] > prices[j] {
+            prices.swap(i, j);
+        }
+    }
+    prices[2]
+}
+
+/// Tickets released for sale at `now`. Without a schedule (or once the last tranche
+/// has opened) the full supply is available.
+pub fn released_supply(event: &Event, now: i64) -> u32 {
+    match event.release_schedule.last() {
+        None => event.supply,
+        Some(last) if now >= last.release_ts => event.supply,
+        Some(_) => event
+            .release_schedule
+            .iter()
+            .filter(|t| t.release_ts <= now)
+            .fold(event.released_early, |acc, t| acc.saturating_add(t.count))
+            .min(event.supply),
+    }
+}
+
+/// Enforce the event's `max_listing_multiple` cap (percent of the ticket tier's primary
+/// price) on a resale price.
+pub fn check_listing_multiple(event: &Event, tier_index: u8, price_lamports: u64) -> Result<()> {
+    if event.max_listing_multiple > 0 {
+        let max_allowed = event.tiers[tier_index as usize]
+            .price_lamports
+            .saturating_mul(event.max_listing_multiple as u64)
+            / 100;
+        require!(price_lamports <= max_allowed, ErrorCode::ListingMultipleTooHigh);
+    }
+    Ok(())
+}
+
+/// Enforce the event's resale floor: `min_resale_lamports`, raised to the ticket tier's
+/// primary price while that tier and the event have tickets left when
+/// `dynamic_resale_floor` is set. Seats under a live sponsor hold aren't on primary
+/// sale; seats under an expired hold are.
+pub fn check_resale_floor(event: &Event, tier_index: u8, price_lamports: u64, now: i64) -> Result<()> {
+    let mut floor = event.min_resale_lamports;
+    let tier = &event.tiers[tier_index as usize];
+    if event.dynamic_resale_floor && event.sold + active_holds(event, now) < event.supply && tier.sold < tier.supply {
+        floor = floor.max(tier.price_lamports);
+    }
+    require!(price_lamports >= floor, ErrorCode::ResaleBelowDynamicFloor);
+    Ok(())
+}
+
+/// The tier a ticket sold from, read from its purchase receipt. Single-tier events need
+/// no receipt.
+pub fn ticket_tier(event: &Event, receipt: Option<&Receipt>) -> Result<u8> {
+    if event.tiers.len() == 1 {
+        return Ok(0);
+    }
+    Ok(receipt.ok_or(ErrorCode::TierReceiptRequired)?.tier_index)
+}
+
+/// Unsold seats in tier `tier_index`. Holds and pass seats come from tier 0, so their
+/// reservations count against it.
+pub fn tier_available(event: &Event, tier_index: u8) -> Result<u32> {
+    let tier = event.tiers.get(tier_index as usize).ok_or(ErrorCode::InvalidTierIndex)?;
+    let reserved = if tier_index == 0 { event.held + event.pass_reserved } else { 0 };
+    Ok(tier.supply.saturating_sub(tier.sold.saturating_add(reserved)))
+}
+
+/// Count `count` tickets minted from tier `tier_index` on the event and the tier.
+/// Every mint path goes through here so no tier sells past its supply.
+pub fn record_tier_sale(event: &mut Event, tier_index: u8, count: u32) -> Result<()> {
+    let tier = event.tiers.get_mut(tier_index as usize).ok_or(ErrorCode::InvalidTierIndex)?;
+    let sold = tier.sold.checked_add(count).ok_or(ErrorCode::Overflow)?;
+    require!(sold <= tier.supply, ErrorCode::TierSoldOut);
+    tier.sold = sold;
+    event.sold = event.sold.checked_add(count).ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+/// Held seats still reserved for their sponsors at `now`. Holds aren't tracked one by
+/// one on the event, so once the earliest outstanding hold lapses every held seat
+/// counts as back on sale until the holds are claimed or released.
+pub fn active_holds(event: &Event, now: i64) -> u32 {
+    if now < event.holds_expire_at {
+        event.held
+    } else {
+        0
+    }
+}
+
+/// Create the PDA `target` with `space` bytes owned by `owner`, signing with its
+/// `signer_seeds`. Lamports someone already sent to the address are kept and topped up
+/// to rent exemption, so pre-funding it can't block creation (create_account would fail).
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    if target.lamports() == 0 {
+        return anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount { from: payer.clone(), to: target.clone() },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+    let top_up = rent.saturating_sub(target.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer { from: payer.clone(), to: target.clone() },
+            ),
+            top_up,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate { account_to_allocate: target.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign { account_to_assign: target.clone() },
+            signer_seeds,
+        ),
+        owner,
+    )
+}
+
+/// Move lamports out of a program-owned account (e.g. a PDA holding escrowed SOL).
+pub fn transfer_lamports_from_pda(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let from_balance = from.lamports().checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+    let to_balance = to.lamports().checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Access control: the signer must be the event's organizer.
+pub fn organizer_only(event: &Event, organizer: &Signer) -> Result<()> {
+    require_keys_eq!(event.organizer, organizer.key(), ErrorCode::InvalidOrganizer);
+    Ok(())
+}
+
+/// Build the 96-byte QR payload venue scanners verify offline.
+///
+/// Layout: `ticket_mint (32) | holder (32) | timestamp LE (8) | digest (24)`, where
+/// `digest` is the first 24 bytes of `hashv([ticket_mint, event_key, holder, timestamp])`.
+/// The event key is derived from the event's organizer and nonce, so the digest binds
+/// the payload to one event without spending payload bytes on it.
+pub fn generate_verification_payload(
+    ticket_mint: &Pubkey,
+    event: &Event,
+    holder: &Pubkey,
+    timestamp: i64,
+) -> [u8; 96] {
+    let (event_key, _) = Pubkey::find_program_address(
+        &[b"event", event.organizer.as_ref(), &event.nonce.to_le_bytes()],
+        &crate::ID,
+    );
+    build_qr_payload(ticket_mint, &event_key, holder, timestamp)
+}
+
+/// Check a QR payload produced by `generate_verification_payload` for `event_key`.
+pub fn verify_qr_payload(payload: &[u8; 96], event_key: &Pubkey) -> bool {
+    let ticket_mint = Pubkey::new_from_array(payload[0..32].try_into().unwrap());
+    let holder = Pubkey::new_from_array(payload[32..64].try_into().unwrap());
+    let timestamp = i64::from_le_bytes(payload[64..72].try_into().unwrap());
+    build_qr_payload(&ticket_mint, event_key, &holder, timestamp) == *payload
+}
+
+fn build_qr_payload(ticket_mint: &Pubkey, event_key: &Pubkey, holder: &Pubkey, timestamp: i64) -> [u8; 96] {
+    let ts_bytes = timestamp.to_le_bytes();
+    let digest = hashv(&[ticket_mint.as_ref(), event_key.as_ref(), holder.as_ref(), &ts_bytes]);
+
+    let mut payload = [0u8; 96];
+    payload[0..32].copy_from_slice(ticket_mint.as_ref());
+    payload[32..64].copy_from_slice(holder.as_ref());
+    payload[64..72].copy_from_slice(&ts_bytes);
+    payload[72..96].copy_from_slice(&digest.to_bytes()[..24]);
+    payload
+}
+
+/// Write an AttendanceRecord for a ticket and bump the event's check-in counter. The
+/// ticket's perks bitmask and note are also set as return data for scanner apps:
+/// perks u16 LE ‖ note (32 bytes, zeros when none).
+pub fn record_attendance(
+    event: &mut Account<Event>,
+    record: &mut Account<AttendanceRecord>,
+    ticket_mint: Pubkey,
+    holder: Pubkey,
+    ticket_index: u32,
+    category: Option<u8>,
+    perks_bitmask: u16,
+    note: Option<[u8; 32]>,
+    bump: u8,
+) -> Result<()> {
+    let attendance_index = event.checked_in;
+
+    record.event = event.key();
+    record.ticket_mint = ticket_mint;
+    record.holder = holder;
+    record.ticket_index = ticket_index;
+    record.attendance_index = attendance_index;
+    record.checked_in_ts = Clock::get()?.unix_timestamp;
+    record.bump = bump;
+
+    event.checked_in = event.checked_in.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+    emit!(CheckedIn {
+        event: event.key(),
+        ticket_mint,
+        holder,
+        attendance_index,
+        category,
+        perks_bitmask,
+        note,
+    });
+    let mut return_data = [0u8; 2 + 32];
+    return_data[..2].copy_from_slice(&perks_bitmask.to_le_bytes());
+    return_data[2..].copy_from_slice(&note.unwrap_or_default());
+    set_return_data(&return_data);
+
+    Ok(())
+}
+
+/// Find a single-signature ed25519 precompile instruction earlier in this transaction
+/// whose key is `signer` and whose signed message starts with `prefix`, returning the
+/// full message. Offsets must point into the precompile instruction itself.
+pub fn find_ed25519_message(ix_sysvar: &AccountInfo, signer: &Pubkey, prefix: &[u8]) -> Result<Option<Vec<u8>>> {
+    let current = load_current_index_checked(ix_sysvar)? as usize;
+    for index in 0..current {
+        let ix = load_instruction_at_checked(index, ix_sysvar)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+
+        // Layout: count (1) | padding (1) | offsets (14): signature, its ix index,
+        // public key, its ix index, message, message size, its ix index (u16 LE each)
+        let data = &ix.data;
+        if data.len() < 16 || data[0] != 1 {
+            continue;
+        }
+        let read = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+        let this_ix = u16::MAX as usize;
+        if read(4) != this_ix || read(8) != this_ix || read(14) != this_ix {
+            continue;
+        }
+        let (Some(public_key), Some(signed)) = (
+            data.get(read(6)..read(6) + 32),
+            data.get(read(10)..read(10) + read(12)),
+        ) else {
+            continue;
+        };
+        if public_key == signer.as_ref() && signed.starts_with(prefix) {
+            return Ok(Some(signed.to_vec()));
+        }
+    }
+    Ok(None)
+}
+
+/// Require an ed25519 precompile instruction earlier in this transaction in which
+/// `signer` signed exactly `message`.
+pub fn verify_ed25519_attestation(ix_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let signed = find_ed25519_message(ix_sysvar, signer, message)?.ok_or(ErrorCode::MissingAttestation)?;
+    require!(signed.len() == message.len(), ErrorCode::InvalidAttestation);
+    Ok(())
+}
+
+/// Image URI for a ticket rendered from the event's design template.
+pub fn ticket_image_uri(event: &Event, ticket_index: u32) -> String {
+    format!("ipfs://{}?index={}", event.ticket_design_cid, ticket_index)
+}
+
+/// Token Metadata URI for a ticket: its rendered design when the event has a design
+/// CID, else the event's metadata_uri. None when neither is set and tickets mint
+/// without metadata.
+pub fn ticket_metadata_uri(event: &Event, ticket_index: u32) -> Option<String> {
+    if !event.ticket_design_cid.is_empty() {
+        Some(ticket_image_uri(event, ticket_index))
+    } else if !event.metadata_uri.is_empty() {
+        Some(event.metadata_uri.clone())
+    } else {
+        None
+    }
+}
+
+/// Wallet display name for a ticket, `"{title} #{serial}"`, with the title cut on a
+/// char boundary to fit Token Metadata's name limit.
+pub fn ticket_metadata_name(title: &str, serial: u32) -> String {
+    let suffix = format!(" #{}", serial);
+    let mut end = title.len().min(MAX_METADATA_NAME_LEN - suffix.len());
+    while !title.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &title[..end], suffix)
+}
+
+/// Token Metadata accounts every CPI on a programmable (pNFT) ticket takes.
+pub struct PnftAccounts<'a, 'info> {
+    pub metadata_program: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub instructions: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> PnftAccounts<'a, 'info> {
+    /// The optional metadata accounts of a context, all required for pNFT events;
+    /// the metadata and edition are checked against their PDAs for `mint`.
+    pub fn new(
+        metadata_program: Option<&'a AccountInfo<'info>>,
+        metadata: Option<&'a AccountInfo<'info>>,
+        edition: Option<&'a AccountInfo<'info>>,
+        instructions: Option<&'a AccountInfo<'info>>,
+        mint: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+        token_program: &'a AccountInfo<'info>,
+    ) -> Result<Self> {
+        let (Some(metadata_program), Some(metadata), Some(edition), Some(instructions)) =
+            (metadata_program, metadata, edition, instructions)
+        else {
+            return err!(ErrorCode::MissingMetadataAccounts);
+        };
+        let mint_key = mint.key();
+        let seeds: &[&[u8]] = &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint_key.as_ref()];
+        let (expected, _) = Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID);
+        require_keys_eq!(metadata.key(), expected, ErrorCode::MissingMetadataAccounts);
+        let (expected, _) = Pubkey::find_program_address(&[seeds[0], seeds[1], seeds[2], b"edition"], &TOKEN_METADATA_PROGRAM_ID);
+        require_keys_eq!(edition.key(), expected, ErrorCode::MissingMetadataAccounts);
+        Ok(Self { metadata_program, metadata, edition, mint, instructions, system_program, token_program })
+    }
+
+    /// Stands in for an optional Token Metadata account that isn't passed.
+    fn none() -> AccountMeta {
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false)
+    }
+
+    fn invoke(
+        &self,
+        accounts: Vec<AccountMeta>,
+        infos: &[&AccountInfo<'info>],
+        data: Vec<u8>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut account_infos: Vec<AccountInfo<'info>> = infos.iter().map(|info| (*info).clone()).collect();
+        account_infos.extend([
+            self.metadata.clone(),
+            self.edition.clone(),
+            self.mint.clone(),
+            self.instructions.clone(),
+            self.system_program.clone(),
+            self.token_program.clone(),
+            self.metadata_program.clone(),
+        ]);
+        invoke_signed(
+            &Instruction { program_id: TOKEN_METADATA_PROGRAM_ID, accounts, data },
+            &account_infos,
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// Make the ticket a pNFT with `create_v1` and mint it to `token` with `mint_v1`,
+    /// which also creates its TokenRecord. The ticket_authority PDA signs as mint and
+    /// update authority; Token Metadata's master edition takes over the mint, with a
+    /// print supply of zero, so no second token can be minted.
+    pub fn create_and_mint(
+        &self,
+        authority: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        token: &AccountInfo<'info>,
+        token_owner: &AccountInfo<'info>,
+        token_record: &AccountInfo<'info>,
+        associated_token_program: &AccountInfo<'info>,
+        event: &Event,
+        ticket_index: u32,
+        custom_name: Option<&str>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let uri = ticket_metadata_uri(event, ticket_index).ok_or(ErrorCode::PnftRequiresMetadata)?;
+        let name = custom_name.map_or_else(|| ticket_metadata_name(&event.title, ticket_index + 1), str::to_string);
+        let mut data = vec![CREATE_V1, 0];
+        (name, TICKET_SYMBOL.to_string(), uri, 0u16).serialize(&mut data)?;
+        // No creators; primary sale not happened; immutable
+        data.extend_from_slice(&[0, 0, 0, PROGRAMMABLE_NON_FUNGIBLE]);
+        // No collection, uses, collection details or rule set; decimals 0; print supply zero
+        data.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        let accounts = vec![
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new(self.edition.key(), false),
+            AccountMeta::new(self.mint.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+        ];
+        self.invoke(accounts, &[authority, payer], data, signer_seeds)?;
+
+        let mut data = vec![MINT_V1, 0];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.push(0); // no authorization data
+        let accounts = vec![
+            AccountMeta::new(token.key(), false),
+            AccountMeta::new_readonly(token_owner.key(), false),
+            AccountMeta::new_readonly(self.metadata.key(), false),
+            AccountMeta::new(self.edition.key(), false),
+            AccountMeta::new(token_record.key(), false),
+            AccountMeta::new(self.mint.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            Self::none(),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            AccountMeta::new_readonly(associated_token_program.key(), false),
+            Self::none(),
+            Self::none(),
+        ];
+        self.invoke(
+            accounts,
+            &[token, token_owner, token_record, authority, payer, associated_token_program],
+            data,
+            signer_seeds,
+        )
+    }
+
+    /// Make `listing` the ticket's locked-transfer delegate, signed by the owner, and
+    /// have it lock the TokenRecord so the ticket stays in the owner's wallet but
+    /// can't move until the listing is bought or cancelled.
+    pub fn lock_for_listing(
+        &self,
+        owner: &AccountInfo<'info>,
+        token: &AccountInfo<'info>,
+        token_record: &AccountInfo<'info>,
+        listing: &AccountInfo<'info>,
+        listing_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = vec![DELEGATE_V1, LOCKED_TRANSFER_DELEGATE];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(listing.key().as_ref());
+        data.push(0); // no authorization data
+        self.invoke(self.delegation_metas(owner, token, token_record, listing), &[owner, token, token_record, listing], data, &[])?;
+        self.set_lock(LOCK_V1, owner, token, token_record, listing, owner, listing_seeds)
+    }
+
+    /// Unlock a listed ticket and, signed by its owner, drop the listing's delegate.
+    pub fn release_listing(
+        &self,
+        owner: &AccountInfo<'info>,
+        token: &AccountInfo<'info>,
+        token_record: &AccountInfo<'info>,
+        listing: &AccountInfo<'info>,
+        listing_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        self.set_lock(UNLOCK_V1, owner, token, token_record, listing, owner, listing_seeds)?;
+        let data = vec![REVOKE_V1, LOCKED_TRANSFER_DELEGATE];
+        self.invoke(self.delegation_metas(owner, token, token_record, listing), &[owner, token, token_record, listing], data, &[])
+    }
+
+    fn delegation_metas(
+        &self,
+        owner: &AccountInfo<'info>,
+        token: &AccountInfo<'info>,
+        token_record: &AccountInfo<'info>,
+        delegate: &AccountInfo<'info>,
+    ) -> Vec<AccountMeta> {
+        vec![
+            Self::none(),
+            AccountMeta::new_readonly(delegate.key(), false),
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new_readonly(self.edition.key(), false),
+            AccountMeta::new(token_record.key(), false),
+            AccountMeta::new_readonly(self.mint.key(), false),
+            AccountMeta::new(token.key(), false),
+            AccountMeta::new_readonly(owner.key(), true),
+            AccountMeta::new(owner.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            Self::none(),
+            Self::none(),
+        ]
+    }
+
+    /// `lock_v1` or `unlock_v1` by the listing delegate.
+    pub fn set_lock(
+        &self,
+        discriminator: u8,
+        owner: &AccountInfo<'info>,
+        token: &AccountInfo<'info>,
+        token_record: &AccountInfo<'info>,
+        listing: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        listing_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let accounts = vec![
+            AccountMeta::new_readonly(listing.key(), true),
+            AccountMeta::new_readonly(owner.key(), false),
+            AccountMeta::new(token.key(), false),
+            AccountMeta::new_readonly(self.mint.key(), false),
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new_readonly(self.edition.key(), false),
+            AccountMeta::new(token_record.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            Self::none(),
+            Self::none(),
+        ];
+        self.invoke(accounts, &[listing, owner, token, token_record, payer], vec![discriminator, 0, 0], listing_seeds)
+    }
+
+    /// Move the ticket with the pNFT-aware `transfer_v1`, signed by `authority` (the
+    /// owner or its delegate), creating the destination's TokenRecord.
+    pub fn transfer(
+        &self,
+        from: (&AccountInfo<'info>, &AccountInfo<'info>, &AccountInfo<'info>),
+        to: (&AccountInfo<'info>, &AccountInfo<'info>, &AccountInfo<'info>),
+        authority: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        associated_token_program: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let (token, token_owner, token_record) = from;
+        let (destination, destination_owner, destination_record) = to;
+        let mut data = vec![TRANSFER_V1, 0];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.push(0); // no authorization data
+        let accounts = vec![
+            AccountMeta::new(token.key(), false),
+            AccountMeta::new_readonly(token_owner.key(), false),
+            AccountMeta::new(destination.key(), false),
+            AccountMeta::new_readonly(destination_owner.key(), false),
+            AccountMeta::new_readonly(self.mint.key(), false),
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new_readonly(self.edition.key(), false),
+            AccountMeta::new(token_record.key(), false),
+            AccountMeta::new(destination_record.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            AccountMeta::new_readonly(associated_token_program.key(), false),
+            Self::none(),
+            Self::none(),
+        ];
+        self.invoke(
+            accounts,
+            &[token, token_owner, token_record, destination, destination_owner, destination_record, authority, payer, associated_token_program],
+            data,
+            signer_seeds,
+        )
+    }
+
+    /// Burn the ticket with `burn_v1`, signed by its owner, closing its TokenRecord.
+    pub fn burn(&self, owner: &AccountInfo<'info>, token: &AccountInfo<'info>, token_record: &AccountInfo<'info>) -> Result<()> {
+        let mut data = vec![BURN_V1, 0];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        let accounts = vec![
+            AccountMeta::new(owner.key(), true),
+            Self::none(),
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new(self.edition.key(), false),
+            AccountMeta::new(self.mint.key(), false),
+            AccountMeta::new(token.key(), false),
+            Self::none(),
+            Self::none(),
+            Self::none(),
+            Self::none(),
+            AccountMeta::new(token_record.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+        ];
+        self.invoke(accounts, &[owner, token, token_record], data, &[])
+    }
+}
+
+/// Create the Token Metadata account for a ticket mint by CPI, before its mint
+/// authority is revoked. The ticket_authority PDA signs as mint and update authority
+/// and the metadata is immutable, so the artwork can't be swapped after the sale.
+/// Named `custom_name` when the organizer set one, else by serial counted from 1
+/// (`ticket_index + 1`). Does nothing when `ticket_metadata_uri` is None.
+pub fn create_ticket_metadata<'info>(
+    metadata_program: Option<&AccountInfo<'info>>,
+    metadata: Option<&AccountInfo<'info>>,
+    ticket_mint: &AccountInfo<'info>,
+    ticket_authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    event: &Event,
+    ticket_index: u32,
+    custom_name: Option<&str>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let Some(uri) = ticket_metadata_uri(event, ticket_index) else {
+        return Ok(());
+    };
+    let (Some(metadata_program), Some(metadata)) = (metadata_program, metadata) else {
+        return err!(ErrorCode::MissingMetadataAccounts);
+    };
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), ticket_mint.key().as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require_keys_eq!(metadata.key(), expected, ErrorCode::MissingMetadataAccounts);
+
+    let name = custom_name.map_or_else(|| ticket_metadata_name(&event.title, ticket_index + 1), str::to_string);
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    (name, TICKET_SYMBOL.to_string(), uri, 0u16).serialize(&mut data)?;
+    // No creators, collection or uses; not mutable; no collection details
+    data.extend_from_slice(&[0, 0, 0, 0, 0]);
+    let accounts = vec![
+        AccountMeta::new(metadata.key(), false),
+        AccountMeta::new_readonly(ticket_mint.key(), false),
+        AccountMeta::new_readonly(ticket_authority.key(), true),
+        AccountMeta::new(payer.key(), true),
+        AccountMeta::new_readonly(ticket_authority.key(), true),
+        AccountMeta::new_readonly(system_program.key(), false),
+    ];
+    invoke_signed(
+        &Instruction { program_id: TOKEN_METADATA_PROGRAM_ID, accounts, data },
+        &[
+            metadata.clone(),
+            ticket_mint.clone(),
+            ticket_authority.clone(),
+            payer.clone(),
+            system_program.clone(),
+            metadata_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Whether primary proceeds go to the event vault instead of straight to the organizer.
+pub fn proceeds_escrowed(event: &Event) -> bool {
+    event.grace_period_seconds > 0 || event.vesting_duration_seconds > 0
+}
+
+/// SOL the vault must hold to refund every outstanding ticket: primary revenue less
+/// refunds already paid and the remitted tax and donations.
+pub fn refunds_owed(event: &Event) -> u64 {
+    let analytics = &event.analytics;
+    analytics
+        .gross_primary_revenue
+        .saturating_sub(analytics.refunded_lamports)
+        .saturating_sub(event.tax_collected)
+        .saturating_sub(event.total_donated)
+}
+
+/// A festival pass's share of its price for the bundled event at `position`, by
+/// shares_bps; the last event takes the rounding remainder.
+pub fn pass_share(bundle: &PassBundle, position: usize) -> u64 {
+    let share = |i: usize| (bundle.price_lamports as u128 * bundle.shares_bps[i] as u128 / 10_000) as u64;
+    if position == bundle.events.len() - 1 {
+        bundle.price_lamports - (0..position).map(share).sum::<u64>()
+    } else {
+        share(position)
+    }
+}
+
+/// Open claim_refund for holders of an event moved later to `new_date`, for
+/// POSTPONEMENT_REFUND_SECONDS and never past the new date. The event vault is
+/// created if needed and topped up from `payer` to cover every outstanding refund.
+/// Nothing happens when no ticket is outstanding.
+pub fn open_postponement_refunds<'info>(
+    event: &mut Event,
+    event_key: &Pubkey,
+    new_date: i64,
+    vault: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    now: i64,
+) -> Result<()> {
+    if event.sold <= event.analytics.refund_count {
+        return Ok(());
+    }
+    require!(!accepts_token_payments(event), ErrorCode::InvalidPaymentOption);
+    let (vault_key, bump) = Pubkey::find_program_address(&[b"vault", event_key.as_ref()], &crate::ID);
+    require_keys_eq!(vault.key(), vault_key, ErrorCode::InvalidVault);
+    require_keys_eq!(system_program.key(), anchor_lang::system_program::ID, ErrorCode::InvalidVault);
+    if vault.data_is_empty() {
+        create_pda_account(payer, vault, system_program, 8 + 32 + 1, &crate::ID, &[&[b"vault", event_key.as_ref(), &[bump]]])?;
+        EventVault { event: *event_key, bump }.try_serialize(&mut &mut vault.try_borrow_mut_data()?[..])?;
+    }
+
+    let owed = refunds_owed(event);
+    let escrowed = vault.lamports().saturating_sub(Rent::get()?.minimum_balance(vault.data_len()));
+    if escrowed < owed {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer { from: payer.clone(), to: vault.clone() },
+            ),
+            owed - escrowed,
+        )?;
+    }
+    let until = now.saturating_add(POSTPONEMENT_REFUND_SECONDS).min(new_date);
+    event.postponement_refund_until = until;
+    event.refundable_until = event.refundable_until.max(until);
+    Ok(())
+}
+
+/// Lamports the organizer may withdraw now: the vested share of everything ever
+/// vaulted (`escrowed + proceeds_withdrawn`), less what was already withdrawn,
+/// capped at the current `escrowed` balance.
+pub fn withdrawable_proceeds(event: &Event, escrowed: u64, now: i64) -> Result<u64> {
+    let total = escrowed.checked_add(event.proceeds_withdrawn).ok_or(ErrorCode::Overflow)?;
+    let vested = if event.vesting_duration_seconds == 0 {
+        total
+    } else if now < event.vesting_cliff_ts {
+        0
+    } else {
+        let elapsed = (now - event.vesting_cliff_ts).min(event.vesting_duration_seconds) as u128;
+        (total as u128 * elapsed / event.vesting_duration_seconds as u128) as u64
+    };
+    Ok(vested.saturating_sub(event.proceeds_withdrawn).min(escrowed))
+}
+
+/// Require that a resale split's bps sum to exactly 10000.
+pub fn validate_split(artist_bps: u16, seller_bps: u16, platform_bps: u16) -> Result<()> {
+    let total = artist_bps as u32 + seller_bps as u32 + platform_bps as u32;
+    require!(total == 10_000, ErrorCode::InvalidSplit);
+    Ok(())
+}
+
+/// Attendance deposit a primary purchase of `tier_index` takes: event.deposit_lamports
+/// on free tiers, nothing on paid ones.
+pub fn ticket_deposit(event: &Event, tier_index: u8) -> u64 {
+    match event.tiers.get(tier_index as usize) {
+        Some(tier) if tier.price_lamports == 0 => event.deposit_lamports,
+        _ => 0,
+    }
+}
+
+/// Attendance deposit a resale at `price` pays back to the seller before the split.
+/// The deposit stays in the DepositEscrow for the new holder, so the price covers it.
+/// The ticket's Receipt is required when the event takes deposits.
+pub fn resale_deposit(event: &Event, receipt: Option<&Receipt>, price: u64) -> Result<u64> {
+    if event.deposit_lamports == 0 {
+        return Ok(0);
+    }
+    let receipt = receipt.ok_or(ErrorCode::DepositReceiptRequired)?;
+    require!(price >= receipt.deposit_lamports, ErrorCode::PriceBelowDeposit);
+    Ok(receipt.deposit_lamports)
+}
+
+/// Record a resale for fair-value estimates and the circuit breaker, initializing the
+/// TicketStats and FloorMonitor accounts on first use. Resale is paused when `price`
+/// lands below CIRCUIT_BREAKER_FLOOR_PCT of the window's high. Shared by buy_resale
+/// and match_orders so every secondary sale is counted.
+pub fn record_resale_price(
+    event: &mut Account<Event>,
+    stats: &mut TicketStats,
+    stats_bump: u8,
+    monitor: &mut FloorMonitor,
+    monitor_bump: u8,
+    price: u64,
+) -> Result<()> {
+    let event_key = event.key();
+    if stats.event == Pubkey::default() {
+        stats.event = event_key;
+        stats.bump = stats_bump;
+    }
+    require_keys_eq!(stats.event, event_key, ErrorCode::StatsAccountMismatch);
+    stats.price_history.copy_within(0..PRICE_HISTORY_LEN - 1, 1);
+    stats.price_history[0] = price;
+    stats.sales_count = stats.sales_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if monitor.event == Pubkey::default() {
+        monitor.event = event_key;
+        monitor.bump = monitor_bump;
+    }
+    require_keys_eq!(monitor.event, event_key, ErrorCode::StatsAccountMismatch);
+    if now > monitor.hour_start_ts + FLOOR_MONITOR_WINDOW_SECONDS {
+        monitor.hourly_high_price = price;
+        monitor.hour_start_ts = now;
+    } else if price > monitor.hourly_high_price {
+        monitor.hourly_high_price = price;
+    }
+    let high = monitor.hourly_high_price;
+    if (price as u128) < high as u128 * CIRCUIT_BREAKER_FLOOR_PCT as u128 / 100 {
+        event.resale_paused = true;
+        emit!(ResaleCircuitBreakerTriggered {
+            event: event_key,
+            price_drop_bps: ((high - price) as u128 * 10_000 / high as u128) as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Split a resale price into (artist, seller, platform) shares, using the event's fee
+/// override when set. The seller absorbs rounding so the shares always sum to `price`.
+pub fn resale_split(event: &Event, price: u64) -> (u64, u64, u64) {
+    let platform_bps = event.platform_fee_override_bps.unwrap_or(event.platform_bps);
+    let artist_share = (price as u128 * event.artist_bps as u128 / 10_000) as u64;
+    let platform_share = (price as u128 * platform_bps as u128 / 10_000) as u64;
+    (artist_share, price - artist_share - platform_share, platform_share)
+}
+
+/// Shared body of buy_ticket and buy_accessible_ticket. Tickets reserved by sponsor
+/// holds and festival passes are never purchasable; accessible seats are drawn only
+/// when `accessible` is set.
+fn purchase_ticket(
+    ctx: Context<BuyTicket>,
+    ticket_index: u32,
+    tier_index: u8,
+    category: Option<u8>,
+    tip_lamports: u64,
+    allow_large_tip: bool,
+    payment_index: Option<u8>,
+    note: Option<[u8; 32]>,
+    identity_commitment: Option<[u8; 32]>,
+    accessible: bool,
+) -> Result<()> {
+    // A replayed purchase of the same ticket index is a no-op; the key keeps its mint
+    if ctx.accounts.idempotency_key.mint != Pubkey::default() {
+        return Ok(());
+    }
+
+    // Per-wallet rate limit across all events, over a fixed one-minute window
+    let now = Clock::get()?.unix_timestamp;
+    let rate_limit = &mut ctx.accounts.rate_limit;
+    if now - rate_limit.window_start_ts > RATE_LIMIT_WINDOW_SECONDS {
+        rate_limit.purchases_last_minute = 0;
+        rate_limit.window_start_ts = now;
+    }
+    require!(
+        rate_limit.purchases_last_minute < RATE_LIMIT_PER_MINUTE,
+        ErrorCode::RateLimitExceeded
+    );
+    rate_limit.purchases_last_minute += 1;
+    rate_limit.bump = ctx.bumps.rate_limit;
+
+    // Per-wallet cap, counted on the buyer's BuyerRecord for this event
+    if ctx.accounts.event.max_per_wallet > 0 {
+        let event_key = ctx.accounts.event.key();
+        let limit = ctx.accounts.event.max_per_wallet;
+        let record = ctx.accounts.buyer_record.as_mut().ok_or(ErrorCode::PurchaseLimitReached)?;
+        require!(record.tickets_bought < limit, ErrorCode::PurchaseLimitReached);
+        record.event = event_key;
+        record.buyer = ctx.accounts.buyer.key();
+        record.tickets_bought += 1;
+        record.bump = ctx.bumps.buyer_record.ok_or(ErrorCode::PurchaseLimitReached)?;
+    }
+
+    let event = &ctx.accounts.event;
+    require!(!event.sales_paused, ErrorCode::SalesPaused);
+    require!(!event.cancelled, ErrorCode::EventCancelled);
+    require!(!is_superseded(event), ErrorCode::EventSuperseded);
+    require!(now < event.date_ts, ErrorCode::EventEnded);
+    require!(!matches!(event.sale_start_ts, Some(start) if now < start), ErrorCode::SaleNotStarted);
+    require!(!matches!(event.sale_end_ts, Some(end) if now >= end), ErrorCode::SaleEnded);
+    require!(ticket_index == event.sold, ErrorCode::InvalidTicketIndex);
+    require!(event.sold + event.held + event.pass_reserved < event.supply, ErrorCode::SoldOut);
+    // Unsold accessible seats are kept out of general sale until released
+    if accessible {
+        require!(
+            event.accessible_sold < event.accessible_seating_count,
+            ErrorCode::AccessibleSeatingUnavailable
+        );
+        let approval = ctx.accounts.accessible_approval.as_mut().ok_or(ErrorCode::AccessibleApprovalRequired)?;
+        require!(!approval.used, ErrorCode::AccessibleApprovalUsed);
+        approval.used = true;
+    } else {
+        let accessible_unsold = event.accessible_seating_count - event.accessible_sold;
+        require!(
+            event.sold + event.held + event.pass_reserved + accessible_unsold < event.supply,
+            ErrorCode::SoldOut
+        );
+    }
+    require!(event.sold < released_supply(event, now), ErrorCode::TrancheSoldOut);
+    // Each tier sells out on its own
+    require!(tier_available(event, tier_index)? > 0, ErrorCode::TierSoldOut);
+    let tier = &event.tiers[tier_index as usize];
+
+    let buyer = &ctx.accounts.buyer;
+    let organizer = &ctx.accounts.organizer;
+
+    // Concession categories charge their own price, capped per category
+    let price = match category {
+        None => tier.price_lamports,
+        Some(index) => {
+            let concession = event
+                .concessions
+                .get(index as usize)
+                .ok_or(ErrorCode::InvalidConcession)?;
+            require!(concession.tier_index == tier_index, ErrorCode::InvalidConcession);
+            require!(concession.sold < concession.max_count, ErrorCode::ConcessionSoldOut);
+            if concession.verified_only {
+                let ix_sysvar = ctx.accounts.instructions.as_ref().ok_or(ErrorCode::MissingAttestation)?;
+                let message = [
+                    b"concession".as_ref(),
+                    event.key().as_ref(),
+                    buyer.key().as_ref(),
+                    &[index],
+                ]
+                .concat();
+                verify_ed25519_attestation(ix_sysvar, &event.organizer, &message)?;
+            }
+            concession.price_lamports
+        }
+    };
+
+    // A payment option replaces the SOL price with its own mint and amount
+    let payment = match payment_index {
+        None => None,
+        Some(index) => {
+            require!(category.is_none(), ErrorCode::InvalidPaymentOption);
+            let option = event
+                .accepted_payments
+                .get(index as usize)
+                .ok_or(ErrorCode::InvalidPaymentOption)?;
+            require!(option.tier_index == tier_index, ErrorCode::InvalidPaymentOption);
+            Some(option.clone())
+        }
+    };
+    let price = payment.as_ref().map_or(price, |option| option.price);
+    let payment_mint = payment.and_then(|option| option.mint);
+    require!(!event.token_only || payment_mint.is_some(), ErrorCode::SolPaymentDisabled);
+    // Fail early with the shortfall logged, not on a system transfer halfway through.
+    // The ticket mint and the buyer's ticket account are created below, after the check.
+    let rent = Rent::get()?;
+    let mint_rent = if ctx.accounts.ticket_mint.data_is_empty() {
+        rent.minimum_balance(SplMint::LEN).saturating_sub(ctx.accounts.ticket_mint.lamports())
+    } else {
+        0
+    };
+    let ata_rent = if ctx.accounts.buyer_token_account.data_is_empty() {
+        rent.minimum_balance(spl_token_2022::state::Account::LEN)
+            .saturating_sub(ctx.accounts.buyer_token_account.lamports())
+    } else {
+        0
+    };
+    require_funds(
+        &buyer.to_account_info(),
+        primary_purchase_lamports(event, tier_index, price, payment_mint.is_some(), tip_lamports)?
+            .checked_add(mint_rent + ata_rent)
+            .ok_or(ErrorCode::Overflow)?,
+    )?;
+
+    // Bot deterrence: the platform attestor signed event || buyer || recent slot (u64 LE)
+    if event.requires_attestation {
+        let (Some(config), Some(ix_sysvar)) = (&ctx.accounts.platform_config, &ctx.accounts.instructions) else {
+            return err!(ErrorCode::AttestationRequired);
+        };
+        let prefix = [event.key().as_ref(), buyer.key().as_ref()].concat();
+        let message = find_ed25519_message(ix_sysvar, &config.attestor_key, &prefix)?
+            .ok_or(ErrorCode::AttestationRequired)?;
+        let slot_bytes: [u8; 8] = message[prefix.len()..]
+            .try_into()
+            .map_err(|_| ErrorCode::AttestationRequired)?;
+        let attested_slot = u64::from_le_bytes(slot_bytes);
+        let current_slot = Clock::get()?.slot;
+        require!(
+            attested_slot <= current_slot && current_slot - attested_slot <= MAX_ATTESTATION_AGE_SLOTS,
+            ErrorCode::AttestationExpired
+        );
+    }
+
+    // One ticket per human: the credential's stable id keys a PersonPurchase PDA
+    if event.one_per_person {
+        let (Some(config), Some(credential), Some(person_purchase)) = (
+            &ctx.accounts.platform_config,
+            &ctx.accounts.personhood_credential,
+            &ctx.accounts.person_purchase,
+        ) else {
+            return err!(ErrorCode::InvalidPersonhoodCredential);
+        };
+        let person = person_id(
+            config.personhood_provider,
+            credential,
+            &buyer.key(),
+            &config.personhood_network,
+            now,
+        )?;
+        record_person_purchase(
+            person_purchase,
+            &buyer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &event.key(),
+            person,
+            buyer.key(),
+        )?;
+    }
+
+    let identity_commitment = take_identity_commitment(
+        event,
+        identity_commitment,
+        ctx.accounts.identity_purchase.as_ref(),
+        &buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    let tax = (price as u128 * event.tax_bps as u128 / 10_000) as u64;
+    let donation = (price as u128 * event.donation_bps as u128 / 10_000) as u64;
+    let lamports = price - tax - donation;
+
+    // Optional tip goes straight to the organizer: no tax, vault or refund
+    if tip_lamports > 0 {
+        let tip_cap = price
+            .max(MIN_TICKET_PRICE_LAMPORTS)
+            .saturating_mul(MAX_TIP_MULTIPLE);
+        require!(allow_large_tip || tip_lamports <= tip_cap, ErrorCode::TipTooLarge);
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: organizer.to_account_info(),
+                },
+            ),
+            tip_lamports,
+        )?;
+    }
+
+    // Carve sales tax out of the price and send it to the tax wallet
+    if tax > 0 {
+        let tax_wallet = ctx.accounts.tax_wallet.as_ref().ok_or(ErrorCode::InvalidTaxWallet)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: tax_wallet.to_account_info(),
+                },
+            ),
+            tax,
+        )?;
+    }
+
+    // Carve the charity donation out of the price and send it to the donation wallet
+    if donation > 0 {
+        let donation_wallet = ctx
+            .accounts
+            .donation_wallet
+            .as_ref()
+            .ok_or(ErrorCode::InvalidDonationWallet)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: donation_wallet.to_account_info(),
+                },
+            ),
+            donation,
+        )?;
+    }
+
+    if let Some(payment_mint) = payment_mint {
+        // Token payment: tax and donations are never configured alongside token options,
+        // so the full price goes to the organizer's or the vault's account for the mint
+        let (Some(mint), Some(from), Some(to)) = (
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.buyer_payment_account,
+            &ctx.accounts.payment_destination,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+        require_keys_eq!(mint.key(), payment_mint, ErrorCode::InvalidPaymentOption);
+        let recipient = match (proceeds_escrowed(event), &ctx.accounts.vault) {
+            (false, _) => organizer.key(),
+            (true, Some(vault)) => vault.key(),
+            (true, None) => return err!(ErrorCode::InvalidVault),
+        };
+        require!(
+            to.mint == payment_mint && to.owner == recipient,
+            ErrorCode::InvalidPaymentAccount
+        );
+        transfer_checked_exact(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: buyer.to_account_info(),
+                },
+            ),
+            price,
+            mint.decimals,
+        )?;
+    } else {
+        // Transfer SOL from buyer to organizer, or to the vault while refunds or vesting apply
+        let proceeds_to = match (proceeds_escrowed(event), &ctx.accounts.vault) {
+            (false, _) => organizer.to_account_info(),
+            (true, Some(vault)) => vault.to_account_info(),
+            (true, None) => return err!(ErrorCode::InvalidVault),
+        };
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: proceeds_to,
+                },
+            ),
+            lamports,
+        )?;
+    }
+
+    // Mint one ticket NFT (decimals 0) to buyer; program PDA signs as mint authority
+    let event_key = event.key();
+    let sold = event.sold;
+    let sold_bytes = sold.to_le_bytes();
+    let bump = ctx.bumps.ticket_authority;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"ticket_authority",
+        event_key.as_ref(),
+        &sold_bytes,
+        &[bump],
+    ]];
+    create_ticket_mint(
+        &buyer.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &event_key,
+        sold,
+        ctx.bumps.ticket_mint,
+        &ctx.accounts.ticket_authority.key(),
+    )?;
+    anchor_spl::associated_token::create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        anchor_spl::associated_token::Create {
+            payer: buyer.to_account_info(),
+            associated_token: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: buyer.to_account_info(),
+            mint: ctx.accounts.ticket_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+    let custom_name = take_name_override(&ctx.accounts.name_override, &ctx.accounts.organizer.to_account_info())?;
+    if event.use_pnft {
+        let mint = ctx.accounts.ticket_mint.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let pnft = PnftAccounts::new(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.ticket_metadata.as_ref(),
+            ctx.accounts.ticket_edition.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            &mint,
+            &system_program,
+            &token_program,
+        )?;
+        let token_record = ctx.accounts.ticket_token_record.as_ref().ok_or(ErrorCode::MissingMetadataAccounts)?;
+        pnft.create_and_mint(
+            &ctx.accounts.ticket_authority.to_account_info(),
+            &buyer.to_account_info(),
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            &buyer.to_account_info(),
+            token_record,
+            &ctx.accounts.associated_token_program.to_account_info(),
+            event,
+            sold,
+            custom_name.as_deref(),
+            signer_seeds,
+        )?;
+    } else {
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.ticket_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        create_ticket_metadata(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.ticket_metadata.as_ref(),
+            &ctx.accounts.ticket_mint.to_account_info(),
+            &ctx.accounts.ticket_authority.to_account_info(),
+            &buyer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            event,
+            sold,
+            custom_name.as_deref(),
+            signer_seeds,
+        )?;
+        revoke_mint_authority(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.ticket_mint.to_account_info(),
+            ctx.accounts.ticket_authority.to_account_info(),
+            signer_seeds,
+        )?;
+    }
+
+    // Attendance deposit on free tickets, held apart from proceeds until settlement
+    let deposit = ticket_deposit(event, tier_index);
+    if deposit > 0 {
+        let escrow = ctx.accounts.deposit_escrow.as_ref().ok_or(ErrorCode::MissingDepositEscrow)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+    }
+
+    let receipt = &mut ctx.accounts.receipt;
+    receipt.buyer = buyer.key();
+    receipt.event = event_key;
+    receipt.ticket_mint = ctx.accounts.ticket_mint.key();
+    receipt.paid_lamports = price;
+    receipt.purchased_ts = now;
+    receipt.refunded = false;
+    receipt.category = category;
+    receipt.payment_index = payment_index;
+    receipt.tip_lamports = tip_lamports;
+    receipt.perks_bitmask = tier.perks_bitmask;
+    receipt.deposit_lamports = deposit;
+    receipt.note = note.unwrap_or_default();
+    receipt.note_holder = buyer.key();
+    receipt.identity_commitment = identity_commitment;
+    receipt.tier_index = tier_index;
+    receipt.bump = ctx.bumps.receipt;
+
+    let event = &mut ctx.accounts.event;
+    record_tier_sale(event, tier_index, 1)?;
+    event.deposits_held = event.deposits_held.checked_add(deposit).ok_or(ErrorCode::Overflow)?;
+    if accessible {
+        event.accessible_sold += 1;
+    }
+    event.tax_collected = event.tax_collected.checked_add(tax).ok_or(ErrorCode::Overflow)?;
+    event.total_donated = event.total_donated.checked_add(donation).ok_or(ErrorCode::Overflow)?;
+    let sol_revenue = if payment_mint.is_some() { 0 } else { price };
+    event.analytics.record_primary_sale(&buyer.key(), sol_revenue)?;
+    if let Some(index) = category {
+        event.concessions[index as usize].sold += 1;
+    }
+
+    let idempotency_key = &mut ctx.accounts.idempotency_key;
+    idempotency_key.mint = ctx.accounts.ticket_mint.key();
+    idempotency_key.bump = ctx.bumps.idempotency_key;
+
+    emit!(TicketPurchased {
+        event: event_key,
+        buyer: buyer.key(),
+        ticket_mint: ctx.accounts.ticket_mint.key(),
+        price_lamports: price,
+        tip_lamports,
+        donation_lamports: donation,
+        custom_name,
+    });
+    if event.grace_period_seconds > 0 {
+        event.refundable_until = now + event.grace_period_seconds as i64;
+    }
+
+    // Airdrop fan tokens to the buyer; the fan token authority PDA signs
+    if let Some(fan_token_mint) = event.fan_token_mint {
+        let (Some(mint), Some(authority), Some(to)) = (
+            &ctx.accounts.fan_token_mint,
+            &ctx.accounts.fan_token_authority,
+            &ctx.accounts.buyer_fan_token_account,
+        ) else {
+            return err!(ErrorCode::MissingFanTokenAccounts);
+        };
+        require_keys_eq!(mint.key(), fan_token_mint, ErrorCode::InvalidFanTokenMint);
+        let fan_bump = ctx.bumps.fan_token_authority.ok_or(ErrorCode::MissingFanTokenAccounts)?;
+        let fan_seeds: &[&[&[u8]]] = &[&[b"fan_token_authority", event_key.as_ref(), &[fan_bump]]];
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: authority.to_account_info(),
+                },
+                fan_seeds,
+            ),
+            event.fan_tokens_per_purchase,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Require `payer` to hold `required` lamports, logging the shortfall, so a short
+/// wallet fails with InsufficientFundsForPurchase instead of a system program error.
+pub fn require_funds(payer: &AccountInfo, required: u64) -> Result<()> {
+    let balance = payer.lamports();
+    if balance < required {
+        msg!(
+            "Insufficient funds: {} lamports required, {} available, short by {}",
+            required,
+            balance,
+            required - balance
+        );
+        return err!(ErrorCode::InsufficientFundsForPurchase);
+    }
+    Ok(())
+}
+
+/// SOL a primary purchase still charges once buy_ticket's accounts exist: the price
+/// unless paid in a token (tax and donations come out of it), the tip, the attendance
+/// deposit and rent for the personhood and identity records and the Token Metadata
+/// accounts created in the handler (with the master edition and TokenRecord for pNFT
+/// events). Shared by buy_ticket's pre-flight check and quote_ticket.
+pub fn primary_purchase_lamports(
+    event: &Event,
+    tier_index: u8,
+    price: u64,
+    paid_in_token: bool,
+    tip_lamports: u64,
+) -> Result<u64> {
+    let rent = Rent::get()?;
+    let records = event.one_per_person as u64 + event.identity_required as u64;
+    let record_rent = rent.minimum_balance(8 + 32 + 32 + 32 + 1) * records;
+    let metadata_rent = match ticket_metadata_uri(event, 0) {
+        Some(_) if event.use_pnft => {
+            rent.minimum_balance(TOKEN_METADATA_ACCOUNT_LEN)
+                + rent.minimum_balance(MASTER_EDITION_ACCOUNT_LEN)
+                + rent.minimum_balance(TOKEN_RECORD_ACCOUNT_LEN)
+        }
+        Some(_) => rent.minimum_balance(TOKEN_METADATA_ACCOUNT_LEN),
+        None => 0,
+    };
+    let sol_price = if paid_in_token { 0 } else { price };
+    [sol_price, tip_lamports, ticket_deposit(event, tier_index), record_rent, metadata_rent]
+        .into_iter()
+        .try_fold(0u64, |total, amount| total.checked_add(amount))
+        .ok_or_else(|| ErrorCode::Overflow.into())
+}
+
+/// Rent a fresh buy_ticket pays for its ticket mint, buyer ATA, receipt and
+/// idempotency key, plus the rate limit and buyer record PDAs when they don't exist
+/// yet.
+pub fn fresh_purchase_rent(event: &Event, new_rate_limit: bool, new_buyer_record: bool) -> Result<u64> {
+    let rent = Rent::get()?;
+    let mut total = rent.minimum_balance(SplMint::LEN)
+        + rent.minimum_balance(spl_token_2022::state::Account::LEN)
+        + rent.minimum_balance(8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1)
+        + rent.minimum_balance(8 + 32 + 1);
+    if new_rate_limit {
+        total += rent.minimum_balance(8 + 1 + 8 + 1);
+    }
+    if new_buyer_record && event.max_per_wallet > 0 {
+        total += rent.minimum_balance(8 + 32 + 32 + 2 + 1);
+    }
+    Ok(total)
+}
+
+/// Consume the organizer's custom name for a ticket index, if one was set, and return
+/// its rent to the organizer.
+pub fn take_name_override<'info>(
+    name_override: &AccountInfo<'info>,
+    organizer: &AccountInfo<'info>,
+) -> Result<Option<String>> {
+    if name_override.data_is_empty() {
+        return Ok(None);
+    }
+    let name = TicketNameOverride::try_deserialize(&mut &name_override.try_borrow_data()?[..])?.custom_name;
+    close_pda(name_override, organizer)?;
+    Ok(Some(name))
+}
+
+/// Close a program-owned PDA held as a raw account, sending its rent to `destination`.
+pub fn close_pda<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let rent = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination.lamports().checked_add(rent).ok_or(ErrorCode::Overflow)?;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// Whether any accepted payment option is paid in an SPL token rather than SOL.
+pub fn accepts_token_payments(event: &Event) -> bool {
+    event.accepted_payments.iter().any(|option| option.mint.is_some())
+}
+
+/// Whether resale purchases are held back because the organizer still has primary
+/// tickets on sale. Primary sales close at `sale_end_ts`, or at the event start.
+pub fn resale_competes_with_primary(event: &Event, now: i64) -> bool {
+    let primary_open = now < event.date_ts && !matches!(event.sale_end_ts, Some(end) if now >= end);
+    event.resale_blocked_while_primary_available && event.sold < event.supply && primary_open
+}
+
+/// Where a resale's artist share goes: the organizer, or the event's RoyaltyEscrow
+/// while `royalty_escrow_mode` is set, in which case the deposit is recorded. The lock
+/// starts with the first deposit into an empty escrow.
+pub fn royalty_recipient<'info>(
+    event: &Event,
+    organizer: &AccountInfo<'info>,
+    royalty_escrow: Option<&mut Account<'info, RoyaltyEscrow>>,
+    artist_share: u64,
+) -> Result<AccountInfo<'info>> {
+    if !event.royalty_escrow_mode {
+        return Ok(organizer.clone());
+    }
+    let escrow = royalty_escrow.ok_or(ErrorCode::MissingRoyaltyEscrow)?;
+    if escrow.claimable_amount == 0 {
+        escrow.locked_since_ts = Clock::get()?.unix_timestamp;
+    }
+    escrow.claimable_amount = escrow.claimable_amount.checked_add(artist_share).ok_or(ErrorCode::Overflow)?;
+    Ok(escrow.to_account_info())
+}
+
+/// Fee a Token-2022 TransferFee mint withholds from `amount` this epoch (0 for mints
+/// without the extension or owned by the classic token program).
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    Ok(config.calculate_epoch_fee(Clock::get()?.epoch, amount).ok_or(ErrorCode::Overflow)?)
+}
+
+/// `transfer_checked` for flows that need the destination to receive exactly `amount`
+/// (ticket escrows, refunds): rejects mints that would withhold a transfer fee and
+/// verifies the destination balance afterwards.
+pub fn transfer_checked_exact<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    require!(transfer_fee(&ctx.accounts.mint, amount)? == 0, ErrorCode::UnexpectedTransferFee);
+    let to = ctx.accounts.to.clone();
+    let before = token_account_amount(&to)?;
+    transfer_checked(ctx, amount, decimals)?;
+    let received = token_account_amount(&to)?.checked_sub(before).ok_or(ErrorCode::UnexpectedTransferFee)?;
+    require!(received == amount, ErrorCode::UnexpectedTransferFee);
+    Ok(())
+}
+
+// Token account amount, at the same offset for both token programs
+fn token_account_amount(info: &AccountInfo) -> Result<u64> {
+    let data = info.try_borrow_data()?;
+    let bytes: [u8; 8] = data
+        .get(64..72)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Canonical ownership attestation message, version 1 (118 bytes):
+/// `"TCOWN"` ‖ version: u8 ‖ event ‖ ticket_mint ‖ owner ‖ slot: u64 LE ‖ nonce: u64 LE.
+/// Later versions bump the version byte and may only append fields.
+pub fn ownership_attestation_message(
+    event: &Pubkey,
+    ticket_mint: &Pubkey,
+    owner: &Pubkey,
+    slot: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(5 + 1 + 32 * 3 + 8 + 8);
+    message.extend_from_slice(b"TCOWN");
+    message.push(OWNERSHIP_ATTESTATION_VERSION);
+    message.extend_from_slice(event.as_ref());
+    message.extend_from_slice(ticket_mint.as_ref());
+    message.extend_from_slice(owner.as_ref());
+    message.extend_from_slice(&slot.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message
+}
+
+/// Create the `["ticket_mint", event, index]` PDA as a 0-decimal mint under
+/// `authority`, unless pre_allocate_tickets already did.
+#[allow(clippy::too_many_arguments)]
+pub fn create_ticket_mint<'info>(
+    payer: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    event: &Pubkey,
+    index: u32,
+    mint_bump: u8,
+    authority: &Pubkey,
+) -> Result<()> {
+    if !mint.data_is_empty() {
+        return Ok(());
+    }
+    create_pda_account(
+        payer,
+        mint,
+        system_program,
+        SplMint::LEN,
+        &token_program.key(),
+        &[&[b"ticket_mint", event.as_ref(), &index.to_le_bytes(), &[mint_bump]]],
+    )?;
+    initialize_mint2(
+        CpiContext::new(token_program.clone(), InitializeMint2 { mint: mint.clone() }),
+        0,
+        authority,
+        None,
+    )
+}
+
+/// Clear an NFT mint's mint authority right after its single token is minted, so its
+/// supply is fixed at 1 even if the signing PDA were ever misused. Ticket, pass and
+/// perk mints are created without a freeze authority, so there is nothing else to revoke.
+pub fn revoke_mint_authority<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    set_authority(
+        CpiContext::new_with_signer(
+            token_program,
+            SetAuthority { current_authority: authority, account_or_mint: mint },
+            signer_seeds,
+        ),
+        spl_token_2022::instruction::AuthorityType::MintTokens,
+        None,
+    )
+}
+
+/// Require a freshly created escrow token account to be owned by `authority` with no
+/// delegate and no close authority, whatever defaults the token program applied.
+pub fn check_escrow_clean(escrow: &TokenAccount, authority: &Pubkey) -> Result<()> {
+    require_keys_eq!(escrow.owner, *authority, ErrorCode::InvalidEscrow);
+    require!(
+        escrow.delegate.is_none() && escrow.delegated_amount == 0,
+        ErrorCode::DelegateMustBeRevoked
+    );
+    require!(escrow.close_authority.is_none(), ErrorCode::InvalidEscrow);
+    Ok(())
+}
+
+/// Shared body of list_for_resale and list_for_resale_usd. `usd_price_cents` is 0 for
+/// lamport listings; USD listings pass the lamport price converted from the feed.
+fn create_listing(ctx: Context<ListForResale>, price_lamports: u64, usd_price_cents: u64) -> Result<()> {
+    require!(price_lamports > 0, ErrorCode::InvalidPrice);
+    require!(!ctx.accounts.event.cancelled, ErrorCode::EventCancelled);
+    require!(Clock::get()?.unix_timestamp < ctx.accounts.event.date_ts, ErrorCode::EventEnded);
+    let tier_index = ticket_tier(&ctx.accounts.event, ctx.accounts.purchase_receipt.as_deref().map(|r| &**r))?;
+    check_listing_multiple(&ctx.accounts.event, tier_index, price_lamports)?;
+    check_resale_floor(&ctx.accounts.event, tier_index, price_lamports, Clock::get()?.unix_timestamp)?;
+    check_original_buyer(
+        &ctx.accounts.event,
+        ctx.accounts.receipt.as_deref().map(|r| &**r),
+        &ctx.accounts.seller.key(),
+    )?;
+    check_resale_blackout(
+        &ctx.accounts.event,
+        ctx.accounts.purchase_receipt.as_deref().map(|r| &**r),
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    check_escrow_clean(&ctx.accounts.escrow_token_account, &ctx.accounts.listing.key())?;
+
+    if ctx.accounts.event.use_pnft {
+        // pNFT: lock the ticket in the seller's wallet, with the listing as delegate
+        let mint = ctx.accounts.ticket_mint.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let pnft = PnftAccounts::new(
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.accounts.ticket_metadata.as_ref(),
+            ctx.accounts.ticket_edition.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+            &mint,
+            &system_program,
+            &token_program,
+        )?;
+        let token_record = ctx.accounts.seller_token_record.as_ref().ok_or(ErrorCode::MissingMetadataAccounts)?;
+        let mint_key = mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"listing", mint_key.as_ref(), &[ctx.bumps.listing]]];
+        pnft.lock_for_listing(
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.seller_token_account.to_account_info(),
+            token_record,
+            &ctx.accounts.listing.to_account_info(),
+            signer_seeds,
+        )?;
+    } else {
+        // Transfer NFT from seller to escrow
+        transfer_checked_exact(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.seller_token_account.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            1,
+            0, // decimals
+        )?;
+    }
+
+    let listing = &mut ctx.accounts.listing;
+    listing.seller = ctx.accounts.seller.key();
+    listing.event = ctx.accounts.event.key();
+    listing.ticket_mint = ctx.accounts.ticket_mint.key();
+    listing.price_lamports = price_lamports;
+    listing.anomaly_flag = false;
+    listing.created_at = Clock::get()?.unix_timestamp;
+    listing.last_price_update_at = listing.created_at;
+    listing.visible = true;
+    listing.usd_price_cents = usd_price_cents;
+    listing.price_feed = match (usd_price_cents, &ctx.accounts.price_feed) {
+        (0, _) | (_, None) => Pubkey::default(),
+        (_, Some(feed)) => feed.key(),
+    };
+    listing.tier_index = tier_index;
+    listing.bump = ctx.bumps.listing;
+
+    if let Some(book) = ctx.accounts.ask_book.as_mut() {
+        if usd_price_cents == 0 {
+            book.offer(ctx.accounts.listing.key(), price_lamports);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert US cents to lamports at the SOL/USD price in a Pyth PriceUpdateV2 account.
+/// The update must be fully verified, for the SOL/USD feed, no older than
+/// MAX_PRICE_FEED_AGE_SECONDS, and with a confidence interval within MAX_PRICE_CONF_BPS.
+pub fn usd_cents_to_lamports(feed: &AccountInfo, usd_cents: u64, now: i64) -> Result<u64> {
+    const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+    const VERIFICATION_FULL: u8 = 1;
+    require_keys_eq!(*feed.owner, PYTH_RECEIVER_PROGRAM_ID, ErrorCode::InvalidPriceFeed);
+    let data = feed.try_borrow_data()?;
+    // discriminator 8 | write_authority 32 | verification_level 1 (Full) | feed_id 32 |
+    // price i64 | conf u64 | exponent i32 | publish_time i64 | ...
+    require!(data.len() >= 41 + 32 + 8 + 8 + 4 + 8, ErrorCode::InvalidPriceFeed);
+    require!(
+        data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR && data[40] == VERIFICATION_FULL,
+        ErrorCode::InvalidPriceFeed
+    );
+    require!(data[41..73] == SOL_USD_FEED_ID, ErrorCode::InvalidPriceFeed);
+    let price = i64::from_le_bytes(data[73..81].try_into().unwrap());
+    let conf = u64::from_le_bytes(data[81..89].try_into().unwrap());
+    let exponent = i32::from_le_bytes(data[89..93].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(data[93..101].try_into().unwrap());
+    require!(price > 0 && (-18..=18).contains(&exponent), ErrorCode::InvalidPriceFeed);
+    require!(now - publish_time <= MAX_PRICE_FEED_AGE_SECONDS, ErrorCode::StalePriceFeed);
+    require!(
+        conf as u128 * 10_000 <= price as u128 * MAX_PRICE_CONF_BPS as u128,
+        ErrorCode::PriceConfidenceTooWide
+    );
+
+    // lamports = cents * 10^9 / 100 / (price * 10^exponent)
+    let mut numerator = usd_cents as u128 * 10_000_000;
+    let mut denominator = price as u128;
+    if exponent < 0 {
+        numerator = numerator.checked_mul(10u128.pow(exponent.unsigned_abs())).ok_or(ErrorCode::Overflow)?;
+    } else {
+        denominator *= 10u128.pow(exponent as u32);
+    }
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+/// A proof-of-personhood credential format. Each provider validates its credential
+/// account for `buyer` and returns an identifier that is the same for every wallet the
+/// person controls. Add a provider by implementing this and a PERSONHOOD_* arm in
+/// person_id.
+pub trait PersonhoodProvider {
+    fn person_id(credential: &AccountInfo, buyer: &Pubkey, network: &Pubkey, now: i64) -> Result<[u8; 32]>;
+}
+
+/// Civic gateway tokens. The id is the token's linked identity account, or the owner
+/// wallet when none is linked (uniqueness networks issue one token per person).
+pub struct CivicGateway;
+
+impl PersonhoodProvider for CivicGateway {
+    fn person_id(credential: &AccountInfo, buyer: &Pubkey, network: &Pubkey, now: i64) -> Result<[u8; 32]> {
+        const STATE_ACTIVE: u8 = 0;
+        require_keys_eq!(*credential.owner, CIVIC_GATEWAY_PROGRAM_ID, ErrorCode::InvalidPersonhoodCredential);
+        let data = credential.try_borrow_data()?;
+        // features u8 | parent Option<Pubkey> | owner_wallet | owner_identity Option<Pubkey> |
+        // gatekeeper_network | issuing_gatekeeper | state u8 | expire_time Option<i64>
+        let mut cursor = 1;
+        let mut read = |len: usize| -> Result<&[u8]> {
+            let bytes = data.get(cursor..cursor + len).ok_or(ErrorCode::InvalidPersonhoodCredential)?;
+            cursor += len;
+            Ok(bytes)
+        };
+        if read(1)?[0] == 1 {
+            read(32)?;
+        }
+        let owner_wallet = Pubkey::try_from(read(32)?).unwrap();
+        let owner_identity = match read(1)?[0] {
+            1 => Some(Pubkey::try_from(read(32)?).unwrap()),
+            _ => None,
+        };
+        let gatekeeper_network = Pubkey::try_from(read(32)?).unwrap();
+        read(32)?;
+        let state = read(1)?[0];
+        let expire_time = match read(1)?[0] {
+            1 => Some(i64::from_le_bytes(read(8)?.try_into().unwrap())),
+            _ => None,
+        };
+
+        require_keys_eq!(owner_wallet, *buyer, ErrorCode::InvalidPersonhoodCredential);
+        require_keys_eq!(gatekeeper_network, *network, ErrorCode::InvalidPersonhoodCredential);
+        require!(
+            state == STATE_ACTIVE && !matches!(expire_time, Some(expiry) if expiry <= now),
+            ErrorCode::InvalidPersonhoodCredential
+        );
+        Ok(owner_identity.unwrap_or(owner_wallet).to_bytes())
+    }
+}
+
+/// Dispatch to the configured PersonhoodProvider.
+pub fn person_id(provider: u8, credential: &AccountInfo, buyer: &Pubkey, network: &Pubkey, now: i64) -> Result<[u8; 32]> {
+    match provider {
+        PERSONHOOD_CIVIC => CivicGateway::person_id(credential, buyer, network, now),
+        _ => err!(ErrorCode::InvalidPersonhoodCredential),
+    }
+}
+
+/// Create the `["person", event, person]` PersonPurchase PDA, failing if this person
+/// already bought a ticket to the event from any wallet.
+pub fn record_person_purchase<'info>(
+    person_purchase: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    event: &Pubkey,
+    person: [u8; 32],
+    buyer: Pubkey,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(&[b"person", event.as_ref(), &person], &crate::ID);
+    require_keys_eq!(person_purchase.key(), expected, ErrorCode::InvalidPersonhoodCredential);
+    require!(person_purchase.data_is_empty(), ErrorCode::AlreadyPurchasedAsPerson);
+
+    create_pda_account(payer, person_purchase, system_program, 8 + 32 + 32 + 32 + 1, &crate::ID, &[&[b"person", event.as_ref(), &person, &[bump]]])?;
+    let record = PersonPurchase { event: *event, person_id: person, buyer, bump };
+    record.try_serialize(&mut &mut person_purchase.try_borrow_mut_data()?[..])
+}
+
+/// Decode a CHANGE_DATE proposal's `date_ts`, which must be after `now` and keep the
+/// event's sale window valid.
+pub fn proposed_date(proposed_value: &[u8; 64], event: &Event, now: i64) -> Result<i64> {
+    let mut date_bytes = [0u8; 8];
+    date_bytes.copy_from_slice(&proposed_value[..8]);
+    let date_ts = i64::from_le_bytes(date_bytes);
+    require!(date_ts > now, ErrorCode::InvalidEventDate);
+    validate_sale_window(event.sale_start_ts, event.sale_end_ts, date_ts)?;
+    Ok(date_ts)
+}
+
+/// Whether the event was split into replacement shows by split_event.
+pub fn is_superseded(event: &Event) -> bool {
+    event.split_into[0] != Pubkey::default()
+}
+
+/// Count a resale's platform share on the config, the event and, if it is today's,
+/// the passed fee bucket.
+fn record_platform_fee(
+    config: &mut PlatformConfig,
+    event: &mut Event,
+    fee_day: Option<&mut Account<FeeDay>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    config.resale_fees_collected = config.resale_fees_collected.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    config.resale_fee_count = config.resale_fee_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    event.platform_fees_collected = event.platform_fees_collected.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    if let Some(bucket) = fee_day {
+        if bucket.day as i64 == Clock::get()?.unix_timestamp / FEE_DAY_SECONDS {
+            bucket.resale_fees_collected = bucket.resale_fees_collected.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+            bucket.resale_fee_count = bucket.resale_fee_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+    }
+    Ok(())
+}
+
+/// Under event.require_original_buyer_only, only the wallet holding the ticket's
+/// primary purchase Receipt may sell it.
+pub fn check_original_buyer(event: &Event, receipt: Option<&Receipt>, seller: &Pubkey) -> Result<()> {
+    if event.require_original_buyer_only {
+        let receipt = receipt.ok_or(ErrorCode::NotOriginalBuyer)?;
+        require!(receipt.buyer == *seller, ErrorCode::NotOriginalBuyer);
+    }
+    Ok(())
+}
+
+/// Reject listings within event.resale_blackout_secs of the ticket's primary purchase.
+/// The purchase time comes from the original buyer's Receipt, so transfers don't reset
+/// it. Split events are exempt so holders can always exit.
+pub fn check_resale_blackout(event: &Event, purchase_receipt: Option<&Receipt>, now: i64) -> Result<()> {
+    if event.resale_blackout_secs == 0 || is_superseded(event) {
+        return Ok(());
+    }
+    let receipt = purchase_receipt.ok_or(ErrorCode::MissingPurchaseReceipt)?;
+    require!(
+        now >= receipt.purchased_ts.saturating_add(event.resale_blackout_secs),
+        ErrorCode::ResaleBlackoutActive
+    );
+    Ok(())
+}
+
+/// The identity commitment a new ticket's receipt stores. On identity_required events
+/// it is mandatory and claims its IdentityPurchase PDA (one ticket per identity, the
+/// commitment itself keys the PDA); elsewhere it is optional and unchecked.
+pub fn take_identity_commitment<'info>(
+    event: &Account<'info, Event>,
+    commitment: Option<[u8; 32]>,
+    identity_purchase: Option<&AccountInfo<'info>>,
+    buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<[u8; 32]> {
+    if !event.identity_required {
+        return Ok(commitment.unwrap_or_default());
+    }
+    let commitment = commitment.filter(|c| *c != [0; 32]).ok_or(ErrorCode::IdentityCommitmentRequired)?;
+    let identity_purchase = identity_purchase.ok_or(ErrorCode::IdentityCommitmentRequired)?;
+    record_identity_purchase(identity_purchase, buyer, system_program, &event.key(), commitment, buyer.key())?;
+    Ok(commitment)
+}
+
+/// Create the `["identity", event, commitment]` IdentityPurchase PDA, failing if the
+/// commitment was already used for a ticket to the event.
+pub fn record_identity_purchase<'info>(
+    identity_purchase: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    event: &Pubkey,
+    commitment: [u8; 32],
+    buyer: Pubkey,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(&[b"identity", event.as_ref(), &commitment], &crate::ID);
+    require_keys_eq!(identity_purchase.key(), expected, ErrorCode::IdentityCommitmentRequired);
+    require!(identity_purchase.data_is_empty(), ErrorCode::IdentityAlreadyUsed);
+
+    create_pda_account(payer, identity_purchase, system_program, 8 + 32 + 32 + 32 + 1, &crate::ID, &[&[b"identity", event.as_ref(), &commitment, &[bump]]])?;
+    let record = IdentityPurchase { event: *event, commitment, buyer, bump };
+    record.try_serialize(&mut &mut identity_purchase.try_borrow_mut_data()?[..])
+}
+
+/// Create the seller's ticket ATA if it doesn't exist (idempotent, so an existing one
+/// is never re-initialized), then check it belongs to the seller and holds this mint.
+/// An ATA with a delegate is refused: the delegate could take the returned ticket.
+fn ensure_seller_ata(ctx: &Context<CancelListing>) -> Result<()> {
+    let ata = &ctx.accounts.seller_token_account;
+    if ata.data_is_empty() {
+        anchor_spl::associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: ctx.accounts.seller.to_account_info(),
+                associated_token: ata.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+    require_keys_eq!(*ata.owner, ctx.accounts.token_program.key(), ErrorCode::SellerTokenAccountMissing);
+    let data = ata.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    require!(
+        state.base.owner == ctx.accounts.seller.key() && state.base.mint == ctx.accounts.ticket_mint.key(),
+        ErrorCode::SellerTokenAccountMissing
+    );
+    // A pNFT listing is the delegate of the ticket it locks; cancel_listing revokes it
+    require!(
+        state.base.delegate.is_none() || state.base.delegate == COption::Some(ctx.accounts.listing.key()),
+        ErrorCode::DelegateMustBeRevoked
+    );
+    Ok(())
+}
+
+/// A sale window must open before it closes, and close before the event starts.
+pub fn validate_sale_window(sale_start_ts: Option<i64>, sale_end_ts: Option<i64>, date_ts: i64) -> Result<()> {
+    if let (Some(start), Some(end)) = (sale_start_ts, sale_end_ts) {
+        require!(start < end, ErrorCode::InvalidSaleWindow);
+    }
+    require!(!matches!(sale_end_ts, Some(end) if end > date_ts), ErrorCode::InvalidSaleWindow);
+    Ok(())
+}
+
+// ── Account structs ──────────────────────────────────────────────────
+
+#[account]
+pub struct Event {
+    pub organizer: Pubkey,
+    pub nonce: u64,
+    pub title: String,
+    pub venue: String,
+    pub date_ts: i64,
+    pub tier_name: String,
+    pub price_lamports: u64,
+    pub supply: u32,
+    pub sold: u32,
+    pub artist_pct: u8,       // artist_bps / 100, kept for display; resale math uses the bps
+    pub capacity: u32,        // upper bound for automatic supply increases
+    pub waitlist_head: u32,   // number of waitlist entries created so far
+    pub auto_increase_supply: bool,
+    pub demand_increment: u32,
+    pub auto_increments: u8,  // automatic increases applied so far (max MAX_AUTO_INCREMENTS)
+    pub checked_in: u32,      // tickets checked in at the venue
+    pub release_schedule: Vec<ReleaseTranche>, // max MAX_RELEASE_TRANCHES, ascending
+    pub released_early: u32,  // tickets pulled forward via release_now
+    pub max_listing_multiple: u16, // resale cap as % of primary price (0 = no cap)
+    pub held: u32,            // tickets reserved by unclaimed sponsor holds
+    pub tax_bps: u16,         // share of each primary price remitted to tax_wallet
+    pub tax_wallet: Pubkey,
+    pub tax_collected: u64,   // cumulative tax remitted
+    pub donation_bps: u16,    // share of each primary price sent to donation_wallet
+    pub donation_wallet: Pubkey,
+    pub total_donated: u64,   // cumulative donations remitted
+    pub resale_paused: bool,  // set by the resale circuit breaker
+    pub verification_ts: i64, // when the organizer was verified (0 = unverified)
+    pub require_original_buyer_only: bool, // only primary buyers may list for resale
+    pub grace_period_seconds: u32, // refund window after purchase (0 = no refunds)
+    pub refundable_until: i64,     // latest open refund window; vault is locked until then
+    pub vesting_cliff_ts: i64,     // vault proceeds start vesting here
+    pub vesting_duration_seconds: i64, // linear vesting after the cliff (0 = no vesting)
+    pub proceeds_withdrawn: u64,   // cumulative lamports withdrawn from the vault
+    pub fan_token_mint: Option<Pubkey>, // fungible reward minted to primary buyers
+    pub fan_tokens_per_purchase: u64,   // raw token units per ticket bought
+    pub pass_reserved: u32,   // seats reserved for unredeemed festival passes
+    pub social_link: String,  // https:// URL, max MAX_SOCIAL_LINK_LEN; required to verify
+    pub concessions: Vec<Concession>, // reduced-price categories, max MAX_CONCESSIONS
+    pub ticket_design_cid: String, // IPFS CIDv1 of the ticket template SVG, max MAX_DESIGN_CID_LEN
+    pub artist_bps: u16,      // resale split, copied at creation; sums to 10000
+    pub seller_bps: u16,
+    pub platform_bps: u16,
+    pub accessible_seating_count: u32, // seats reserved for buy_accessible_ticket
+    pub accessible_sold: u32,
+    pub requires_attestation: bool, // buy_ticket needs a platform attestor signature
+    pub analytics: EventAnalytics,
+    pub use_pnft: bool,       // tickets are programmable NFTs with a TokenRecord; see set_pnft_mode
+    pub accepted_payments: Vec<PaymentOption>, // alternatives to the SOL price, max MAX_PAYMENT_OPTIONS
+    pub sales_paused: bool,   // set by a failed health check; blocks buy_ticket
+    pub last_health_check_ts: i64,
+    pub resale_blocked_while_primary_available: bool,
+    pub min_resale_lamports: u64,  // static resale floor (0 = none)
+    pub dynamic_resale_floor: bool, // floor rises to price_lamports until sellout
+    pub royalty_escrow_mode: bool, // resale artist shares go to the RoyaltyEscrow PDA
+    pub one_per_person: bool,      // buy_ticket needs a personhood credential; one ticket each
+    pub deposit_lamports: u64,     // refundable attendance deposit on free tickets (0 = none)
+    pub deposit_forfeit_wallet: Pubkey, // receives no-show deposits
+    pub deposits_held: u64,        // outstanding deposits in the DepositEscrow
+    pub notes_survive_transfer: bool, // ticket notes stay valid for later holders
+    pub split_into: [Pubkey; 2],   // replacement shows; set = superseded, no more sales
+    pub split_from: Pubkey,        // superseded event this show replaces
+    pub migrated_out: u32,         // tickets exchanged for a replacement show
+    pub platform_fees_collected: u64, // lifetime platform share of this event's resales
+    pub resale_blackout_secs: i64, // no listing until this long after the primary purchase
+    pub platform_fee_override_bps: Option<u16>, // negotiated platform share, at most platform_bps; the seller keeps the rest
+    pub identity_required: bool,   // one ticket per identity commitment, see set_identity_mode
+    pub identity_at_check_in: bool, // check-in must present the commitment's preimage
+    pub closed_receipt_revenue: u64, // SOL paid on receipts since closed; kept for audits
+    pub tiers: Vec<Tier>,          // max MAX_TIERS; supplies sum to the initial supply
+    pub token_only: bool,          // SOL purchases refused; buyers pick a token payment option
+    pub sale_start_ts: Option<i64>, // buy_ticket opens at this time, if set
+    pub sale_end_ts: Option<i64>,  // and closes at this time, if set
+    pub max_per_wallet: u16,       // tickets one wallet may buy; 0 = unlimited
+    pub last_updated_ts: i64,      // creation or last update_event, for indexers
+    pub cancelled: bool,           // cancel_event was called; holders claim refunds
+    pub refunds_pending: u32,      // tickets still to be refunded after cancellation
+    pub metadata_uri: String,      // Token Metadata URI for every ticket, max MAX_METADATA_URI_LEN; empty = none
+    pub perks_created: u32,        // perk generation; the next perk's PDA index
+    pub accessibility_verifier: Pubkey, // may approve accessible-seat buyers; default = organizer only
+    pub holds_expire_at: i64,      // earliest expires_at among outstanding holds; meaningless while held == 0
+    pub postponement_refund_until: i64, // claim_refund is open until then after a postponement; 0 = none
+    pub pass_refunds_owed: u64,    // this event's share of festival passes sold; refunded by claim_pass_refund on cancellation
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReleaseTranche {
+    pub release_ts: i64,
+    pub count: u32,
+}
+
+/// A ticket tier as passed to create_event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TierConfig {
+    pub name: String,          // e.g. "GA", "VIP", "Backstage"
+    pub price_lamports: u64,
+    pub supply: u32,
+}
+
+/// A ticket tier as stored on the Event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Tier {
+    pub name: String,
+    pub price_lamports: u64,
+    pub supply: u32,
+    pub sold: u32,
+    pub perks_bitmask: u16, // PERK_* bits granted by the tier; copied to receipts
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Concession {
+    pub name: String,          // e.g. "child", "senior", "student"
+    pub tier_index: u8,        // the tier this reduced price applies to
+    pub price_lamports: u64,
+    pub max_count: u32,
+    pub sold: u32,
+    pub verified_only: bool,   // purchase needs an organizer ed25519 attestation
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PaymentOption {
+    pub tier_index: u8,        // the tier this price buys
+    pub mint: Option<Pubkey>,  // None = native SOL
+    pub price: u64,            // in the mint's base units (lamports for SOL)
+}
+
+/// buy_ticket_v2 options. The first four match buy_ticket's arguments (version 1).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct BuyTicketOptions {
+    pub category: Option<u8>,
+    pub tip_lamports: u64,
+    pub allow_large_tip: bool,
+    pub payment_index: Option<u8>,
+    pub voucher_hash: Option<[u8; 32]>,
+    pub referrer: Option<Pubkey>,
+    pub offered_lamports: Option<u64>,
+    pub note: Option<[u8; 32]>, // version 2: buyer note, see set_ticket_note
+    pub identity_commitment: Option<[u8; 32]>, // version 3: see set_identity_mode
+    pub tier_index: u8,        // version 4: buy_ticket's tier_index
+}
+
+/// Dashboard counters, updated in the same instructions (and from the same amounts)
+/// that move the money.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EventAnalytics {
+    pub gross_primary_revenue: u64, // SOL ticket prices paid on buy_ticket and claim_hold
+    pub resale_volume: u64,
+    pub resale_count: u32,
+    pub refund_count: u32,
+    pub refunded_lamports: u64,
+    pub unique_buyers: u32,         // approximate: buyers whose bitmap bit was still unset
+    pub buyer_bitmap: [u8; ANALYTICS_BITMAP_BYTES], // linear-counting sketch of buyer keys
+}
+
+impl Default for EventAnalytics {
+    fn default() -> Self {
+        Self {
+            gross_primary_revenue: 0,
+            resale_volume: 0,
+            resale_count: 0,
+            refund_count: 0,
+            refunded_lamports: 0,
+            unique_buyers: 0,
+            buyer_bitmap: [0; ANALYTICS_BITMAP_BYTES],
+        }
+    }
+}
+
+impl EventAnalytics {
+    pub fn record_primary_sale(&mut self, buyer: &Pubkey, lamports: u64) -> Result<()> {
+        self.gross_primary_revenue = self.gross_primary_revenue.checked_add(lamports).ok_or(ErrorCode::Overflow)?;
+        self.record_buyer(buyer)
+    }
+
+    pub fn record_resale(&mut self, buyer: &Pubkey, lamports: u64) -> Result<()> {
+        self.resale_volume = self.resale_volume.checked_add(lamports).ok_or(ErrorCode::Overflow)?;
+        self.resale_count = self.resale_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        self.record_buyer(buyer)
+    }
+
+    pub fn record_refund(&mut self, lamports: u64) -> Result<()> {
+        self.refunded_lamports = self.refunded_lamports.checked_add(lamports).ok_or(ErrorCode::Overflow)?;
+        self.refund_count = self.refund_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    pub fn average_resale_price(&self) -> u64 {
+        self.resale_volume.checked_div(self.resale_count as u64).unwrap_or(0)
+    }
+
+    // Wallet keys are uniformly distributed, so their leading bytes serve as the hash
+    fn record_buyer(&mut self, buyer: &Pubkey) -> Result<()> {
+        let bytes = buyer.to_bytes();
+        let bit = u16::from_le_bytes([bytes[0], bytes[1]]) as usize % (ANALYTICS_BITMAP_BYTES * 8);
+        let mask = 1u8 << (bit % 8);
+        if self.buyer_bitmap[bit / 8] & mask == 0 {
+            self.buyer_bitmap[bit / 8] |= mask;
+            self.unique_buyers = self.unique_buyers.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,        // 32
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub price_lamports: u64,   // 8
+    pub anomaly_flag: bool,    // 1: escrow balance was found != 1; resale is blocked
+    pub created_at: i64,       // 8
+    pub last_price_update_at: i64, // 8
+    pub visible: bool,         // 1: organizer-controlled; clients hide listings when false
+    pub usd_price_cents: u64,  // 8: 0 = priced in lamports; else price_lamports is indicative
+    pub price_feed: Pubkey,    // 32: SOL/USD feed for USD listings
+    pub tier_index: u8,        // 1: the ticket's tier; resale caps and floors use its price
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct WaitlistEntry {
+    pub event: Pubkey,         // 32
+    pub wallet: Pubkey,        // 32
+    pub position: u32,         // 4
+    pub joined_ts: i64,        // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct AttendanceRecord {
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub holder: Pubkey,        // 32
+    pub ticket_index: u32,     // 4
+    pub attendance_index: u32, // 4: order of check-in, used by raffles
+    pub checked_in_ts: i64,    // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct Raffle {
+    pub event: Pubkey,         // 32
+    pub prize_count: u8,       // 1
+    pub draw_after_ts: i64,    // 8
+    pub drawn: bool,           // 1
+    pub seed: [u8; 32],        // 32
+    pub winners: Vec<u32>,     // 4 + 4 * MAX_RAFFLE_WINNERS: attendance indices
+    pub claimed_mask: u16,     // 2: bit i set once winners[i] has claimed
+    pub bump: u8,              // 1
+    pub commitment: [u8; 32],  // 32: hash of the randomness revealed in draw_raffle
+    pub lock_slot: u64,        // 8: slot whose hash seeds the draw; 0 until locked
+    pub entrants: u32,         // 4: event.checked_in when locked
+}
+
+#[account]
+pub struct TicketStats {
+    pub event: Pubkey,                           // 32
+    pub price_history: [u64; PRICE_HISTORY_LEN], // 8 * 5: most recent resale first
+    pub sales_count: u32,                        // 4
+    pub bump: u8,                                // 1
+}
+
+#[account]
+pub struct Hold {
+    pub event: Pubkey,         // 32
+    pub beneficiary: Pubkey,   // 32
+    pub remaining: u32,        // 4: held tickets not yet claimed
+    pub price_lamports: u64,   // 8: price per claimed ticket
+    pub expires_at: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct NoShowRecord {
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub holder: Pubkey,        // 32
+    pub claimed_ts: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct PlatformConfig {
+    pub authority: Pubkey,     // 32
+    pub max_ticket_price: u64, // 8: ceiling on primary ticket prices
+    pub min_price_update_interval_secs: i64, // 8: cooldown between listing price changes
+    pub attestor_key: Pubkey,  // 32: signs off-chain purchase challenges
+    pub monitor_reward_fund: u64, // 8: retired; monitor rewards come from per-event MonitorRewardPools
+    pub royalty_release_delay_seconds: u32, // 4: lock on escrowed royalties from their first deposit
+    pub bump: u8,              // 1
+    // Fields below were appended after the first deployment; migrate_platform_config
+    // grows older accounts to PLATFORM_CONFIG_LEN
+    pub messaging_program: Option<Pubkey>, // 33: receives ownership attestations by CPI
+    pub personhood_provider: u8, // 1: PERSONHOOD_* credential format
+    pub personhood_network: Pubkey, // 32: gatekeeper network or collection credentials must belong to
+    pub resale_fees_collected: u64, // 8: lifetime platform share of resales, paid out as collected
+    pub resale_fee_count: u64,  // 8: resales that paid a platform share
+}
+
+#[account]
+pub struct ClaimLink {
+    pub sender: Pubkey,        // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub claim_key: Pubkey,     // 32: one-time key that signs the claimer's wallet
+    pub expires_at: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+/// The event's ASK_BOOK_SIZE cheapest lamport listings, price ascending. Best effort:
+/// entries can go stale when an instruction that changes a listing isn't given the
+/// book; repair_ask_book re-syncs them. USD-priced, flagged and hidden listings are
+/// left out.
+#[account]
+pub struct AskBook {
+    pub event: Pubkey,             // 32
+    pub asks: Vec<AskEntry>,       // 4 + 40 * ASK_BOOK_SIZE
+    pub bump: u8,                  // 1
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AskEntry {
+    pub price_lamports: u64,       // 8
+    pub listing: Pubkey,           // 32
+}
+
+impl AskBook {
+    /// Drop the listing's entry, if any.
+    pub fn remove(&mut self, listing: &Pubkey) {
+        self.asks.retain(|a| a.listing != *listing);
+    }
+
+    /// Record the listing at `price_lamports`, replacing its old entry. Kept only if
+    /// the book has room or the price beats the most expensive ask, which is evicted.
+    pub fn offer(&mut self, listing: Pubkey, price_lamports: u64) {
+        self.remove(&listing);
+        let pos = self.asks.partition_point(|a| a.price_lamports <= price_lamports);
+        if pos < ASK_BOOK_SIZE {
+            self.asks.insert(pos, AskEntry { price_lamports, listing });
+            self.asks.truncate(ASK_BOOK_SIZE);
+        }
+    }
+}
+
+#[account]
+pub struct ResaleOrderBook {
+    pub event: Pubkey,                       // 32
+    pub buy_orders: Vec<OrderBookEntry>,     // 4 + 84 * MAX_BOOK_ORDERS, price descending
+    pub sell_orders: Vec<OrderBookEntry>,    // 4 + 84 * MAX_BOOK_ORDERS, price ascending
+    pub bump: u8,                            // 1
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OrderBookEntry {
+    pub price: u64,            // 8: lamports per ticket
+    pub quantity: u32,         // 4: always 1 for asks
+    pub wallet: Pubkey,        // 32
+    pub ticket_mint: Pubkey,   // 32: escrowed ticket for asks, default for bids
+    pub expires_at: i64,       // 8: match_orders skips the order from then on
+}
+
+#[account]
+pub struct Receipt {
+    pub buyer: Pubkey,         // 32
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub paid_lamports: u64,    // 8
+    pub purchased_ts: i64,     // 8
+    pub refunded: bool,        // 1
+    pub category: Option<u8>,  // 2: concession category index, if any
+    pub payment_index: Option<u8>, // 2: accepted_payments entry used; paid_lamports is in its units
+    pub tip_lamports: u64,     // 8: non-refundable tip paid on top of the price
+    pub perks_bitmask: u16,    // 2: event.perks_bitmask at mint
+    pub deposit_lamports: u64, // 8: attendance deposit still held; 0 once settled
+    pub note: [u8; 32],        // 32: holder's note (merch size, diet, ...); zeros = none
+    pub note_holder: Pubkey,   // 32: wallet that wrote the note
+    pub identity_commitment: [u8; 32], // 32: buyer's identity commitment; zeros = none
+    pub tier_index: u8,        // 1: Event.tiers entry the ticket was sold from
+    pub bump: u8,              // 1
+}
+
+impl Receipt {
+    /// The note as seen by `holder`. Notes lapse when the ticket changes hands unless
+    /// the event keeps them across transfers.
+    pub fn note_for(&self, holder: &Pubkey, survives_transfer: bool) -> Option<[u8; 32]> {
+        let valid = survives_transfer || self.note_holder == *holder;
+        (valid && self.note != [0; 32]).then_some(self.note)
+    }
+}
+
+#[account]
+pub struct TransferOffer {
+    pub sender: Pubkey,        // 32
+    pub recipient: Pubkey,     // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub expires_at: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct GiftNote {
+    pub sender: Pubkey,        // 32
+    pub recipient: Pubkey,     // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub message: String,       // 4 + MAX_GIFT_MESSAGE_LEN
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct EventVault {
+    pub event: Pubkey,         // 32
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct FanTokenConfig {
+    pub mint: Pubkey,              // 32
+    pub authority_bump: u8,        // 1
+    pub tokens_per_purchase: u64,  // 8
+    pub bump: u8,                  // 1
+}
+
+#[account]
+pub struct Perk {
+    pub event: Pubkey,         // 32
+    pub uri: String,           // 4 + MAX_PERK_URI_LEN
+    pub claim_until: i64,      // 8
+    pub claimed: u32,          // 4
+    pub bump: u8,              // 1
+    pub index: u32,            // 4: event.perks_created when announced, part of the PDA seeds
+}
+
+#[account]
+pub struct PerkClaimed {
+    pub perk: Pubkey,          // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub claimer: Pubkey,       // 32
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct PassBundle {
+    pub creator: Pubkey,          // 32
+    pub nonce: u64,               // 8
+    pub events: Vec<Pubkey>,      // 4 + 32 * MAX_BUNDLE_EVENTS
+    pub organizers: Vec<Pubkey>,  // 4 + 32 * MAX_BUNDLE_EVENTS: parallel to events
+    pub shares_bps: Vec<u16>,     // 4 + 2 * MAX_BUNDLE_EVENTS: revenue split, sums to 10000
+    pub price_lamports: u64,      // 8
+    pub supply: u32,              // 4
+    pub sold: u32,                // 4
+    pub bump: u8,                 // 1
+    pub sale_end_ts: i64,         // 8: buy_pass closes; unsold passes' seats can be released
+    pub reserved: Vec<u32>,       // 4 + 4 * MAX_BUNDLE_EVENTS: seats still held on each event
+    pub redeemed: Vec<u32>,       // 4 + 4 * MAX_BUNDLE_EVENTS: passes redeemed on each event
+}
+
+#[account]
+pub struct PassRedemption {
+    pub pass_mint: Pubkey,     // 32
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub bump: u8,              // 1
+}
+
+/// Marks a festival pass as refunded for one cancelled event.
+#[account]
+pub struct PassRefund {
+    pub bump: u8, // 1
+}
+
+#[account]
+pub struct RevenueAudit {
+    pub event: Pubkey,            // 32
+    pub auditor: Pubkey,          // 32
+    pub expected_revenue: u64,    // 8: SOL receipt total over the batches so far
+    pub last_receipt: Pubkey,     // 32: highest receipt address counted; the next must be higher
+    pub bump: u8,                 // 1
+}
+
+#[account]
+pub struct MonitorRewardPool {
+    pub event: Pubkey, // 32
+    pub balance: u64,  // 8: lamports left for rewards, above the pool's rent
+    pub bump: u8,      // 1
+}
+
+#[account]
+pub struct MonitorClaims {
+    pub event: Pubkey,   // 32
+    pub monitor: Pubkey, // 32
+    pub earned: u64,     // 8: rewards paid to this monitor, at most MONITOR_REWARD_CAP_LAMPORTS
+    pub bump: u8,        // 1
+}
+
+#[account]
+pub struct AccessibleApproval {
+    pub event: Pubkey,    // 32
+    pub buyer: Pubkey,    // 32
+    pub approver: Pubkey, // 32: organizer or accessibility verifier who signed
+    pub used: bool,       // 1: set by buy_accessible_ticket
+    pub bump: u8,         // 1
+}
+
+#[account]
+pub struct DelegateEntry {
+    pub ticket_mint: Pubkey,   // 32
+    pub holder: Pubkey,        // 32
+    pub delegate: Pubkey,      // 32
+    pub expires_ts: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct PurchaseIdempotencyKey {
+    pub mint: Pubkey,          // 32: ticket minted by this purchase
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct SplitTemplate {
+    pub name: String,          // 4 + MAX_TEMPLATE_NAME_LEN
+    pub artist_bps: u16,       // 2
+    pub seller_bps: u16,       // 2
+    pub platform_bps: u16,     // 2
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct TicketAllocation {
+    pub event: Pubkey,         // 32
+    pub start_index: u32,      // 4: event.sold when the batch was made; mints are ticket_mint PDAs from here
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct TicketholderVote {
+    pub event: Pubkey,              // 32
+    pub proposal_id: u64,           // 8
+    pub change_type: u8,            // 1: CHANGE_VENUE or CHANGE_DATE
+    pub proposed_value: [u8; 64],   // 64
+    pub yes_votes: u32,             // 4
+    pub no_votes: u32,              // 4
+    pub applied: bool,              // 1
+    pub bump: u8,                   // 1
+    pub voting_ends_at: i64,        // 8: cast_vote closes, apply_governance_change opens
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,      // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub voter: Pubkey,         // 32
+    pub yes: bool,             // 1
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct FloorMonitor {
+    pub event: Pubkey,            // 32
+    pub hourly_high_price: u64,   // 8: highest resale in the current window
+    pub hour_start_ts: i64,       // 8
+    pub bump: u8,                 // 1
+}
+
+#[account]
+pub struct BuyerRateLimit {
+    pub purchases_last_minute: u8, // 1
+    pub window_start_ts: i64,      // 8
+    pub bump: u8,                  // 1
+}
+
+#[account]
+pub struct RoyaltyEscrow {
+    pub event: Pubkey,         // 32
+    pub locked_since_ts: i64,  // 8: first deposit since the last claim
+    pub claimable_amount: u64, // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct TicketFlag {
+    pub event: Pubkey,         // 32
+    pub ticket_mint: Pubkey,   // 32
+    pub reason_code: u8,       // 1: organizer-defined (stolen payment, duplicate, ...)
+    pub flagged_ts: i64,       // 8
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct TicketNameOverride {
+    pub custom_name: String,   // 4 + MAX_TICKET_NAME_LEN
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct OrganizerBan {
+    pub organizer: Pubkey,     // 32
+    pub wallet: Pubkey,        // 32
+    pub ban_ts: i64,           // 8
+    pub reason_hash: [u8; 32], // 32: hash of the off-chain reason
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct AttestationNonce {
+    pub last_nonce: u64,       // 8: highest nonce attested for this ticket
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct PersonPurchase {
+    pub event: Pubkey,         // 32
+    pub person_id: [u8; 32],   // 32: PersonhoodProvider identifier
+    pub buyer: Pubkey,         // 32: wallet that bought
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct BuyerRecord {
+    pub event: Pubkey,         // 32
+    pub buyer: Pubkey,         // 32
+    pub tickets_bought: u16,   // 2: buy_ticket purchases counted against max_per_wallet
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct IdentityPurchase {
+    pub event: Pubkey,         // 32
+    pub commitment: [u8; 32],  // 32: opaque hash of an ID or membership number
+    pub buyer: Pubkey,         // 32: wallet that bought
+    pub bump: u8,              // 1
+}
+
+#[account]
+pub struct DepositEscrow {
+    pub event: Pubkey,         // 32
+    pub bump: u8,              // 1
+}
+
+/// Platform fees for one day (unix time / FEE_DAY_SECONDS), for time-series reports.
+#[account]
+pub struct FeeDay {
+    pub day: u32,                  // 4
+    pub resale_fees_collected: u64, // 8
+    pub resale_fee_count: u64,     // 8
+    pub bump: u8,                  // 1
+}
+
+/// get_fee_report's return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct FeeReport {
+    pub resale_fees_collected: u64,
+    pub resale_fee_count: u64,
+    /// The passed event's platform_fees_collected.
+    pub event_fees_collected: Option<u64>,
+    /// The passed bucket's (day, resale_fees_collected, resale_fee_count).
+    pub day: Option<(u32, u64, u64)>,
+}
+
+// ── Instruction contexts ─────────────────────────────────────────────
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CreateEvent<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 8 + 68 + 68 + 8 + 36 + 8 + 4 + 4 + 1 + 4 + 4 + 1 + 4 + 1 + 4
+            + 4 + 12 * MAX_RELEASE_TRANCHES + 4 + 2 + 4 + 2 + 32 + 8 + 8 + 1 + 4 + 8 + 8 + 8 + 8 + 33 + 8 + 4
+            + 4 + MAX_SOCIAL_LINK_LEN + 4 + (4 + MAX_CONCESSION_NAME_LEN + 1 + 8 + 4 + 4 + 1) * MAX_CONCESSIONS
+            + 4 + MAX_DESIGN_CID_LEN + 2 + 2 + 2 + 2 + 32 + 8 + 1 + 4 + 4 + 1
+            + 8 + 8 + 4 + 4 + 8 + 4 + ANALYTICS_BITMAP_BYTES + 1 + 4 + (1 + 33 + 8) * MAX_PAYMENT_OPTIONS
+            + 1 + 8 + 1 + 8 + 1 + 1 + 1 + 8 + 32 + 8 + 1 + 64 + 32 + 4 + 8 + 8 + 3 + 1 + 1 + 8
+            + 4 + (4 + MAX_TIER_NAME_LEN + 8 + 4 + 4 + 2) * MAX_TIERS + 1 + 9 + 9 + 2 + 8 + 1 + 4
+            + 4 + MAX_METADATA_URI_LEN + 4 + 32 + 8 + 8 + 8,
+        seeds = [b"event", organizer.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub event: Account<'info, Event>,
+
+    /// Supplies the platform's price ceiling.
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Preset resale split; its values are copied onto the event.
+    #[account(seeds = [b"split_template", split_template.name.as_bytes()], bump = split_template.bump)]
+    pub split_template: Option<Account<'info, SplitTemplate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAutoSupply<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct JoinWaitlist<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + 32 + 32 + 4 + 8 + 1,
+        seeds = [b"waitlist", event.key().as_ref(), wallet.key().as_ref()],
+        bump,
+    )]
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct BuyTicket<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, address = event.organizer)]
+    pub organizer: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    /// CHECK: PDA used as mint authority for ticket mints.
+    #[account(
+        seeds = [b"ticket_authority", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump
+    )]
+    pub ticket_authority: AccountInfo<'info>,
+
+    /// CHECK: `["ticket_mint", event, ticket_index]` PDA; created in the handler unless
+    /// pre_allocate_tickets did. Not checked as a mint, since a replay passes it after
+    /// its mint authority was revoked.
+    #[account(
+        mut,
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump
+    )]
+    pub ticket_mint: AccountInfo<'info>,
+
+    /// CHECK: Buyer's ticket ATA; created in the handler.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &buyer.key(),
+            &ticket_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub buyer_token_account: AccountInfo<'info>,
+
+    /// Receives the sales tax share. Required when event.tax_bps > 0.
+    #[account(mut, address = event.tax_wallet @ ErrorCode::InvalidTaxWallet)]
+    pub tax_wallet: Option<SystemAccount<'info>>,
+
+    /// Receives the charity donation share. Required when event.donation_bps > 0.
+    #[account(mut, address = event.donation_wallet @ ErrorCode::InvalidDonationWallet)]
+    pub donation_wallet: Option<SystemAccount<'info>>,
+
+    /// Holds proceeds during refund windows and vesting. Required when proceeds_escrowed(event).
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Option<Account<'info, EventVault>>,
+
+    /// CHECK: Instructions sysvar. Required to buy a verified-only concession
+    /// or when event.requires_attestation is set.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    /// Names the purchase attestor. Required when event.requires_attestation is set.
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Option<Box<Account<'info, PlatformConfig>>>,
+
+    /// Fan token accounts. Required when event.fan_token_mint is set.
+    #[account(mut)]
+    pub fan_token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// CHECK: PDA used as mint authority for the event's fan token.
+    #[account(seeds = [b"fan_token_authority", event.key().as_ref()], bump)]
+    pub fan_token_authority: Option<AccountInfo<'info>>,
+
+    #[account(mut, token::authority = buyer)]
+    pub buyer_fan_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Token payment accounts. Required when paying with an accepted_payments entry
+    /// that has a mint; the destination belongs to the organizer, or to the vault
+    /// while proceeds are escrowed.
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut, token::authority = buyer)]
+    pub buyer_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub payment_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"receipt", buyer.key().as_ref(), ticket_mint.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 1,
+        seeds = [b"idem", buyer.key().as_ref(), event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump
+    )]
+    pub idempotency_key: Account<'info, PurchaseIdempotencyKey>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint; created by CPI in the handler.
+    /// Required when the event has a metadata_uri or design CID, and with the fields
+    /// below and the instructions sysvar when event.use_pnft.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Master edition PDA for ticket_mint, created by Token Metadata.
+    #[account(mut)]
+    pub ticket_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for buyer_token_account, created by Token Metadata.
+    #[account(mut)]
+    pub ticket_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program. Required when the event has a metadata_uri or design CID.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 1 + 8 + 1,
+        seeds = [b"rate_limit", buyer.key().as_ref()],
+        bump
+    )]
+    pub rate_limit: Account<'info, BuyerRateLimit>,
+
+    /// Tickets this wallet bought for the event. Required when event.max_per_wallet > 0.
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 32 + 2 + 1,
+        seeds = [b"buyer", event.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_record: Option<Box<Account<'info, BuyerRecord>>>,
+
+    /// Holds attendance deposits. Required when event.deposit_lamports > 0.
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Option<Box<Account<'info, DepositEscrow>>>,
+
+    /// CHECK: Proof-of-personhood credential (format per platform_config.personhood_provider).
+    /// Required when event.one_per_person.
+    pub personhood_credential: Option<AccountInfo<'info>>,
+
+    /// CHECK: `["person", event, person_id]` PDA; derived from the credential and
+    /// created in the handler. Required when event.one_per_person.
+    #[account(mut)]
+    pub person_purchase: Option<AccountInfo<'info>>,
+
+    /// CHECK: `["identity", event, commitment]` PDA; created in the handler. Required
+    /// when event.identity_required.
+    #[account(mut)]
+    pub identity_purchase: Option<AccountInfo<'info>>,
+
+    /// CHECK: Organizer ban PDA for the buyer; must not exist.
+    #[account(
+        seeds = [b"org_ban", event.organizer.as_ref(), buyer.key().as_ref()],
+        bump,
+        constraint = organizer_ban.data_is_empty() @ ErrorCode::BannedFromOrganizerEvents,
+    )]
+    pub organizer_ban: AccountInfo<'info>,
+
+    /// CHECK: Custom name PDA for this index; consumed and closed to the organizer
+    /// when it exists.
+    #[account(
+        mut,
+        seeds = [b"name_override", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump
+    )]
+    pub name_override: AccountInfo<'info>,
+
+    /// Required by buy_accessible_ticket; ignored by the other purchase paths.
+    #[account(
+        mut,
+        seeds = [b"accessible_approval", event.key().as_ref(), buyer.key().as_ref()],
+        bump = accessible_approval.bump,
+    )]
+    pub accessible_approval: Option<Box<Account<'info, AccessibleApproval>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct ListForResale<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + 32 + 32 + 32 + 8 + 1 + 8 + 8 + 1 + 8 + 32 + 1 + 1,
+        seeds = [b"listing", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    // A delegate from another dapp could pull the ticket out from under the listing
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+        constraint = seller_token_account.delegate.is_none() @ ErrorCode::DelegateMustBeRevoked,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        token::mint = ticket_mint,
+        token::authority = listing,
+        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Pyth SOL/USD PriceUpdateV2; parsed in the handler. Required by
+    /// list_for_resale_usd.
+    pub price_feed: Option<AccountInfo<'info>>,
+
+    /// Cheapest-asks cache; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"askbook", event.key().as_ref()], bump = ask_book.bump)]
+    pub ask_book: Option<Box<Account<'info, AskBook>>>,
+
+    /// Seller's primary purchase receipt. Required when event.require_original_buyer_only.
+    #[account(
+        seeds = [b"receipt", seller.key().as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+    )]
+    pub receipt: Option<Box<Account<'info, Receipt>>>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint. Required, with the fields below,
+    /// when event.use_pnft.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Master edition PDA for ticket_mint.
+    pub ticket_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for seller_token_account.
+    #[account(mut)]
+    pub seller_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: Instructions sysvar, which Token Metadata reads.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it. Required when
+    /// event.resale_blackout_secs > 0 or the event has more than one tier.
+    #[account(
+        seeds = [b"receipt", purchase_receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = purchase_receipt.bump,
+        constraint = purchase_receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub purchase_receipt: Option<Box<Account<'info, Receipt>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyResale<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Seller receives the `seller_bps` share from resale_split. Validated by
+    /// listing.seller constraint.
+    #[account(mut, constraint = seller.key() == listing.seller @ ErrorCode::InvalidSeller)]
+    pub seller: AccountInfo<'info>,
+
+    /// CHECK: Organizer (artist) receives the `artist_bps` share from resale_split.
+    /// Validated by event.organizer.
+    #[account(mut, constraint = organizer.key() == event.organizer @ ErrorCode::InvalidOrganizer)]
+    pub organizer: AccountInfo<'info>,
+
+    /// CHECK: Platform wallet; receives the platform share.
+    #[account(mut, address = PLATFORM_WALLET @ ErrorCode::InvalidPlatformWallet)]
+    pub platform: AccountInfo<'info>,
+
+    /// Counts the platform share.
+    #[account(mut, seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// Today's fee bucket; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"fee_day".as_ref(), &fee_day.day.to_le_bytes()], bump = fee_day.bump)]
+    pub fee_day: Option<Box<Account<'info, FeeDay>>>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    /// Holds artist shares while event.royalty_escrow_mode is set.
+    #[account(mut, seeds = [b"royalty_escrow", event.key().as_ref()], bump = royalty_escrow.bump)]
+    pub royalty_escrow: Option<Box<Account<'info, RoyaltyEscrow>>>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", ticket_mint.key().as_ref()],
+        bump = listing.bump,
+        constraint = listing.event == event.key(),
+        constraint = listing.ticket_mint == ticket_mint.key(),
+        close = seller,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    /// CHECK: Pyth SOL/USD PriceUpdateV2 named by the listing; parsed in the handler.
+    /// Required for USD listings.
+    #[account(address = listing.price_feed @ ErrorCode::InvalidPriceFeed)]
+    pub price_feed: Option<AccountInfo<'info>>,
+
+    /// Cheapest-asks cache; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"askbook", event.key().as_ref()], bump = ask_book.bump)]
+    pub ask_book: Option<Box<Account<'info, AskBook>>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it. Required when the
+    /// event takes attendance deposits.
+    #[account(
+        seeds = [b"receipt", deposit_receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = deposit_receipt.bump,
+    )]
+    pub deposit_receipt: Option<Box<Account<'info, Receipt>>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = listing,
+        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Buyer's associated token account for the ticket mint; created in the
+    /// handler once the buyer's funds are checked.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &buyer.key(),
+            &ticket_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub buyer_token_account: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 8 * PRICE_HISTORY_LEN + 4 + 1,
+        seeds = [b"ticket_stats", event.key().as_ref()],
+        bump,
+    )]
+    pub ticket_stats: Box<Account<'info, TicketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"floor_monitor", event.key().as_ref()],
+        bump,
+    )]
+    pub floor_monitor: Box<Account<'info, FloorMonitor>>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint. Required, with the fields below,
+    /// when event.use_pnft.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Master edition PDA for ticket_mint.
+    pub ticket_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: Seller's associated token account, where a pNFT ticket stays locked.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &seller.key(),
+            &ticket_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub seller_token_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for the seller's ticket account.
+    #[account(mut)]
+    pub seller_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for escrow_token_account.
+    #[account(mut)]
+    pub escrow_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for buyer_token_account.
+    #[account(mut)]
+    pub buyer_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: Instructions sysvar, which Token Metadata reads.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", ticket_mint.key().as_ref()],
+        bump = listing.bump,
+        constraint = listing.seller == seller.key() @ ErrorCode::InvalidSeller,
+        close = seller,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    /// CHECK: Seller's associated token account for the ticket mint. Created in the
+    /// handler if missing, then checked for owner, mint and delegate.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &seller.key(),
+            &ticket_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub seller_token_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = listing,
+        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint. Required, with the fields below,
+    /// when event.use_pnft.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Master edition PDA for ticket_mint.
+    pub ticket_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for seller_token_account.
+    #[account(mut)]
+    pub seller_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: Instructions sysvar, which Token Metadata reads.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    /// Cheapest-asks cache; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"askbook", listing.event.as_ref()], bump = ask_book.bump)]
+    pub ask_book: Option<Box<Account<'info, AskBook>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEvent<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    // Listings, receipts, tickets and passes all point at the event. A cancelled event
+    // can close once every ticket and pass has been refunded.
+    #[account(
+        mut,
+        close = organizer,
+        constraint = event.pass_refunds_owed == 0
+            && (event.sold == 0 || (event.cancelled && event.refunds_pending == 0))
+            @ ErrorCode::EventHasSales,
+    )]
+    pub event: Account<'info, Event>,
+
+    /// Closed with the event, returning anything left in it to the organizer.
+    #[account(mut, close = organizer, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Option<Account<'info, EventVault>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct CheckIn<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    /// CHECK: Organizer ban PDA for the holder; must not exist.
+    #[account(
+        seeds = [b"org_ban", event.organizer.as_ref(), holder.key().as_ref()],
+        bump,
+        constraint = organizer_ban.data_is_empty() @ ErrorCode::BannedFromOrganizerEvents,
+    )]
+    pub organizer_ban: AccountInfo<'info>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Primary purchase receipt for this ticket; surfaces its concession category.
+    #[account(
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+    )]
+    pub receipt: Option<Box<Account<'info, Receipt>>>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 32 + 32 + 4 + 4 + 8 + 1,
+        seeds = [b"attendance", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub attendance_record: Box<Account<'info, AttendanceRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRaffle<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 1 + 8 + 1 + 32 + 4 + 4 * MAX_RAFFLE_WINNERS as usize + 2 + 1 + 32 + 8 + 4,
+        seeds = [b"raffle", event.key().as_ref()],
+        bump,
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LockRaffle<'info> {
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"raffle", event.key().as_ref()],
+        bump = raffle.bump,
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: SlotHashes sysvar, read raw to tell whether the lock slot has aged out.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DrawRaffle<'info> {
+    /// The organizer when the raffle has a commitment; anyone otherwise.
+    pub caller: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"raffle", event.key().as_ref()],
+        bump = raffle.bump,
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: SlotHashes sysvar, read raw for the lock slot's hash.
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRaffleWin<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"raffle", attendance_record.event.as_ref()],
+        bump = raffle.bump,
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    #[account(
+        seeds = [b"attendance", attendance_record.ticket_mint.as_ref()],
+        bump = attendance_record.bump,
+        constraint = attendance_record.holder == holder.key() @ ErrorCode::NotTicketHolder,
+    )]
+    pub attendance_record: Account<'info, AttendanceRecord>,
+}
+
+#[derive(Accounts)]
+pub struct InitTicketStats<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 8 * PRICE_HISTORY_LEN + 4 + 1,
+        seeds = [b"ticket_stats", event.key().as_ref()],
+        bump,
+    )]
+    pub ticket_stats: Account<'info, TicketStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetFairValueEstimate<'info> {
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        seeds = [b"ticket_stats", listing.event.as_ref()],
+        bump = ticket_stats.bump,
+    )]
+    pub ticket_stats: Account<'info, TicketStats>,
+}
+
+#[derive(Accounts)]
+pub struct SetReleaseSchedule<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxListingMultiple<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListingPrice<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(address = listing.event)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        constraint = listing.seller == seller.key() @ ErrorCode::InvalidSeller,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Cheapest-asks cache; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"askbook", listing.event.as_ref()], bump = ask_book.bump)]
+    pub ask_book: Option<Box<Account<'info, AskBook>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct CreateHold<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 32 + 4 + 8 + 8 + 1,
+        seeds = [b"hold", event.key().as_ref(), beneficiary.as_ref()],
+        bump,
+    )]
+    pub hold: Account<'info, Hold>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimHold<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, address = event.organizer)]
+    pub organizer: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"hold", event.key().as_ref(), beneficiary.key().as_ref()],
+        bump = hold.bump,
+    )]
+    pub hold: Box<Account<'info, Hold>>,
+
+    /// CHECK: PDA used as mint authority for ticket mints.
+    #[account(
+        seeds = [b"ticket_authority", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub ticket_authority: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        mint::decimals = 0,
+        mint::authority = ticket_authority.key(),
+        seeds = [b"ticket_mint", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    pub beneficiary_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holds proceeds during refund windows and vesting. Required when proceeds_escrowed(event).
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Option<Box<Account<'info, EventVault>>>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"receipt", beneficiary.key().as_ref(), ticket_mint.key().as_ref()],
+        bump
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    /// CHECK: `["identity", event, commitment]` PDA; created in the handler. Required
+    /// when event.identity_required.
+    #[account(mut)]
+    pub identity_purchase: Option<AccountInfo<'info>>,
+
+    /// CHECK: Custom name PDA for this index; consumed and closed to the organizer
+    /// when it exists.
+    #[account(
+        mut,
+        seeds = [b"name_override", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub name_override: AccountInfo<'info>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint; created by CPI in the handler.
+    /// Required when the event has a metadata_uri or design CID.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program. Required when the event has a metadata_uri or design CID.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseExpiredHold<'info> {
+    /// CHECK: Receives the hold account's rent. Validated against event.organizer.
+    #[account(mut, address = event.organizer)]
+    pub organizer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"hold", event.key().as_ref(), hold.beneficiary.as_ref()],
+        bump = hold.bump,
+        close = organizer,
+    )]
+    pub hold: Account<'info, Hold>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct ClaimNoShow<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Attendance PDA for this mint; must not exist (ticket never checked in).
+    #[account(seeds = [b"attendance", ticket_mint.key().as_ref()], bump)]
+    pub attendance_record: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"no_show", ticket_mint.key().as_ref(), holder.key().as_ref()],
+        bump,
+    )]
+    pub no_show: Box<Account<'info, NoShowRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTaxConfig<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct InitPlatformConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PLATFORM_CONFIG_LEN,
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Ticketchain>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeOverride<'info> {
+    pub organizer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePlatformConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: PlatformConfig in either layout; the handler checks its discriminator and
+    /// authority before resizing.
+    #[account(mut, owner = crate::ID, seeds = [b"platform_config"], bump)]
+    pub platform_config: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOrganizerVerification<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct CreateClaimLink<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"claim_link", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub claim_link: Box<Account<'info, ClaimLink>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = sender,
+        token::mint = ticket_mint,
+        token::authority = claim_link,
+        seeds = [b"claim_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimViaLink<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    /// CHECK: Original sender; receives escrow and link rent. Validated by claim_link.sender.
+    #[account(mut, address = claim_link.sender)]
+    pub sender: AccountInfo<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"claim_link", ticket_mint.key().as_ref()],
+        bump = claim_link.bump,
+        close = sender,
+    )]
+    pub claim_link: Box<Account<'info, ClaimLink>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = claim_link,
+        seeds = [b"claim_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = claimer,
+        associated_token::token_program = token_program,
+    )]
+    pub claimer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Instructions sysvar, read for the claim key's ed25519 signature.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelClaimLink<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: Original sender; receives the ticket and rent. Validated by claim_link.sender.
+    #[account(mut, address = claim_link.sender)]
+    pub sender: AccountInfo<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"claim_link", ticket_mint.key().as_ref()],
+        bump = claim_link.bump,
+        close = sender,
+    )]
+    pub claim_link: Box<Account<'info, ClaimLink>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = claim_link,
+        seeds = [b"claim_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 2 * (4 + 84 * MAX_BOOK_ORDERS) + 1,
+        seeds = [b"orderbook", event.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBuyOrder<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", order_book.event.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(price: u64, ticket_index: u32)]
+pub struct PlaceSellOrder<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", event.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    // A delegate from another dapp could pull the ticket out from under the listing
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+        constraint = seller_token_account.amount == 1 @ ErrorCode::NotTicketHolder,
+        constraint = seller_token_account.delegate.is_none() @ ErrorCode::DelegateMustBeRevoked,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        token::mint = ticket_mint,
+        token::authority = order_book,
+        seeds = [b"book_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Seller's primary purchase receipt. Required when event.require_original_buyer_only.
+    #[account(
+        seeds = [b"receipt", seller.key().as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+    )]
+    pub receipt: Option<Box<Account<'info, Receipt>>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it. Required when
+    /// event.resale_blackout_secs > 0 or the event has more than one tier.
+    #[account(
+        seeds = [b"receipt", purchase_receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = purchase_receipt.bump,
+        constraint = purchase_receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub purchase_receipt: Option<Box<Account<'info, Receipt>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct MatchOrders<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Best bidder; receives the ticket and any price improvement. Validated in handler.
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+
+    /// CHECK: Best ask's seller. Validated in handler.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+
+    /// CHECK: Organizer (artist) share. Validated by event.organizer.
+    #[account(mut, constraint = organizer.key() == event.organizer @ ErrorCode::InvalidOrganizer)]
+    pub organizer: AccountInfo<'info>,
+
+    /// CHECK: Platform wallet; receives the platform share.
+    #[account(mut, address = PLATFORM_WALLET @ ErrorCode::InvalidPlatformWallet)]
+    pub platform: AccountInfo<'info>,
+
+    /// Counts the platform share.
+    #[account(mut, seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// Today's fee bucket; updated when passed, never blocks the instruction.
+    #[account(mut, seeds = [b"fee_day".as_ref(), &fee_day.day.to_le_bytes()], bump = fee_day.bump)]
+    pub fee_day: Option<Box<Account<'info, FeeDay>>>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    /// Holds artist shares while event.royalty_escrow_mode is set.
+    #[account(mut, seeds = [b"royalty_escrow", event.key().as_ref()], bump = royalty_escrow.bump)]
+    pub royalty_escrow: Option<Box<Account<'info, RoyaltyEscrow>>>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", event.key().as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = order_book,
+        seeds = [b"book_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Seller's primary purchase receipt. Required when event.require_original_buyer_only.
+    #[account(
+        seeds = [b"receipt", seller.key().as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+    )]
+    pub receipt: Option<Box<Account<'info, Receipt>>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it. Required when the
+    /// event takes attendance deposits or has more than one tier.
+    #[account(
+        seeds = [b"receipt", deposit_receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = deposit_receipt.bump,
+    )]
+    pub deposit_receipt: Option<Box<Account<'info, Receipt>>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 32 + 8 * PRICE_HISTORY_LEN + 4 + 1,
+        seeds = [b"ticket_stats", event.key().as_ref()],
+        bump,
+    )]
+    pub ticket_stats: Box<Account<'info, TicketStats>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"floor_monitor", event.key().as_ref()],
+        bump,
+    )]
+    pub floor_monitor: Box<Account<'info, FloorMonitor>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBuyOrder<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", order_book.event.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpiredOffer<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Receives the refund. Validated against the order's wallet.
+    #[account(mut)]
+    pub bidder: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", order_book.event.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSellOrder<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: The ask's seller; receives the ticket and escrow rent. Validated in handler.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"orderbook", order_book.event.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, ResaleOrderBook>>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; a flagged ticket's ask can be cancelled by anyone.
+    #[account(seeds = [b"ticket_flag", ticket_mint.key().as_ref()], bump)]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = order_book,
+        seeds = [b"book_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetOriginalBuyerOnly<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetResaleBlackout<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetIdentityMode<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSaleWindow<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct OfferTransfer<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"transfer_offer", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub transfer_offer: Box<Account<'info, TransferOffer>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = sender,
+        token::mint = ticket_mint,
+        token::authority = transfer_offer,
+        seeds = [b"transfer_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pass only when attaching a message. One note per (ticket, recipient).
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 32 + 4 + MAX_GIFT_MESSAGE_LEN + 1,
+        seeds = [b"gift_note", ticket_mint.key().as_ref(), recipient.as_ref()],
+        bump,
+    )]
+    pub gift_note: Option<Box<Account<'info, GiftNote>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTransfer<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    /// CHECK: Original sender; receives escrow and offer rent. Validated by transfer_offer.sender.
+    #[account(mut, address = transfer_offer.sender)]
+    pub sender: AccountInfo<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"transfer_offer", ticket_mint.key().as_ref()],
+        bump = transfer_offer.bump,
+        constraint = transfer_offer.recipient == recipient.key() @ ErrorCode::InvalidRecipient,
+        close = sender,
+    )]
+    pub transfer_offer: Box<Account<'info, TransferOffer>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = transfer_offer,
+        seeds = [b"transfer_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTransfer<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: Original sender; receives the ticket and rent. Validated by transfer_offer.sender.
+    #[account(mut, address = transfer_offer.sender)]
+    pub sender: AccountInfo<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"transfer_offer", ticket_mint.key().as_ref()],
+        bump = transfer_offer.bump,
+        close = sender,
+    )]
+    pub transfer_offer: Box<Account<'info, TransferOffer>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = transfer_offer,
+        seeds = [b"transfer_escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGiftNote<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: Rent payer; receives the note's rent. Validated by gift_note.sender.
+    #[account(mut, address = gift_note.sender)]
+    pub sender: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"gift_note", gift_note.ticket_mint.as_ref(), gift_note.recipient.as_ref()],
+        bump = gift_note.bump,
+        close = sender,
+    )]
+    pub gift_note: Account<'info, GiftNote>,
+}
+
+#[derive(Accounts)]
+pub struct SetGracePeriod<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1,
+        seeds = [b"vault", event.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestGraceRefund<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt", buyer.key().as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+        constraint = buyer_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token refund accounts. Required when the receipt's payment option has a mint.
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut, token::authority = buyer)]
+    pub buyer_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Returns the attendance deposit. Required when receipt.deposit_lamports > 0.
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Option<Box<Account<'info, DepositEscrow>>>,
+
+    /// Closed so the identity can buy again. Required when the receipt has an identity
+    /// commitment.
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"identity", event.key().as_ref(), receipt.identity_commitment.as_ref()],
+        bump = identity_purchase.bump,
+    )]
+    pub identity_purchase: Option<Box<Account<'info, IdentityPurchase>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProceeds<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, EventVault>,
+}
+
+#[derive(Accounts)]
+pub struct SetVestingSchedule<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1,
+        seeds = [b"vault", event.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CleanupAccounts<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: PDA that owners set as close authority to approve cleanup.
+    #[account(seeds = [b"cleanup_authority"], bump)]
+    pub cleanup_authority: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFanToken<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    pub fan_token_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as mint authority for the event's fan token.
+    #[account(seeds = [b"fan_token_authority", event.key().as_ref()], bump)]
+    pub fan_token_authority: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1 + 8 + 1,
+        seeds = [b"fan_token", event.key().as_ref()],
+        bump,
+    )]
+    pub fan_token_config: Account<'info, FanTokenConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePerk<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 4 + MAX_PERK_URI_LEN + 8 + 4 + 1 + 4,
+        seeds = [b"perk", event.key().as_ref(), &event.perks_created.to_le_bytes()],
+        bump,
+    )]
+    pub perk: Account<'info, Perk>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct ClaimPerk<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"perk", event.key().as_ref(), &perk.index.to_le_bytes()],
+        bump = perk.bump,
+    )]
+    pub perk: Box<Account<'info, Perk>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 1,
+        seeds = [b"perk_claimed", perk.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub perk_claimed: Box<Account<'info, PerkClaimed>>,
+
+    #[account(
+        init,
+        payer = holder,
+        mint::decimals = 0,
+        mint::authority = perk,
+        seeds = [b"perk_mint", perk.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub perk_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = perk_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_perk_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePerk<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"perk", event.key().as_ref(), &perk.index.to_le_bytes()],
+        bump = perk.bump,
+        close = organizer,
+    )]
+    pub perk: Account<'info, Perk>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxTicketPrice<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinPriceUpdateInterval<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoyaltyReleaseDelay<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestorKey<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetMessagingProgram<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPersonhoodProvider<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CreatePassBundle<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + 32 + 8 + 4 + 32 * MAX_BUNDLE_EVENTS + 4 + 32 * MAX_BUNDLE_EVENTS
+            + 4 + 2 * MAX_BUNDLE_EVENTS + 8 + 4 + 4 + 1 + 8 + 4 + 4 * MAX_BUNDLE_EVENTS
+            + 4 + 4 * MAX_BUNDLE_EVENTS,
+        seeds = [b"pass_bundle", creator.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyPass<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pass_bundle", bundle.creator.as_ref(), &bundle.nonce.to_le_bytes()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        mint::decimals = 0,
+        mint::authority = bundle,
+        seeds = [b"pass_mint", bundle.key().as_ref(), &bundle.sold.to_le_bytes()],
+        bump,
+    )]
+    pub pass_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = pass_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_pass_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pass_index: u32)]
+pub struct RedeemBundleTicket<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut, address = event.organizer)]
+    pub organizer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pass_bundle", bundle.creator.as_ref(), &bundle.nonce.to_le_bytes()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    #[account(
+        seeds = [b"pass_mint", bundle.key().as_ref(), &pass_index.to_le_bytes()],
+        bump,
+    )]
+    pub pass_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = pass_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_pass_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_pass_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 1,
+        seeds = [b"pass_redemption", pass_mint.key().as_ref(), event.key().as_ref()],
+        bump,
+    )]
+    pub redemption: Box<Account<'info, PassRedemption>>,
+
+    /// CHECK: PDA used as mint authority for ticket mints.
+    #[account(
+        seeds = [b"ticket_authority", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub ticket_authority: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        mint::decimals = 0,
+        mint::authority = ticket_authority.key(),
+        seeds = [b"ticket_mint", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"receipt", holder.key().as_ref(), ticket_mint.key().as_ref()],
+        bump
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    /// CHECK: `["identity", event, commitment]` PDA; created in the handler. Required
+    /// when event.identity_required.
+    #[account(mut)]
+    pub identity_purchase: Option<AccountInfo<'info>>,
+
+    /// CHECK: Custom name PDA for this index; consumed and closed to the organizer
+    /// when it exists.
+    #[account(
+        mut,
+        seeds = [b"name_override", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump
+    )]
+    pub name_override: AccountInfo<'info>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint; created by CPI in the handler.
+    /// Required when the event has a metadata_uri or design CID.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program. Required when the event has a metadata_uri or design CID.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pass_index: u32)]
+pub struct ClaimPassRefund<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"pass_bundle", bundle.creator.as_ref(), &bundle.nonce.to_le_bytes()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    #[account(
+        seeds = [b"pass_mint", bundle.key().as_ref(), &pass_index.to_le_bytes()],
+        bump,
+    )]
+    pub pass_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = pass_mint,
+        associated_token::authority = holder,
+        constraint = holder_pass_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_pass_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 1,
+        seeds = [b"pass_refund", pass_mint.key().as_ref(), event.key().as_ref()],
+        bump,
+    )]
+    pub pass_refund: Account<'info, PassRefund>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePassSeats<'info> {
+    #[account(
+        mut,
+        seeds = [b"pass_bundle", bundle.creator.as_ref(), &bundle.nonce.to_le_bytes()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyEscrowHealth<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        token::authority = listing,
+        seeds = [b"escrow", listing.ticket_mint.as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveEscrowAnomaly<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// CHECK: Receives escrowed tokens and rent. Validated against listing.seller.
+    #[account(mut, address = listing.seller @ ErrorCode::InvalidSeller)]
+    pub seller: AccountInfo<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", ticket_mint.key().as_ref()],
+        bump = listing.bump,
+        close = seller,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = listing,
+        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSocialLink<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateEntryRights<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"entry_delegate", ticket_mint.key().as_ref(), holder.key().as_ref()],
+        bump,
+    )]
+    pub entry_delegate: Account<'info, DelegateEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEntryDelegation<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"entry_delegate", entry_delegate.ticket_mint.as_ref(), holder.key().as_ref()],
+        bump = entry_delegate.bump,
+        close = holder,
+    )]
+    pub entry_delegate: Account<'info, DelegateEntry>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct CheckInDelegate<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub delegate: Signer<'info>,
+
+    /// CHECK: Ticket owner; ownership is checked via holder_token_account.
+    pub holder: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
+    )]
+    pub ticket_flag: AccountInfo<'info>,
+
+    /// CHECK: Organizer ban PDA for the holder; must not exist.
+    #[account(
+        seeds = [b"org_ban", event.organizer.as_ref(), holder.key().as_ref()],
+        bump,
+        constraint = organizer_ban.data_is_empty() @ ErrorCode::BannedFromOrganizerEvents,
+    )]
+    pub organizer_ban: AccountInfo<'info>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"entry_delegate", ticket_mint.key().as_ref(), holder.key().as_ref()],
+        bump = entry_delegate.bump,
+        has_one = delegate @ ErrorCode::Unauthorized,
+    )]
+    pub entry_delegate: Box<Account<'info, DelegateEntry>>,
+
+    /// Primary purchase receipt for this ticket; surfaces its concession category.
+    #[account(
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+    )]
+    pub receipt: Option<Box<Account<'info, Receipt>>>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 32 + 32 + 4 + 4 + 8 + 1,
+        seeds = [b"attendance", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub attendance_record: Box<Account<'info, AttendanceRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetConcessions<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTicketDesign<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateSplitTemplate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 4 + MAX_TEMPLATE_NAME_LEN + 2 + 2 + 2 + 1,
+        seeds = [b"split_template", name.as_bytes()],
+        bump,
+    )]
+    pub split_template: Account<'info, SplitTemplate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSplitTemplate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"split_template", split_template.name.as_bytes()],
+        bump = split_template.bump,
+    )]
+    pub split_template: Account<'info, SplitTemplate>,
+}
+
+#[derive(Accounts)]
+pub struct PreAllocateTickets<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    /// Reused when the batch is extended before the next sale.
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 4 + 1,
+        seeds = [b"allocation", event.key().as_ref(), &event.sold.to_le_bytes()],
+        bump,
+        constraint = allocation.event == Pubkey::default() || allocation.event == event.key()
+            @ ErrorCode::InvalidTicketMint,
+    )]
+    pub allocation: Account<'info, TicketAllocation>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDonationConfig<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64, ticket_index: u32)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = proposer,
+        constraint = proposer_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub proposer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + 32 + 8 + 1 + 64 + 4 + 4 + 1 + 1 + 8,
+        seeds = [b"vote", event.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Box<Account<'info, TicketholderVote>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64, ticket_index: u32)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", event.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, TicketholderVote>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = voter,
+        constraint = voter_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub voter_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"vote_record", proposal.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Box<Account<'info, VoteRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApplyGovernanceChange<'info> {
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", event.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, TicketholderVote>,
+}
+
+#[derive(Accounts)]
+pub struct SweepExpiredHolds<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Receives swept hold rent. Validated against event.organizer.
+    #[account(mut, address = event.organizer)]
+    pub organizer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct PendingWorkView<'info> {
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Option<Account<'info, PlatformConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAccessibleSeating<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetAccessibilityVerifier<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer: Pubkey)]
+pub struct ApproveAccessibleBuyer<'info> {
+    #[account(mut)]
+    pub approver: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = approver,
+        space = 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"accessible_approval", event.key().as_ref(), buyer.as_ref()],
+        bump,
+    )]
+    pub approval: Account<'info, AccessibleApproval>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRequiresAttestation<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetPnftMode<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetListingVisibility<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(address = listing.event)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"listing", listing.ticket_mint.as_ref()], bump = listing.bump)]
+    pub listing: Box<Account<'info, Listing>>,
+}
+
+#[derive(Accounts)]
+pub struct EventStatsView<'info> {
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetAcceptedPayments<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenOnly<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokenProceeds<'info> {
+    pub organizer: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::mint = payment_mint, token::authority = vault)]
+    pub vault_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub organizer_payment_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MonitorEventHealth<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    /// Required while proceeds are escrowed.
+    #[account(seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Option<Box<Account<'info, EventVault>>>,
+
+    /// The event's reward pool; without it the check runs unrewarded.
+    #[account(mut, seeds = [b"monitor_pool", event.key().as_ref()], bump = reward_pool.bump)]
+    pub reward_pool: Option<Box<Account<'info, MonitorRewardPool>>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [b"monitor_claims", event.key().as_ref(), cranker.key().as_ref()],
+        bump,
+    )]
+    pub monitor_claims: Box<Account<'info, MonitorClaims>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResetHealthCheck<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct FundMonitorRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + 32 + 8 + 1,
+        seeds = [b"monitor_pool", event.key().as_ref()],
+        bump,
+    )]
+    pub reward_pool: Account<'info, MonitorRewardPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetResaleBlocking<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetResaleFloor<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct AuditRoyalties<'info> {
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = auditor,
+        space = 8 + 32 + 32 + 8 + 32 + 1,
+        seeds = [b"revenue_audit", event.key().as_ref(), auditor.key().as_ref()],
+        bump,
+    )]
+    pub audit: Account<'info, RevenueAudit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoyaltyEscrowMode<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"royalty_escrow", event.key().as_ref()],
+        bump,
+    )]
+    pub royalty_escrow: Account<'info, RoyaltyEscrow>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEscrowedRoyalties<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(mut, seeds = [b"royalty_escrow", event.key().as_ref()], bump = royalty_escrow.bump)]
+    pub royalty_escrow: Account<'info, RoyaltyEscrow>,
+
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct FlagTicket<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 32 + 1 + 8 + 1,
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub ticket_flag: Box<Account<'info, TicketFlag>>,
+
+    /// CHECK: Attendance PDA for this mint; must not exist (ticket never checked in).
+    #[account(
+        seeds = [b"attendance", ticket_mint.key().as_ref()],
+        bump,
+        constraint = attendance_record.data_is_empty() @ ErrorCode::TicketCheckedIn,
+    )]
+    pub attendance_record: AccountInfo<'info>,
+
+    /// CHECK: Listing PDA for this mint; when it holds an open listing, the escrow, seller
+    /// and seller token accounts must be passed so it can be cancelled.
+    #[account(mut, seeds = [b"listing", ticket_mint.key().as_ref()], bump)]
+    pub listing: AccountInfo<'info>,
+
+    #[account(mut, seeds = [b"escrow", ticket_mint.key().as_ref()], bump)]
+    pub escrow_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Listing seller; validated against listing.seller.
+    #[account(mut)]
+    pub seller: Option<AccountInfo<'info>>,
+
+    #[account(mut, token::mint = ticket_mint)]
+    pub seller_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnflagTicket<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket_flag", ticket_flag.ticket_mint.as_ref()],
+        bump = ticket_flag.bump,
+        constraint = ticket_flag.event == event.key() @ ErrorCode::InvalidTicketMint,
+        close = organizer,
+    )]
+    pub ticket_flag: Account<'info, TicketFlag>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct SetTicketName<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 4 + MAX_TICKET_NAME_LEN + 1,
+        seeds = [b"name_override", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub name_override: Account<'info, TicketNameOverride>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct BanFromOrganizerEvents<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + 32 + 32 + 8 + 32 + 1,
+        seeds = [b"org_ban", organizer.key().as_ref(), wallet.as_ref()],
+        bump,
+    )]
+    pub organizer_ban: Account<'info, OrganizerBan>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct LiftOrganizerBan<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = organizer,
+        seeds = [b"org_ban", organizer.key().as_ref(), wallet.as_ref()],
+        bump = organizer_ban.bump,
+    )]
+    pub organizer_ban: Account<'info, OrganizerBan>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct AttestOwnership<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 8 + 1,
+        seeds = [b"attest_nonce", ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub attestation_nonce: Account<'info, AttestationNonce>,
+
+    /// Names the messaging program, if any. Omit only if no platform config exists.
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Option<Box<Account<'info, PlatformConfig>>>,
+
+    /// CHECK: Must equal platform_config.messaging_program; checked in the handler.
+    pub messaging_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: PDA that signs attestations sent to the messaging program.
+    #[account(seeds = [b"ownership_attestor"], bump)]
+    pub ownership_attestor: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTierPerks<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SetOnePerPerson<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSeriesSchedule<'info> {
+    /// Tops up the vaults of its own events when they are postponed.
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"pass_bundle", creator.key().as_ref(), &bundle.nonce.to_le_bytes()],
+        bump = bundle.bump,
+    )]
+    pub bundle: Box<Account<'info, PassBundle>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttendanceDeposit<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1,
+        seeds = [b"deposit_escrow", event.key().as_ref()],
+        bump,
+    )]
+    pub deposit_escrow: Box<Account<'info, DepositEscrow>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimDeposit<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.buyer.as_ref(), receipt.ticket_mint.as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(
+        seeds = [b"attendance", receipt.ticket_mint.as_ref()],
+        bump = attendance_record.bump,
+        has_one = holder @ ErrorCode::NotTicketHolder,
+    )]
+    pub attendance_record: Box<Account<'info, AttendanceRecord>>,
+
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Box<Account<'info, DepositEscrow>>,
+}
+
+#[derive(Accounts)]
+pub struct SweepForfeitedDeposits<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Box<Account<'info, DepositEscrow>>,
+
+    /// CHECK: Receives forfeited deposits; must be event.deposit_forfeit_wallet.
+    #[account(mut, address = event.deposit_forfeit_wallet @ ErrorCode::InvalidDepositConfig)]
+    pub forfeit_wallet: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTicketNote<'info> {
+    pub holder: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SetNotesSurviveTransfer<'info> {
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+}
+
+#[derive(Accounts)]
+pub struct SplitEvent<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut)]
+    pub event_a: Box<Account<'info, Event>>,
+
+    #[account(mut)]
+    pub event_b: Box<Account<'info, Event>>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1,
+        seeds = [b"vault", event.key().as_ref()],
+        bump,
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct MigrateTicket<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Flag PDA for this mint; must not exist (ticket not flagged).
+    #[account(
+        seeds = [b"ticket_flag", ticket_mint.key().as_ref()],
+        bump,
+        constraint = ticket_flag.data_is_empty() @ ErrorCode::TicketFlagged,
     )]
-    pub listing: Box<Account<'info, Listing>>,
+    pub ticket_flag: AccountInfo<'info>,
 
     #[account(
         mut,
-        token::mint = ticket_mint,
-        token::authority = listing,
-        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it.
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    /// The original's vault, holding the split refund reserve.
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    /// Returns the attendance deposit. Required when receipt.deposit_lamports > 0.
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Option<Box<Account<'info, DepositEscrow>>>,
+
+    #[account(mut)]
+    pub target_event: Box<Account<'info, Event>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 1,
+        seeds = [b"vault", target_event.key().as_ref()],
         bump,
     )]
-    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub target_vault: Box<Account<'info, EventVault>>,
+
+    /// CHECK: PDA used as mint authority for the replacement ticket.
+    #[account(
+        seeds = [b"ticket_authority", target_event.key().as_ref(), &target_event.sold.to_le_bytes()],
+        bump
+    )]
+    pub target_ticket_authority: AccountInfo<'info>,
 
     #[account(
         init_if_needed,
-        payer = buyer,
-        associated_token::mint = ticket_mint,
-        associated_token::authority = buyer,
+        payer = holder,
+        mint::decimals = 0,
+        mint::authority = target_ticket_authority.key(),
+        seeds = [b"ticket_mint", target_event.key().as_ref(), &target_event.sold.to_le_bytes()],
+        bump
     )]
-    pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub target_ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = target_ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program
+    )]
+    pub holder_target_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1,
+        seeds = [b"receipt", holder.key().as_ref(), target_ticket_mint.key().as_ref()],
+        bump
+    )]
+    pub target_receipt: Box<Account<'info, Receipt>>,
+
+    /// CHECK: `["identity", target_event, commitment]` PDA; created in the handler.
+    /// Required when target_event.identity_required.
+    #[account(mut)]
+    pub identity_purchase: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata PDA for target_ticket_mint; created by CPI in the handler.
+    /// Required when the event has a metadata_uri or design CID.
+    #[account(mut)]
+    pub target_ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program. Required when the event has a metadata_uri or design CID.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -450,37 +10788,192 @@ pub struct BuyResale<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CancelListing<'info> {
+pub struct ClaimSplitRefund<'info> {
     #[account(mut)]
-    pub seller: Signer<'info>,
+    pub holder: Signer<'info>,
 
-    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
 
+    /// The ticket's primary purchase receipt, whoever bought it.
     #[account(
         mut,
-        seeds = [b"listing", ticket_mint.key().as_ref()],
-        bump = listing.bump,
-        constraint = listing.seller == seller.key() @ ErrorCode::InvalidSeller,
-        close = seller,
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
     )]
-    pub listing: Box<Account<'info, Listing>>,
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(mut)]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
-        init_if_needed,
-        payer = seller,
+        mut,
         associated_token::mint = ticket_mint,
-        associated_token::authority = seller,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
     )]
-    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Returns the attendance deposit. Required when receipt.deposit_lamports > 0.
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Option<Box<Account<'info, DepositEscrow>>>,
 
+    /// Closed so the identity can buy again. Required when the receipt has an identity
+    /// commitment.
     #[account(
         mut,
-        token::mint = ticket_mint,
-        token::authority = listing,
-        seeds = [b"escrow", ticket_mint.key().as_ref()],
+        close = holder,
+        seeds = [b"identity", event.key().as_ref(), receipt.identity_commitment.as_ref()],
+        bump = identity_purchase.bump,
+    )]
+    pub identity_purchase: Option<Box<Account<'info, IdentityPurchase>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitAskBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 4 + 40 * ASK_BOOK_SIZE + 1,
+        seeds = [b"askbook", event.key().as_ref()],
         bump,
     )]
-    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub ask_book: Box<Account<'info, AskBook>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RepairAskBook<'info> {
+    pub event: Account<'info, Event>,
+
+    #[account(mut, seeds = [b"askbook", event.key().as_ref()], bump = ask_book.bump)]
+    pub ask_book: Box<Account<'info, AskBook>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTicketRecord<'info> {
+    /// CHECK: Original buyer; receives the receipt's rent.
+    #[account(mut, address = receipt.buyer)]
+    pub buyer: AccountInfo<'info>,
+
+    /// CHECK: The receipt's event; may already be closed. Deserialized in the handler.
+    #[account(mut, address = receipt.event)]
+    pub event: AccountInfo<'info>,
+
+    #[account(address = receipt.ticket_mint)]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.buyer.as_ref(), receipt.ticket_mint.as_ref()],
+        bump = receipt.bump,
+        close = buyer,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEvent<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    /// Supplies the platform's price ceiling.
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: the event vault, created if needed; required to move the date later.
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump)]
+    pub vault: Option<UncheckedAccount<'info>>,
+
+    /// Required to move the date later.
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBuyerRecord<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer", event.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_record.bump,
+        close = buyer,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTickets<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, address = event.organizer)]
+    pub organizer: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    /// Receives the sales tax share. Required when event.tax_bps > 0.
+    #[account(mut, address = event.tax_wallet @ ErrorCode::InvalidTaxWallet)]
+    pub tax_wallet: Option<SystemAccount<'info>>,
+
+    /// Receives the charity donation share. Required when event.donation_bps > 0.
+    #[account(mut, address = event.donation_wallet @ ErrorCode::InvalidDonationWallet)]
+    pub donation_wallet: Option<SystemAccount<'info>>,
+
+    /// Holds proceeds during refund windows and vesting. Required when proceeds_escrowed(event).
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Option<Box<Account<'info, EventVault>>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 1 + 8 + 1,
+        seeds = [b"rate_limit", buyer.key().as_ref()],
+        bump
+    )]
+    pub rate_limit: Account<'info, BuyerRateLimit>,
+
+    /// Tickets this wallet bought for the event. Required when event.max_per_wallet > 0.
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + 32 + 32 + 2 + 1,
+        seeds = [b"buyer", event.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_record: Option<Box<Account<'info, BuyerRecord>>>,
+
+    /// CHECK: Organizer ban PDA for the buyer; must not exist.
+    #[account(
+        seeds = [b"org_ban", event.organizer.as_ref(), buyer.key().as_ref()],
+        bump,
+        constraint = organizer_ban.data_is_empty() @ ErrorCode::BannedFromOrganizerEvents,
+    )]
+    pub organizer_ban: AccountInfo<'info>,
+
+    /// CHECK: Token Metadata program. Required when the event has a metadata_uri or design CID.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -488,20 +10981,297 @@ pub struct CancelListing<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CloseEvent<'info> {
+pub struct CancelEvent<'info> {
     #[account(mut)]
     pub organizer: Signer<'info>,
 
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + 32 + 1,
+        seeds = [b"vault", event.key().as_ref()],
+        bump,
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut, seeds = [b"vault", event.key().as_ref()], bump = vault.bump)]
+    pub vault: Box<Account<'info, EventVault>>,
+
     #[account(
         mut,
-        constraint = event.organizer == organizer.key() @ ErrorCode::InvalidOrganizer,
-        close = organizer,
+        seeds = [b"ticket_mint", event.key().as_ref(), &ticket_index.to_le_bytes()],
+        bump,
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The ticket's primary purchase receipt, whoever bought it.
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.buyer.as_ref(), ticket_mint.key().as_ref()],
+        bump = receipt.bump,
+        constraint = receipt.event == event.key() @ ErrorCode::InvalidTicketMint,
+    )]
+    pub receipt: Box<Account<'info, Receipt>>,
+
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+        constraint = holder_token_account.amount >= 1 @ ErrorCode::NotTicketHolder,
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Returns the attendance deposit. Required when receipt.deposit_lamports > 0.
+    #[account(mut, seeds = [b"deposit_escrow", event.key().as_ref()], bump = deposit_escrow.bump)]
+    pub deposit_escrow: Option<Box<Account<'info, DepositEscrow>>>,
+
+    /// Closed so the identity is released. Required when the receipt has an identity
+    /// commitment.
+    #[account(
+        mut,
+        close = holder,
+        seeds = [b"identity", event.key().as_ref(), receipt.identity_commitment.as_ref()],
+        bump = identity_purchase.bump,
     )]
+    pub identity_purchase: Option<Box<Account<'info, IdentityPurchase>>>,
+
+    /// CHECK: Token Metadata PDA for ticket_mint. Required, with the fields below,
+    /// when event.use_pnft.
+    #[account(mut)]
+    pub ticket_metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: Master edition PDA for ticket_mint.
+    #[account(mut)]
+    pub ticket_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: TokenRecord PDA for holder_token_account.
+    #[account(mut)]
+    pub holder_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: Instructions sysvar, which Token Metadata reads.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token Metadata program.
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: Option<AccountInfo<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteTicket<'info> {
     pub event: Account<'info, Event>,
 
+    /// CHECK: Wallet the quote is for; only its key and balance are read.
+    pub buyer: AccountInfo<'info>,
+
+    /// CHECK: `["rate_limit", buyer]` PDA; its rent is quoted when it doesn't exist.
+    #[account(seeds = [b"rate_limit", buyer.key().as_ref()], bump)]
+    pub rate_limit: AccountInfo<'info>,
+
+    /// CHECK: `["buyer", event, buyer]` PDA; its rent is quoted when it doesn't exist.
+    #[account(seeds = [b"buyer", event.key().as_ref(), buyer.key().as_ref()], bump)]
+    pub buyer_record: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(day: u32)]
+pub struct InitFeeDay<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 4 + 8 + 8 + 1,
+        seeds = [b"fee_day".as_ref(), &day.to_le_bytes()],
+        bump,
+    )]
+    pub fee_day: Account<'info, FeeDay>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct GetFeeReport<'info> {
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub event: Option<Account<'info, Event>>,
+
+    #[account(seeds = [b"fee_day".as_ref(), &fee_day.day.to_le_bytes()], bump = fee_day.bump)]
+    pub fee_day: Option<Account<'info, FeeDay>>,
+}
+
+// ── Events ───────────────────────────────────────────────────────────
+
+#[event]
+pub struct AutoSupplyIncrease {
+    pub event: Pubkey,
+    pub new_supply: u32,
+    pub trigger_waitlist_size: u32,
+}
+
+#[event]
+pub struct CheckedIn {
+    pub event: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub holder: Pubkey,
+    pub attendance_index: u32,
+    pub category: Option<u8>, // concession category for door staff to check eligibility
+    pub perks_bitmask: u16,   // PERK_* bits (fast lane, backstage, ...) from the receipt
+    pub note: Option<[u8; 32]>, // holder's note, if still valid for this holder
+}
+
+#[event]
+pub struct RaffleDrawn {
+    pub event: Pubkey,
+    pub seed: [u8; 32],
+    pub winners: Vec<u32>,
+}
+
+#[event]
+pub struct FairValueEstimate {
+    pub listing: Pubkey,
+    pub estimated_fair_value: u64,
+    pub deviation_bps: u64,
+}
+
+#[event]
+pub struct PriceDeviationWarning {
+    pub listing: Pubkey,
+    pub listing_price: u64,
+    pub estimated_fair_value: u64,
+    pub deviation_bps: u64,
+}
+
+#[event]
+pub struct EscrowAnomalyDetected {
+    pub listing: Pubkey,
+    pub escrow: Pubkey,
+    pub actual_balance: u64,
+}
+
+#[event]
+pub struct TicketPurchased {
+    pub event: Pubkey,
+    pub buyer: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub price_lamports: u64,
+    pub tip_lamports: u64,
+    pub donation_lamports: u64,
+    /// Organizer-set name for this index; None means the default naming applies.
+    pub custom_name: Option<String>,
+}
+
+#[event]
+pub struct PendingWork {
+    pub event: Pubkey,
+    pub expired_holds: u32,
+    pub releasable_seats: u32,
+}
+
+#[event]
+pub struct ResaleCircuitBreakerTriggered {
+    pub event: Pubkey,
+    pub price_drop_bps: u64,
+}
+
+#[event]
+pub struct EventStats {
+    pub event: Pubkey,
+    pub average_resale_price: u64,
+    pub analytics: EventAnalytics,
+}
+
+#[event]
+pub struct HealthCheckFailed {
+    pub event: Pubkey,
+    pub reason: u8, // HEALTH_ESCROW, HEALTH_SUPPLY or HEALTH_VAULT
+}
+
+#[event]
+pub struct AuditResult {
+    pub event: Pubkey,
+    pub expected_revenue: u64, // summed from receipts so far
+    pub recorded_revenue: u64, // analytics.gross_primary_revenue
+    pub discrepancy: i64,      // recorded - expected
+}
+
+#[event]
+pub struct AuditDiscrepancyFound {
+    pub event: Pubkey,
+    pub discrepancy: i64,
+}
+
+#[event]
+pub struct TicketFlagRaised {
+    pub event: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub reason_code: u8,
+    pub listing_cancelled: bool,
+}
+
+#[event]
+pub struct TicketFlagCleared {
+    pub event: Pubkey,
+    pub ticket_mint: Pubkey,
+}
+
+#[event]
+pub struct OwnershipAttested {
+    pub version: u8,
+    pub event: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u64,
+    pub nonce: u64,
+    /// ownership_attestation_message bytes, as sent to the messaging program.
+    pub message: Vec<u8>,
+}
+
+#[event]
+pub struct TicketMigrated {
+    pub from_event: Pubkey,
+    pub to_event: Pubkey,
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub holder: Pubkey,
+}
+
+#[event]
+pub struct PurchaseQuote {
+    pub event: Pubkey,
+    pub buyer: Pubkey,
+    pub price_lamports: u64,
+    /// Price, tip, deposit and handler-created record and metadata rent, as buy_ticket
+    /// checks.
+    pub charges_lamports: u64,
+    /// Rent for the accounts a fresh purchase creates.
+    pub rent_lamports: u64,
+    pub total_lamports: u64,
+    pub shortfall_lamports: u64,
+}
+
 // ── Errors ───────────────────────────────────────────────────────────
 
 #[error_code]
@@ -526,4 +11296,338 @@ pub enum ErrorCode {
     InvalidOrganizer,
     #[msg("Artist percentage must be 0-80")]
     InvalidSplit,
+    #[msg("Capacity must be at least the current supply")]
+    InvalidCapacity,
+    #[msg("Demand increment must be positive when auto supply is enabled")]
+    InvalidDemandIncrement,
+    #[msg("Event is not sold out")]
+    NotSoldOut,
+    #[msg("Signer does not hold this ticket")]
+    NotTicketHolder,
+    #[msg("Prize count must be 1-16")]
+    InvalidPrizeCount,
+    #[msg("Raffle cannot be drawn before attendance is finalized")]
+    InvalidDrawTime,
+    #[msg("Raffle has already been drawn")]
+    RaffleAlreadyDrawn,
+    #[msg("Raffle cannot be drawn yet")]
+    RaffleNotReady,
+    #[msg("Ticket did not win the raffle")]
+    NotRaffleWinner,
+    #[msg("Raffle prize already claimed")]
+    RaffleAlreadyClaimed,
+    #[msg("Not enough resale history for a fair value estimate")]
+    InsufficientPriceHistory,
+    #[msg("Current tranche is sold out; more tickets will be released later")]
+    TrancheSoldOut,
+    #[msg("Invalid release schedule")]
+    InvalidReleaseSchedule,
+    #[msg("Event already has ticket sales")]
+    EventHasSales,
+    #[msg("Listing price exceeds the event's maximum listing multiple")]
+    ListingMultipleTooHigh,
+    #[msg("Invalid hold")]
+    InvalidHold,
+    #[msg("Not enough unreserved supply")]
+    InsufficientSupply,
+    #[msg("Hold has expired")]
+    HoldExpired,
+    #[msg("Hold has not expired yet")]
+    HoldNotExpired,
+    #[msg("Event has not ended yet")]
+    EventNotOver,
+    #[msg("Ticket has been checked in")]
+    TicketCheckedIn,
+    #[msg("Invalid tax configuration")]
+    InvalidTaxConfig,
+    #[msg("Invalid tax wallet")]
+    InvalidTaxWallet,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Claim link has expired")]
+    ClaimLinkExpired,
+    #[msg("Missing or invalid claim link signature")]
+    InvalidClaimSignature,
+    #[msg("Claim link is still active")]
+    ClaimLinkActive,
+    #[msg("Quantity must be positive")]
+    InvalidQuantity,
+    #[msg("Order book is full")]
+    OrderBookFull,
+    #[msg("Best bid and ask do not cross")]
+    NoCrossingOrders,
+    #[msg("Invalid buyer")]
+    InvalidBuyer,
+    #[msg("Invalid ticket mint")]
+    InvalidTicketMint,
+    #[msg("Invalid order index")]
+    InvalidOrderIndex,
+    #[msg("Only the original buyer can list this ticket")]
+    NotOriginalBuyer,
+    #[msg("Invalid recipient")]
+    InvalidRecipient,
+    #[msg("Transfer offer has expired")]
+    TransferOfferExpired,
+    #[msg("Transfer offer is still active")]
+    TransferOfferActive,
+    #[msg("Gift message exceeds 200 bytes")]
+    GiftMessageTooLong,
+    #[msg("Gift message and gift note account must be provided together")]
+    GiftNoteMismatch,
+    #[msg("Event vault is required")]
+    InvalidVault,
+    #[msg("Outside the refund grace period")]
+    OutsideGracePeriod,
+    #[msg("Receipt has already been refunded")]
+    AlreadyRefunded,
+    #[msg("Proceeds are locked until refund windows close")]
+    ProceedsLocked,
+    #[msg("Cleanup batch must be non-empty (token account, owner) pairs")]
+    InvalidCleanupBatch,
+    #[msg("Account is not eligible for cleanup")]
+    InvalidCleanupAccount,
+    #[msg("Fan token accounts are required")]
+    MissingFanTokenAccounts,
+    #[msg("Invalid fan token mint")]
+    InvalidFanTokenMint,
+    #[msg("Perk URI exceeds 200 bytes")]
+    PerkUriTooLong,
+    #[msg("Perk claim window has closed")]
+    PerkClaimClosed,
+    #[msg("Perk claim window is still open")]
+    PerkClaimOpen,
+    #[msg("Price exceeds the platform ceiling")]
+    PriceAboveCeiling,
+    #[msg("Invalid pass bundle")]
+    InvalidBundle,
+    #[msg("Listing escrow is flagged as anomalous")]
+    EscrowAnomaly,
+    #[msg("Listing is not flagged as anomalous")]
+    NoEscrowAnomaly,
+    #[msg("Social link must be an https:// URL of at most 128 bytes")]
+    SocialLinkInvalid,
+    #[msg("Entry delegation has expired")]
+    DelegationExpired,
+    #[msg("Invalid concession category")]
+    InvalidConcession,
+    #[msg("Concession category is sold out")]
+    ConcessionSoldOut,
+    #[msg("Organizer attestation is required for this category")]
+    MissingAttestation,
+    #[msg("Invalid organizer attestation")]
+    InvalidAttestation,
+    #[msg("Design CID must be a CIDv1 (baf...) of at most 64 bytes")]
+    InvalidCid,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Ticket stats account belongs to a different event")]
+    StatsAccountMismatch,
+    #[msg("Tip exceeds 10x the ticket price; set allow_large_tip to confirm")]
+    TipTooLarge,
+    #[msg("Ticket index does not match the next ticket")]
+    InvalidTicketIndex,
+    #[msg("Invalid donation configuration")]
+    InvalidDonationConfig,
+    #[msg("Invalid donation wallet")]
+    InvalidDonationWallet,
+    #[msg("Invalid governance proposal")]
+    InvalidProposal,
+    #[msg("Proposal has already been applied")]
+    ProposalApplied,
+    #[msg("Proposal has not reached the yes threshold")]
+    ProposalNotPassed,
+    #[msg("No items in the batch were ready")]
+    NothingToCrank,
+    #[msg("Resale is paused by the circuit breaker")]
+    ResalePaused,
+    #[msg("Accessible seating is unavailable")]
+    AccessibleSeatingUnavailable,
+    #[msg("Accessible seat count must cover seats already sold and fit unsold supply")]
+    InvalidAccessibleSeating,
+    #[msg("Listing price was updated too recently")]
+    PriceUpdateTooFrequent,
+    #[msg("Price update interval must not be negative")]
+    InvalidPriceUpdateInterval,
+    #[msg("Purchase requires a platform attestation")]
+    AttestationRequired,
+    #[msg("Purchase attestation has expired")]
+    AttestationExpired,
+    #[msg("Listing is hidden by the organizer")]
+    ListingHiddenByOrganizer,
+    #[msg("Invalid payment option")]
+    InvalidPaymentOption,
+    #[msg("Token payment accounts are required for this payment option")]
+    MissingPaymentAccounts,
+    #[msg("Payment token account has the wrong mint or owner")]
+    InvalidPaymentAccount,
+    #[msg("Token payments cannot be combined with tax, donations or vesting")]
+    TokenPaymentConflict,
+    #[msg("Primary sales are paused by a failed health check")]
+    SalesPaused,
+    #[msg("Health check accounts must be (listing, escrow) pairs for this event")]
+    InvalidHealthCheckBatch,
+    #[msg("Resale is blocked while primary tickets are available")]
+    PrimaryStillAvailable,
+    #[msg("Too many purchases from this wallet; try again in a minute")]
+    RateLimitExceeded,
+    #[msg("Resale price is below the event's resale floor")]
+    ResaleBelowDynamicFloor,
+    #[msg("Audit accounts must be distinct receipts for this event")]
+    InvalidAuditBatch,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Royalty escrow account is required while royalty escrow mode is on")]
+    MissingRoyaltyEscrow,
+    #[msg("Escrowed royalties are still locked")]
+    RoyaltyEscrowLocked,
+    #[msg("Token transfer would not deliver the exact amount (transfer fee)")]
+    UnexpectedTransferFee,
+    #[msg("Ticket is flagged by the organizer")]
+    TicketFlagged,
+    #[msg("Escrow, seller and seller token accounts are required to cancel the listing")]
+    MissingListingAccounts,
+    #[msg("Ticket name must be 1 to 32 bytes")]
+    TicketNameTooLong,
+    #[msg("Wallet is banned from this organizer's events")]
+    BannedFromOrganizerEvents,
+    #[msg("Unsupported instruction version or option")]
+    UnsupportedVersion,
+    #[msg("Attestation nonce must exceed the last one used for this ticket")]
+    AttestationNonceUsed,
+    #[msg("Messaging program and ownership attestor accounts are required")]
+    MissingMessagingAccounts,
+    #[msg("Revoke the token account's delegate before escrowing the ticket")]
+    DelegateMustBeRevoked,
+    #[msg("Escrow token account has an unexpected owner or close authority")]
+    InvalidEscrow,
+    #[msg("Price feed is not a verified Pyth SOL/USD update")]
+    InvalidPriceFeed,
+    #[msg("Price feed update is too old")]
+    StalePriceFeed,
+    #[msg("Price feed confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Resale price exceeds the buyer's maximum")]
+    PriceAboveMax,
+    #[msg("Missing or invalid proof-of-personhood credential")]
+    InvalidPersonhoodCredential,
+    #[msg("This person already bought a ticket to this event")]
+    AlreadyPurchasedAsPerson,
+    #[msg("Event has already started or the new date is in the past")]
+    EventAlreadyStarted,
+    #[msg("Attendance deposits apply only to free events, set before the first sale")]
+    InvalidDepositConfig,
+    #[msg("Deposit escrow account is required")]
+    MissingDepositEscrow,
+    #[msg("Attendance is not final yet")]
+    AttendanceNotFinal,
+    #[msg("No deposit is held for this ticket")]
+    NoDepositHeld,
+    #[msg("Ticket notes are locked this close to the event")]
+    TicketNoteLocked,
+    #[msg("Invalid event split or replacement show")]
+    InvalidEventSplit,
+    #[msg("Event was split into replacement shows")]
+    EventSuperseded,
+    #[msg("Resale is blocked this soon after the primary purchase")]
+    ResaleBlackoutActive,
+    #[msg("The ticket's primary purchase receipt is required")]
+    MissingPurchaseReceipt,
+    #[msg("Resale blackout must not be negative")]
+    InvalidResaleBlackout,
+    #[msg("This event requires an identity commitment")]
+    IdentityCommitmentRequired,
+    #[msg("This identity already bought a ticket to the event")]
+    IdentityAlreadyUsed,
+    #[msg("Check-in must present the identity preimage")]
+    IdentityPreimageRequired,
+    #[msg("Identity preimage does not match the ticket's commitment")]
+    IdentityPreimageMismatch,
+    #[msg("Account is not a live or closed listing on this ask book")]
+    InvalidAskBookEntry,
+    #[msg("Ticket has not been burned or still holds a deposit")]
+    TicketStillLive,
+    #[msg("Seller's associated token account is missing or invalid")]
+    SellerTokenAccountMissing,
+    #[msg("An event needs between 1 and MAX_TIERS ticket tiers")]
+    InvalidTiers,
+    #[msg("No ticket tier at this index")]
+    InvalidTierIndex,
+    #[msg("This ticket tier is sold out")]
+    TierSoldOut,
+    #[msg("The event has already taken place")]
+    EventEnded,
+    #[msg("This event only sells tickets for tokens")]
+    SolPaymentDisabled,
+    #[msg("Ticket sales have not started yet")]
+    SaleNotStarted,
+    #[msg("Ticket sales have ended")]
+    SaleEnded,
+    #[msg("Sale window must open before it closes and close before the event")]
+    InvalidSaleWindow,
+    #[msg("This wallet has reached the event's purchase limit")]
+    PurchaseLimitReached,
+    #[msg("The event has not started yet")]
+    EventNotStarted,
+    #[msg("This event needs per-ticket checks; buy tickets one at a time")]
+    BatchPurchaseUnsupported,
+    #[msg("The event has been cancelled")]
+    EventCancelled,
+    #[msg("The event has not been cancelled or postponed")]
+    EventNotCancelled,
+    #[msg("Metadata URI too long")]
+    MetadataUriTooLong,
+    #[msg("Not enough SOL for this purchase")]
+    InsufficientFundsForPurchase,
+    #[msg("Ticket metadata accounts are required for this event")]
+    MissingMetadataAccounts,
+    #[msg("Programmable (pNFT) tickets need a metadata_uri or ticket design CID")]
+    PnftRequiresMetadata,
+    #[msg("This instruction does not support programmable (pNFT) tickets")]
+    PnftUnsupported,
+    #[msg("That fee day has already passed")]
+    FeeDayPassed,
+    #[msg("The fee override is above the event's platform share")]
+    FeeOverrideAboveDefault,
+    #[msg("Raffle is already locked")]
+    RaffleAlreadyLocked,
+    #[msg("Raffle must be locked before it is drawn")]
+    RaffleNotLocked,
+    #[msg("Revealed randomness does not match the raffle commitment")]
+    RaffleRevealMismatch,
+    #[msg("The lock slot's hash is no longer in SlotHashes")]
+    SlotHashUnavailable,
+    #[msg("Platform share must go to the platform wallet")]
+    InvalidPlatformWallet,
+    #[msg("Only the seller can cancel a live, unflagged order")]
+    OrderStillActive,
+    #[msg("Pass sales for this bundle have closed")]
+    PassSaleClosed,
+    #[msg("Pass sales are still open; unsold seats can't be released yet")]
+    PassSaleOpen,
+    #[msg("Voting on this proposal has closed")]
+    VotingClosed,
+    #[msg("Voting on this proposal is still open")]
+    VotingOpen,
+    #[msg("Too few ticket holders voted on this proposal")]
+    QuorumNotReached,
+    #[msg("Event date must be in the future")]
+    InvalidEventDate,
+    #[msg("Accessible seats require an approval from the organizer or accessibility verifier")]
+    AccessibleApprovalRequired,
+    #[msg("This accessible-seat approval was already used")]
+    AccessibleApprovalUsed,
+    #[msg("The ticket's receipt is required to settle its attendance deposit")]
+    DepositReceiptRequired,
+    #[msg("Resale price does not cover the ticket's attendance deposit")]
+    PriceBelowDeposit,
+    #[msg("The replacement tier costs more than the ticket's price")]
+    TierAbovePaidPrice,
+    #[msg("This event has several tiers; the ticket's purchase receipt is required")]
+    TierReceiptRequired,
+    #[msg("The event date can't move earlier once tickets are sold")]
+    DateMovedEarlierAfterSales,
 }
@@ -0,0 +1,40 @@
+mod common;
+
+use anchor_lang::prelude::{AccountInfo, Pubkey, Signer};
+use common::*;
+use ticketchain::{organizer_only, ErrorCode};
+
+/// Runs `organizer_only` directly against `event` with `key` as the signer.
+fn check(event: &ticketchain::Event, key: Pubkey) -> anchor_lang::Result<()> {
+    let (mut lamports, mut data, owner) = (0u64, Vec::new(), anchor_lang::solana_program::system_program::ID);
+    let info = AccountInfo::new(&key, true, false, &mut lamports, &mut data, &owner, false, 0);
+    organizer_only(event, &Signer::try_from(&info).unwrap())
+}
+
+#[test]
+fn organizer_only_accepts_just_the_event_organizer() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let state = event(&svm, &fixture);
+    assert!(check(&state, fixture.organizer).is_ok());
+    let err = check(&state, Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidOrganizer.into());
+}
+
+#[test]
+fn gated_instruction_rejects_another_signer() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let stranger = svm.wallet(SOL);
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::SetTaxConfig { organizer: stranger, event: fixture.event },
+                ticketchain::instruction::SetTaxConfig { tax_bps: 500, tax_wallet: stranger },
+            ),
+            &[stranger],
+        ),
+        ErrorCode::InvalidOrganizer,
+    );
+    assert_eq!(event(&svm, &fixture).tax_bps, 0);
+}
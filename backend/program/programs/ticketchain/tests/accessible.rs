@@ -0,0 +1,143 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn approval_pda(fixture: &EventFixture, buyer: &Pubkey) -> Pubkey {
+    pda(&[b"accessible_approval", fixture.event.as_ref(), buyer.as_ref()])
+}
+
+fn accessible_fixture(svm: &mut Svm, count: u32) -> EventFixture {
+    let fixture = create_event(svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ConfigureAccessibleSeating { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetAccessibleSeating { count },
+        ),
+        &[fixture.organizer],
+    ));
+    fixture
+}
+
+fn approve(svm: &mut Svm, fixture: &EventFixture, approver: &Pubkey, buyer: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ApproveAccessibleBuyer {
+                approver: *approver,
+                event: fixture.event,
+                approval: approval_pda(fixture, buyer),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ApproveAccessibleBuyer { buyer: *buyer },
+        ),
+        &[*approver],
+    )
+}
+
+fn buy_accessible(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, with_approval: bool) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    if with_approval {
+        accounts.accessible_approval = Some(approval_pda(fixture, buyer));
+    }
+    svm.send(ix(accounts, ticketchain::instruction::BuyAccessibleTicket { ticket_index: index }), &[*buyer])
+}
+
+#[test]
+fn accessible_seat_requires_a_single_use_organizer_approval() {
+    let mut svm = setup();
+    let fixture = accessible_fixture(&mut svm, 2);
+    let buyer = svm.wallet(10 * SOL);
+
+    assert_err(buy_accessible(&mut svm, &fixture, &buyer, false), ErrorCode::AccessibleApprovalRequired);
+    assert_ok(approve(&mut svm, &fixture, &fixture.organizer, &buyer));
+    assert_ok(buy_accessible(&mut svm, &fixture, &buyer, true));
+    let approval: ticketchain::AccessibleApproval = svm.get(&approval_pda(&fixture, &buyer));
+    assert!(approval.used);
+    assert_eq!(approval.approver, fixture.organizer);
+    assert_eq!(event(&svm, &fixture).accessible_sold, 1);
+
+    svm.warp(61);
+    assert_err(buy_accessible(&mut svm, &fixture, &buyer, true), ErrorCode::AccessibleApprovalUsed);
+}
+
+#[test]
+fn accessibility_verifier_approves_buyers_once_named() {
+    let mut svm = setup();
+    let fixture = accessible_fixture(&mut svm, 1);
+    let verifier = svm.wallet(SOL);
+    let buyer = svm.wallet(10 * SOL);
+
+    assert_err(approve(&mut svm, &fixture, &verifier, &buyer), ErrorCode::Unauthorized);
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::SetAccessibilityVerifier { organizer: verifier, event: fixture.event },
+                ticketchain::instruction::SetAccessibilityVerifier { verifier },
+            ),
+            &[verifier],
+        ),
+        ErrorCode::InvalidOrganizer,
+    );
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAccessibilityVerifier { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetAccessibilityVerifier { verifier },
+        ),
+        &[fixture.organizer],
+    ));
+    assert_ok(approve(&mut svm, &fixture, &verifier, &buyer));
+    assert_ok(buy_accessible(&mut svm, &fixture, &buyer, true));
+    assert_eq!(event(&svm, &fixture).accessible_sold, 1);
+}
+
+fn release(svm: &mut Svm, fixture: &EventFixture, count: u32) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ConfigureAccessibleSeating { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::ReleaseAccessibleSeats { count },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn released_accessible_seats_go_on_general_sale_before_the_event() {
+    let mut svm = setup();
+    // Every seat reserved, so general sale is sold out
+    let fixture = accessible_fixture(&mut svm, 10);
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SoldOut);
+
+    assert_err(release(&mut svm, &fixture, 11), ErrorCode::AccessibleSeatingUnavailable);
+    assert_ok(release(&mut svm, &fixture, 1));
+    assert_eq!(event(&svm, &fixture).accessible_seating_count, 9);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp(61);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SoldOut);
+
+    // Once the event starts the seats could no longer be sold
+    svm.warp_to(event(&svm, &fixture).date_ts);
+    assert_err(release(&mut svm, &fixture, 1), ErrorCode::EventEnded);
+}
+
+#[test]
+fn oversized_accessible_reserve_is_rejected_not_overflowed() {
+    let mut svm = setup();
+    let fixture = accessible_fixture(&mut svm, 0);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::ConfigureAccessibleSeating { organizer: fixture.organizer, event: fixture.event },
+                ticketchain::instruction::SetAccessibleSeating { count: u32::MAX },
+            ),
+            &[fixture.organizer],
+        ),
+        ErrorCode::InvalidAccessibleSeating,
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, solana_program::system_program};
+use common::*;
+
+/// A funded wallet whose leading bytes, which the buyer sketch hashes, don't collide with the others.
+fn wallet(svm: &mut Svm, tag: u8) -> Pubkey {
+    let key = Pubkey::new_from_array([tag; 32]);
+    svm.airdrop(&key, 10 * SOL);
+    key
+}
+
+#[test]
+fn counters_reconcile_with_the_lamports_moved() {
+    let mut svm = setup();
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    let (alice, bob, carol) = (wallet(&mut svm, 1), wallet(&mut svm, 2), wallet(&mut svm, 3));
+
+    // Primary sales: everything the organizer receives is gross revenue
+    let before = svm.lamports(&fixture.organizer);
+    let resold = assert_ok(buy_ticket(&mut svm, &fixture, &alice, 0));
+    assert_ok(buy_ticket(&mut svm, &fixture, &alice, 0));
+    let refunded = assert_ok(buy_ticket(&mut svm, &fixture, &bob, 0));
+    let primary_received = svm.lamports(&fixture.organizer) - before;
+
+    // One resale: seller, organizer and platform shares add up to the volume
+    assert_ok(list_ticket(&mut svm, &fixture, &alice, &resold, 3 * SOL / 10));
+    let closed_rent = svm.lamports(&listing_pda(&resold)) + svm.lamports(&pda(&[b"escrow", resold.as_ref()]));
+    let recipients = [alice, fixture.organizer, ticketchain::PLATFORM_WALLET];
+    let before: Vec<u64> = recipients.iter().map(|key| svm.lamports(key)).collect();
+    assert_ok(svm.send(buy_resale_ix(&fixture, &carol, &alice, &resold, u64::MAX), &[carol]));
+    let resale_received: u64 =
+        recipients.iter().zip(&before).map(|(key, before)| svm.lamports(key) - before).sum::<u64>() - closed_rent;
+
+    // One refund after cancellation
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    let before = svm.lamports(&vault_pda(&fixture.event));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: bob,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                ticket_mint: refunded,
+                receipt: receipt_pda(&bob, &refunded),
+                holder_token_account: ata(&bob, &refunded),
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 2 },
+        ),
+        &[bob],
+    ));
+    let refund_paid = before - svm.lamports(&vault_pda(&fixture.event));
+
+    let analytics = event(&svm, &fixture).analytics;
+    assert_eq!(analytics.gross_primary_revenue, primary_received);
+    assert_eq!(primary_received, 3 * SOL / 10);
+    assert_eq!((analytics.resale_count, analytics.resale_volume), (1, resale_received));
+    assert_eq!(resale_received, 3 * SOL / 10);
+    assert_eq!((analytics.refund_count, analytics.refunded_lamports), (1, refund_paid));
+    assert_eq!(refund_paid, SOL / 10);
+    assert_eq!(analytics.unique_buyers, 3);
+}
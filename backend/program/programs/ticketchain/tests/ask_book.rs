@@ -0,0 +1,172 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{AskBook, ErrorCode, ASK_BOOK_SIZE};
+
+fn ask_book_pda(event: &Pubkey) -> Pubkey {
+    pda(&[b"askbook", event.as_ref()])
+}
+
+fn book_event(svm: &mut Svm) -> EventFixture {
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let fixture = create_event(svm);
+    let payer = svm.wallet(SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::InitAskBook {
+                payer,
+                event: fixture.event,
+                ask_book: ask_book_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::InitAskBook {},
+        ),
+        &[payer],
+    ));
+    fixture
+}
+
+/// A new holder lists their ticket at `price`, updating the book when `book` is set.
+fn list_new(svm: &mut Svm, fixture: &EventFixture, price: u64, book: bool) -> (Pubkey, Pubkey) {
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, fixture, &seller, 0));
+    let mut accounts = list_accounts(fixture, &seller, &mint);
+    accounts.ask_book = book.then(|| ask_book_pda(&fixture.event));
+    assert_ok(svm.send(ix(accounts, list_args(fixture, &mint, price)), &[seller]));
+    (seller, mint)
+}
+
+fn cancel(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey, book: bool) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelListing {
+                seller: *seller,
+                ticket_mint: *mint,
+                listing: listing_pda(mint),
+                seller_token_account: ata(seller, mint),
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+                ask_book: book.then(|| ask_book_pda(&fixture.event)),
+                ticket_metadata: None,
+                ticket_edition: None,
+                seller_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelListing {},
+        ),
+        &[*seller],
+    ));
+}
+
+fn repair(svm: &mut Svm, fixture: &EventFixture, accounts: &[Pubkey]) -> Result<TxMeta, TxError> {
+    let cranker = svm.wallet(SOL);
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::RepairAskBook { event: fixture.event, ask_book: ask_book_pda(&fixture.event) },
+            ticketchain::instruction::RepairAskBook {},
+            accounts.iter().map(|key| AccountMeta::new_readonly(*key, false)).collect(),
+        ),
+        &[cranker],
+    )
+}
+
+fn book(svm: &Svm, fixture: &EventFixture) -> Vec<(u64, Pubkey)> {
+    svm.get::<AskBook>(&ask_book_pda(&fixture.event)).asks.iter().map(|a| (a.price_lamports, a.listing)).collect()
+}
+
+#[test]
+fn book_keeps_the_cheapest_asks_in_order() {
+    let mut svm = setup();
+    let fixture = book_event(&mut svm);
+    let step = SOL / 100;
+    // Listed out of order: 0.15, 0.11, 0.18, ... SOL
+    let listed: Vec<_> = [5, 1, 8, 3, 7, 2, 6, 4]
+        .map(|k| (SOL / 10 + k * step, list_new(&mut svm, &fixture, SOL / 10 + k * step, true)))
+        .into_iter()
+        .collect();
+    let prices: Vec<u64> = book(&svm, &fixture).iter().map(|(price, _)| *price).collect();
+    assert_eq!(prices, (1..=8).map(|k| SOL / 10 + k * step).collect::<Vec<_>>());
+
+    // A full book evicts its dearest ask for a cheaper one and ignores dearer ones
+    let dear = list_new(&mut svm, &fixture, SOL / 10 + 9 * step, true).1;
+    assert!(book(&svm, &fixture).iter().all(|(_, listing)| *listing != listing_pda(&dear)));
+    let cheap = list_new(&mut svm, &fixture, SOL / 10, true).1;
+    let entries = book(&svm, &fixture);
+    assert_eq!(entries.len(), ASK_BOOK_SIZE);
+    assert_eq!(entries[0], (SOL / 10, listing_pda(&cheap)));
+    assert_eq!(entries[ASK_BOOK_SIZE - 1].0, SOL / 10 + 7 * step);
+
+    // Fills and cancels with the book attached take their entry off
+    let (price, (seller, mint)) = listed[1];
+    let buyer = svm.wallet(10 * SOL);
+    let mut accounts = buy_resale_accounts(&fixture, &buyer, &seller, &mint);
+    accounts.ask_book = Some(ask_book_pda(&fixture.event));
+    assert_ok(svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports: price }), &[buyer]));
+    let (_, (seller, mint)) = listed[5];
+    cancel(&mut svm, &fixture, &seller, &mint, true);
+    let prices: Vec<u64> = book(&svm, &fixture).iter().map(|(price, _)| *price).collect();
+    assert_eq!(prices, [0, 3, 4, 5, 6, 7].map(|k| SOL / 10 + k * step));
+}
+
+#[test]
+fn repair_drops_stale_entries_and_rejects_fabricated_ones() {
+    let mut svm = setup();
+    let fixture = book_event(&mut svm);
+    let (seller, cancelled) = list_new(&mut svm, &fixture, SOL / 5, true);
+    let live = list_new(&mut svm, &fixture, SOL / 4, true).1;
+    let unbooked = list_new(&mut svm, &fixture, SOL / 8, false).1;
+    cancel(&mut svm, &fixture, &seller, &cancelled, false);
+    assert_eq!(book(&svm, &fixture).len(), 2);
+
+    // Closed accounts must be on the book; live ones must be this event's listings
+    let other = book_event(&mut svm);
+    let elsewhere = list_new(&mut svm, &other, SOL / 5, false).1;
+    assert_err(repair(&mut svm, &fixture, &[Pubkey::new_unique()]), ErrorCode::InvalidAskBookEntry);
+    assert_err(repair(&mut svm, &fixture, &[listing_pda(&elsewhere)]), ErrorCode::InvalidAskBookEntry);
+    assert_err(repair(&mut svm, &fixture, &[fixture.event]), ErrorCode::InvalidAskBookEntry);
+    let stranger = svm.wallet(SOL);
+    assert_err(repair(&mut svm, &fixture, &[stranger]), ErrorCode::InvalidAskBookEntry);
+
+    assert_ok(repair(&mut svm, &fixture, &[listing_pda(&cancelled), listing_pda(&unbooked)]));
+    assert_eq!(book(&svm, &fixture), vec![(SOL / 8, listing_pda(&unbooked)), (SOL / 4, listing_pda(&live))]);
+}
+
+fn set_visible(svm: &mut Svm, fixture: &EventFixture, signer: &Pubkey, mint: &Pubkey, visible: bool) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetListingVisibility {
+                organizer: *signer,
+                event: fixture.event,
+                listing: listing_pda(mint),
+            },
+            ticketchain::instruction::SetListingVisibility { visible },
+        ),
+        &[*signer],
+    )
+}
+
+#[test]
+fn organizer_hidden_listings_leave_the_book_on_repair() {
+    let mut svm = setup();
+    let fixture = book_event(&mut svm);
+    let (seller, hidden) = list_new(&mut svm, &fixture, SOL / 5, true);
+    let (_, shown) = list_new(&mut svm, &fixture, SOL / 4, true);
+    let listings = [listing_pda(&hidden), listing_pda(&shown)];
+
+    let stranger = svm.wallet(SOL);
+    assert_err(set_visible(&mut svm, &fixture, &stranger, &hidden, false), ErrorCode::InvalidOrganizer);
+    assert_err(set_visible(&mut svm, &fixture, &seller, &hidden, false), ErrorCode::InvalidOrganizer);
+    assert_ok(set_visible(&mut svm, &fixture, &fixture.organizer, &hidden, false));
+    assert!(!svm.get::<ticketchain::Listing>(&listings[0]).visible);
+    assert_ok(repair(&mut svm, &fixture, &listings));
+    assert_eq!(book(&svm, &fixture), vec![(SOL / 4, listings[1])]);
+
+    assert_ok(set_visible(&mut svm, &fixture, &fixture.organizer, &hidden, true));
+    assert_ok(repair(&mut svm, &fixture, &listings));
+    assert_eq!(book(&svm, &fixture), vec![(SOL / 5, listings[0]), (SOL / 4, listings[1])]);
+}
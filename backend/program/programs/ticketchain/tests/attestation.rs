@@ -0,0 +1,88 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, OwnershipAttested, OWNERSHIP_ATTESTATION_VERSION};
+
+fn attest(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32, nonce: u64) -> Result<TxMeta, TxError> {
+    let ticket_mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::AttestOwnership {
+                holder: *holder,
+                event: fixture.event,
+                ticket_mint,
+                holder_token_account: ata(holder, &ticket_mint),
+                attestation_nonce: pda(&[b"attest_nonce", ticket_mint.as_ref()]),
+                platform_config: Some(platform_config_pda()),
+                messaging_program: None,
+                ownership_attestor: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::AttestOwnership { _ticket_index: index, nonce },
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn attestation_carries_the_canonical_message_once_per_nonce() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+
+    let attested = assert_ok(attest(&mut svm, &fixture, &holder, 0, 1)).event::<OwnershipAttested>();
+    assert_eq!(attested.version, OWNERSHIP_ATTESTATION_VERSION);
+    assert_eq!((attested.event, attested.ticket_mint, attested.owner, attested.nonce), (fixture.event, mint, holder, 1));
+    let message = &attested.message;
+    assert_eq!(message.len(), 118);
+    assert_eq!(&message[..5], b"TCOWN");
+    assert_eq!(message[5], OWNERSHIP_ATTESTATION_VERSION);
+    assert_eq!(&message[6..38], fixture.event.as_ref());
+    assert_eq!(&message[38..70], mint.as_ref());
+    assert_eq!(&message[70..102], holder.as_ref());
+    assert_eq!(message[102..110], attested.slot.to_le_bytes());
+    assert_eq!(message[110..], 1u64.to_le_bytes());
+
+    svm.warp(1);
+    assert_err(attest(&mut svm, &fixture, &holder, 0, 1), ErrorCode::AttestationNonceUsed);
+    assert_err(attest(&mut svm, &fixture, &holder, 0, 0), ErrorCode::AttestationNonceUsed);
+    assert_ok(attest(&mut svm, &fixture, &holder, 0, 2));
+}
+
+#[test]
+fn only_the_holder_can_attest() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let buyer = svm.wallet(SOL);
+    assert_ok(transfer_ticket(&mut svm, &holder, &buyer, &mint));
+
+    assert_err(attest(&mut svm, &fixture, &holder, 0, 1), ErrorCode::NotTicketHolder);
+    assert_ok(attest(&mut svm, &fixture, &buyer, 0, 1));
+}
+
+#[test]
+fn configured_messaging_program_must_be_passed() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetMessagingProgram {
+                authority: PLATFORM_AUTHORITY,
+                platform_config: platform_config_pda(),
+            },
+            ticketchain::instruction::SetMessagingProgram { messaging_program: Some(Pubkey::new_unique()) },
+        ),
+        &[PLATFORM_AUTHORITY],
+    ));
+
+    assert_err(attest(&mut svm, &fixture, &holder, 0, 1), ErrorCode::MissingMessagingAccounts);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{AuditDiscrepancyFound, AuditResult, ErrorCode};
+
+fn audit_pda(fixture: &EventFixture, auditor: &Pubkey) -> Pubkey {
+    pda(&[b"revenue_audit", fixture.event.as_ref(), auditor.as_ref()])
+}
+
+fn audit(svm: &mut Svm, fixture: &EventFixture, auditor: &Pubkey, receipts: &[Pubkey], final_batch: bool) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::AuditRoyalties {
+                auditor: *auditor,
+                event: fixture.event,
+                audit: audit_pda(fixture, auditor),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::AuditRoyalties { final_batch },
+            receipts.iter().map(|key| AccountMeta::new_readonly(*key, false)).collect(),
+        ),
+        &[*auditor],
+    )
+}
+
+/// Three tickets bought by different wallets; returns their receipts in address order.
+fn sold_receipts(svm: &mut Svm, fixture: &EventFixture) -> Vec<Pubkey> {
+    let mut receipts: Vec<Pubkey> = (0..3)
+        .map(|_| {
+            let buyer = svm.wallet(10 * SOL);
+            let mint = assert_ok(buy_ticket(svm, fixture, &buyer, 0));
+            receipt_pda(&buyer, &mint)
+        })
+        .collect();
+    receipts.sort();
+    receipts
+}
+
+#[test]
+fn batched_audit_carries_its_total_on_chain() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let receipts = sold_receipts(&mut svm, &fixture);
+    let auditor = svm.wallet(SOL);
+
+    let first = assert_ok(audit(&mut svm, &fixture, &auditor, &receipts[..2], false));
+    assert_eq!(first.event::<AuditResult>().expected_revenue, 2 * SOL / 10);
+    let last = assert_ok(audit(&mut svm, &fixture, &auditor, &receipts[2..], true));
+    let result = last.event::<AuditResult>();
+    assert_eq!(result.expected_revenue, 3 * SOL / 10);
+    assert_eq!(result.discrepancy, 0);
+    assert!(last.events::<AuditDiscrepancyFound>().is_empty());
+    assert!(!svm.exists(&audit_pda(&fixture, &auditor)));
+}
+
+#[test]
+fn audit_rejects_receipts_counted_twice_or_out_of_order() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let receipts = sold_receipts(&mut svm, &fixture);
+    let auditor = svm.wallet(SOL);
+
+    assert_err(
+        audit(&mut svm, &fixture, &auditor, &[receipts[1], receipts[0]], false),
+        ErrorCode::InvalidAuditBatch,
+    );
+    assert_ok(audit(&mut svm, &fixture, &auditor, &receipts[..2], false));
+    // Re-sending a counted receipt in a later batch is caught
+    assert_err(audit(&mut svm, &fixture, &auditor, &receipts[1..], true), ErrorCode::InvalidAuditBatch);
+
+    // A final batch that skips a receipt reports the gap
+    let result = assert_ok(audit(&mut svm, &fixture, &auditor, &[], true));
+    assert_eq!(result.event::<AuditDiscrepancyFound>().discrepancy, (SOL / 10) as i64);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn cancel(svm: &mut Svm, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CancelListing {
+                seller: *seller,
+                ticket_mint: *mint,
+                listing: listing_pda(mint),
+                seller_token_account: ata(seller, mint),
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+                ask_book: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                seller_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelListing {},
+        ),
+        &[*seller],
+    )
+}
+
+/// A seller whose ticket is listed, leaving their ticket account empty.
+fn listed(svm: &mut Svm) -> (Pubkey, Pubkey) {
+    let fixture = create_event(svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(svm, &fixture, &seller, &mint, SOL / 5));
+    (seller, mint)
+}
+
+/// Rent returned by closing the listing and its escrow.
+fn listing_rent(svm: &Svm, mint: &Pubkey) -> u64 {
+    svm.lamports(&listing_pda(mint)) + svm.lamports(&pda(&[b"escrow", mint.as_ref()]))
+}
+
+#[test]
+fn ticket_returns_to_the_existing_account() {
+    let mut svm = setup();
+    let (seller, mint) = listed(&mut svm);
+    let account = ata(&seller, &mint);
+    assert!(svm.exists(&account));
+
+    let (rent, before) = (listing_rent(&svm, &mint), svm.lamports(&seller));
+    assert_ok(cancel(&mut svm, &seller, &mint));
+    assert_eq!(token_balance(&svm, &account), 1);
+    assert_eq!(svm.lamports(&seller), before + rent);
+}
+
+#[test]
+fn missing_account_is_created_for_the_returned_ticket() {
+    let mut svm = setup();
+    let (seller, mint) = listed(&mut svm);
+    let account = ata(&seller, &mint);
+    let close = spl_token::instruction::close_account(&TOKEN_PROGRAM, &account, &seller, &seller, &[]).unwrap();
+    assert_ok(svm.process(&[close], &[seller]));
+    assert!(!svm.exists(&account));
+
+    let (rent, before) = (listing_rent(&svm, &mint), svm.lamports(&seller));
+    assert_ok(cancel(&mut svm, &seller, &mint));
+    assert_eq!(token_balance(&svm, &account), 1);
+    assert_eq!(svm.lamports(&seller), before + rent - svm.lamports(&account));
+}
+
+#[test]
+fn delegated_account_gets_the_ticket_only_after_a_revoke() {
+    let mut svm = setup();
+    let (seller, mint) = listed(&mut svm);
+    let account = ata(&seller, &mint);
+    let other_dapp = svm.wallet(SOL);
+    let approve = spl_token::instruction::approve(&TOKEN_PROGRAM, &account, &other_dapp, &seller, &[], 1).unwrap();
+    assert_ok(svm.process(&[approve], &[seller]));
+
+    assert_err(cancel(&mut svm, &seller, &mint), ErrorCode::DelegateMustBeRevoked);
+    assert_eq!(token_balance(&svm, &pda(&[b"escrow", mint.as_ref()])), 1);
+    let revoke = spl_token::instruction::revoke(&TOKEN_PROGRAM, &account, &seller, &[]).unwrap();
+    assert_ok(svm.process(&[revoke], &[seller]));
+    assert_ok(cancel(&mut svm, &seller, &mint));
+    assert_eq!(token_balance(&svm, &account), 1);
+}
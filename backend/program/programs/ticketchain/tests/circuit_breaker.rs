@@ -0,0 +1,53 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, ResaleCircuitBreakerTriggered, PLATFORM_WALLET};
+
+fn reset(svm: &mut Svm, fixture: &EventFixture, authority: &Pubkey, with_config: bool) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ResetCircuitBreaker {
+                authority: *authority,
+                event: fixture.event,
+                platform_config: with_config.then(platform_config_pda),
+            },
+            ticketchain::instruction::ResetCircuitBreaker {},
+        ),
+        &[*authority],
+    )
+}
+
+#[test]
+fn sale_below_half_the_hourly_high_pauses_resale_until_reset() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    let fixture = create_event(&mut svm);
+    let (seller, buyer, stranger) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mints: Vec<Pubkey> = (0..4).map(|_| assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0))).collect();
+    let prices = [SOL, SOL * 2 / 5, SOL, SOL * 2 / 5];
+    for (mint, price) in mints.iter().zip(prices) {
+        assert_ok(list_ticket(&mut svm, &fixture, &seller, mint, price));
+    }
+
+    assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mints[0], SOL), &[buyer]));
+    // 40% of the hour's high trips the breaker; the sale itself goes through
+    let meta = assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mints[1], SOL), &[buyer]));
+    let tripped = meta.event::<ResaleCircuitBreakerTriggered>();
+    assert_eq!((tripped.event, tripped.price_drop_bps), (fixture.event, 6_000));
+    assert!(event(&svm, &fixture).resale_paused);
+    let blocked = buy_resale_ix(&fixture, &buyer, &seller, &mints[2], SOL);
+    assert_err(svm.send(blocked.clone(), &[buyer]), ErrorCode::ResalePaused);
+
+    assert_err(reset(&mut svm, &fixture, &stranger, true), ErrorCode::Unauthorized);
+    assert_ok(reset(&mut svm, &fixture, &fixture.organizer, false));
+    assert_ok(svm.send(blocked, &[buyer]));
+
+    // Trips again within the same hour; the platform authority can reset it too
+    assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mints[3], SOL), &[buyer]));
+    assert!(event(&svm, &fixture).resale_paused);
+    assert_err(reset(&mut svm, &fixture, &PLATFORM_AUTHORITY, false), ErrorCode::Unauthorized);
+    assert_ok(reset(&mut svm, &fixture, &PLATFORM_AUTHORITY, true));
+    assert!(!event(&svm, &fixture).resale_paused);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+struct Link {
+    fixture: EventFixture,
+    sender: Pubkey,
+    mint: Pubkey,
+    claim_key: ed25519_dalek::Keypair,
+}
+
+fn link_fixture(svm: &mut Svm) -> Link {
+    let fixture = create_event(svm);
+    let sender = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, &fixture, &sender, 0));
+    let claim_key = ed25519_keypair(3);
+    let expires_at = svm.now() + 3600;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateClaimLink {
+                sender,
+                ticket_mint: mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                claim_link: pda(&[b"claim_link", mint.as_ref()]),
+                sender_token_account: ata(&sender, &mint),
+                escrow_token_account: pda(&[b"claim_escrow", mint.as_ref()]),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateClaimLink {
+                claim_key: Pubkey::new_from_array(claim_key.public.to_bytes()),
+                expires_at,
+            },
+        ),
+        &[sender],
+    ));
+    Link { fixture, sender, mint, claim_key }
+}
+
+fn claim(svm: &mut Svm, link: &Link, claimer: &Pubkey, signed_for: &Pubkey, key: &ed25519_dalek::Keypair) -> Result<TxMeta, TxError> {
+    let message = [b"claim_link".as_ref(), link.mint.as_ref(), signed_for.as_ref()].concat();
+    let claim_ix = ix(
+        ticketchain::accounts::ClaimViaLink {
+            claimer: *claimer,
+            sender: link.sender,
+            ticket_mint: link.mint,
+            ticket_flag: pda(&[b"ticket_flag", link.mint.as_ref()]),
+            claim_link: pda(&[b"claim_link", link.mint.as_ref()]),
+            escrow_token_account: pda(&[b"claim_escrow", link.mint.as_ref()]),
+            claimer_token_account: ata(claimer, &link.mint),
+            instructions: instructions_sysvar(),
+            token_program: TOKEN_PROGRAM,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain::instruction::ClaimViaLink {},
+    );
+    svm.process(&[ed25519_instruction(key, &message), claim_ix], &[*claimer])
+}
+
+fn cancel(svm: &mut Svm, link: &Link, caller: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CancelClaimLink {
+                caller: *caller,
+                sender: link.sender,
+                ticket_mint: link.mint,
+                claim_link: pda(&[b"claim_link", link.mint.as_ref()]),
+                sender_token_account: ata(&link.sender, &link.mint),
+                escrow_token_account: pda(&[b"claim_escrow", link.mint.as_ref()]),
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::CancelClaimLink {},
+        ),
+        &[*caller],
+    )
+}
+
+#[test]
+fn claim_key_signature_delivers_the_ticket_once() {
+    let mut svm = setup();
+    let link = link_fixture(&mut svm);
+    let friend = svm.wallet(SOL);
+
+    assert_err(claim(&mut svm, &link, &friend, &friend, &ed25519_keypair(4)), ErrorCode::InvalidClaimSignature);
+    assert_ok(claim(&mut svm, &link, &friend, &friend, &link.claim_key));
+    assert_eq!(token_balance(&svm, &ata(&friend, &link.mint)), 1);
+    assert!(!svm.exists(&pda(&[b"claim_link", link.mint.as_ref()])));
+
+    // The link is closed, so the same signature can't claim again
+    let result = claim(&mut svm, &link, &friend, &friend, &link.claim_key);
+    assert!(result.is_err());
+}
+
+#[test]
+fn observed_claim_cannot_be_redirected_to_another_wallet() {
+    let mut svm = setup();
+    let link = link_fixture(&mut svm);
+    let friend = svm.wallet(SOL);
+    let front_runner = svm.wallet(SOL);
+
+    // The front-runner replays the friend's signed message from their own wallet
+    assert_err(
+        claim(&mut svm, &link, &front_runner, &friend, &link.claim_key),
+        ErrorCode::InvalidClaimSignature,
+    );
+    assert_ok(claim(&mut svm, &link, &friend, &friend, &link.claim_key));
+}
+
+#[test]
+fn expired_link_returns_the_ticket_to_the_sender() {
+    let mut svm = setup();
+    let link = link_fixture(&mut svm);
+    let stranger = svm.wallet(SOL);
+
+    assert_err(cancel(&mut svm, &link, &stranger), ErrorCode::ClaimLinkActive);
+    svm.warp(3600);
+    assert_err(claim(&mut svm, &link, &stranger, &stranger, &link.claim_key), ErrorCode::ClaimLinkExpired);
+    assert_ok(cancel(&mut svm, &link, &stranger));
+    assert_eq!(token_balance(&svm, &ata(&link.sender, &link.mint)), 1);
+}
+
+#[test]
+fn flagged_ticket_cannot_be_claimed_from_a_link() {
+    let mut svm = setup();
+    let link = link_fixture(&mut svm);
+    let friend = svm.wallet(SOL);
+    assert_ok(flag_ticket(&mut svm, &link.fixture, 0));
+
+    assert_err(claim(&mut svm, &link, &friend, &friend, &link.claim_key), ErrorCode::TicketFlagged);
+    // The sender can still take it back
+    assert_ok(cancel(&mut svm, &link, &link.sender));
+    assert_eq!(token_balance(&svm, &ata(&link.sender, &link.mint)), 1);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn cleanup_authority() -> Pubkey {
+    pda(&[b"cleanup_authority"])
+}
+
+/// Sets the cleanup PDA as close authority on the owner's account.
+fn approve_cleanup(svm: &mut Svm, owner: &Pubkey, account: &Pubkey) {
+    let approve = spl_token::instruction::set_authority(
+        &TOKEN_PROGRAM,
+        account,
+        Some(&cleanup_authority()),
+        spl_token::instruction::AuthorityType::CloseAccount,
+        owner,
+        &[],
+    )
+    .unwrap();
+    assert_ok(svm.process(&[approve], &[*owner]));
+}
+
+fn cleanup(svm: &mut Svm, cranker: &Pubkey, pairs: &[(Pubkey, Pubkey)]) -> Result<TxMeta, TxError> {
+    let remaining = pairs
+        .iter()
+        .flat_map(|(account, owner)| [AccountMeta::new(*account, false), AccountMeta::new(*owner, false)])
+        .collect();
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::CleanupAccounts {
+                cranker: *cranker,
+                cleanup_authority: cleanup_authority(),
+                token_program: TOKEN_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CleanupAccounts {},
+            remaining,
+        ),
+        &[*cranker],
+    )
+}
+
+#[test]
+fn approved_empty_account_is_closed_and_rent_split() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, friend, cranker) = (svm.wallet(10 * SOL), svm.wallet(SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(transfer_ticket(&mut svm, &holder, &friend, &mint));
+    let account = ata(&holder, &mint);
+    approve_cleanup(&mut svm, &holder, &account);
+
+    let rent = svm.lamports(&account);
+    let (holder_before, cranker_before) = (svm.lamports(&holder), svm.lamports(&cranker));
+    assert_ok(cleanup(&mut svm, &cranker, &[(account, holder)]));
+    assert!(!svm.exists(&account));
+    let tip = rent * ticketchain::CLEANUP_TIP_BPS / 10_000;
+    assert_eq!(svm.lamports(&holder) - holder_before, rent - tip);
+    assert_eq!(svm.lamports(&cranker) - cranker_before, tip);
+}
+
+#[test]
+fn funded_or_unapproved_accounts_are_never_closed() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, seller, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+
+    // A funded ticket account, even with a standing approval
+    let held = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let held_account = ata(&holder, &held);
+    approve_cleanup(&mut svm, &holder, &held_account);
+    assert_err(cleanup(&mut svm, &cranker, &[(held_account, holder)]), ErrorCode::InvalidCleanupAccount);
+
+    // A live listing's escrow holding the ticket
+    let listed = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &listed, SOL));
+    let escrow = pda(&[b"escrow", listed.as_ref()]);
+    assert_err(cleanup(&mut svm, &cranker, &[(escrow, listing_pda(&listed))]), ErrorCode::InvalidCleanupAccount);
+
+    // An empty account whose owner never approved cleanup
+    let friend = svm.wallet(SOL);
+    assert_ok(transfer_ticket(&mut svm, &holder, &friend, &held));
+    let unapproved = ata(&seller, &listed);
+    assert_eq!(token_balance(&svm, &unapproved), 0);
+    assert_err(cleanup(&mut svm, &cranker, &[(unapproved, seller)]), ErrorCode::InvalidCleanupAccount);
+
+    // One bad pair fails the whole batch
+    assert_err(
+        cleanup(&mut svm, &cranker, &[(held_account, holder), (escrow, listing_pda(&listed))]),
+        ErrorCode::InvalidCleanupAccount,
+    );
+    assert!(svm.exists(&held_account) && svm.exists(&escrow));
+}
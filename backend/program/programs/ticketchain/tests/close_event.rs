@@ -0,0 +1,92 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn close_event(svm: &mut Svm, fixture: &EventFixture, vault: Option<Pubkey>) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CloseEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CloseEvent {},
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn event_with_a_sale_stays_open() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &buyer, &mint, SOL / 5));
+
+    assert_err(close_event(&mut svm, &fixture, None), ErrorCode::EventHasSales);
+    assert!(svm.exists(&fixture.event));
+    assert_eq!(svm.get::<ticketchain::Listing>(&listing_pda(&mint)).event, fixture.event);
+}
+
+#[test]
+fn unsold_event_closes_to_the_organizer() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (rent, before) = (svm.lamports(&fixture.event), svm.lamports(&fixture.organizer));
+    assert_ok(close_event(&mut svm, &fixture, None));
+    assert!(!svm.exists(&fixture.event));
+    assert_eq!(svm.lamports(&fixture.organizer), before + rent);
+}
+
+#[test]
+fn cancelled_event_closes_once_every_ticket_is_refunded() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let vault = vault_pda(&fixture.event);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    assert_err(close_event(&mut svm, &fixture, Some(vault)), ErrorCode::EventHasSales);
+
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: buyer,
+                event: fixture.event,
+                vault,
+                ticket_mint: mint,
+                receipt: receipt_pda(&buyer, &mint),
+                holder_token_account: ata(&buyer, &mint),
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 0 },
+        ),
+        &[buyer],
+    ));
+    assert_ok(close_event(&mut svm, &fixture, Some(vault)));
+    assert!(!svm.exists(&fixture.event) && !svm.exists(&vault));
+}
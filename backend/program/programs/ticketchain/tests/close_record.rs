@@ -0,0 +1,48 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn close_record(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let cranker = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::CloseTicketRecord {
+                buyer: *buyer,
+                event: fixture.event,
+                ticket_mint: *mint,
+                receipt: receipt_pda(buyer, mint),
+            },
+            ticketchain::instruction::CloseTicketRecord {},
+        ),
+        &[cranker],
+    )
+}
+
+fn burn(svm: &mut Svm, holder: &Pubkey, mint: &Pubkey) {
+    let burn = spl_token::instruction::burn(&TOKEN_PROGRAM, &ata(holder, mint), mint, holder, &[], 1).unwrap();
+    assert_ok(svm.process(&[burn], &[*holder]));
+}
+
+#[test]
+fn burned_ticket_record_closes_to_its_buyer() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (buyer, friend) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_err(close_record(&mut svm, &fixture, &buyer, &mint), ErrorCode::TicketStillLive);
+
+    // Wherever the ticket went, the buyer who paid the rent gets it back once it's burned
+    assert_ok(transfer_ticket(&mut svm, &buyer, &friend, &mint));
+    assert_err(close_record(&mut svm, &fixture, &buyer, &mint), ErrorCode::TicketStillLive);
+    burn(&mut svm, &friend, &mint);
+    let rent = svm.lamports(&receipt_pda(&buyer, &mint));
+    let before = svm.lamports(&buyer);
+    assert_ok(close_record(&mut svm, &fixture, &buyer, &mint));
+    assert!(!svm.exists(&receipt_pda(&buyer, &mint)));
+    assert_eq!(svm.lamports(&buyer), before + rent);
+    assert_eq!(event(&svm, &fixture).closed_receipt_revenue, SOL / 10);
+    assert!(close_record(&mut svm, &fixture, &buyer, &mint).is_err());
+}
@@ -0,0 +1,619 @@
+//! Shared fixtures for the TicketChain integration tests.
+#![allow(dead_code)]
+
+pub mod pnft;
+pub mod svm;
+
+pub use svm::*;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    system_instruction, system_program, sysvar,
+};
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, spl_associated_token_account};
+use anchor_spl::token_2022::spl_token_2022;
+use spl_token_2022::extension::StateWithExtensions;
+use ticketchain::{TierConfig, TOKEN_METADATA_PROGRAM_ID};
+
+pub const SOL: u64 = 1_000_000_000;
+/// Ticket mints and ATAs live under the legacy Token program, which the contexts
+/// without an explicit `token_program` derive associated addresses with.
+pub const TOKEN_PROGRAM: Pubkey = anchor_spl::token::spl_token::ID;
+pub const ATA_PROGRAM: Pubkey = spl_associated_token_account::ID;
+/// Authority of the PlatformConfig that `setup` installs.
+pub const PLATFORM_AUTHORITY: Pubkey = Pubkey::new_from_array([0xAA; 32]);
+
+/// A runtime with TicketChain, both token programs, the ATA program and a Token
+/// Metadata stub.
+pub fn setup() -> Svm {
+    let mut svm = Svm::new();
+    svm.add_program(ticketchain::ID, ticketchain::entry);
+    svm.add_program(TOKEN_PROGRAM, anchor_spl::token::spl_token::processor::Processor::process);
+    svm.add_program(spl_token_2022::ID, spl_token_2022::processor::Processor::process);
+    svm.add_program(ATA_PROGRAM, spl_associated_token_account::processor::process_instruction);
+    svm.add_program(TOKEN_METADATA_PROGRAM_ID, token_metadata_stub);
+    install_platform_config(&mut svm);
+    svm
+}
+
+/// Writes the PlatformConfig init_platform_config would create, with its defaults,
+/// since the upgrade-authority check needs a real ProgramData account.
+fn install_platform_config(svm: &mut Svm) {
+    let (key, bump) = Pubkey::find_program_address(&[b"platform_config"], &ticketchain::ID);
+    let config = ticketchain::PlatformConfig {
+        authority: PLATFORM_AUTHORITY,
+        max_ticket_price: ticketchain::MAX_TICKET_PRICE_LAMPORTS,
+        min_price_update_interval_secs: ticketchain::DEFAULT_PRICE_UPDATE_INTERVAL_SECS,
+        attestor_key: Pubkey::default(),
+        monitor_reward_fund: 0,
+        royalty_release_delay_seconds: ticketchain::DEFAULT_ROYALTY_RELEASE_DELAY_SECONDS,
+        bump,
+        messaging_program: None,
+        personhood_provider: ticketchain::PERSONHOOD_CIVIC,
+        personhood_network: Pubkey::default(),
+        resale_fees_collected: 0,
+        resale_fee_count: 0,
+    };
+    let mut data = Vec::new();
+    anchor_lang::AccountSerialize::try_serialize(&config, &mut data).unwrap();
+    data.resize(ticketchain::PLATFORM_CONFIG_LEN, 0);
+    let lamports = anchor_lang::solana_program::rent::Rent::default().minimum_balance(data.len());
+    svm.set_account(key, Account { lamports, data, owner: ticketchain::ID, executable: false });
+}
+
+/// Token Metadata stand-in for CreateMetadataAccountV3: checks the PDA and the mint
+/// authority's signature, then stores `[4, update_authority, mint, (name, symbol, uri)]`
+/// zero-padded to the size Token Metadata allocates. The pNFT instructions are handled
+/// in `pnft`.
+fn token_metadata_stub(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.first() != Some(&ticketchain::CREATE_METADATA_ACCOUNT_V3) {
+        return pnft::process(program_id, accounts, data);
+    }
+    let [metadata, mint, mint_authority, payer, update_authority, system] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let seeds: &[&[u8]] = &[b"metadata", program_id.as_ref(), mint.key.as_ref()];
+    let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+    if *metadata.key != expected || !metadata.data_is_empty() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let authority = {
+        let mint_data = mint.data.borrow();
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        state.base.mint_authority
+    };
+    if !mint_authority.is_signer || authority != Some(*mint_authority.key).into() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut cursor = &data[1..];
+    let (name, symbol, uri): (String, String, String) =
+        anchor_lang::AnchorDeserialize::deserialize(&mut cursor).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let mut stored = vec![4u8];
+    stored.extend_from_slice(update_authority.key.as_ref());
+    stored.extend_from_slice(mint.key.as_ref());
+    (name, symbol, uri).serialize(&mut stored).unwrap();
+    stored.resize(ticketchain::TOKEN_METADATA_ACCOUNT_LEN, 0);
+
+    let rent = anchor_lang::solana_program::rent::Rent::default().minimum_balance(stored.len());
+    invoke_signed(
+        &system_instruction::create_account(payer.key, metadata.key, rent, stored.len() as u64, program_id),
+        &[payer.clone(), metadata.clone(), system.clone()],
+        &[&[b"metadata", program_id.as_ref(), mint.key.as_ref(), &[bump]]],
+    )?;
+    metadata.data.borrow_mut().copy_from_slice(&stored);
+    Ok(())
+}
+
+/// (name, symbol, uri) stored by the Token Metadata stub for `mint`.
+pub fn ticket_metadata(svm: &Svm, mint: &Pubkey) -> Option<(String, String, String)> {
+    let account = svm.account(&metadata_pda(mint))?;
+    anchor_lang::AnchorDeserialize::deserialize(&mut &account.data[65..]).ok()
+}
+
+pub fn assert_err<T: std::fmt::Debug>(result: Result<T, TxError>, code: impl Into<u32>) {
+    let code = code.into();
+    match result {
+        Ok(ok) => panic!("expected error {code}, transaction succeeded: {ok:?}"),
+        Err(err) => assert_eq!(err.custom_code(), Some(code), "unexpected error; logs: {:#?}", err.logs),
+    }
+}
+
+pub fn assert_ok<T>(result: Result<T, TxError>) -> T {
+    result.unwrap_or_else(|err| panic!("transaction failed: {:?}; logs: {:#?}", err.error, err.logs))
+}
+
+/// Builds an Anchor instruction for TicketChain from its generated account and
+/// argument structs.
+pub fn ix(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction { program_id: ticketchain::ID, accounts: accounts.to_account_metas(None), data: args.data() }
+}
+
+/// As `ix`, with extra accounts appended as remaining accounts.
+pub fn ix_with_remaining(accounts: impl ToAccountMetas, args: impl InstructionData, remaining: Vec<AccountMeta>) -> Instruction {
+    let mut instruction = ix(accounts, args);
+    instruction.accounts.extend(remaining);
+    instruction
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &ticketchain::ID).0
+}
+
+pub fn event_pda(organizer: &Pubkey, nonce: u64) -> Pubkey {
+    pda(&[b"event", organizer.as_ref(), &nonce.to_le_bytes()])
+}
+
+pub fn ticket_mint_pda(event: &Pubkey, index: u32) -> Pubkey {
+    pda(&[b"ticket_mint", event.as_ref(), &index.to_le_bytes()])
+}
+
+/// Index of `mint` among `event`'s ticket mints, for instructions that take it as an arg.
+pub fn ticket_index(event: &Pubkey, mint: &Pubkey) -> u32 {
+    (0..1_000).find(|&index| ticket_mint_pda(event, index) == *mint).expect("not a ticket mint of this event")
+}
+
+pub fn ticket_authority_pda(event: &Pubkey, index: u32) -> Pubkey {
+    pda(&[b"ticket_authority", event.as_ref(), &index.to_le_bytes()])
+}
+
+pub fn receipt_pda(buyer: &Pubkey, mint: &Pubkey) -> Pubkey {
+    pda(&[b"receipt", buyer.as_ref(), mint.as_ref()])
+}
+
+pub fn vault_pda(event: &Pubkey) -> Pubkey {
+    pda(&[b"vault", event.as_ref()])
+}
+
+pub fn platform_config_pda() -> Pubkey {
+    pda(&[b"platform_config"])
+}
+
+pub fn listing_pda(mint: &Pubkey) -> Pubkey {
+    pda(&[b"listing", mint.as_ref()])
+}
+
+pub fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &TOKEN_PROGRAM)
+}
+
+/// Moves a ticket between wallets with a plain token transfer, creating the
+/// recipient's associated account.
+pub fn transfer_ticket(svm: &mut Svm, from: &Pubkey, to: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let create = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        from,
+        to,
+        mint,
+        &TOKEN_PROGRAM,
+    );
+    let transfer =
+        anchor_spl::token::spl_token::instruction::transfer(&TOKEN_PROGRAM, &ata(from, mint), &ata(to, mint), from, &[], 1)
+            .unwrap();
+    svm.process(&[create, transfer], &[*from])
+}
+
+/// Token balance of a token account, or 0 when it does not exist.
+pub fn token_balance(svm: &Svm, account: &Pubkey) -> u64 {
+    svm.account(account).map_or(0, |a| {
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&a.data).map_or(0, |s| s.base.amount)
+    })
+}
+
+/// Decoded state of a mint account.
+pub fn mint_state(svm: &Svm, mint: &Pubkey) -> spl_token_2022::state::Mint {
+    StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&svm.account(mint).unwrap().data).unwrap().base
+}
+
+/// Writes an initialized mint directly into the store.
+pub fn create_mint(svm: &mut Svm, authority: &Pubkey, decimals: u8) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let mut data = vec![0u8; spl_token_2022::state::Mint::LEN];
+    spl_token_2022::state::Mint {
+        mint_authority: Some(*authority).into(),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    }
+    .pack_into_slice(&mut data);
+    let lamports = anchor_lang::solana_program::rent::Rent::default().minimum_balance(data.len());
+    svm.set_account(key, Account { lamports, data, owner: TOKEN_PROGRAM, executable: false });
+    key
+}
+
+/// Writes an initialized token account for `owner` holding `amount` of `mint`,
+/// at the owner's associated address.
+pub fn create_token_account(svm: &mut Svm, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+    let key = ata(owner, mint);
+    let mut data = vec![0u8; spl_token_2022::state::Account::LEN];
+    spl_token_2022::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: None.into(),
+        state: spl_token_2022::state::AccountState::Initialized,
+        is_native: None.into(),
+        delegated_amount: 0,
+        close_authority: None.into(),
+    }
+    .pack_into_slice(&mut data);
+    let lamports = anchor_lang::solana_program::rent::Rent::default().minimum_balance(data.len());
+    svm.set_account(key, Account { lamports, data, owner: TOKEN_PROGRAM, executable: false });
+    let mut mint_account = svm.account(mint).expect("mint exists").clone();
+    let mut state = spl_token_2022::state::Mint::unpack_from_slice(&mint_account.data).unwrap();
+    state.supply += amount;
+    state.pack_into_slice(&mut mint_account.data);
+    svm.set_account(*mint, mint_account);
+    key
+}
+
+pub fn tier(price_lamports: u64, supply: u32) -> TierConfig {
+    TierConfig { name: "GA".to_string(), price_lamports, supply }
+}
+
+/// An event created by `organizer`.
+#[derive(Clone, Copy, Debug)]
+pub struct EventFixture {
+    pub organizer: Pubkey,
+    pub event: Pubkey,
+    pub nonce: u64,
+}
+
+/// create_event arguments with defaults: one 0.1 SOL tier of 10 tickets, a week out.
+pub struct EventArgs {
+    pub title: String,
+    pub venue: String,
+    pub date_ts: i64,
+    pub tiers: Vec<TierConfig>,
+    pub artist_pct: u8,
+    pub sale_start_ts: Option<i64>,
+    pub sale_end_ts: Option<i64>,
+    pub max_per_wallet: u16,
+    pub metadata_uri: String,
+}
+
+impl EventArgs {
+    pub fn new(svm: &Svm) -> Self {
+        Self {
+            title: "Launch Night".to_string(),
+            venue: "Main Hall".to_string(),
+            date_ts: svm.now() + 7 * 24 * 60 * 60,
+            tiers: vec![tier(SOL / 10, 10)],
+            artist_pct: 10,
+            sale_start_ts: None,
+            sale_end_ts: None,
+            max_per_wallet: 0,
+            metadata_uri: String::new(),
+        }
+    }
+}
+
+pub fn create_event_ix(svm: &Svm, organizer: &Pubkey, nonce: u64, args: EventArgs) -> Instruction {
+    let _ = svm;
+    ix(
+        ticketchain::accounts::CreateEvent {
+            organizer: *organizer,
+            event: event_pda(organizer, nonce),
+            platform_config: platform_config_pda(),
+            split_template: None,
+            system_program: system_program::ID,
+        },
+        ticketchain::instruction::CreateEvent {
+            nonce,
+            title: args.title,
+            venue: args.venue,
+            date_ts: args.date_ts,
+            tiers: args.tiers,
+            artist_pct: args.artist_pct,
+            sale_start_ts: args.sale_start_ts,
+            sale_end_ts: args.sale_end_ts,
+            max_per_wallet: args.max_per_wallet,
+            metadata_uri: args.metadata_uri,
+        },
+    )
+}
+
+pub fn create_event_with(svm: &mut Svm, args: EventArgs) -> EventFixture {
+    let organizer = svm.wallet(100 * SOL);
+    let nonce = 1;
+    let instruction = create_event_ix(svm, &organizer, nonce, args);
+    assert_ok(svm.send(instruction, &[organizer]));
+    EventFixture { organizer, event: event_pda(&organizer, nonce), nonce }
+}
+
+pub fn create_event(svm: &mut Svm) -> EventFixture {
+    let args = EventArgs::new(svm);
+    create_event_with(svm, args)
+}
+
+pub fn event(svm: &Svm, fixture: &EventFixture) -> ticketchain::Event {
+    svm.get(&fixture.event)
+}
+
+/// buy_ticket accounts for ticket `index`, with every optional account unset.
+pub fn buy_ticket_accounts(fixture: &EventFixture, buyer: &Pubkey, index: u32) -> ticketchain::accounts::BuyTicket {
+    let event = fixture.event;
+    let mint = ticket_mint_pda(&event, index);
+    ticketchain::accounts::BuyTicket {
+        buyer: *buyer,
+        organizer: fixture.organizer,
+        event,
+        ticket_authority: ticket_authority_pda(&event, index),
+        ticket_mint: mint,
+        buyer_token_account: ata(buyer, &mint),
+        tax_wallet: None,
+        donation_wallet: None,
+        vault: None,
+        instructions: None,
+        platform_config: None,
+        fan_token_mint: None,
+        fan_token_authority: None,
+        buyer_fan_token_account: None,
+        payment_mint: None,
+        buyer_payment_account: None,
+        payment_destination: None,
+        receipt: receipt_pda(buyer, &mint),
+        idempotency_key: pda(&[b"idem", buyer.as_ref(), event.as_ref(), &index.to_le_bytes()]),
+        rate_limit: pda(&[b"rate_limit", buyer.as_ref()]),
+        buyer_record: None,
+        deposit_escrow: None,
+        personhood_credential: None,
+        person_purchase: None,
+        identity_purchase: None,
+        organizer_ban: pda(&[b"org_ban", fixture.organizer.as_ref(), buyer.as_ref()]),
+        name_override: name_override_pda(&event, index),
+        ticket_metadata: None,
+        token_metadata_program: None,
+        ticket_edition: None,
+        ticket_token_record: None,
+        accessible_approval: None,
+        token_program: TOKEN_PROGRAM,
+        associated_token_program: ATA_PROGRAM,
+        system_program: system_program::ID,
+    }
+}
+
+pub fn buy_ticket_args(index: u32, tier_index: u8) -> ticketchain::instruction::BuyTicket {
+    ticketchain::instruction::BuyTicket {
+        ticket_index: index,
+        tier_index,
+        category: None,
+        tip_lamports: 0,
+        allow_large_tip: false,
+        payment_index: None,
+    }
+}
+
+/// Buys the event's next ticket in `tier_index` for `buyer`; returns its mint.
+pub fn buy_ticket(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, tier_index: u8) -> Result<Pubkey, TxError> {
+    let index = event(svm, fixture).sold;
+    let accounts = buy_ticket_accounts(fixture, buyer, index);
+    svm.send(ix(accounts, buy_ticket_args(index, tier_index)), &[*buyer])?;
+    Ok(ticket_mint_pda(&fixture.event, index))
+}
+
+pub fn list_accounts(fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey) -> ticketchain::accounts::ListForResale {
+    ticketchain::accounts::ListForResale {
+        seller: *seller,
+        event: fixture.event,
+        ticket_mint: *mint,
+        ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+        listing: listing_pda(mint),
+        seller_token_account: ata(seller, mint),
+        escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+        price_feed: None,
+        ask_book: None,
+        receipt: None,
+        purchase_receipt: None,
+        ticket_metadata: None,
+        ticket_edition: None,
+        seller_token_record: None,
+        instructions: None,
+        token_metadata_program: None,
+        token_program: TOKEN_PROGRAM,
+        associated_token_program: ATA_PROGRAM,
+        system_program: system_program::ID,
+    }
+}
+
+/// list_for_resale args for `mint` at `price_lamports`.
+pub fn list_args(fixture: &EventFixture, mint: &Pubkey, price_lamports: u64) -> ticketchain::instruction::ListForResale {
+    ticketchain::instruction::ListForResale { _ticket_index: ticket_index(&fixture.event, mint), price_lamports }
+}
+
+/// Lists `mint` for resale at `price_lamports` with no optional accounts.
+pub fn list_ticket(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey, price_lamports: u64) -> Result<TxMeta, TxError> {
+    svm.send(ix(list_accounts(fixture, seller, mint), list_args(fixture, mint, price_lamports)), &[*seller])
+}
+
+/// buy_resale accounts for `mint`'s listing, with no optional accounts.
+pub fn buy_resale_accounts(fixture: &EventFixture, buyer: &Pubkey, seller: &Pubkey, mint: &Pubkey) -> ticketchain::accounts::BuyResale {
+    let event = fixture.event;
+    ticketchain::accounts::BuyResale {
+        buyer: *buyer,
+        seller: *seller,
+        organizer: fixture.organizer,
+        platform: ticketchain::PLATFORM_WALLET,
+        platform_config: platform_config_pda(),
+        fee_day: None,
+        event,
+        royalty_escrow: None,
+        ticket_mint: *mint,
+        ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+        listing: listing_pda(mint),
+        price_feed: None,
+        ask_book: None,
+        deposit_receipt: None,
+        escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+        buyer_token_account: ata(buyer, mint),
+        ticket_stats: pda(&[b"ticket_stats", event.as_ref()]),
+        floor_monitor: pda(&[b"floor_monitor", event.as_ref()]),
+        ticket_metadata: None,
+        ticket_edition: None,
+        seller_token_account: None,
+        seller_token_record: None,
+        escrow_token_record: None,
+        buyer_token_record: None,
+        instructions: None,
+        token_metadata_program: None,
+        token_program: TOKEN_PROGRAM,
+        associated_token_program: ATA_PROGRAM,
+        system_program: system_program::ID,
+    }
+}
+
+pub fn buy_resale_ix(fixture: &EventFixture, buyer: &Pubkey, seller: &Pubkey, mint: &Pubkey, max_lamports: u64) -> Instruction {
+    ix(
+        buy_resale_accounts(fixture, buyer, seller, mint),
+        ticketchain::instruction::BuyResale { max_lamports },
+    )
+}
+
+/// flag_ticket accounts for ticket `index`, with no listing to cancel.
+pub fn flag_accounts(fixture: &EventFixture, index: u32) -> ticketchain::accounts::FlagTicket {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    ticketchain::accounts::FlagTicket {
+        organizer: fixture.organizer,
+        event: fixture.event,
+        ticket_mint: mint,
+        ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+        attendance_record: pda(&[b"attendance", mint.as_ref()]),
+        listing: listing_pda(&mint),
+        escrow_token_account: None,
+        seller: None,
+        seller_token_account: None,
+        token_program: TOKEN_PROGRAM,
+        system_program: system_program::ID,
+    }
+}
+
+/// Organizer flags ticket `index` (not listed) with reason code 1.
+pub fn flag_ticket(svm: &mut Svm, fixture: &EventFixture, index: u32) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(flag_accounts(fixture, index), ticketchain::instruction::FlagTicket { _ticket_index: index, reason_code: 1 }),
+        &[fixture.organizer],
+    )
+}
+
+pub fn name_override_pda(event: &Pubkey, index: u32) -> Pubkey {
+    pda(&[b"name_override", event.as_ref(), &index.to_le_bytes()])
+}
+
+pub fn set_ticket_name(svm: &mut Svm, fixture: &EventFixture, index: u32, name: &str) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetTicketName {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                name_override: name_override_pda(&fixture.event, index),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetTicketName { ticket_index: index, name: name.to_string() },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+pub fn check_in_accounts(fixture: &EventFixture, holder: &Pubkey, buyer: &Pubkey, index: u32) -> ticketchain::accounts::CheckIn {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    ticketchain::accounts::CheckIn {
+        organizer: fixture.organizer,
+        holder: *holder,
+        event: fixture.event,
+        ticket_mint: mint,
+        ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+        organizer_ban: pda(&[b"org_ban", fixture.organizer.as_ref(), holder.as_ref()]),
+        holder_token_account: ata(holder, &mint),
+        receipt: Some(receipt_pda(buyer, &mint)),
+        attendance_record: pda(&[b"attendance", mint.as_ref()]),
+        system_program: system_program::ID,
+    }
+}
+
+/// Checks in ticket `index`, held by the wallet that bought it.
+pub fn check_in(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let accounts = check_in_accounts(fixture, holder, holder, index);
+    svm.send(
+        ix(accounts, ticketchain::instruction::CheckIn { ticket_index: index }),
+        &[fixture.organizer, *holder],
+    )
+}
+
+pub fn entry_delegate_pda(mint: &Pubkey, holder: &Pubkey) -> Pubkey {
+    pda(&[b"entry_delegate", mint.as_ref(), holder.as_ref()])
+}
+
+/// Lets `delegate` present ticket `index` for `holder`, signed by `signer`.
+pub fn delegate_entry(
+    svm: &mut Svm,
+    fixture: &EventFixture,
+    signer: &Pubkey,
+    delegate: &Pubkey,
+    index: u32,
+    valid_hours: u8,
+) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::DelegateEntryRights {
+                holder: *signer,
+                ticket_mint: mint,
+                holder_token_account: ata(signer, &mint),
+                entry_delegate: entry_delegate_pda(&mint, signer),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::DelegateEntryRights { delegate: *delegate, valid_hours },
+        ),
+        &[*signer],
+    )
+}
+
+/// Checks in ticket `index`, bought and held by `holder`, with `delegate` signing.
+pub fn check_in_delegate(
+    svm: &mut Svm,
+    fixture: &EventFixture,
+    delegate: &Pubkey,
+    holder: &Pubkey,
+    index: u32,
+) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::CheckInDelegate {
+                organizer: fixture.organizer,
+                delegate: *delegate,
+                holder: *holder,
+                event: fixture.event,
+                ticket_mint: mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                organizer_ban: pda(&[b"org_ban", fixture.organizer.as_ref(), holder.as_ref()]),
+                holder_token_account: ata(holder, &mint),
+                entry_delegate: entry_delegate_pda(&mint, holder),
+                receipt: Some(receipt_pda(holder, &mint)),
+                attendance_record: pda(&[b"attendance", mint.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CheckInDelegate { ticket_index: index },
+        ),
+        &[fixture.organizer, *delegate],
+    )
+}
+
+/// The instructions sysvar, for accounts that read it.
+pub fn instructions_sysvar() -> Pubkey {
+    sysvar::instructions::ID
+}
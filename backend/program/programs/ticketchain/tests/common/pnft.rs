@@ -0,0 +1,365 @@
+//! Token Metadata stand-in for the programmable NFT (pNFT) instructions TicketChain
+//! calls: `create_v1`, `mint_v1`, `delegate_v1`, `revoke_v1`, `lock_v1`,
+//! `unlock_v1`, `transfer_v1` and `burn_v1`. It keeps a TokenRecord per token account,
+//! enforces the lock and the locked-transfer delegate's destination, and moves tokens
+//! with the real token program. Token accounts aren't frozen.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    rent::Rent,
+    system_instruction,
+};
+use anchor_lang::AnchorSerialize;
+use anchor_spl::token_2022::spl_token_2022;
+use spl_token_2022::extension::StateWithExtensions;
+use ticketchain::{LOCKED_TRANSFER_DELEGATE, TOKEN_METADATA_PROGRAM_ID};
+
+use super::Svm;
+
+const TOKEN_RECORD_KEY: u8 = 11;
+const MASTER_EDITION_KEY: u8 = 6;
+const UNLOCKED: u8 = 0;
+const LOCKED: u8 = 1;
+
+pub fn edition_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"edition"],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn token_record_pda(mint: &Pubkey, token: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"token_record", token.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// A TokenRecord as the stub stores it: `[key, state, delegate?, role, locked_address]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenRecord {
+    pub locked: bool,
+    pub delegate: Option<Pubkey>,
+    pub locked_address: Option<Pubkey>,
+}
+
+impl TokenRecord {
+    const EMPTY: Self = Self { locked: false, delegate: None, locked_address: None };
+
+    fn read(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.data.borrow();
+        if data.first() != Some(&TOKEN_RECORD_KEY) {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(Self::decode(&data))
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        let key = |at: usize| Pubkey::new_from_array(data[at..at + 32].try_into().unwrap());
+        Self {
+            locked: data[1] == LOCKED,
+            delegate: (data[2] == 1).then(|| key(3)),
+            locked_address: (data[35] == LOCKED_TRANSFER_DELEGATE).then(|| key(36)),
+        }
+    }
+
+    fn write(&self, account: &AccountInfo) {
+        let mut data = account.data.borrow_mut();
+        data.fill(0);
+        data[0] = TOKEN_RECORD_KEY;
+        data[1] = if self.locked { LOCKED } else { UNLOCKED };
+        if let Some(delegate) = self.delegate {
+            data[2] = 1;
+            data[3..35].copy_from_slice(delegate.as_ref());
+        }
+        if let Some(address) = self.locked_address {
+            data[35] = LOCKED_TRANSFER_DELEGATE;
+            data[36..68].copy_from_slice(address.as_ref());
+        }
+    }
+}
+
+/// The TokenRecord of `token`, a token account for `mint`, if it exists.
+pub fn token_record(svm: &Svm, mint: &Pubkey, token: &Pubkey) -> Option<TokenRecord> {
+    let account = svm.account(&token_record_pda(mint, token))?;
+    (account.data.first() == Some(&TOKEN_RECORD_KEY)).then(|| TokenRecord::decode(&account.data))
+}
+
+pub(super) fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match data.first().copied() {
+        Some(ticketchain::CREATE_V1) => create(program_id, accounts, data),
+        Some(ticketchain::MINT_V1) => mint(program_id, accounts),
+        Some(ticketchain::DELEGATE_V1) => delegate(accounts, data),
+        Some(ticketchain::REVOKE_V1) => revoke(accounts),
+        Some(ticketchain::LOCK_V1) => set_lock(accounts, true),
+        Some(ticketchain::UNLOCK_V1) => set_lock(accounts, false),
+        Some(ticketchain::TRANSFER_V1) => transfer(program_id, accounts),
+        Some(ticketchain::BURN_V1) => burn(accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn account<'a, 'info>(accounts: &'a [AccountInfo<'info>], index: usize) -> Result<&'a AccountInfo<'info>, ProgramError> {
+    accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn token_state(token: &AccountInfo) -> Result<spl_token_2022::state::Account, ProgramError> {
+    let data = token.data.borrow();
+    Ok(StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?.base)
+}
+
+/// Create a `program_id`-owned PDA at `seeds` (bump found here) of `space` bytes.
+fn create_pda<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> ProgramResult {
+    let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+    if *target.key != expected || !target.data_is_empty() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let bump = [bump];
+    let mut signer: Vec<&[u8]> = seeds.to_vec();
+    signer.push(&bump);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            target.key,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), target.clone(), system.clone()],
+        &[&signer],
+    )
+}
+
+fn create_token_record<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    record: &AccountInfo<'info>,
+    system: &AccountInfo<'info>,
+    mint: &AccountInfo,
+    token: &AccountInfo,
+) -> ProgramResult {
+    let seeds: &[&[u8]] = &[b"metadata", program_id.as_ref(), mint.key.as_ref(), b"token_record", token.key.as_ref()];
+    create_pda(program_id, payer, record, system, seeds, ticketchain::TOKEN_RECORD_ACCOUNT_LEN)?;
+    TokenRecord::EMPTY.write(record);
+    Ok(())
+}
+
+fn token_record_of(program_id: &Pubkey, record: &AccountInfo, mint: &AccountInfo, token: &AccountInfo) -> Result<TokenRecord, ProgramError> {
+    let seeds: &[&[u8]] = &[b"metadata", program_id.as_ref(), mint.key.as_ref(), b"token_record", token.key.as_ref()];
+    if *record.key != Pubkey::find_program_address(seeds, program_id).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    TokenRecord::read(record)
+}
+
+/// create_v1: `[metadata, edition, mint, authority, payer, update_authority, system,
+/// sysvar_instructions, token_program]`. Stores the metadata like
+/// CreateMetadataAccountV3, creates the master edition and hands it the mint authority.
+fn create(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [metadata, edition, mint, authority, payer, update_authority, system, _, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let mint_authority = {
+        let mint_data = mint.data.borrow();
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?.base.mint_authority
+    };
+    if !authority.is_signer || mint_authority != COption::Some(*authority.key) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut cursor = &data[2..];
+    let (name, symbol, uri, _fee): (String, String, String, u16) =
+        anchor_lang::AnchorDeserialize::deserialize(&mut cursor).map_err(|_| ProgramError::InvalidInstructionData)?;
+    if cursor.get(3) != Some(&ticketchain::PROGRAMMABLE_NON_FUNGIBLE) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let seeds: &[&[u8]] = &[b"metadata", program_id.as_ref(), mint.key.as_ref()];
+    create_pda(program_id, payer, metadata, system, seeds, ticketchain::TOKEN_METADATA_ACCOUNT_LEN)?;
+    let mut stored = vec![4u8];
+    stored.extend_from_slice(update_authority.key.as_ref());
+    stored.extend_from_slice(mint.key.as_ref());
+    (name, symbol, uri).serialize(&mut stored).unwrap();
+    metadata.data.borrow_mut()[..stored.len()].copy_from_slice(&stored);
+
+    let seeds: &[&[u8]] = &[b"metadata", program_id.as_ref(), mint.key.as_ref(), b"edition"];
+    create_pda(program_id, payer, edition, system, seeds, ticketchain::MASTER_EDITION_ACCOUNT_LEN)?;
+    edition.data.borrow_mut()[0] = MASTER_EDITION_KEY;
+    invoke_signed(
+        &spl_token_2022::instruction::set_authority(
+            token_program.key,
+            mint.key,
+            Some(edition.key),
+            spl_token_2022::instruction::AuthorityType::MintTokens,
+            authority.key,
+            &[],
+        )?,
+        &[mint.clone(), authority.clone(), token_program.clone()],
+        &[],
+    )
+}
+
+/// mint_v1: `[token, token_owner, metadata, edition, token_record, mint, authority,
+/// delegate_record, payer, system, sysvar_instructions, token_program, ...]`. Only the
+/// update authority may mint; the master edition signs as mint authority.
+fn mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let (token, metadata, edition, record, mint) =
+        (account(accounts, 0)?, account(accounts, 2)?, account(accounts, 3)?, account(accounts, 4)?, account(accounts, 5)?);
+    let (authority, payer, system, token_program) =
+        (account(accounts, 6)?, account(accounts, 8)?, account(accounts, 9)?, account(accounts, 11)?);
+    if !authority.is_signer || metadata.data.borrow()[1..33] != authority.key.to_bytes() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    create_token_record(program_id, payer, record, system, mint, token)?;
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"metadata", program_id.as_ref(), mint.key.as_ref(), b"edition"], program_id);
+    invoke_signed(
+        &spl_token_2022::instruction::mint_to(token_program.key, mint.key, token.key, edition.key, &[], 1)?,
+        &[mint.clone(), token.clone(), edition.clone(), token_program.clone()],
+        &[&[b"metadata", program_id.as_ref(), mint.key.as_ref(), b"edition", &[bump]]],
+    )
+}
+
+/// delegate_v1 / revoke_v1: `[delegate_record, delegate, metadata, edition,
+/// token_record, mint, token, authority, payer, system, sysvar_instructions,
+/// token_program, ...]`, signed by the token owner.
+fn delegation<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<[&'a AccountInfo<'info>; 5], ProgramError> {
+    let (delegate, record, mint, token, owner) =
+        (account(accounts, 1)?, account(accounts, 4)?, account(accounts, 5)?, account(accounts, 6)?, account(accounts, 7)?);
+    if !owner.is_signer || token_state(token)?.owner != *owner.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    token_record_of(&TOKEN_METADATA_PROGRAM_ID, record, mint, token)?;
+    Ok([delegate, record, mint, token, owner])
+}
+
+fn delegate(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [delegate, record, _, token, owner] = delegation(accounts)?;
+    if data.get(1) != Some(&LOCKED_TRANSFER_DELEGATE) || data.len() < 42 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let locked_address = Pubkey::new_from_array(data[10..42].try_into().unwrap());
+    TokenRecord { locked: false, delegate: Some(*delegate.key), locked_address: Some(locked_address) }.write(record);
+    let token_program = account(accounts, 11)?;
+    invoke_signed(
+        &spl_token_2022::instruction::approve(token_program.key, token.key, delegate.key, owner.key, &[], 1)?,
+        &[token.clone(), delegate.clone(), owner.clone(), token_program.clone()],
+        &[],
+    )
+}
+
+fn revoke(accounts: &[AccountInfo]) -> ProgramResult {
+    let [_, record, _, token, owner] = delegation(accounts)?;
+    if TokenRecord::read(record)?.locked {
+        return Err(ProgramError::Custom(LOCKED as u32));
+    }
+    TokenRecord::EMPTY.write(record);
+    let token_program = account(accounts, 11)?;
+    invoke_signed(
+        &spl_token_2022::instruction::revoke(token_program.key, token.key, owner.key, &[])?,
+        &[token.clone(), owner.clone(), token_program.clone()],
+        &[],
+    )
+}
+
+/// lock_v1 / unlock_v1: `[authority, token_owner, token, mint, metadata, edition,
+/// token_record, payer, ...]`, signed by the token's locked-transfer delegate.
+fn set_lock(accounts: &[AccountInfo], locked: bool) -> ProgramResult {
+    let (authority, token, mint, record) =
+        (account(accounts, 0)?, account(accounts, 2)?, account(accounts, 3)?, account(accounts, 6)?);
+    let state = token_record_of(&TOKEN_METADATA_PROGRAM_ID, record, mint, token)?;
+    if !authority.is_signer || state.delegate != Some(*authority.key) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    TokenRecord { locked, ..state }.write(record);
+    Ok(())
+}
+
+/// transfer_v1: `[token, token_owner, destination, destination_owner, mint, metadata,
+/// edition, token_record, destination_token_record, authority, payer, system,
+/// sysvar_instructions, token_program, ...]`. The owner or its delegate signs; a
+/// locked ticket can't move and a locked-transfer delegate only moves it to its
+/// locked address. The destination gets a fresh TokenRecord.
+fn transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let (token, owner, destination, destination_owner, mint) =
+        (account(accounts, 0)?, account(accounts, 1)?, account(accounts, 2)?, account(accounts, 3)?, account(accounts, 4)?);
+    let (record, destination_record, authority, payer, system, token_program) = (
+        account(accounts, 7)?,
+        account(accounts, 8)?,
+        account(accounts, 9)?,
+        account(accounts, 10)?,
+        account(accounts, 11)?,
+        account(accounts, 13)?,
+    );
+    let state = token_record_of(program_id, record, mint, token)?;
+    if state.locked {
+        return Err(ProgramError::Custom(LOCKED as u32));
+    }
+    let allowed = *authority.key == *owner.key
+        || (state.delegate == Some(*authority.key)
+            && state.locked_address.is_none_or(|address| address == *destination_owner.key));
+    if !authority.is_signer || !allowed || token_state(token)?.owner != *owner.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    invoke_signed(
+        &spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            token.key,
+            mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            1,
+            0,
+        )?,
+        &[token.clone(), mint.clone(), destination.clone(), authority.clone(), token_program.clone()],
+        &[],
+    )?;
+    TokenRecord::EMPTY.write(record);
+    if destination_record.data_is_empty() {
+        create_token_record(program_id, payer, destination_record, system, mint, destination)?;
+    } else {
+        token_record_of(program_id, destination_record, mint, destination)?;
+        TokenRecord::EMPTY.write(destination_record);
+    }
+    Ok(())
+}
+
+/// burn_v1: `[authority, collection_metadata, metadata, edition, mint, token, ...,
+/// token_record (10), system, sysvar_instructions, token_program]`, signed by the
+/// owner of an unlocked ticket. Closes the TokenRecord to the owner.
+fn burn(accounts: &[AccountInfo]) -> ProgramResult {
+    let (owner, mint, token, record, token_program) =
+        (account(accounts, 0)?, account(accounts, 4)?, account(accounts, 5)?, account(accounts, 10)?, account(accounts, 13)?);
+    let state = token_record_of(&TOKEN_METADATA_PROGRAM_ID, record, mint, token)?;
+    if !owner.is_signer || token_state(token)?.owner != *owner.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if state.locked {
+        return Err(ProgramError::Custom(LOCKED as u32));
+    }
+    invoke_signed(
+        &spl_token_2022::instruction::burn(token_program.key, token.key, mint.key, owner.key, &[], 1)?,
+        &[token.clone(), mint.clone(), owner.clone(), token_program.clone()],
+        &[],
+    )?;
+    **owner.lamports.borrow_mut() += record.lamports();
+    **record.lamports.borrow_mut() = 0;
+    record.realloc(0, false)?;
+    Ok(())
+}
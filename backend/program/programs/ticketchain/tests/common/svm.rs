@@ -0,0 +1,687 @@
+//! A small in-process Solana runtime for integration tests.
+//!
+//! Programs run natively through `program_stubs`: each instruction gets freshly laid
+//! out AccountInfos, CPIs are dispatched to registered processors, and the runtime's
+//! account rules (signer and writable privileges, ownership, lamport conservation,
+//! rent exemption) are checked at every program boundary. Transactions are atomic.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Once;
+
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    clock::Clock,
+    ed25519_program,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    epoch_schedule::EpochSchedule,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::SystemInstruction,
+    system_program,
+    sysvar::{
+        self,
+        instructions::{construct_instructions_data, store_current_index, BorrowedAccountMeta, BorrowedInstruction},
+    },
+};
+
+pub type Processor = for<'a> fn(&Pubkey, &'a [AccountInfo<'a>], &[u8]) -> ProgramResult;
+
+const NATIVE_LOADER_ID: Pubkey = anchor_lang::prelude::pubkey!("NativeLoader1111111111111111111111111111111");
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+#[derive(Debug)]
+pub struct TxError {
+    /// Index of the failing instruction.
+    pub index: usize,
+    pub error: ProgramError,
+    pub logs: Vec<String>,
+}
+
+impl TxError {
+    pub fn custom_code(&self) -> Option<u32> {
+        match self.error {
+            ProgramError::Custom(code) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TxMeta {
+    pub logs: Vec<String>,
+    /// Raw `sol_log_data` payloads, in emission order.
+    pub data: Vec<Vec<u8>>,
+    /// `sol_set_return_data` payload left by the last instruction, if any.
+    pub return_data: Option<Vec<u8>>,
+}
+
+impl TxMeta {
+    /// Anchor events of type `T` emitted by the transaction.
+    pub fn events<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(&self) -> Vec<T> {
+        self.data
+            .iter()
+            .filter(|d| d.len() >= 8 && d[..8] == T::DISCRIMINATOR)
+            .map(|d| T::deserialize(&mut &d[8..]).expect("event decodes"))
+            .collect()
+    }
+
+    pub fn event<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(&self) -> T {
+        self.events::<T>().pop().expect("event emitted")
+    }
+}
+
+#[derive(Clone)]
+struct Snap {
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    writable: bool,
+}
+
+struct Frame {
+    program: Pubkey,
+    snapshot: HashMap<Pubkey, Snap>,
+}
+
+#[derive(Default)]
+struct Runtime {
+    programs: HashMap<Pubkey, Processor>,
+    frames: Vec<Frame>,
+    clock: Clock,
+    logs: Vec<String>,
+    data: Vec<Vec<u8>>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+thread_local! {
+    static RT: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        if std::env::var_os("SVM_LOG").is_some() {
+            println!("{message}");
+        }
+        RT.with(|rt| rt.borrow_mut().logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        RT.with(|rt| rt.borrow_mut().data.extend(fields.iter().map(|f| f.to_vec())));
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = RT.with(|rt| rt.borrow().clock.clone());
+        unsafe { std::ptr::write(var_addr as *mut Clock, clock) };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut Rent, Rent::default()) };
+        0
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut EpochSchedule, EpochSchedule::default()) };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RT.with(|rt| rt.borrow().return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RT.with(|rt| {
+            let mut rt = rt.borrow_mut();
+            let program = rt.frames.last().map(|f| f.program).unwrap_or_default();
+            rt.return_data = (!data.is_empty()).then(|| (program, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        RT.with(|rt| rt.borrow().frames.len() as u64)
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        cpi(instruction, account_infos, signers_seeds)
+    }
+}
+
+fn harness_error(message: String) -> ProgramError {
+    Stubs.sol_log(&format!("svm: {message}"));
+    ProgramError::InvalidAccountData
+}
+
+fn snapshot(infos: &[AccountInfo]) -> HashMap<Pubkey, Snap> {
+    let mut snapshot: HashMap<Pubkey, Snap> = HashMap::new();
+    for info in infos {
+        let entry = snapshot.entry(*info.key).or_insert_with(|| Snap {
+            lamports: info.lamports(),
+            data: info.data.borrow().to_vec(),
+            owner: *info.owner,
+            writable: false,
+        });
+        entry.writable |= info.is_writable;
+    }
+    snapshot
+}
+
+/// Checks the top frame's changes to `infos` since its last checkpoint and moves the
+/// checkpoint's data and owners forward. Lamports keep the frame's baseline, so
+/// conservation is only checked over the frame's full account list, when it returns.
+fn verify_top_frame(infos: &[AccountInfo], full: bool) -> ProgramResult {
+    let (program, pre) = RT.with(|rt| {
+        let rt = rt.borrow();
+        let frame = rt.frames.last().expect("frame");
+        (frame.program, frame.snapshot.clone())
+    });
+    let post = snapshot(infos);
+    let (mut pre_sum, mut post_sum) = (0u128, 0u128);
+    for (key, after) in &post {
+        let Some(before) = pre.get(key) else { continue };
+        pre_sum += before.lamports as u128;
+        post_sum += after.lamports as u128;
+        let data_changed = before.data != after.data;
+        let owner_changed = before.owner != after.owner;
+        let lamports_changed = before.lamports != after.lamports;
+        if !before.writable && (data_changed || owner_changed || lamports_changed) {
+            return Err(harness_error(format!("{program} modified read-only account {key}")));
+        }
+        if (data_changed || owner_changed) && before.owner != program {
+            return Err(harness_error(format!("{program} modified data of {key} it does not own")));
+        }
+        if owner_changed && after.data.iter().any(|b| *b != 0) {
+            return Err(harness_error(format!("{program} reassigned {key} with non-zero data")));
+        }
+        if after.lamports < before.lamports && before.owner != program {
+            return Err(harness_error(format!("{program} debited {key} it does not own")));
+        }
+    }
+    if full && pre_sum != post_sum {
+        return Err(harness_error(format!("{program} did not conserve lamports")));
+    }
+    RT.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        let frame = rt.frames.last_mut().expect("frame");
+        for (key, mut after) in post {
+            if let Some(before) = frame.snapshot.get(&key) {
+                after.writable = before.writable;
+                after.lamports = before.lamports;
+                frame.snapshot.insert(key, after);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn execute<'a>(program_id: &Pubkey, infos: &'a [AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+    let processor = RT
+        .with(|rt| rt.borrow().programs.get(program_id).copied())
+        .ok_or(ProgramError::IncorrectProgramId)?;
+    let frame = Frame { program: *program_id, snapshot: snapshot(infos) };
+    RT.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.frames.push(frame);
+        rt.return_data = None;
+    });
+    let result = processor(program_id, infos, data).and_then(|()| verify_top_frame(infos, true));
+    RT.with(|rt| rt.borrow_mut().frames.pop());
+    result
+}
+
+fn cpi(instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    let caller = RT.with(|rt| rt.borrow().frames.last().expect("caller frame").program);
+    verify_top_frame(account_infos, false)?;
+    let pda_signers: HashSet<Pubkey> = signers_seeds
+        .iter()
+        .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+        .collect::<Result<_, _>>()
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+
+    // Duplicate metas share one account with the union of their privileges
+    let mut flags: HashMap<Pubkey, (bool, bool)> = HashMap::new();
+    for meta in &instruction.accounts {
+        let entry = flags.entry(meta.pubkey).or_default();
+        entry.0 |= meta.is_signer;
+        entry.1 |= meta.is_writable;
+    }
+    let mut callee_infos = Vec::with_capacity(instruction.accounts.len());
+    for meta in &instruction.accounts {
+        let info = account_infos
+            .iter()
+            .find(|info| *info.key == meta.pubkey)
+            .ok_or_else(|| harness_error(format!("CPI account {} missing from account infos", meta.pubkey)))?;
+        let (is_signer, is_writable) = flags[&meta.pubkey];
+        if is_signer && !info.is_signer && !pda_signers.contains(info.key) {
+            return Err(harness_error(format!("signer privilege escalated for {}", info.key)));
+        }
+        if is_writable && !info.is_writable {
+            return Err(harness_error(format!("writable privilege escalated for {}", info.key)));
+        }
+        let mut callee = info.clone();
+        callee.is_signer = is_signer;
+        callee.is_writable = is_writable;
+        callee_infos.push(callee);
+    }
+    let callee_infos: &[AccountInfo] = Box::leak(callee_infos.into_boxed_slice());
+    let at_call = snapshot(account_infos);
+    execute(&instruction.program_id, callee_infos, &instruction.data)?;
+
+    // The callee's changes were checked when it returned; they become the caller's baseline.
+    // Lamports only move by the callee's delta so the caller's own unbalanced moves still count.
+    let post = snapshot(account_infos);
+    RT.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        let frame = rt.frames.last_mut().expect("caller frame");
+        for (key, mut after) in post {
+            if let Some(before) = frame.snapshot.get(&key) {
+                after.writable = before.writable;
+                after.lamports = (before.lamports as i128 + after.lamports as i128
+                    - at_call[&key].lamports as i128) as u64;
+                frame.snapshot.insert(key, after);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Lays an account out the way the loader serializes it, so AccountInfo::realloc and
+/// original_data_len work: a u32 original length before the key, a u64 length before
+/// the data and MAX_PERMITTED_DATA_INCREASE of spare room after it.
+fn leak_account_info(key: Pubkey, account: &Account, is_signer: bool, is_writable: bool) -> AccountInfo<'static> {
+    let key_block: &'static mut [u32] = Box::leak(vec![0u32; 9].into_boxed_slice());
+    key_block[0] = account.data.len() as u32;
+    let key_ptr = unsafe { (key_block.as_mut_ptr() as *mut u8).add(4) };
+    unsafe { std::ptr::copy_nonoverlapping(key.as_ref().as_ptr(), key_ptr, 32) };
+    let key_ref: &'static Pubkey = unsafe { &*(key_ptr as *const Pubkey) };
+
+    let len = account.data.len();
+    let words = 1 + (len + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+    let data_block: &'static mut [u64] = Box::leak(vec![0u64; words].into_boxed_slice());
+    data_block[0] = len as u64;
+    let data_ptr = unsafe { (data_block.as_mut_ptr() as *mut u8).add(8) };
+    unsafe { std::ptr::copy_nonoverlapping(account.data.as_ptr(), data_ptr, len) };
+    let data: &'static mut [u8] = unsafe { std::slice::from_raw_parts_mut(data_ptr, len) };
+
+    let lamports: &'static mut u64 = Box::leak(Box::new(account.lamports));
+    let owner: &'static Pubkey = Box::leak(Box::new(account.owner));
+    AccountInfo {
+        key: key_ref,
+        lamports: Rc::new(RefCell::new(lamports)),
+        data: Rc::new(RefCell::new(data)),
+        owner,
+        rent_epoch: u64::MAX,
+        is_signer,
+        is_writable,
+        executable: account.executable,
+    }
+}
+
+fn system_processor(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let account = |i: usize| accounts.get(i).ok_or(ProgramError::NotEnoughAccountKeys);
+    let allocate = |to: &AccountInfo, space: u64| -> ProgramResult {
+        if !to.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !to.data_is_empty() || *to.owner != system_program::ID {
+            return Err(ProgramError::Custom(0)); // AccountAlreadyInUse
+        }
+        to.realloc(space as usize, true)
+    };
+    let assign = |to: &AccountInfo, owner: &Pubkey| -> ProgramResult {
+        if *to.owner == *owner {
+            return Ok(());
+        }
+        if !to.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        to.assign(owner);
+        Ok(())
+    };
+    let transfer = |from: &AccountInfo, to: &AccountInfo, lamports: u64| -> ProgramResult {
+        if !from.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !from.data_is_empty() || *from.owner != system_program::ID {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if from.lamports() < lamports {
+            return Err(ProgramError::Custom(1)); // ResultWithNegativeLamports
+        }
+        **from.try_borrow_mut_lamports()? -= lamports;
+        **to.try_borrow_mut_lamports()? += lamports;
+        Ok(())
+    };
+    match instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => {
+            let (from, to) = (account(0)?, account(1)?);
+            if to.lamports() > 0 {
+                return Err(ProgramError::Custom(0));
+            }
+            allocate(to, space)?;
+            assign(to, &owner)?;
+            transfer(from, to, lamports)
+        }
+        SystemInstruction::Transfer { lamports } => transfer(account(0)?, account(1)?, lamports),
+        SystemInstruction::Allocate { space } => allocate(account(0)?, space),
+        SystemInstruction::Assign { owner } => assign(account(0)?, &owner),
+        other => panic!("svm: unsupported system instruction {other:?}"),
+    }
+}
+
+/// Checks an Ed25519 precompile instruction whose offsets all point into itself.
+fn verify_ed25519(instruction: &Instruction) -> Result<(), ProgramError> {
+    use ed25519_dalek::Verifier;
+    let data = &instruction.data;
+    let count = *data.first().ok_or(ProgramError::InvalidInstructionData)? as usize;
+    let u16_at = |offset: usize| -> usize { u16::from_le_bytes([data[offset], data[offset + 1]]) as usize };
+    for i in 0..count {
+        let base = 2 + i * 14;
+        let (sig, pk, msg, msg_len) = (u16_at(base), u16_at(base + 4), u16_at(base + 8), u16_at(base + 10));
+        let public = ed25519_dalek::PublicKey::from_bytes(&data[pk..pk + 32])
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        let signature = ed25519_dalek::Signature::try_from(&data[sig..sig + 64])
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        public
+            .verify(&data[msg..msg + msg_len], &signature)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+    }
+    Ok(())
+}
+
+pub struct Svm {
+    pub accounts: HashMap<Pubkey, Account>,
+}
+
+impl Default for Svm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svm {
+    /// A runtime with the System program and the clock at slot 1, unix time 1_700_000_000.
+    pub fn new() -> Self {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+        RT.with(|rt| *rt.borrow_mut() = Runtime::default());
+        let mut svm = Self { accounts: HashMap::new() };
+        svm.add_program_with_loader(system_program::ID, system_processor, NATIVE_LOADER_ID);
+        svm.set_clock(1, 1_700_000_000);
+        svm.set_slot_hash([7; 32]);
+        svm
+    }
+
+    pub fn add_program(&mut self, program_id: Pubkey, processor: Processor) {
+        self.add_program_with_loader(program_id, processor, bpf_loader_upgradeable::ID);
+    }
+
+    fn add_program_with_loader(&mut self, program_id: Pubkey, processor: Processor, loader: Pubkey) {
+        RT.with(|rt| rt.borrow_mut().programs.insert(program_id, processor));
+        self.accounts.insert(
+            program_id,
+            Account { lamports: 1, data: Vec::new(), owner: loader, executable: true },
+        );
+    }
+
+    pub fn clock(&self) -> Clock {
+        RT.with(|rt| rt.borrow().clock.clone())
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock().unix_timestamp
+    }
+
+    pub fn set_clock(&mut self, slot: u64, unix_timestamp: i64) {
+        let clock = Clock { slot, unix_timestamp, ..Clock::default() };
+        self.accounts.insert(
+            sysvar::clock::ID,
+            Account {
+                lamports: 1,
+                data: bincode::serialize(&clock).unwrap(),
+                owner: sysvar::ID,
+                executable: false,
+            },
+        );
+        RT.with(|rt| rt.borrow_mut().clock = clock);
+    }
+
+    /// Moves the clock forward by `seconds`, and the slot by the same amount.
+    pub fn warp(&mut self, seconds: i64) {
+        let clock = self.clock();
+        self.set_clock(clock.slot + seconds.max(0) as u64, clock.unix_timestamp + seconds);
+    }
+
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        let now = self.now();
+        self.warp(unix_timestamp - now);
+    }
+
+    /// Makes `hash` the most recent entry of the SlotHashes sysvar, for the current slot.
+    pub fn set_slot_hash(&mut self, hash: [u8; 32]) {
+        let slot = self.clock().slot;
+        self.set_slot_hashes(&[(slot, hash)]);
+    }
+
+    /// Replaces the SlotHashes sysvar with `entries`, newest first.
+    pub fn set_slot_hashes(&mut self, entries: &[(u64, [u8; 32])]) {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        self.accounts.insert(
+            sysvar::slot_hashes::ID,
+            Account { lamports: 1, data, owner: sysvar::ID, executable: false },
+        );
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        let account = self.accounts.entry(*key).or_insert_with(|| Account {
+            owner: system_program::ID,
+            ..Account::default()
+        });
+        account.lamports += lamports;
+    }
+
+    /// A new system-owned wallet holding `lamports`.
+    pub fn wallet(&mut self, lamports: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.airdrop(&key, lamports);
+        key
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |a| a.lamports)
+    }
+
+    pub fn exists(&self, key: &Pubkey) -> bool {
+        self.accounts.contains_key(key)
+    }
+
+    /// Deserializes an Anchor account.
+    pub fn get<T: anchor_lang::AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self.accounts.get(key).unwrap_or_else(|| panic!("account {key} exists"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")
+    }
+
+    /// Runs `instructions` atomically. Every signer meta must be listed in `signers`.
+    pub fn process(&mut self, instructions: &[Instruction], signers: &[Pubkey]) -> Result<TxMeta, TxError> {
+        RT.with(|rt| {
+            let mut rt = rt.borrow_mut();
+            rt.logs.clear();
+            rt.data.clear();
+            rt.return_data = None;
+        });
+        let backup = self.accounts.clone();
+        let result = self.process_inner(instructions, signers);
+        let (logs, data, return_data) = RT.with(|rt| {
+            let mut rt = rt.borrow_mut();
+            let return_data = rt.return_data.take().map(|(_, data)| data);
+            (std::mem::take(&mut rt.logs), std::mem::take(&mut rt.data), return_data)
+        });
+        match result {
+            Ok(()) => Ok(TxMeta { logs, data, return_data }),
+            Err((index, error)) => {
+                self.accounts = backup;
+                Err(TxError { index, error, logs })
+            }
+        }
+    }
+
+    pub fn send(&mut self, instruction: Instruction, signers: &[Pubkey]) -> Result<TxMeta, TxError> {
+        self.process(&[instruction], signers)
+    }
+
+    fn process_inner(&mut self, instructions: &[Instruction], signers: &[Pubkey]) -> Result<(), (usize, ProgramError)> {
+        let pre_tx = self.accounts.clone();
+        let signers: HashSet<Pubkey> = signers.iter().copied().collect();
+        let borrowed: Vec<BorrowedInstruction> = instructions
+            .iter()
+            .map(|ix| BorrowedInstruction {
+                program_id: &ix.program_id,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|m| BorrowedAccountMeta { pubkey: &m.pubkey, is_signer: m.is_signer, is_writable: m.is_writable })
+                    .collect(),
+                data: &ix.data,
+            })
+            .collect();
+        let instructions_data = construct_instructions_data(&borrowed);
+        let mut touched: HashSet<Pubkey> = HashSet::new();
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            if instruction.program_id == ed25519_program::ID {
+                verify_ed25519(instruction).map_err(|e| (index, e))?;
+                continue;
+            }
+            let mut flags: Vec<(Pubkey, bool, bool)> = Vec::new();
+            for meta in &instruction.accounts {
+                if meta.is_signer && !signers.contains(&meta.pubkey) {
+                    return Err((index, ProgramError::MissingRequiredSignature));
+                }
+                match flags.iter_mut().find(|(key, _, _)| *key == meta.pubkey) {
+                    Some(entry) => {
+                        entry.1 |= meta.is_signer;
+                        entry.2 |= meta.is_writable;
+                    }
+                    None => flags.push((meta.pubkey, meta.is_signer, meta.is_writable)),
+                }
+            }
+            let mut unique: HashMap<Pubkey, AccountInfo<'static>> = HashMap::new();
+            for (key, is_signer, is_writable) in &flags {
+                let account = if *key == sysvar::instructions::ID {
+                    let mut data = instructions_data.clone();
+                    store_current_index(&mut data, index as u16);
+                    Account { lamports: 1, data, owner: sysvar::ID, executable: false }
+                } else {
+                    self.accounts.get(key).cloned().unwrap_or(Account {
+                        owner: system_program::ID,
+                        ..Account::default()
+                    })
+                };
+                let is_writable = *is_writable && !account.executable && account.owner != sysvar::ID;
+                unique.insert(*key, leak_account_info(*key, &account, *is_signer, is_writable));
+            }
+            let infos: Vec<AccountInfo<'static>> =
+                instruction.accounts.iter().map(|m| unique[&m.pubkey].clone()).collect();
+            let infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+
+            execute(&instruction.program_id, infos, &instruction.data).map_err(|e| (index, e))?;
+
+            for (key, info) in unique {
+                if !info.is_writable || key == sysvar::instructions::ID {
+                    continue;
+                }
+                touched.insert(key);
+                let account = Account {
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                    executable: info.executable,
+                };
+                if account.lamports == 0 {
+                    self.accounts.remove(&key);
+                } else {
+                    self.accounts.insert(key, account);
+                }
+            }
+        }
+
+        // Accounts may not end the transaction newly rent-paying
+        let rent = Rent::default();
+        for key in touched {
+            let Some(post) = self.accounts.get(&key) else { continue };
+            if rent.is_exempt(post.lamports, post.data.len()) {
+                continue;
+            }
+            let allowed = pre_tx.get(&key).is_some_and(|pre| {
+                !rent.is_exempt(pre.lamports, pre.data.len())
+                    && pre.data.len() == post.data.len()
+                    && post.lamports <= pre.lamports
+            });
+            if !allowed {
+                Stubs.sol_log(&format!("svm: {key} left below rent exemption"));
+                return Err((instructions.len().saturating_sub(1), ProgramError::AccountNotRentExempt));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An Ed25519 precompile instruction verifying one signature by `signer` over `message`.
+pub fn ed25519_instruction(signer: &ed25519_dalek::Keypair, message: &[u8]) -> Instruction {
+    use ed25519_dalek::Signer;
+    let signature = signer.sign(message).to_bytes();
+    let pubkey = signer.public.to_bytes();
+    const HEADER: usize = 2 + 14;
+    let (pk_offset, sig_offset) = (HEADER, HEADER + 32);
+    let msg_offset = sig_offset + 64;
+    let mut data = vec![1u8, 0];
+    for value in [sig_offset, u16::MAX as usize, pk_offset, u16::MAX as usize, msg_offset, message.len(), u16::MAX as usize] {
+        data.extend_from_slice(&(value as u16).to_le_bytes());
+    }
+    data.extend_from_slice(&pubkey);
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(message);
+    Instruction { program_id: ed25519_program::ID, accounts: Vec::new(), data }
+}
+
+/// A deterministic Ed25519 keypair.
+pub fn ed25519_keypair(seed: u8) -> ed25519_dalek::Keypair {
+    let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = ed25519_dalek::PublicKey::from(&secret);
+    ed25519_dalek::Keypair { secret, public }
+}
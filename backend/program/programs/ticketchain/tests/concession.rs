@@ -0,0 +1,82 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{Concession, ErrorCode, Receipt};
+
+fn child(max_count: u32, verified_only: bool) -> Concession {
+    Concession { name: "child".into(), tier_index: 0, price_lamports: SOL / 20, max_count, sold: 0, verified_only }
+}
+
+fn set_concessions(svm: &mut Svm, fixture: &EventFixture, concessions: Vec<Concession>) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetConcessions { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetConcessions { concessions },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+/// buy_ticket for the next index in `category`, preceded by `attestation` if given.
+fn buy_concession(
+    svm: &mut Svm,
+    fixture: &EventFixture,
+    buyer: &Pubkey,
+    category: Option<u8>,
+    attestation: Option<anchor_lang::solana_program::instruction::Instruction>,
+) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.instructions = attestation.as_ref().map(|_| instructions_sysvar());
+    let mut args = buy_ticket_args(index, 0);
+    args.category = category;
+    let instructions: Vec<_> = attestation.into_iter().chain([ix(accounts, args)]).collect();
+    svm.process(&instructions, &[*buyer])
+}
+
+#[test]
+fn concession_price_applies_until_its_cap() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    assert_err(set_concessions(&mut svm, &fixture, vec![child(11, false)]), ErrorCode::InvalidConcession);
+    assert_ok(set_concessions(&mut svm, &fixture, vec![child(2, false)]));
+    let buyer = svm.wallet(10 * SOL);
+
+    let before = svm.lamports(&fixture.organizer);
+    assert_ok(buy_concession(&mut svm, &fixture, &buyer, Some(0), None));
+    assert_ok(buy_concession(&mut svm, &fixture, &buyer, Some(0), None));
+    assert_eq!(svm.lamports(&fixture.organizer), before + 2 * (SOL / 20));
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &mint)).category, Some(0));
+
+    // The cap only closes the category; full-price seats stay on sale
+    assert_err(buy_concession(&mut svm, &fixture, &buyer, Some(0), None), ErrorCode::ConcessionSoldOut);
+    assert_err(buy_concession(&mut svm, &fixture, &buyer, Some(1), None), ErrorCode::InvalidConcession);
+    assert_ok(buy_concession(&mut svm, &fixture, &buyer, None, None));
+    let state = event(&svm, &fixture);
+    assert_eq!((state.sold, state.concessions[0].sold), (3, 2));
+}
+
+#[test]
+fn verified_only_category_needs_the_organizer_attestation() {
+    let mut svm = setup();
+    let key = ed25519_keypair(9);
+    let organizer = Pubkey::new_from_array(key.public.to_bytes());
+    svm.airdrop(&organizer, 100 * SOL);
+    let instruction = create_event_ix(&svm, &organizer, 1, EventArgs::new(&svm));
+    assert_ok(svm.send(instruction, &[organizer]));
+    let fixture = EventFixture { organizer, event: event_pda(&organizer, 1), nonce: 1 };
+    assert_ok(set_concessions(&mut svm, &fixture, vec![child(5, true)]));
+
+    let buyer = svm.wallet(10 * SOL);
+    let other = svm.wallet(SOL);
+    let message = |buyer: &Pubkey| [b"concession".as_ref(), fixture.event.as_ref(), buyer.as_ref(), &[0]].concat();
+    assert_err(buy_concession(&mut svm, &fixture, &buyer, Some(0), None), ErrorCode::MissingAttestation);
+    let for_other = ed25519_instruction(&key, &message(&other));
+    assert_err(buy_concession(&mut svm, &fixture, &buyer, Some(0), Some(for_other)), ErrorCode::MissingAttestation);
+    let by_stranger = ed25519_instruction(&ed25519_keypair(1), &message(&buyer));
+    assert_err(buy_concession(&mut svm, &fixture, &buyer, Some(0), Some(by_stranger)), ErrorCode::MissingAttestation);
+    let attestation = ed25519_instruction(&key, &message(&buyer));
+    assert_ok(buy_concession(&mut svm, &fixture, &buyer, Some(0), Some(attestation)));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{AttendanceRecord, DelegateEntry, ErrorCode};
+
+fn revoke(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::RevokeEntryDelegation {
+                holder: *holder,
+                entry_delegate: entry_delegate_pda(&mint, holder),
+            },
+            ticketchain::instruction::RevokeEntryDelegation {},
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn delegate_checks_in_for_the_holder_until_the_delegation_expires() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, friend, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL), svm.wallet(SOL));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_err(delegate_entry(&mut svm, &fixture, &holder, &holder, 0, 2), ErrorCode::InvalidRecipient);
+    for index in 0..2 {
+        assert_ok(delegate_entry(&mut svm, &fixture, &holder, &friend, index, 2));
+    }
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    let expires_ts = svm.get::<DelegateEntry>(&entry_delegate_pda(&mint, &holder)).expires_ts;
+    assert_eq!(expires_ts, svm.now() + 2 * 3600);
+
+    assert_err(check_in_delegate(&mut svm, &fixture, &stranger, &holder, 0), ErrorCode::Unauthorized);
+    svm.warp_to(expires_ts - 1);
+    assert_ok(check_in_delegate(&mut svm, &fixture, &friend, &holder, 0));
+    let record: AttendanceRecord = svm.get(&pda(&[b"attendance", mint.as_ref()]));
+    assert_eq!(record.holder, holder);
+
+    svm.warp_to(expires_ts);
+    assert_err(check_in_delegate(&mut svm, &fixture, &friend, &holder, 1), ErrorCode::DelegationExpired);
+}
+
+#[test]
+fn revoked_delegation_no_longer_checks_in() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, friend) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(delegate_entry(&mut svm, &fixture, &holder, &friend, 0, 2));
+
+    let delegation = entry_delegate_pda(&ticket_mint_pda(&fixture.event, 0), &holder);
+    let (rent, before) = (svm.lamports(&delegation), svm.lamports(&holder));
+    assert_ok(revoke(&mut svm, &fixture, &holder, 0));
+    assert_eq!(svm.lamports(&holder), before + rent);
+    assert_err(
+        check_in_delegate(&mut svm, &fixture, &friend, &holder, 0),
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+    );
+    assert_ok(check_in(&mut svm, &fixture, &holder, 0));
+}
+
+#[test]
+fn delegate_cannot_re_delegate() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, friend, other) = (svm.wallet(10 * SOL), svm.wallet(SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(delegate_entry(&mut svm, &fixture, &holder, &friend, 0, 2));
+
+    // The delegate holds no ticket, so it cannot hand the entry on
+    create_token_account(&mut svm, &friend, &mint, 0);
+    assert_err(delegate_entry(&mut svm, &fixture, &friend, &other, 0, 2), ErrorCode::NotTicketHolder);
+    assert_err(check_in_delegate(&mut svm, &fixture, &other, &holder, 0), ErrorCode::Unauthorized);
+}
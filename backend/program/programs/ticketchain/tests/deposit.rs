@@ -0,0 +1,167 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, Receipt, ATTENDANCE_FINALIZE_SECONDS};
+
+const DEPOSIT: u64 = SOL / 20;
+
+fn escrow_pda(fixture: &EventFixture) -> Pubkey {
+    pda(&[b"deposit_escrow", fixture.event.as_ref()])
+}
+
+/// A free tier and a 0.1 SOL tier.
+fn mixed_event(svm: &mut Svm) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.tiers = vec![tier(0, 10), tier(SOL / 10, 10)];
+    create_event_with(svm, args)
+}
+
+fn set_deposit(svm: &mut Svm, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetAttendanceDeposit {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                deposit_escrow: escrow_pda(fixture),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetAttendanceDeposit { deposit_lamports: DEPOSIT, forfeit_wallet: None },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn buy(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, tier_index: u8) -> Pubkey {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.deposit_escrow = Some(escrow_pda(fixture));
+    assert_ok(svm.send(ix(accounts, buy_ticket_args(index, tier_index)), &[*buyer]));
+    ticket_mint_pda(&fixture.event, index)
+}
+
+/// Lists `mint` with its purchase receipt, which multi-tier events need.
+fn list(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey, price_lamports: u64) {
+    let mut accounts = list_accounts(fixture, seller, mint);
+    accounts.purchase_receipt = Some(receipt_pda(seller, mint));
+    assert_ok(svm.send(ix(accounts, list_args(fixture, mint, price_lamports)), &[*seller]));
+}
+
+fn buy_resale(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, seller: &Pubkey, mint: &Pubkey, receipt: Option<Pubkey>) -> Result<TxMeta, TxError> {
+    let mut accounts = buy_resale_accounts(fixture, buyer, seller, mint);
+    accounts.deposit_receipt = receipt;
+    svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports: u64::MAX }), &[*buyer])
+}
+
+#[test]
+fn deposit_is_taken_on_free_tiers_only() {
+    let mut svm = setup();
+    let paid_only = create_event(&mut svm);
+    assert_err(set_deposit(&mut svm, &paid_only), ErrorCode::InvalidDepositConfig);
+
+    let fixture = mixed_event(&mut svm);
+    assert_ok(set_deposit(&mut svm, &fixture));
+    let free = svm.wallet(10 * SOL);
+    let paid = svm.wallet(10 * SOL);
+    let free_mint = buy(&mut svm, &fixture, &free, 0);
+    let paid_mint = buy(&mut svm, &fixture, &paid, 1);
+
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&free, &free_mint)).deposit_lamports, DEPOSIT);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&paid, &paid_mint)).deposit_lamports, 0);
+    assert_eq!(event(&svm, &fixture).deposits_held, DEPOSIT);
+}
+
+#[test]
+fn deposit_follows_the_ticket_through_resale_to_settlement() {
+    let mut svm = setup();
+    let fixture = mixed_event(&mut svm);
+    assert_ok(set_deposit(&mut svm, &fixture));
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let seller = svm.wallet(10 * SOL);
+    let no_show = svm.wallet(10 * SOL);
+    let mint = buy(&mut svm, &fixture, &seller, 0);
+    let skipped = buy(&mut svm, &fixture, &no_show, 0);
+    let receipt = receipt_pda(&seller, &mint);
+
+    // Below the deposit the sale can't settle
+    let buyer = svm.wallet(10 * SOL);
+    list(&mut svm, &fixture, &seller, &mint, DEPOSIT / 2);
+    assert_err(buy_resale(&mut svm, &fixture, &buyer, &seller, &mint, Some(receipt)), ErrorCode::PriceBelowDeposit);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelListing {
+                seller,
+                ticket_mint: mint,
+                listing: listing_pda(&mint),
+                seller_token_account: ata(&seller, &mint),
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+                ask_book: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                seller_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelListing {},
+        ),
+        &[seller],
+    ));
+
+    // The seller gets their deposit back before the split; the deposit stays escrowed
+    let price = DEPOSIT + SOL / 10;
+    list(&mut svm, &fixture, &seller, &mint, price);
+    assert_err(buy_resale(&mut svm, &fixture, &buyer, &seller, &mint, None), ErrorCode::DepositReceiptRequired);
+    let closed_rent = svm.lamports(&listing_pda(&mint)) + svm.lamports(&pda(&[b"escrow", mint.as_ref()]));
+    let before = svm.lamports(&seller);
+    assert_ok(buy_resale(&mut svm, &fixture, &buyer, &seller, &mint, Some(receipt)));
+    let (_, seller_share, _) = ticketchain::resale_split(&event(&svm, &fixture), SOL / 10);
+    assert_eq!(svm.lamports(&seller), before + seller_share + DEPOSIT + closed_rent);
+    assert_eq!(event(&svm, &fixture).deposits_held, 2 * DEPOSIT);
+
+    // The new holder checks in and reclaims the deposit; the no-show's is forfeited
+    let accounts = check_in_accounts(&fixture, &buyer, &seller, 0);
+    assert_ok(svm.send(
+        ix(accounts, ticketchain::instruction::CheckIn { ticket_index: 0 }),
+        &[fixture.organizer, buyer],
+    ));
+    svm.warp_to(event(&svm, &fixture).date_ts + ATTENDANCE_FINALIZE_SECONDS);
+    let before = svm.lamports(&buyer);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ReclaimDeposit {
+                holder: buyer,
+                event: fixture.event,
+                receipt,
+                attendance_record: pda(&[b"attendance", mint.as_ref()]),
+                deposit_escrow: escrow_pda(&fixture),
+            },
+            ticketchain::instruction::ReclaimDeposit {},
+        ),
+        &[buyer],
+    ));
+    assert_eq!(svm.lamports(&buyer), before + DEPOSIT);
+
+    let before = svm.lamports(&fixture.organizer);
+    let cranker = svm.wallet(SOL);
+    assert_ok(svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::SweepForfeitedDeposits {
+                event: fixture.event,
+                deposit_escrow: escrow_pda(&fixture),
+                forfeit_wallet: fixture.organizer,
+            },
+            ticketchain::instruction::SweepForfeitedDeposits {},
+            vec![
+                AccountMeta::new(receipt_pda(&no_show, &skipped), false),
+                AccountMeta::new_readonly(pda(&[b"attendance", skipped.as_ref()]), false),
+            ],
+        ),
+        &[cranker],
+    ));
+    assert_eq!(svm.lamports(&fixture.organizer), before + DEPOSIT);
+    assert_eq!(event(&svm, &fixture).deposits_held, 0);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token::{self, state::Account as TokenAccount};
+use common::*;
+use ticketchain::{ErrorCode, EscrowAnomalyDetected, Listing};
+
+fn token_account(svm: &Svm, key: &Pubkey) -> TokenAccount {
+    TokenAccount::unpack(&svm.account(key).unwrap().data).unwrap()
+}
+
+#[test]
+fn delegated_seller_account_must_be_revoked_before_listing() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (seller, other_dapp) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let account = ata(&seller, &mint);
+    let approve = spl_token::instruction::approve(&TOKEN_PROGRAM, &account, &other_dapp, &seller, &[], 1).unwrap();
+    assert_ok(svm.process(&[approve], &[seller]));
+
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5), ErrorCode::DelegateMustBeRevoked);
+    assert_eq!(token_balance(&svm, &account), 1);
+    assert!(!svm.exists(&listing_pda(&mint)));
+
+    let revoke = spl_token::instruction::revoke(&TOKEN_PROGRAM, &account, &seller, &[]).unwrap();
+    assert_ok(svm.process(&[revoke], &[seller]));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+
+    // The fresh escrow answers only to the listing
+    let escrow = token_account(&svm, &pda(&[b"escrow", mint.as_ref()]));
+    assert_eq!((escrow.owner, escrow.amount), (listing_pda(&mint), 1));
+    assert!(escrow.delegate.is_none() && escrow.close_authority.is_none());
+}
+
+fn verify_health(svm: &mut Svm, authority: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::VerifyEscrowHealth {
+                authority: *authority,
+                platform_config: platform_config_pda(),
+                listing: listing_pda(mint),
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+            },
+            ticketchain::instruction::VerifyEscrowHealth {},
+        ),
+        &[*authority],
+    )
+}
+
+fn resolve_anomaly(svm: &mut Svm, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ResolveEscrowAnomaly {
+                authority: PLATFORM_AUTHORITY,
+                platform_config: platform_config_pda(),
+                seller: *seller,
+                ticket_mint: *mint,
+                listing: listing_pda(mint),
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+                seller_token_account: ata(seller, mint),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ResolveEscrowAnomaly {},
+        ),
+        &[PLATFORM_AUTHORITY],
+    )
+}
+
+#[test]
+fn escrow_anomaly_blocks_the_listing_until_resolved_back_to_the_seller() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    let fixture = create_event(&mut svm);
+    let (seller, buyer) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+    let escrow = pda(&[b"escrow", mint.as_ref()]);
+
+    // A healthy escrow is left alone, and only the platform authority may check it
+    assert_ok(verify_health(&mut svm, &PLATFORM_AUTHORITY, &mint));
+    assert!(!svm.get::<Listing>(&listing_pda(&mint)).anomaly_flag);
+    assert_err(verify_health(&mut svm, &seller, &mint), ErrorCode::Unauthorized);
+    assert_err(resolve_anomaly(&mut svm, &seller, &mint), ErrorCode::NoEscrowAnomaly);
+
+    // Simulate the escrow drifting from the single ticket it should hold
+    let mut account = svm.account(&escrow).unwrap().clone();
+    let mut state = TokenAccount::unpack(&account.data).unwrap();
+    state.amount = 2;
+    TokenAccount::pack(state, &mut account.data).unwrap();
+    svm.set_account(escrow, account);
+
+    let detected = assert_ok(verify_health(&mut svm, &PLATFORM_AUTHORITY, &mint)).event::<EscrowAnomalyDetected>();
+    assert_eq!((detected.listing, detected.escrow, detected.actual_balance), (listing_pda(&mint), escrow, 2));
+    assert!(svm.get::<Listing>(&listing_pda(&mint)).anomaly_flag);
+    assert_err(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL), &[buyer]), ErrorCode::EscrowAnomaly);
+
+    let rent = svm.lamports(&listing_pda(&mint)) + svm.lamports(&escrow);
+    let before = svm.lamports(&seller);
+    assert_ok(resolve_anomaly(&mut svm, &seller, &mint));
+    assert_eq!(token_balance(&svm, &ata(&seller, &mint)), 2);
+    assert_eq!(svm.lamports(&seller), before + rent);
+    assert!(!svm.exists(&listing_pda(&mint)) && !svm.exists(&escrow));
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::*;
+use ticketchain::ErrorCode;
+
+#[test]
+fn primary_sale_closes_at_the_event_date() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let date_ts = event(&svm, &fixture).date_ts;
+
+    svm.warp_to(date_ts - 1);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(date_ts);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::EventEnded);
+    svm.warp_to(date_ts + 1);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::EventEnded);
+    assert_eq!(event(&svm, &fixture).sold, 1);
+}
+
+#[test]
+fn resale_closes_at_the_event_date() {
+    let mut svm = setup();
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    let (seller, buyer) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let listed = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let unlisted = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let date_ts = event(&svm, &fixture).date_ts;
+
+    svm.warp_to(date_ts - 1);
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &listed, SOL / 5));
+    svm.warp_to(date_ts);
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &unlisted, SOL / 5), ErrorCode::EventEnded);
+    assert_err(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &listed, SOL / 5), &[buyer]), ErrorCode::EventEnded);
+    assert!(!svm.exists(&ata(&buyer, &listed)));
+}
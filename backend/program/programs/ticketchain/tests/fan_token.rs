@@ -0,0 +1,67 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn fan_authority(fixture: &EventFixture) -> Pubkey {
+    pda(&[b"fan_token_authority", fixture.event.as_ref()])
+}
+
+fn configure(svm: &mut Svm, fixture: &EventFixture, mint: &Pubkey, tokens_per_purchase: u64) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ConfigureFanToken {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                fan_token_mint: *mint,
+                fan_token_authority: fan_authority(fixture),
+                fan_token_config: pda(&[b"fan_token", fixture.event.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ConfigureFanToken { tokens_per_purchase },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn buy_with_fan_token(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.fan_token_mint = Some(*mint);
+    accounts.fan_token_authority = Some(fan_authority(fixture));
+    accounts.buyer_fan_token_account = Some(ata(buyer, mint));
+    svm.send(ix(accounts, buy_ticket_args(index, 0)), &[*buyer])
+}
+
+#[test]
+fn fan_token_mint_must_answer_to_the_event_authority() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let organizer_mint = create_mint(&mut svm, &fixture.organizer, 0);
+    assert_err(configure(&mut svm, &fixture, &organizer_mint, 10), ErrorCode::InvalidFanTokenMint);
+    assert_eq!(event(&svm, &fixture).fan_token_mint, None);
+}
+
+#[test]
+fn each_primary_purchase_mints_the_configured_fan_tokens() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let fan_mint = create_mint(&mut svm, &fan_authority(&fixture), 0);
+    assert_ok(configure(&mut svm, &fixture, &fan_mint, 25));
+    let state = event(&svm, &fixture);
+    assert_eq!((state.fan_token_mint, state.fan_tokens_per_purchase), (Some(fan_mint), 25));
+
+    let buyer = svm.wallet(10 * SOL);
+    let fan_account = create_token_account(&mut svm, &buyer, &fan_mint, 0);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::MissingFanTokenAccounts);
+    let other_mint = create_mint(&mut svm, &fan_authority(&fixture), 0);
+    create_token_account(&mut svm, &buyer, &other_mint, 0);
+    assert_err(buy_with_fan_token(&mut svm, &fixture, &buyer, &other_mint), ErrorCode::InvalidFanTokenMint);
+
+    assert_ok(buy_with_fan_token(&mut svm, &fixture, &buyer, &fan_mint));
+    assert_eq!(token_balance(&svm, &fan_account), 25);
+    assert_ok(buy_with_fan_token(&mut svm, &fixture, &buyer, &fan_mint));
+    assert_eq!(token_balance(&svm, &fan_account), 50);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, PLATFORM_WALLET};
+
+fn set_override(svm: &mut Svm, fixture: &EventFixture, organizer: &Pubkey, authority: &Pubkey, bps: Option<u16>) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetFeeOverride {
+                organizer: *organizer,
+                authority: *authority,
+                platform_config: platform_config_pda(),
+                event: fixture.event,
+            },
+            ticketchain::instruction::SetFeeOverride { platform_fee_override_bps: bps },
+        ),
+        &[*organizer, *authority],
+    )
+}
+
+#[test]
+fn fee_override_needs_both_signers_and_stays_below_the_default() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let stranger = svm.wallet(SOL);
+    let default_bps = event(&svm, &fixture).platform_bps;
+
+    assert_err(set_override(&mut svm, &fixture, &fixture.organizer, &stranger, Some(500)), ErrorCode::Unauthorized);
+    assert_err(set_override(&mut svm, &fixture, &stranger, &PLATFORM_AUTHORITY, Some(500)), ErrorCode::InvalidOrganizer);
+    assert_err(
+        set_override(&mut svm, &fixture, &fixture.organizer, &PLATFORM_AUTHORITY, Some(default_bps + 1)),
+        ErrorCode::FeeOverrideAboveDefault,
+    );
+    assert_ok(set_override(&mut svm, &fixture, &fixture.organizer, &PLATFORM_AUTHORITY, Some(500)));
+    assert_eq!(event(&svm, &fixture).platform_fee_override_bps, Some(500));
+
+    // Once a ticket sells the override is locked
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_err(set_override(&mut svm, &fixture, &fixture.organizer, &PLATFORM_AUTHORITY, None), ErrorCode::EventHasSales);
+}
+
+#[test]
+fn resales_pay_the_overridden_platform_share() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    assert_ok(set_override(&mut svm, &fixture, &fixture.organizer, &PLATFORM_AUTHORITY, Some(500)));
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL));
+
+    let state = event(&svm, &fixture);
+    let artist = SOL * state.artist_bps as u64 / 10_000;
+    assert_eq!(ticketchain::resale_split(&state, SOL), (artist, SOL - artist - SOL / 20, SOL / 20));
+    let before = svm.lamports(&PLATFORM_WALLET);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL), &[buyer]));
+    assert_eq!(svm.lamports(&PLATFORM_WALLET) - before, SOL / 20);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use ticketchain::{ErrorCode, FeeReport, FEE_DAY_SECONDS, PLATFORM_WALLET};
+
+fn fee_day_pda(day: u32) -> Pubkey {
+    pda(&[b"fee_day", &day.to_le_bytes()])
+}
+
+fn today(svm: &Svm) -> u32 {
+    (svm.now() / FEE_DAY_SECONDS) as u32
+}
+
+fn init_fee_day(svm: &mut Svm, day: u32) -> Result<TxMeta, TxError> {
+    let payer = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::InitFeeDay { payer, fee_day: fee_day_pda(day), system_program: system_program::ID },
+            ticketchain::instruction::InitFeeDay { day },
+        ),
+        &[payer],
+    )
+}
+
+/// Resells a fresh ticket of `fixture` at `price`, passing `fee_day`.
+fn resale(svm: &mut Svm, fixture: &EventFixture, price: u64, fee_day: Option<Pubkey>) {
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, fixture, &seller, 0));
+    assert_ok(list_ticket(svm, fixture, &seller, &mint, price));
+    let buyer = svm.wallet(10 * SOL);
+    let mut accounts = buy_resale_accounts(fixture, &buyer, &seller, &mint);
+    accounts.fee_day = fee_day;
+    assert_ok(svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports: price }), &[buyer]));
+}
+
+fn fee_report(svm: &mut Svm, event: Option<Pubkey>, fee_day: Option<Pubkey>) -> FeeReport {
+    let caller = svm.wallet(SOL);
+    let meta = assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::GetFeeReport { platform_config: platform_config_pda(), event, fee_day },
+            ticketchain::instruction::GetFeeReport {},
+        ),
+        &[caller],
+    ));
+    FeeReport::deserialize(&mut meta.return_data.expect("report returned").as_slice()).unwrap()
+}
+
+#[test]
+fn fee_report_matches_what_the_platform_wallet_received() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let first = create_event(&mut svm);
+    let second = create_event(&mut svm);
+    let day = today(&svm);
+    assert_ok(init_fee_day(&mut svm, day));
+    let start = svm.lamports(&PLATFORM_WALLET);
+
+    resale(&mut svm, &first, SOL / 10, Some(fee_day_pda(day)));
+    resale(&mut svm, &first, SOL / 7, None);
+    let first_fees = svm.lamports(&PLATFORM_WALLET) - start;
+    resale(&mut svm, &second, SOL / 3, Some(fee_day_pda(day)));
+    let day_fees = svm.lamports(&PLATFORM_WALLET) - start - ticketchain::resale_split(&event(&svm, &first), SOL / 7).2;
+
+    // Tomorrow the old bucket is left alone
+    svm.warp(FEE_DAY_SECONDS);
+    resale(&mut svm, &second, SOL / 5, Some(fee_day_pda(day)));
+    let total = svm.lamports(&PLATFORM_WALLET) - start;
+
+    let report = fee_report(&mut svm, Some(first.event), Some(fee_day_pda(day)));
+    assert_eq!((report.resale_fees_collected, report.resale_fee_count), (total, 4));
+    assert_eq!(report.event_fees_collected, Some(first_fees));
+    assert_eq!(report.day, Some((day, day_fees, 2)));
+    assert_eq!(event(&svm, &second).platform_fees_collected, total - first_fees);
+
+    let report = fee_report(&mut svm, None, None);
+    assert_eq!((report.event_fees_collected, report.day), (None, None));
+}
+
+#[test]
+fn fee_days_cannot_be_opened_in_the_past() {
+    let mut svm = setup();
+    let day = today(&svm);
+    assert_err(init_fee_day(&mut svm, day - 1), ErrorCode::FeeDayPassed);
+    assert_ok(init_fee_day(&mut svm, day + 1));
+}
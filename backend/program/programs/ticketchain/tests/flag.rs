@@ -0,0 +1,106 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, TicketFlag};
+
+fn flag_pda(mint: &Pubkey) -> Pubkey {
+    pda(&[b"ticket_flag", mint.as_ref()])
+}
+
+/// Flags ticket `index` while `seller` has it listed, passing the accounts that cancel the listing.
+fn flag_listed(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    let accounts = ticketchain::accounts::FlagTicket {
+        escrow_token_account: Some(pda(&[b"escrow", mint.as_ref()])),
+        seller: Some(*seller),
+        seller_token_account: Some(ata(seller, &mint)),
+        ..flag_accounts(fixture, index)
+    };
+    svm.send(
+        ix(accounts, ticketchain::instruction::FlagTicket { _ticket_index: index, reason_code: 2 }),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn held_ticket_is_flagged() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+
+    assert_ok(flag_ticket(&mut svm, &fixture, 0));
+    let flag: TicketFlag = svm.get(&flag_pda(&mint));
+    assert_eq!(flag.event, fixture.event);
+    assert_eq!(flag.ticket_mint, mint);
+    assert_eq!(flag.reason_code, 1);
+    assert_eq!(flag.flagged_ts, svm.now());
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+}
+
+#[test]
+fn listed_ticket_is_returned_to_the_seller() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+    let escrow = pda(&[b"escrow", mint.as_ref()]);
+    let (rent, before) = (svm.lamports(&listing_pda(&mint)) + svm.lamports(&escrow), svm.lamports(&seller));
+
+    assert_ok(flag_listed(&mut svm, &fixture, &seller, 0));
+    assert_eq!(token_balance(&svm, &ata(&seller, &mint)), 1);
+    assert!(!svm.exists(&listing_pda(&mint)));
+    assert!(!svm.exists(&escrow));
+    assert_eq!(svm.lamports(&seller), before + rent);
+    assert_eq!(svm.get::<TicketFlag>(&flag_pda(&mint)).reason_code, 2);
+}
+
+#[test]
+fn listed_ticket_needs_the_listing_accounts() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+
+    assert_err(flag_ticket(&mut svm, &fixture, 0), ErrorCode::MissingListingAccounts);
+    assert!(svm.exists(&listing_pda(&mint)));
+    assert!(!svm.exists(&flag_pda(&mint)));
+}
+
+#[test]
+fn checked_in_ticket_cannot_be_flagged() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(check_in(&mut svm, &fixture, &buyer, 0));
+
+    assert_err(flag_ticket(&mut svm, &fixture, 0), ErrorCode::TicketCheckedIn);
+    assert!(!svm.exists(&flag_pda(&mint)));
+}
+
+#[test]
+fn flagged_ticket_cannot_be_checked_in() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(flag_ticket(&mut svm, &fixture, 0));
+
+    assert_err(check_in(&mut svm, &fixture, &buyer, 0), ErrorCode::TicketFlagged);
+}
+
+#[test]
+fn flagged_ticket_cannot_be_listed() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(flag_ticket(&mut svm, &fixture, 0));
+
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5), ErrorCode::TicketFlagged);
+    assert!(!svm.exists(&listing_pda(&mint)));
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PurchaseQuote, PLATFORM_WALLET, TOKEN_METADATA_PROGRAM_ID};
+
+fn metadata_event(svm: &mut Svm) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.metadata_uri = "ipfs://ticket".to_string();
+    create_event_with(svm, args)
+}
+
+/// quote_ticket's total for a fresh purchase of tier 0 by `buyer`.
+fn quote(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey) -> u64 {
+    let caller = svm.wallet(SOL);
+    let meta = assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::QuoteTicket {
+                event: fixture.event,
+                buyer: *buyer,
+                rate_limit: pda(&[b"rate_limit", buyer.as_ref()]),
+                buyer_record: pda(&[b"buyer", fixture.event.as_ref(), buyer.as_ref()]),
+            },
+            ticketchain::instruction::QuoteTicket { tier_index: 0, tip_lamports: 0 },
+        ),
+        &[caller],
+    ));
+    meta.event::<PurchaseQuote>().total_lamports
+}
+
+fn buy_with_metadata(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey) -> Result<TxMeta, TxError> {
+    let mut accounts = buy_ticket_accounts(fixture, buyer, 0);
+    accounts.ticket_metadata = Some(metadata_pda(&accounts.ticket_mint));
+    accounts.token_metadata_program = Some(TOKEN_METADATA_PROGRAM_ID);
+    svm.send(ix(accounts, buy_ticket_args(0, 0)), &[*buyer])
+}
+
+fn buy_batch(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, quantity: u8) -> Result<TxMeta, TxError> {
+    let event = fixture.event;
+    let mut remaining = Vec::new();
+    for index in 0..quantity as u32 {
+        let mint = ticket_mint_pda(&event, index);
+        remaining.extend([
+            AccountMeta::new_readonly(ticket_authority_pda(&event, index), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(ata(buyer, &mint), false),
+            AccountMeta::new(receipt_pda(buyer, &mint), false),
+            AccountMeta::new(pda(&[b"name_override", event.as_ref(), &index.to_le_bytes()]), false),
+        ]);
+    }
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::BuyTickets {
+                buyer: *buyer,
+                organizer: fixture.organizer,
+                event,
+                tax_wallet: None,
+                donation_wallet: None,
+                vault: None,
+                rate_limit: pda(&[b"rate_limit", buyer.as_ref()]),
+                buyer_record: None,
+                organizer_ban: pda(&[b"org_ban", fixture.organizer.as_ref(), buyer.as_ref()]),
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::BuyTickets { quantity, tier_index: 0 },
+            remaining,
+        ),
+        &[*buyer],
+    )
+}
+
+/// Rent for the accounts a fresh buy_tickets of `quantity` creates.
+fn batch_rent(quantity: u64) -> u64 {
+    let rent = Rent::default();
+    let receipt = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 2 + 2 + 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1;
+    rent.minimum_balance(8 + 1 + 8 + 1)
+        + quantity * (rent.minimum_balance(82) + rent.minimum_balance(165) + rent.minimum_balance(receipt))
+}
+
+#[test]
+fn quote_covers_metadata_rent_to_the_lamport() {
+    let mut svm = setup();
+    let fixture = metadata_event(&mut svm);
+    let short = svm.wallet(0);
+    let total = quote(&mut svm, &fixture, &short);
+    svm.airdrop(&short, total - 1);
+    assert_err(buy_with_metadata(&mut svm, &fixture, &short), ErrorCode::InsufficientFundsForPurchase);
+
+    let exact = svm.wallet(0);
+    svm.airdrop(&exact, total);
+    assert_ok(buy_with_metadata(&mut svm, &fixture, &exact));
+    assert_eq!(svm.lamports(&exact), 0);
+}
+
+#[test]
+fn buy_tickets_checks_funds_for_the_whole_batch() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let total = 2 * SOL / 10 + batch_rent(2);
+
+    let short = svm.wallet(0);
+    svm.airdrop(&short, total - 1);
+    assert_err(buy_batch(&mut svm, &fixture, &short, 2), ErrorCode::InsufficientFundsForPurchase);
+
+    let exact = svm.wallet(0);
+    svm.airdrop(&exact, total);
+    assert_ok(buy_batch(&mut svm, &fixture, &exact, 2));
+    assert_eq!(event(&svm, &fixture).sold, 2);
+}
+
+#[test]
+fn buy_resale_checks_funds_including_the_buyer_ticket_account() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 10));
+    let rent = Rent::default();
+    let total = SOL / 10
+        + rent.minimum_balance(165)
+        + rent.minimum_balance(8 + 32 + 8 * ticketchain::PRICE_HISTORY_LEN + 4 + 1)
+        + rent.minimum_balance(8 + 32 + 8 + 8 + 1);
+    let buy = |buyer: Pubkey| buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL / 10);
+
+    let short = svm.wallet(0);
+    svm.airdrop(&short, total - 1);
+    assert_err(svm.send(buy(short), &[short]), ErrorCode::InsufficientFundsForPurchase);
+
+    let exact = svm.wallet(0);
+    svm.airdrop(&exact, total);
+    assert_ok(svm.send(buy(exact), &[exact]));
+    assert_eq!(token_balance(&svm, &ata(&exact, &mint)), 1);
+    assert_eq!(svm.lamports(&exact), 0);
+}
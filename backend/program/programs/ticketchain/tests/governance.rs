@@ -0,0 +1,152 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, CHANGE_DATE, CHANGE_VENUE, GOVERNANCE_VOTING_PERIOD_SECS};
+
+fn proposal_pda(event: &Pubkey, id: u64) -> Pubkey {
+    pda(&[b"vote", event.as_ref(), &id.to_le_bytes()])
+}
+
+fn venue_value(venue: &str) -> [u8; 64] {
+    let mut value = [0u8; 64];
+    value[..venue.len()].copy_from_slice(venue.as_bytes());
+    value
+}
+
+fn date_value(date_ts: i64) -> [u8; 64] {
+    let mut value = [0u8; 64];
+    value[..8].copy_from_slice(&date_ts.to_le_bytes());
+    value
+}
+
+fn propose(svm: &mut Svm, fixture: &EventFixture, proposer: &Pubkey, change_type: u8, proposed_value: [u8; 64]) -> Result<TxMeta, TxError> {
+    let ticket_mint = ticket_mint_pda(&fixture.event, 0);
+    svm.send(
+        ix(
+            ticketchain::accounts::CreateProposal {
+                proposer: *proposer,
+                event: fixture.event,
+                ticket_mint,
+                proposer_token_account: ata(proposer, &ticket_mint),
+                proposal: proposal_pda(&fixture.event, 1),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateProposal { proposal_id: 1, _ticket_index: 0, change_type, proposed_value },
+        ),
+        &[*proposer],
+    )
+}
+
+fn vote(svm: &mut Svm, fixture: &EventFixture, voter: &Pubkey, ticket_index: u32, yes: bool) -> Result<TxMeta, TxError> {
+    let ticket_mint = ticket_mint_pda(&fixture.event, ticket_index);
+    let proposal = proposal_pda(&fixture.event, 1);
+    svm.send(
+        ix(
+            ticketchain::accounts::CastVote {
+                voter: *voter,
+                event: fixture.event,
+                proposal,
+                ticket_mint,
+                voter_token_account: ata(voter, &ticket_mint),
+                vote_record: pda(&[b"vote_record", proposal.as_ref(), ticket_mint.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CastVote { _proposal_id: 1, _ticket_index: ticket_index, yes },
+        ),
+        &[*voter],
+    )
+}
+
+fn apply(svm: &mut Svm, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    let anyone = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::ApplyGovernanceChange { event: fixture.event, proposal: proposal_pda(&fixture.event, 1) },
+            ticketchain::instruction::ApplyGovernanceChange { _proposal_id: 1 },
+        ),
+        &[anyone],
+    )
+}
+
+/// An event with four tickets sold, one per holder; holder i owns ticket i.
+fn holders(svm: &mut Svm, fixture: &EventFixture) -> Vec<Pubkey> {
+    (0..4)
+        .map(|_| {
+            let holder = svm.wallet(SOL);
+            assert_ok(buy_ticket(svm, fixture, &holder, 0));
+            holder
+        })
+        .collect()
+}
+
+#[test]
+fn proposal_applies_only_after_the_window_with_quorum() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holders = holders(&mut svm, &fixture);
+    assert_ok(propose(&mut svm, &fixture, &holders[0], CHANGE_VENUE, venue_value("Open Air Stage")));
+
+    // A lone yes vote can neither apply early nor pass without quorum
+    assert_ok(vote(&mut svm, &fixture, &holders[0], 0, true));
+    assert_err(apply(&mut svm, &fixture), ErrorCode::VotingOpen);
+    svm.warp(GOVERNANCE_VOTING_PERIOD_SECS);
+    assert_err(vote(&mut svm, &fixture, &holders[1], 1, true), ErrorCode::VotingClosed);
+    assert_err(apply(&mut svm, &fixture), ErrorCode::QuorumNotReached);
+}
+
+#[test]
+fn proposal_needs_a_yes_majority_of_votes_cast() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holders = holders(&mut svm, &fixture);
+    assert_ok(propose(&mut svm, &fixture, &holders[0], CHANGE_VENUE, venue_value("Open Air Stage")));
+    assert_ok(vote(&mut svm, &fixture, &holders[0], 0, true));
+    assert_ok(vote(&mut svm, &fixture, &holders[1], 1, true));
+    assert_ok(vote(&mut svm, &fixture, &holders[2], 2, false));
+    svm.warp(GOVERNANCE_VOTING_PERIOD_SECS);
+    // Three of four sold voted; two of three yes is above 60%
+    assert_ok(apply(&mut svm, &fixture));
+    assert_eq!(event(&svm, &fixture).venue, "Open Air Stage");
+    assert_err(apply(&mut svm, &fixture), ErrorCode::ProposalApplied);
+}
+
+#[test]
+fn split_vote_fails_the_threshold() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holders = holders(&mut svm, &fixture);
+    assert_ok(propose(&mut svm, &fixture, &holders[0], CHANGE_VENUE, venue_value("Open Air Stage")));
+    assert_ok(vote(&mut svm, &fixture, &holders[0], 0, true));
+    assert_ok(vote(&mut svm, &fixture, &holders[1], 1, false));
+    svm.warp(GOVERNANCE_VOTING_PERIOD_SECS);
+    assert_err(apply(&mut svm, &fixture), ErrorCode::ProposalNotPassed);
+}
+
+#[test]
+fn date_change_must_be_future_and_fit_the_sale_window() {
+    let mut svm = setup();
+    let mut args = EventArgs::new(&svm);
+    let sale_end = svm.now() + 5 * 24 * 60 * 60;
+    args.sale_end_ts = Some(sale_end);
+    let fixture = create_event_with(&mut svm, args);
+    let holders = holders(&mut svm, &fixture);
+
+    let past = svm.now() - 1;
+    assert_err(propose(&mut svm, &fixture, &holders[0], CHANGE_DATE, date_value(past)), ErrorCode::InvalidEventDate);
+    // Would land before the last sale
+    assert_err(
+        propose(&mut svm, &fixture, &holders[0], CHANGE_DATE, date_value(sale_end - 1)),
+        ErrorCode::InvalidSaleWindow,
+    );
+
+    let postponed = event(&svm, &fixture).date_ts + 24 * 60 * 60;
+    assert_ok(propose(&mut svm, &fixture, &holders[0], CHANGE_DATE, date_value(postponed)));
+    for (i, holder) in holders.iter().enumerate().take(3) {
+        assert_ok(vote(&mut svm, &fixture, holder, i as u32, true));
+    }
+    svm.warp(GOVERNANCE_VOTING_PERIOD_SECS);
+    assert_ok(apply(&mut svm, &fixture));
+    assert_eq!(event(&svm, &fixture).date_ts, postponed);
+}
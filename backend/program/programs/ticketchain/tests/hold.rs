@@ -0,0 +1,215 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, Hold, PendingWork, TicketPurchased, CLEANUP_TIP_BPS, TOKEN_METADATA_PROGRAM_ID};
+
+fn hold_pda(fixture: &EventFixture, beneficiary: &Pubkey) -> Pubkey {
+    pda(&[b"hold", fixture.event.as_ref(), beneficiary.as_ref()])
+}
+
+fn create_hold(svm: &mut Svm, fixture: &EventFixture, beneficiary: &Pubkey, count: u32) {
+    let expires_at = svm.now() + 24 * 60 * 60;
+    create_hold_until(svm, fixture, beneficiary, count, expires_at);
+}
+
+fn create_hold_until(svm: &mut Svm, fixture: &EventFixture, beneficiary: &Pubkey, count: u32, expires_at: i64) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateHold {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                hold: hold_pda(fixture, beneficiary),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateHold {
+                beneficiary: *beneficiary,
+                count,
+                price_lamports: SOL / 20,
+                expires_at,
+            },
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+fn claim_hold(svm: &mut Svm, fixture: &EventFixture, beneficiary: &Pubkey) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let ticket_mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimHold {
+                beneficiary: *beneficiary,
+                organizer: fixture.organizer,
+                event: fixture.event,
+                hold: hold_pda(fixture, beneficiary),
+                ticket_authority: ticket_authority_pda(&fixture.event, index),
+                ticket_mint,
+                beneficiary_token_account: ata(beneficiary, &ticket_mint),
+                vault: None,
+                receipt: receipt_pda(beneficiary, &ticket_mint),
+                identity_purchase: None,
+                name_override: name_override_pda(&fixture.event, index),
+                ticket_metadata: Some(metadata_pda(&ticket_mint)),
+                token_metadata_program: Some(TOKEN_METADATA_PROGRAM_ID),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ClaimHold { identity_commitment: None },
+        ),
+        &[*beneficiary],
+    )
+}
+
+#[test]
+fn claimed_hold_takes_the_organizer_name_for_its_index() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sponsor = svm.wallet(10 * SOL);
+    create_hold(&mut svm, &fixture, &sponsor, 2);
+    assert_ok(set_ticket_name(&mut svm, &fixture, 0, "Sponsor Box 1"));
+
+    let before = svm.lamports(&fixture.organizer);
+    let rent = svm.lamports(&name_override_pda(&fixture.event, 0));
+    let meta = assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+    let purchased = meta.event::<TicketPurchased>();
+    assert_eq!(purchased.custom_name.as_deref(), Some("Sponsor Box 1"));
+    assert_eq!(purchased.price_lamports, SOL / 20);
+    assert!(!svm.exists(&name_override_pda(&fixture.event, 0)));
+    assert_eq!(svm.lamports(&fixture.organizer), before + rent + SOL / 20);
+
+    // Unnamed indexes keep the default naming
+    let meta = assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+    assert_eq!(meta.event::<TicketPurchased>().custom_name, None);
+    let state = event(&svm, &fixture);
+    assert_eq!((state.sold, state.tiers[0].sold), (2, 2));
+}
+
+#[test]
+fn claimed_hold_gets_metadata_under_its_custom_name() {
+    let mut svm = setup();
+    let mut args = EventArgs::new(&svm);
+    args.metadata_uri = "ipfs://ticket".to_string();
+    let fixture = create_event_with(&mut svm, args);
+    let sponsor = svm.wallet(10 * SOL);
+    create_hold(&mut svm, &fixture, &sponsor, 2);
+    assert_ok(set_ticket_name(&mut svm, &fixture, 0, "Sponsor Box 1"));
+
+    assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+    assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+    let named = ticket_metadata(&svm, &ticket_mint_pda(&fixture.event, 0)).unwrap();
+    assert_eq!((named.0.as_str(), named.2.as_str()), ("Sponsor Box 1", "ipfs://ticket"));
+    let unnamed = ticket_metadata(&svm, &ticket_mint_pda(&fixture.event, 1)).unwrap();
+    assert_eq!(unnamed.0, "Launch Night #2");
+}
+
+fn release(svm: &mut Svm, fixture: &EventFixture, beneficiary: &Pubkey) -> Result<TxMeta, TxError> {
+    let cranker = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::ReleaseExpiredHold {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                hold: hold_pda(fixture, beneficiary),
+            },
+            ticketchain::instruction::ReleaseExpiredHold {},
+        ),
+        &[cranker],
+    )
+}
+
+#[test]
+fn hold_is_claimable_until_it_expires_and_releasable_from_then_on() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sponsor = svm.wallet(10 * SOL);
+    create_hold(&mut svm, &fixture, &sponsor, 3);
+    let expires_at = svm.get::<Hold>(&hold_pda(&fixture, &sponsor)).expires_at;
+
+    svm.warp_to(expires_at - 1);
+    assert_err(release(&mut svm, &fixture, &sponsor), ErrorCode::HoldNotExpired);
+    assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+
+    svm.warp_to(expires_at);
+    assert_err(claim_hold(&mut svm, &fixture, &sponsor), ErrorCode::HoldExpired);
+    let rent = svm.lamports(&hold_pda(&fixture, &sponsor));
+    let before = svm.lamports(&fixture.organizer);
+    assert_ok(release(&mut svm, &fixture, &sponsor));
+    assert!(!svm.exists(&hold_pda(&fixture, &sponsor)));
+    assert_eq!(svm.lamports(&fixture.organizer), before + rent);
+    let state = event(&svm, &fixture);
+    assert_eq!((state.sold, state.held), (1, 0));
+}
+
+#[test]
+fn keeper_sweeps_only_the_expired_holds_of_a_mixed_batch() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let now = svm.now();
+    let sponsors = [svm.wallet(SOL), svm.wallet(SOL), svm.wallet(SOL)];
+    for (sponsor, (count, expires_in)) in sponsors.iter().zip([(1, 100), (2, 200), (3, 1_000)]) {
+        create_hold_until(&mut svm, &fixture, sponsor, count, now + expires_in);
+    }
+    let holds: Vec<AccountMeta> = sponsors.iter().map(|s| AccountMeta::new(hold_pda(&fixture, s), false)).collect();
+    let cranker = svm.wallet(SOL);
+    svm.warp_to(now + 200);
+
+    let meta = assert_ok(svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::PendingWorkView { event: fixture.event },
+            ticketchain::instruction::PendingWork {},
+            holds.clone(),
+        ),
+        &[cranker],
+    ));
+    let pending = meta.event::<PendingWork>();
+    assert_eq!((pending.expired_holds, pending.releasable_seats), (2, 3));
+
+    let rent: u64 = sponsors[..2].iter().map(|s| svm.lamports(&hold_pda(&fixture, s))).sum();
+    let tips: u64 = sponsors[..2].iter().map(|s| svm.lamports(&hold_pda(&fixture, s)) * CLEANUP_TIP_BPS / 10_000).sum();
+    let (organizer_before, cranker_before) = (svm.lamports(&fixture.organizer), svm.lamports(&cranker));
+    let sweep = |holds: Vec<AccountMeta>| {
+        ix_with_remaining(
+            ticketchain::accounts::SweepExpiredHolds { cranker, organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SweepExpiredHolds {},
+            holds,
+        )
+    };
+    assert_ok(svm.send(sweep(holds.clone()), &[cranker]));
+    assert_eq!(svm.lamports(&cranker), cranker_before + tips);
+    assert_eq!(svm.lamports(&fixture.organizer), organizer_before + rent - tips);
+    assert!(!svm.exists(&hold_pda(&fixture, &sponsors[0])) && !svm.exists(&hold_pda(&fixture, &sponsors[1])));
+    assert!(svm.exists(&hold_pda(&fixture, &sponsors[2])));
+    assert_eq!(event(&svm, &fixture).held, 3);
+
+    // A batch with nothing ready fails
+    assert_err(svm.send(sweep(holds[2..].to_vec()), &[cranker]), ErrorCode::NothingToCrank);
+}
+
+#[test]
+fn hold_cannot_be_claimed_once_the_event_is_cancelled() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sponsor = svm.wallet(10 * SOL);
+    create_hold(&mut svm, &fixture, &sponsor, 2);
+    assert_ok(claim_hold(&mut svm, &fixture, &sponsor));
+
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    let before = svm.lamports(&sponsor);
+    assert_err(claim_hold(&mut svm, &fixture, &sponsor), ErrorCode::EventCancelled);
+    assert_eq!(svm.lamports(&sponsor), before);
+    assert_eq!(event(&svm, &fixture).sold, 1);
+}
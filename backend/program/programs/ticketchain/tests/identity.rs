@@ -0,0 +1,170 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{BuyTicketOptions, ErrorCode, Receipt};
+
+fn identity_pda(fixture: &EventFixture, commitment: &[u8; 32]) -> Pubkey {
+    pda(&[b"identity", fixture.event.as_ref(), commitment])
+}
+
+fn commitment(preimage: &[u8; 32]) -> [u8; 32] {
+    hashv(&[preimage]).to_bytes()
+}
+
+/// An event with identity mode on and check-in by preimage.
+fn identity_event(svm: &mut Svm) -> EventFixture {
+    let fixture = create_event(svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetIdentityMode { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetIdentityMode { required: true, verify_at_check_in: true },
+        ),
+        &[fixture.organizer],
+    ));
+    fixture
+}
+
+fn buy_with_identity(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, commitment: [u8; 32]) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.identity_purchase = Some(identity_pda(fixture, &commitment));
+    let options = BuyTicketOptions { identity_commitment: Some(commitment), ..Default::default() };
+    svm.send(
+        ix(accounts, ticketchain::instruction::BuyTicketV2 { ticket_index: index, version: 3, options }),
+        &[*buyer],
+    )
+}
+
+fn check_in_with_identity(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32, preimage: [u8; 32]) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            check_in_accounts(fixture, holder, holder, index),
+            ticketchain::instruction::CheckInWithIdentity { ticket_index: index, identity_preimage: preimage },
+        ),
+        &[fixture.organizer, *holder],
+    )
+}
+
+#[test]
+fn one_ticket_per_identity_until_it_is_refunded() {
+    let mut svm = setup();
+    let fixture = identity_event(&mut svm);
+    let (first, second) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let id = commitment(&[7; 32]);
+
+    assert_err(buy_ticket(&mut svm, &fixture, &first, 0).map(|_| ()), ErrorCode::IdentityCommitmentRequired);
+    assert_ok(buy_with_identity(&mut svm, &fixture, &first, id));
+    assert_err(buy_with_identity(&mut svm, &fixture, &second, id), ErrorCode::IdentityAlreadyUsed);
+
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    let refund = |identity_purchase: Option<Pubkey>| {
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: first,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                ticket_mint: mint,
+                receipt: receipt_pda(&first, &mint),
+                holder_token_account: ata(&first, &mint),
+                deposit_escrow: None,
+                identity_purchase,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 0 },
+        )
+    };
+    assert_err(svm.send(refund(None), &[first]), ErrorCode::IdentityCommitmentRequired);
+    assert_ok(svm.send(refund(Some(identity_pda(&fixture, &id))), &[first]));
+    assert!(!svm.exists(&identity_pda(&fixture, &id)));
+
+    // The freed identity can buy again on another identity event
+    let other = identity_event(&mut svm);
+    assert_ok(buy_with_identity(&mut svm, &other, &second, id));
+}
+
+#[test]
+fn check_in_needs_the_commitment_preimage() {
+    let mut svm = setup();
+    let fixture = identity_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_with_identity(&mut svm, &fixture, &buyer, commitment(&[7; 32])));
+
+    assert_err(check_in(&mut svm, &fixture, &buyer, 0), ErrorCode::IdentityPreimageRequired);
+    assert_err(check_in_with_identity(&mut svm, &fixture, &buyer, 0, [8; 32]), ErrorCode::IdentityPreimageMismatch);
+    assert_ok(check_in_with_identity(&mut svm, &fixture, &buyer, 0, [7; 32]));
+}
+
+#[test]
+fn held_tickets_take_the_beneficiary_identity() {
+    let mut svm = setup();
+    let fixture = identity_event(&mut svm);
+    let sponsor = svm.wallet(10 * SOL);
+    let hold = pda(&[b"hold", fixture.event.as_ref(), sponsor.as_ref()]);
+    let expires_at = svm.now() + 3600;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateHold {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                hold,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateHold { beneficiary: sponsor, count: 2, price_lamports: 0, expires_at },
+        ),
+        &[fixture.organizer],
+    ));
+    let id = commitment(&[7; 32]);
+    let claim = |svm: &Svm, identity_commitment: Option<[u8; 32]>| {
+        let index = event(svm, &fixture).sold;
+        let ticket_mint = ticket_mint_pda(&fixture.event, index);
+        ix(
+            ticketchain::accounts::ClaimHold {
+                beneficiary: sponsor,
+                organizer: fixture.organizer,
+                event: fixture.event,
+                hold,
+                ticket_authority: ticket_authority_pda(&fixture.event, index),
+                ticket_mint,
+                beneficiary_token_account: ata(&sponsor, &ticket_mint),
+                vault: None,
+                receipt: receipt_pda(&sponsor, &ticket_mint),
+                identity_purchase: identity_commitment.map(|c| identity_pda(&fixture, &c)),
+                name_override: name_override_pda(&fixture.event, index),
+                ticket_metadata: None,
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ClaimHold { identity_commitment },
+        )
+    };
+
+    assert_err(svm.send(claim(&svm, None), &[sponsor]), ErrorCode::IdentityCommitmentRequired);
+    assert_ok(svm.send(claim(&svm, Some(id)), &[sponsor]));
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&sponsor, &mint)).identity_commitment, id);
+    assert_err(svm.send(claim(&svm, Some(id)), &[sponsor]), ErrorCode::IdentityAlreadyUsed);
+    assert_ok(check_in_with_identity(&mut svm, &fixture, &sponsor, 0, [7; 32]));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, Listing, DEFAULT_PRICE_UPDATE_INTERVAL_SECS};
+
+fn update_price(svm: &mut Svm, seller: &Pubkey, mint: &Pubkey, price_lamports: u64) -> Result<TxMeta, TxError> {
+    let listing = listing_pda(mint);
+    let event = svm.get::<Listing>(&listing).event;
+    svm.send(
+        ix(
+            ticketchain::accounts::UpdateListingPrice {
+                seller: *seller,
+                event,
+                listing,
+                platform_config: platform_config_pda(),
+                ask_book: None,
+            },
+            ticketchain::instruction::UpdateListingPrice { price_lamports },
+        ),
+        &[*seller],
+    )
+}
+
+#[test]
+fn price_updates_wait_out_the_interval() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+    let listed_at = svm.now();
+    assert_eq!(svm.get::<Listing>(&listing_pda(&mint)).created_at, listed_at);
+
+    svm.warp_to(listed_at + DEFAULT_PRICE_UPDATE_INTERVAL_SECS - 1);
+    assert_err(update_price(&mut svm, &seller, &mint, SOL / 4), ErrorCode::PriceUpdateTooFrequent);
+    svm.warp_to(listed_at + DEFAULT_PRICE_UPDATE_INTERVAL_SECS);
+    assert_ok(update_price(&mut svm, &seller, &mint, SOL / 4));
+    let listing = svm.get::<Listing>(&listing_pda(&mint));
+    assert_eq!((listing.price_lamports, listing.created_at), (SOL / 4, listed_at));
+    assert_eq!(listing.last_price_update_at, listed_at + DEFAULT_PRICE_UPDATE_INTERVAL_SECS);
+
+    // The interval restarts from the latest update
+    svm.warp(DEFAULT_PRICE_UPDATE_INTERVAL_SECS - 1);
+    assert_err(update_price(&mut svm, &seller, &mint, SOL / 3), ErrorCode::PriceUpdateTooFrequent);
+}
+
+#[test]
+fn buyer_max_price_guards_against_a_raise_before_the_purchase_lands() {
+    let mut svm = setup();
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+
+    // The buyer quotes the listed price; the seller raises it once the interval allows
+    svm.warp(DEFAULT_PRICE_UPDATE_INTERVAL_SECS);
+    assert_ok(update_price(&mut svm, &seller, &mint, SOL / 2));
+    assert_err(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL / 5), &[buyer]), ErrorCode::PriceAboveMax);
+    assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL / 2), &[buyer]));
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use anchor_spl::token::spl_token;
+use common::*;
+
+#[test]
+fn ticket_mint_is_fixed_at_one_token() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+
+    let state = mint_state(&svm, &mint);
+    assert_eq!((state.supply, state.mint_authority, state.freeze_authority), (1, None.into(), None.into()));
+
+    // A second mint_to on the processed mint is refused by the token program
+    let mint_again =
+        spl_token::instruction::mint_to(&TOKEN_PROGRAM, &mint, &ata(&buyer, &mint), &buyer, &[], 1).unwrap();
+    assert_err(svm.process(&[mint_again], &[buyer]), spl_token::error::TokenError::FixedSupply as u32);
+    assert_eq!(mint_state(&svm, &mint).supply, 1);
+}
+
+#[test]
+fn replayed_purchase_is_a_no_op() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp(61);
+
+    // The mint's authority is revoked by now, yet the replay still validates and returns Ok
+    let before = svm.lamports(&buyer);
+    assert_ok(svm.send(ix(buy_ticket_accounts(&fixture, &buyer, 0), buy_ticket_args(0, 0)), &[buyer]));
+    assert_eq!(svm.lamports(&buyer), before);
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+    assert_eq!(mint_state(&svm, &mint).supply, 1);
+    assert_eq!(event(&svm, &fixture).sold, 1);
+    let key: ticketchain::PurchaseIdempotencyKey =
+        svm.get(&pda(&[b"idem", buyer.as_ref(), fixture.event.as_ref(), &0u32.to_le_bytes()]));
+    assert_eq!(key.mint, mint);
+}
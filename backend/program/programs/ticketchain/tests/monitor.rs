@@ -0,0 +1,171 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use common::*;
+use ticketchain::{
+    ErrorCode, HealthCheckFailed, MonitorClaims, MonitorRewardPool, HEALTH_ESCROW, MONITOR_INTERVAL_SECONDS,
+    MONITOR_REWARD_CAP_LAMPORTS, MONITOR_REWARD_LAMPORTS,
+};
+
+fn pool_pda(event: &Pubkey) -> Pubkey {
+    pda(&[b"monitor_pool", event.as_ref()])
+}
+
+fn claims_pda(event: &Pubkey, monitor: &Pubkey) -> Pubkey {
+    pda(&[b"monitor_claims", event.as_ref(), monitor.as_ref()])
+}
+
+fn fund(svm: &mut Svm, fixture: &EventFixture, amount: u64) {
+    let funder = svm.wallet(amount + SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::FundMonitorRewards {
+                funder,
+                event: fixture.event,
+                reward_pool: pool_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::FundMonitorRewards { amount },
+        ),
+        &[funder],
+    ));
+}
+
+fn monitor(svm: &mut Svm, event: &Pubkey, pool: Option<Pubkey>, cranker: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::MonitorEventHealth {
+                cranker: *cranker,
+                event: *event,
+                vault: None,
+                reward_pool: pool,
+                monitor_claims: claims_pda(event, cranker),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::MonitorEventHealth {},
+        ),
+        &[*cranker],
+    )
+}
+
+#[test]
+fn monitor_is_paid_from_the_event_pool() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    fund(&mut svm, &fixture, 2 * MONITOR_REWARD_LAMPORTS);
+    let cranker = svm.wallet(SOL);
+
+    assert_ok(monitor(&mut svm, &fixture.event, Some(pool_pda(&fixture.event)), &cranker));
+    let before = svm.lamports(&cranker);
+    svm.warp(MONITOR_INTERVAL_SECONDS);
+    assert_ok(monitor(&mut svm, &fixture.event, Some(pool_pda(&fixture.event)), &cranker));
+
+    assert_eq!(svm.lamports(&cranker), before + MONITOR_REWARD_LAMPORTS);
+    let pool: MonitorRewardPool = svm.get(&pool_pda(&fixture.event));
+    assert_eq!(pool.balance, 0);
+    let claims: MonitorClaims = svm.get(&claims_pda(&fixture.event, &cranker));
+    assert_eq!(claims.earned, 2 * MONITOR_REWARD_LAMPORTS);
+}
+
+#[test]
+fn another_event_cannot_draw_on_the_pool() {
+    let mut svm = setup();
+    let funded = create_event(&mut svm);
+    fund(&mut svm, &funded, 10 * MONITOR_REWARD_LAMPORTS);
+    let other = create_event(&mut svm);
+    let cranker = svm.wallet(SOL);
+
+    assert_err(
+        monitor(&mut svm, &other.event, Some(pool_pda(&funded.event)), &cranker),
+        anchor_lang::error::ErrorCode::ConstraintSeeds,
+    );
+    // Without a pool the check still runs, unrewarded
+    let before = svm.lamports(&cranker);
+    assert_ok(monitor(&mut svm, &other.event, None, &cranker));
+    let claims_rent = svm.lamports(&claims_pda(&other.event, &cranker));
+    assert_eq!(svm.lamports(&cranker), before - claims_rent);
+}
+
+#[test]
+fn one_monitor_earns_at_most_the_cap() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    fund(&mut svm, &fixture, 2 * MONITOR_REWARD_CAP_LAMPORTS);
+    let cranker = svm.wallet(SOL);
+    let rewards = MONITOR_REWARD_CAP_LAMPORTS / MONITOR_REWARD_LAMPORTS;
+
+    for _ in 0..=rewards {
+        assert_ok(monitor(&mut svm, &fixture.event, Some(pool_pda(&fixture.event)), &cranker));
+        svm.warp(MONITOR_INTERVAL_SECONDS);
+    }
+
+    let claims: MonitorClaims = svm.get(&claims_pda(&fixture.event, &cranker));
+    assert_eq!(claims.earned, MONITOR_REWARD_CAP_LAMPORTS);
+    let pool: MonitorRewardPool = svm.get(&pool_pda(&fixture.event));
+    assert_eq!(pool.balance, MONITOR_REWARD_CAP_LAMPORTS);
+
+    // A second monitor can still be paid
+    let second = svm.wallet(SOL);
+    assert_ok(monitor(&mut svm, &fixture.event, Some(pool_pda(&fixture.event)), &second));
+    let claims: MonitorClaims = svm.get(&claims_pda(&fixture.event, &second));
+    assert_eq!(claims.earned, MONITOR_REWARD_LAMPORTS);
+}
+
+fn reset_health(svm: &mut Svm, fixture: &EventFixture, authority: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ResetHealthCheck {
+                authority: *authority,
+                platform_config: platform_config_pda(),
+                event: fixture.event,
+            },
+            ticketchain::instruction::ResetHealthCheck {},
+        ),
+        &[*authority],
+    )
+}
+
+#[test]
+fn failed_check_pauses_sales_until_the_platform_resets_it() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    let fixture = create_event(&mut svm);
+    let (seller, buyer, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 5));
+
+    // Empty the listing's escrow behind the program's back
+    let escrow = pda(&[b"escrow", mint.as_ref()]);
+    let mut account = svm.account(&escrow).unwrap().clone();
+    let mut state = TokenAccount::unpack(&account.data).unwrap();
+    state.amount = 0;
+    TokenAccount::pack(state, &mut account.data).unwrap();
+    svm.set_account(escrow, account);
+
+    let meta = assert_ok(svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::MonitorEventHealth {
+                cranker,
+                event: fixture.event,
+                vault: None,
+                reward_pool: None,
+                monitor_claims: claims_pda(&fixture.event, &cranker),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::MonitorEventHealth {},
+            vec![AccountMeta::new(listing_pda(&mint), false), AccountMeta::new_readonly(escrow, false)],
+        ),
+        &[cranker],
+    ));
+    assert_eq!(meta.event::<HealthCheckFailed>().reason, HEALTH_ESCROW);
+    assert!(event(&svm, &fixture).sales_paused);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SalesPaused);
+
+    assert_err(reset_health(&mut svm, &fixture, &fixture.organizer), ErrorCode::Unauthorized);
+    assert_ok(reset_health(&mut svm, &fixture, &PLATFORM_AUTHORITY));
+    assert!(!event(&svm, &fixture).sales_paused);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+}
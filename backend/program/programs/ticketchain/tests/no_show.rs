@@ -0,0 +1,61 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, NoShowRecord, NO_SHOW_DELAY_SECONDS};
+
+fn no_show_pda(mint: &Pubkey, holder: &Pubkey) -> Pubkey {
+    pda(&[b"no_show", mint.as_ref(), holder.as_ref()])
+}
+
+fn claim_no_show(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimNoShow {
+                holder: *holder,
+                event: fixture.event,
+                ticket_mint: mint,
+                holder_token_account: ata(holder, &mint),
+                attendance_record: pda(&[b"attendance", mint.as_ref()]),
+                no_show: no_show_pda(&mint, holder),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ClaimNoShow { _ticket_index: index },
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn holder_who_never_checked_in_can_attest_a_no_show() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let claimable_ts = event(&svm, &fixture).date_ts + NO_SHOW_DELAY_SECONDS;
+
+    svm.warp_to(claimable_ts - 1);
+    assert_err(claim_no_show(&mut svm, &fixture, &holder, 0), ErrorCode::EventNotOver);
+    svm.warp_to(claimable_ts);
+    assert_ok(claim_no_show(&mut svm, &fixture, &holder, 0));
+    let record: NoShowRecord = svm.get(&no_show_pda(&mint, &holder));
+    assert_eq!((record.event, record.ticket_mint, record.holder), (fixture.event, mint, holder));
+    assert_eq!(record.claimed_ts, claimable_ts);
+}
+
+#[test]
+fn checked_in_or_unheld_tickets_cannot_claim_a_no_show() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(check_in(&mut svm, &fixture, &holder, 1));
+    svm.warp_to(event(&svm, &fixture).date_ts + NO_SHOW_DELAY_SECONDS);
+
+    assert_err(claim_no_show(&mut svm, &fixture, &holder, 1), ErrorCode::TicketCheckedIn);
+    create_token_account(&mut svm, &stranger, &mint, 0);
+    assert_err(claim_no_show(&mut svm, &fixture, &stranger, 0), ErrorCode::NotTicketHolder);
+}
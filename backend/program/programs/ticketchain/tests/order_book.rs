@@ -0,0 +1,436 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PLATFORM_WALLET};
+
+fn book_pda(event: &Pubkey) -> Pubkey {
+    pda(&[b"orderbook", event.as_ref()])
+}
+
+fn book_fixture(svm: &mut Svm) -> EventFixture {
+    let fixture = create_event(svm);
+    let payer = svm.wallet(SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::InitOrderBook {
+                payer,
+                event: fixture.event,
+                order_book: book_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::InitOrderBook {},
+        ),
+        &[payer],
+    ));
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    fixture
+}
+
+fn place_bid(svm: &mut Svm, fixture: &EventFixture, bidder: &Pubkey, price: u64, expires_at: i64) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::PlaceBuyOrder {
+                bidder: *bidder,
+                order_book: book_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::PlaceBuyOrder { price, quantity: 1, expires_at },
+        ),
+        &[*bidder],
+    ));
+}
+
+fn place_ask(
+    svm: &mut Svm,
+    fixture: &EventFixture,
+    seller: &Pubkey,
+    mint: &Pubkey,
+    ticket_index: u32,
+    price: u64,
+    expires_at: i64,
+) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::PlaceSellOrder {
+                seller: *seller,
+                event: fixture.event,
+                order_book: book_pda(&fixture.event),
+                ticket_mint: *mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                seller_token_account: ata(seller, mint),
+                escrow_token_account: pda(&[b"book_escrow", mint.as_ref()]),
+                receipt: None,
+                purchase_receipt: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::PlaceSellOrder { price, _ticket_index: ticket_index, expires_at },
+        ),
+        &[*seller],
+    )
+}
+
+fn match_ix(
+    fixture: &EventFixture,
+    cranker: &Pubkey,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    ticket_index: u32,
+    platform: Pubkey,
+    receipt: Option<Pubkey>,
+) -> anchor_lang::solana_program::instruction::Instruction {
+    let mint = ticket_mint_pda(&fixture.event, ticket_index);
+    ix(
+        ticketchain::accounts::MatchOrders {
+            cranker: *cranker,
+            buyer: *buyer,
+            seller: *seller,
+            organizer: fixture.organizer,
+            platform,
+            platform_config: platform_config_pda(),
+            fee_day: None,
+            event: fixture.event,
+            royalty_escrow: None,
+            order_book: book_pda(&fixture.event),
+            ticket_mint: mint,
+            ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+            escrow_token_account: pda(&[b"book_escrow", mint.as_ref()]),
+            buyer_token_account: ata(buyer, &mint),
+            receipt,
+            deposit_receipt: None,
+            ticket_stats: pda(&[b"ticket_stats", fixture.event.as_ref()]),
+            floor_monitor: pda(&[b"floor_monitor", fixture.event.as_ref()]),
+            token_program: TOKEN_PROGRAM,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain::instruction::MatchOrders { _ticket_index: ticket_index },
+    )
+}
+
+fn cancel_ask(svm: &mut Svm, fixture: &EventFixture, caller: &Pubkey, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CancelSellOrder {
+                caller: *caller,
+                seller: *seller,
+                order_book: book_pda(&fixture.event),
+                ticket_mint: *mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                seller_token_account: ata(seller, mint),
+                escrow_token_account: pda(&[b"book_escrow", mint.as_ref()]),
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::CancelSellOrder {},
+        ),
+        &[*caller],
+    )
+}
+
+#[test]
+fn sell_order_rejects_a_mint_that_is_not_the_events_ticket() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+
+    // A look-alike token the seller minted themselves, passed as ticket 0
+    let fake = create_mint(&mut svm, &seller, 0);
+    create_token_account(&mut svm, &seller, &fake, 1);
+    let expires_at = svm.now() + 3600;
+    assert_err(
+        place_ask(&mut svm, &fixture, &seller, &fake, 0, SOL, expires_at),
+        anchor_lang::error::ErrorCode::ConstraintSeeds,
+    );
+}
+
+#[test]
+fn expired_head_ask_is_skipped_and_returnable_by_anyone() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (early, late, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let cheap = assert_ok(buy_ticket(&mut svm, &fixture, &early, 0));
+    let pricier = assert_ok(buy_ticket(&mut svm, &fixture, &late, 0));
+    let now = svm.now();
+    assert_ok(place_ask(&mut svm, &fixture, &early, &cheap, 0, SOL / 2, now + 60));
+    assert_ok(place_ask(&mut svm, &fixture, &late, &pricier, 1, SOL, now + 7200));
+    place_bid(&mut svm, &fixture, &bidder, 2 * SOL, now + 7200);
+
+    svm.warp(60);
+    // The cheap ask at the head of the book has expired; the crank fills the next one
+    let seller_before = svm.lamports(&late);
+    assert_ok(svm.send(match_ix(&fixture, &cranker, &bidder, &late, 1, PLATFORM_WALLET, None), &[cranker]));
+    assert_eq!(token_balance(&svm, &ata(&bidder, &pricier)), 1);
+    assert!(svm.lamports(&late) > seller_before);
+
+    let stranger = svm.wallet(SOL);
+    assert_ok(cancel_ask(&mut svm, &fixture, &stranger, &early, &cheap));
+    assert_eq!(token_balance(&svm, &ata(&early, &cheap)), 1);
+}
+
+#[test]
+fn live_ask_can_only_be_cancelled_by_its_seller() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let expires_at = svm.now() + 3600;
+    assert_ok(place_ask(&mut svm, &fixture, &seller, &mint, 0, SOL, expires_at));
+
+    let stranger = svm.wallet(SOL);
+    assert_err(cancel_ask(&mut svm, &fixture, &stranger, &seller, &mint), ErrorCode::OrderStillActive);
+    assert_ok(cancel_ask(&mut svm, &fixture, &seller, &seller, &mint));
+}
+
+#[test]
+fn match_pays_the_platform_share_only_to_the_platform_wallet() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (seller, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let expires_at = svm.now() + 3600;
+    assert_ok(place_ask(&mut svm, &fixture, &seller, &mint, 0, SOL, expires_at));
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+
+    let attacker = svm.wallet(SOL);
+    assert_err(
+        svm.send(match_ix(&fixture, &cranker, &bidder, &seller, 0, attacker, None), &[cranker]),
+        ErrorCode::InvalidPlatformWallet,
+    );
+    let platform_before = svm.lamports(&PLATFORM_WALLET);
+    assert_ok(svm.send(match_ix(&fixture, &cranker, &bidder, &seller, 0, PLATFORM_WALLET, None), &[cranker]));
+    assert_eq!(svm.lamports(&PLATFORM_WALLET) - platform_before, SOL * 2000 / 10_000);
+    let config: ticketchain::PlatformConfig = svm.get(&platform_config_pda());
+    assert_eq!((config.resale_fees_collected, config.resale_fee_count), (SOL * 2000 / 10_000, 1));
+    assert_eq!(event(&svm, &fixture).platform_fees_collected, SOL * 2000 / 10_000);
+}
+
+fn set_original_buyer_only(svm: &mut Svm, fixture: &EventFixture) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetOriginalBuyerOnly { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetOriginalBuyerOnly { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+#[test]
+fn original_buyer_only_applies_to_asks_when_placed_and_filled() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (buyer, holder, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(transfer_ticket(&mut svm, &buyer, &holder, &mint));
+
+    // Placed while the mode is off, by a secondary holder
+    let expires_at = svm.now() + 3600;
+    assert_ok(place_ask(&mut svm, &fixture, &holder, &mint, 0, SOL, expires_at));
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+    set_original_buyer_only(&mut svm, &fixture);
+    assert_err(
+        svm.send(match_ix(&fixture, &cranker, &bidder, &holder, 0, PLATFORM_WALLET, None), &[cranker]),
+        ErrorCode::NotOriginalBuyer,
+    );
+
+    // Back to the holder, who still can't place a new ask
+    assert_ok(cancel_ask(&mut svm, &fixture, &holder, &holder, &mint));
+    assert_err(
+        place_ask(&mut svm, &fixture, &holder, &mint, 0, SOL, expires_at),
+        ErrorCode::NotOriginalBuyer,
+    );
+}
+
+#[test]
+fn original_buyer_can_sell_through_the_book_with_their_receipt() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    set_original_buyer_only(&mut svm, &fixture);
+    let (buyer, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let expires_at = svm.now() + 3600;
+
+    let place = ix(
+        ticketchain::accounts::PlaceSellOrder {
+            seller: buyer,
+            event: fixture.event,
+            order_book: book_pda(&fixture.event),
+            ticket_mint: mint,
+            ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+            seller_token_account: ata(&buyer, &mint),
+            escrow_token_account: pda(&[b"book_escrow", mint.as_ref()]),
+            receipt: Some(receipt_pda(&buyer, &mint)),
+            purchase_receipt: None,
+            token_program: TOKEN_PROGRAM,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain::instruction::PlaceSellOrder { price: SOL, _ticket_index: 0, expires_at },
+    );
+    assert_ok(svm.send(place, &[buyer]));
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+
+    let fill = match_ix(&fixture, &cranker, &bidder, &buyer, 0, PLATFORM_WALLET, Some(receipt_pda(&buyer, &mint)));
+    assert_ok(svm.send(fill, &[cranker]));
+    assert_eq!(token_balance(&svm, &ata(&bidder, &mint)), 1);
+}
+
+fn refund_bid_ix(fixture: &EventFixture, cranker: &Pubkey, bidder: &Pubkey) -> anchor_lang::solana_program::instruction::Instruction {
+    ix(
+        ticketchain::accounts::RefundExpiredOffer { cranker: *cranker, bidder: *bidder, order_book: book_pda(&fixture.event) },
+        ticketchain::instruction::RefundExpiredOffer { index: 0 },
+    )
+}
+
+#[test]
+fn bid_expires_at_its_deadline_and_refunds_to_the_bidder() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (seller, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let expires_at = svm.now() + 100;
+    assert_ok(place_ask(&mut svm, &fixture, &seller, &mint, 0, SOL, expires_at + 3600));
+    let now = svm.now();
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::PlaceBuyOrder {
+                    bidder,
+                    order_book: book_pda(&fixture.event),
+                    system_program: system_program::ID,
+                },
+                ticketchain::instruction::PlaceBuyOrder { price: SOL, quantity: 1, expires_at: now },
+            ),
+            &[bidder],
+        ),
+        ErrorCode::InvalidExpiry,
+    );
+    let before = svm.lamports(&bidder);
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+    assert_eq!(before - svm.lamports(&bidder), SOL + ticketchain::BID_REFUND_TIP_LAMPORTS);
+
+    svm.warp_to(expires_at - 1);
+    assert_err(svm.send(refund_bid_ix(&fixture, &cranker, &bidder), &[cranker]), ErrorCode::OfferNotExpired);
+    // From the deadline on, the crank skips the bid: with no other bid there is nothing to fill
+    svm.warp_to(expires_at);
+    assert_err(
+        svm.send(match_ix(&fixture, &cranker, &bidder, &seller, 0, PLATFORM_WALLET, None), &[cranker]),
+        ErrorCode::NoCrossingOrders,
+    );
+    let cranker_before = svm.lamports(&cranker);
+    assert_ok(svm.send(refund_bid_ix(&fixture, &cranker, &bidder), &[cranker]));
+    assert_eq!(before - svm.lamports(&bidder), ticketchain::BID_REFUND_TIP_LAMPORTS);
+    assert_eq!(svm.lamports(&cranker) - cranker_before, ticketchain::BID_REFUND_TIP_LAMPORTS);
+    assert_err(svm.send(refund_bid_ix(&fixture, &cranker, &bidder), &[cranker]), ErrorCode::InvalidOrderIndex);
+}
+
+#[test]
+fn match_and_refund_in_one_transaction_never_both_succeed() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (seller, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let expires_at = svm.now() + 100;
+    assert_ok(place_ask(&mut svm, &fixture, &seller, &mint, 0, SOL, expires_at + 3600));
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+    let fill = match_ix(&fixture, &cranker, &bidder, &seller, 0, PLATFORM_WALLET, None);
+
+    // Still live: the match lands first, so the refund finds no bid and the whole transaction rolls back
+    svm.warp_to(expires_at - 1);
+    let before = svm.lamports(&bidder);
+    assert_err(
+        svm.process(&[fill.clone(), refund_bid_ix(&fixture, &cranker, &bidder)], &[cranker]),
+        ErrorCode::InvalidOrderIndex,
+    );
+    assert_eq!(svm.lamports(&bidder), before);
+    assert!(!svm.exists(&ata(&bidder, &mint)));
+
+    // Expired: the refund lands first, so the match finds no crossing bid
+    svm.warp_to(expires_at);
+    assert_err(
+        svm.process(&[refund_bid_ix(&fixture, &cranker, &bidder), fill], &[cranker]),
+        ErrorCode::NoCrossingOrders,
+    );
+    assert_eq!(svm.lamports(&bidder), before);
+    assert_ok(svm.send(refund_bid_ix(&fixture, &cranker, &bidder), &[cranker]));
+    assert_eq!(svm.lamports(&bidder), before + SOL);
+}
+
+#[test]
+fn original_buyer_only_cannot_be_dodged_by_listing_under_another_event() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    set_original_buyer_only(&mut svm, &fixture);
+    let (buyer, holder) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(transfer_ticket(&mut svm, &buyer, &holder, &mint));
+    assert_err(list_ticket(&mut svm, &fixture, &holder, &mint, SOL / 5), ErrorCode::NotOriginalBuyer);
+
+    // An event without the mode doesn't own the mint
+    let other = create_event(&mut svm);
+    let instruction = ix(
+        list_accounts(&other, &holder, &mint),
+        ticketchain::instruction::ListForResale { _ticket_index: 0, price_lamports: SOL / 5 },
+    );
+    assert_err(svm.send(instruction, &[holder]), anchor_lang::error::ErrorCode::ConstraintSeeds);
+    assert!(!svm.exists(&listing_pda(&mint)));
+}
+
+fn cancel_bid_ix(fixture: &EventFixture, bidder: &Pubkey, index: u8) -> anchor_lang::solana_program::instruction::Instruction {
+    ix(
+        ticketchain::accounts::CancelBuyOrder { bidder: *bidder, order_book: book_pda(&fixture.event) },
+        ticketchain::instruction::CancelBuyOrder { index },
+    )
+}
+
+#[test]
+fn only_the_bidder_can_cancel_a_bid_and_gets_the_tip_back() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (bidder, other) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let (before, expires_at) = (svm.lamports(&bidder), svm.now() + 3600);
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+
+    assert_err(svm.send(cancel_bid_ix(&fixture, &other, 0), &[other]), ErrorCode::InvalidBuyer);
+    assert_ok(svm.send(cancel_bid_ix(&fixture, &bidder, 0), &[bidder]));
+    assert_eq!(svm.lamports(&bidder), before);
+    assert_err(svm.send(cancel_bid_ix(&fixture, &bidder, 0), &[bidder]), ErrorCode::InvalidOrderIndex);
+}
+
+fn set_multiple(svm: &mut Svm, fixture: &EventFixture, max_listing_multiple: u16) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetMaxListingMultiple { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetMaxListingMultiple { max_listing_multiple },
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+#[test]
+fn fill_rechecks_the_cap_and_records_the_sale() {
+    let mut svm = setup();
+    let fixture = book_fixture(&mut svm);
+    let (seller, bidder, cranker) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let expires_at = svm.now() + 3600;
+    assert_ok(place_ask(&mut svm, &fixture, &seller, &mint, 0, SOL, expires_at));
+    place_bid(&mut svm, &fixture, &bidder, SOL, expires_at);
+
+    // The organizer caps resale after the ask rested
+    set_multiple(&mut svm, &fixture, 150);
+    assert_err(
+        svm.send(match_ix(&fixture, &cranker, &bidder, &seller, 0, PLATFORM_WALLET, None), &[cranker]),
+        ErrorCode::ListingMultipleTooHigh,
+    );
+    set_multiple(&mut svm, &fixture, 0);
+    assert_ok(svm.send(match_ix(&fixture, &cranker, &bidder, &seller, 0, PLATFORM_WALLET, None), &[cranker]));
+
+    let stats: ticketchain::TicketStats = svm.get(&pda(&[b"ticket_stats", fixture.event.as_ref()]));
+    assert_eq!((stats.price_history[0], stats.sales_count), (SOL, 1));
+    let monitor: ticketchain::FloorMonitor = svm.get(&pda(&[b"floor_monitor", fixture.event.as_ref()]));
+    assert_eq!(monitor.hourly_high_price, SOL);
+}
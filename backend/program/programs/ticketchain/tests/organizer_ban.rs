@@ -0,0 +1,69 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, OrganizerBan};
+
+fn ban_pda(organizer: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    pda(&[b"org_ban", organizer.as_ref(), wallet.as_ref()])
+}
+
+fn ban(svm: &mut Svm, organizer: &Pubkey, wallet: &Pubkey) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::BanFromOrganizerEvents {
+                organizer: *organizer,
+                organizer_ban: ban_pda(organizer, wallet),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::BanFromOrganizerEvents { wallet: *wallet, reason_hash: [7; 32] },
+        ),
+        &[*organizer],
+    ));
+}
+
+fn lift(svm: &mut Svm, organizer: &Pubkey, wallet: &Pubkey) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::LiftOrganizerBan { organizer: *organizer, organizer_ban: ban_pda(organizer, wallet) },
+            ticketchain::instruction::LiftOrganizerBan { _wallet: *wallet },
+        ),
+        &[*organizer],
+    ));
+}
+
+#[test]
+fn banned_wallet_cannot_buy_or_check_in_until_the_ban_is_lifted() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, friend) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    assert_ok(delegate_entry(&mut svm, &fixture, &holder, &friend, 0, 4));
+
+    ban(&mut svm, &fixture.organizer, &holder);
+    let record: OrganizerBan = svm.get(&ban_pda(&fixture.organizer, &holder));
+    assert_eq!((record.organizer, record.wallet, record.reason_hash), (fixture.organizer, holder, [7; 32]));
+    assert_err(buy_ticket(&mut svm, &fixture, &holder, 0), ErrorCode::BannedFromOrganizerEvents);
+    assert_err(check_in(&mut svm, &fixture, &holder, 0), ErrorCode::BannedFromOrganizerEvents);
+    assert_err(check_in_delegate(&mut svm, &fixture, &friend, &holder, 0), ErrorCode::BannedFromOrganizerEvents);
+
+    let rent = svm.lamports(&ban_pda(&fixture.organizer, &holder));
+    let before = svm.lamports(&fixture.organizer);
+    lift(&mut svm, &fixture.organizer, &holder);
+    assert_eq!(svm.lamports(&fixture.organizer), before + rent);
+    assert_ok(check_in(&mut svm, &fixture, &holder, 0));
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+}
+
+#[test]
+fn ban_only_covers_the_banning_organizers_events() {
+    let mut svm = setup();
+    let (banning, other) = (create_event(&mut svm), create_event(&mut svm));
+    let holder = svm.wallet(10 * SOL);
+    ban(&mut svm, &banning.organizer, &holder);
+
+    assert_err(buy_ticket(&mut svm, &banning, &holder, 0), ErrorCode::BannedFromOrganizerEvents);
+    assert_ok(buy_ticket(&mut svm, &other, &holder, 0));
+    assert_ok(check_in(&mut svm, &other, &holder, 0));
+}
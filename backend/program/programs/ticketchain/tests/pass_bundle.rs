@@ -0,0 +1,324 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, Receipt, TicketPurchased};
+
+struct Bundle {
+    key: Pubkey,
+    creator: Pubkey,
+    events: [EventFixture; 2],
+}
+
+fn pass_mint_pda(bundle: &Pubkey, index: u32) -> Pubkey {
+    pda(&[b"pass_mint", bundle.as_ref(), &index.to_le_bytes()])
+}
+
+/// Two 10-seat events by different organizers; the second co-signs a bundle of `supply`
+/// passes created by the first.
+fn bundle_fixture(svm: &mut Svm, supply: u32, sale_end_ts: i64) -> Result<Bundle, TxError> {
+    let events = [create_event(svm), create_event(svm)];
+    let creator = events[0].organizer;
+    let key = pda(&[b"pass_bundle", creator.as_ref(), &7u64.to_le_bytes()]);
+    let mut remaining: Vec<AccountMeta> = events.iter().map(|e| AccountMeta::new(e.event, false)).collect();
+    remaining.push(AccountMeta::new_readonly(events[1].organizer, true));
+    svm.process(
+        &[ix_with_remaining(
+            ticketchain::accounts::CreatePassBundle { creator, bundle: key, system_program: system_program::ID },
+            ticketchain::instruction::CreatePassBundle {
+                nonce: 7,
+                events: events.iter().map(|e| e.event).collect(),
+                shares_bps: vec![5_000, 5_000],
+                price_lamports: SOL,
+                supply,
+                sale_end_ts,
+            },
+            remaining,
+        )],
+        &[creator, events[1].organizer],
+    )?;
+    Ok(Bundle { key, creator, events })
+}
+
+fn buy_pass(svm: &mut Svm, bundle: &Bundle, buyer: &Pubkey) -> Result<Pubkey, TxError> {
+    let sold = svm.get::<ticketchain::PassBundle>(&bundle.key).sold;
+    let pass_mint = pass_mint_pda(&bundle.key, sold);
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::BuyPass {
+                buyer: *buyer,
+                bundle: bundle.key,
+                pass_mint,
+                buyer_pass_account: ata(buyer, &pass_mint),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::BuyPass {},
+            bundle
+                .events
+                .iter()
+                .map(|e| AccountMeta::new(e.organizer, false))
+                .chain(bundle.events.iter().map(|e| AccountMeta::new(e.event, false)))
+                .collect(),
+        ),
+        &[*buyer],
+    )?;
+    Ok(pass_mint)
+}
+
+fn redeem(svm: &mut Svm, bundle: &Bundle, holder: &Pubkey, pass_index: u32, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    redeem_with_identity(svm, bundle, holder, pass_index, fixture, None)
+}
+
+fn redeem_with_identity(
+    svm: &mut Svm,
+    bundle: &Bundle,
+    holder: &Pubkey,
+    pass_index: u32,
+    fixture: &EventFixture,
+    identity_commitment: Option<[u8; 32]>,
+) -> Result<TxMeta, TxError> {
+    let sold = event(svm, fixture).sold;
+    let pass_mint = pass_mint_pda(&bundle.key, pass_index);
+    let ticket_mint = ticket_mint_pda(&fixture.event, sold);
+    svm.send(
+        ix(
+            ticketchain::accounts::RedeemBundleTicket {
+                holder: *holder,
+                organizer: fixture.organizer,
+                bundle: bundle.key,
+                pass_mint,
+                holder_pass_account: ata(holder, &pass_mint),
+                event: fixture.event,
+                redemption: pda(&[b"pass_redemption", pass_mint.as_ref(), fixture.event.as_ref()]),
+                ticket_authority: ticket_authority_pda(&fixture.event, sold),
+                ticket_mint,
+                holder_token_account: ata(holder, &ticket_mint),
+                receipt: receipt_pda(holder, &ticket_mint),
+                identity_purchase: identity_commitment.map(|c| pda(&[b"identity", fixture.event.as_ref(), &c])),
+                name_override: name_override_pda(&fixture.event, sold),
+                ticket_metadata: None,
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::RedeemBundleTicket { _pass_index: pass_index, identity_commitment },
+        ),
+        &[*holder],
+    )
+}
+
+fn release(svm: &mut Svm, bundle: &Bundle, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    let anyone = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::ReleasePassSeats { bundle: bundle.key, event: fixture.event },
+            ticketchain::instruction::ReleasePassSeats {},
+        ),
+        &[anyone],
+    )
+}
+
+fn cancel_event(svm: &mut Svm, fixture: &EventFixture) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+#[test]
+fn bundle_reserves_seats_on_every_event_until_general_sale_exhausts() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 4, sale_end_ts));
+    for fixture in &bundle.events {
+        assert_eq!(event(&svm, fixture).pass_reserved, 4);
+    }
+
+    // Six general seats remain on each event
+    for _ in 0..6 {
+        let fan = svm.wallet(SOL);
+        assert_ok(buy_ticket(&mut svm, &bundle.events[1], &fan, 0));
+    }
+    let late = svm.wallet(SOL);
+    assert_err(buy_ticket(&mut svm, &bundle.events[1], &late, 0), ErrorCode::SoldOut);
+
+    // The reserve still covers every pass on the sold-out event
+    let holder = svm.wallet(10 * SOL);
+    let pass_mint = assert_ok(buy_pass(&mut svm, &bundle, &holder));
+    let pass_state = mint_state(&svm, &pass_mint);
+    assert_eq!((pass_state.supply, pass_state.mint_authority), (1, None.into()));
+    assert_ok(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[1]));
+    assert_ok(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[0]));
+    assert!(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[1]).is_err());
+    let sold_out = event(&svm, &bundle.events[1]);
+    assert_eq!((sold_out.sold, sold_out.pass_reserved), (7, 3));
+
+    // A second bundle can't reserve on the event that's out of seats
+    let creator = bundle.creator;
+    let key = pda(&[b"pass_bundle", creator.as_ref(), &8u64.to_le_bytes()]);
+    let mut remaining: Vec<AccountMeta> = bundle.events.iter().map(|e| AccountMeta::new(e.event, false)).collect();
+    remaining.push(AccountMeta::new_readonly(bundle.events[1].organizer, true));
+    let second = ix_with_remaining(
+        ticketchain::accounts::CreatePassBundle { creator, bundle: key, system_program: system_program::ID },
+        ticketchain::instruction::CreatePassBundle {
+            nonce: 8,
+            events: bundle.events.iter().map(|e| e.event).collect(),
+            shares_bps: vec![5_000, 5_000],
+            price_lamports: SOL,
+            supply: 1,
+            sale_end_ts,
+        },
+        remaining,
+    );
+    assert_err(svm.process(&[second], &[creator, bundle.events[1].organizer]), ErrorCode::InsufficientSupply);
+}
+
+#[test]
+fn unsold_pass_seats_are_released_after_the_pass_sale() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 4, sale_end_ts));
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+    assert_ok(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[0]));
+    assert_err(release(&mut svm, &bundle, &bundle.events[0]), ErrorCode::PassSaleOpen);
+
+    svm.warp(3600);
+    assert_err(buy_pass(&mut svm, &bundle, &holder), ErrorCode::PassSaleClosed);
+    assert_ok(release(&mut svm, &bundle, &bundle.events[0]));
+    assert_ok(release(&mut svm, &bundle, &bundle.events[1]));
+    // Event 0 keeps a seat for pass 1; event 1 keeps seats for both passes
+    assert_eq!(event(&svm, &bundle.events[0]).pass_reserved, 1);
+    assert_eq!(event(&svm, &bundle.events[1]).pass_reserved, 2);
+
+    // Releasing twice changes nothing, and the kept seats still redeem
+    assert_ok(release(&mut svm, &bundle, &bundle.events[0]));
+    assert_eq!(event(&svm, &bundle.events[0]).pass_reserved, 1);
+    assert_ok(redeem(&mut svm, &bundle, &holder, 1, &bundle.events[0]));
+    assert_eq!(event(&svm, &bundle.events[0]).pass_reserved, 0);
+}
+
+#[test]
+fn cancelled_event_releases_every_reserved_seat() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 4, sale_end_ts));
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+
+    cancel_event(&mut svm, &bundle.events[0]);
+    assert_err(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[0]), ErrorCode::EventCancelled);
+    assert_ok(release(&mut svm, &bundle, &bundle.events[0]));
+    assert_eq!(event(&svm, &bundle.events[0]).pass_reserved, 0);
+    // The other event is untouched while the pass sale runs
+    assert_err(release(&mut svm, &bundle, &bundle.events[1]), ErrorCode::PassSaleOpen);
+    assert_eq!(event(&svm, &bundle.events[1]).pass_reserved, 4);
+}
+
+fn claim_pass_refund(svm: &mut Svm, bundle: &Bundle, holder: &Pubkey, pass_index: u32, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    let pass_mint = pass_mint_pda(&bundle.key, pass_index);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimPassRefund {
+                holder: *holder,
+                bundle: bundle.key,
+                pass_mint,
+                holder_pass_account: ata(holder, &pass_mint),
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                pass_refund: pda(&[b"pass_refund", pass_mint.as_ref(), fixture.event.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ClaimPassRefund { _pass_index: pass_index },
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn cancelled_event_refunds_its_share_of_every_pass() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 4, sale_end_ts));
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+    assert_ok(redeem(&mut svm, &bundle, &holder, 0, &bundle.events[0]));
+    let fan = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &fan));
+    assert_eq!(event(&svm, &bundle.events[0]).pass_refunds_owed, SOL);
+
+    // The organizer funds both pass shares; redeemed or not, each pass claims its half
+    let fixture = &bundle.events[0];
+    let before = svm.lamports(&fixture.organizer);
+    cancel_event(&mut svm, fixture);
+    assert!(svm.lamports(&fixture.organizer) < before - SOL);
+    let before = svm.lamports(&fan);
+    assert_ok(claim_pass_refund(&mut svm, &bundle, &fan, 1, fixture));
+    let marker_rent = svm.lamports(&pda(&[b"pass_refund", pass_mint_pda(&bundle.key, 1).as_ref(), fixture.event.as_ref()]));
+    assert_eq!(svm.lamports(&fan), before + SOL / 2 - marker_rent);
+    assert!(claim_pass_refund(&mut svm, &bundle, &fan, 1, fixture).is_err());
+    assert_ok(claim_pass_refund(&mut svm, &bundle, &holder, 0, fixture));
+    assert_eq!(event(&svm, fixture).pass_refunds_owed, 0);
+}
+
+#[test]
+fn redeemed_ticket_takes_the_organizer_name_for_its_index() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 2, sale_end_ts));
+    let fixture = &bundle.events[0];
+    assert_ok(set_ticket_name(&mut svm, fixture, 0, "Festival Pit"));
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+
+    let before = svm.lamports(&fixture.organizer);
+    let rent = svm.lamports(&name_override_pda(&fixture.event, 0));
+    let meta = assert_ok(redeem(&mut svm, &bundle, &holder, 0, fixture));
+    assert_eq!(meta.event::<TicketPurchased>().custom_name.as_deref(), Some("Festival Pit"));
+    assert!(!svm.exists(&name_override_pda(&fixture.event, 0)));
+    assert_eq!(svm.lamports(&fixture.organizer), before + rent);
+}
+
+#[test]
+fn redeemed_ticket_gets_a_receipt_with_the_holder_identity() {
+    let mut svm = setup();
+    let sale_end_ts = svm.now() + 3600;
+    let bundle = assert_ok(bundle_fixture(&mut svm, 2, sale_end_ts));
+    let fixture = &bundle.events[1];
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetIdentityMode { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetIdentityMode { required: true, verify_at_check_in: true },
+        ),
+        &[fixture.organizer],
+    ));
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_pass(&mut svm, &bundle, &holder));
+
+    let id = anchor_lang::solana_program::hash::hashv(&[&[7; 32]]).to_bytes();
+    assert_err(redeem(&mut svm, &bundle, &holder, 0, fixture), ErrorCode::IdentityCommitmentRequired);
+    assert_ok(redeem_with_identity(&mut svm, &bundle, &holder, 0, fixture, Some(id)));
+    let mint = ticket_mint_pda(&fixture.event, 0);
+    let receipt: Receipt = svm.get(&receipt_pda(&holder, &mint));
+    assert_eq!((receipt.buyer, receipt.paid_lamports, receipt.identity_commitment), (holder, 0, id));
+    assert_ok(svm.send(
+        ix(
+            check_in_accounts(fixture, &holder, &holder, 0),
+            ticketchain::instruction::CheckInWithIdentity { ticket_index: 0, identity_preimage: [7; 32] },
+        ),
+        &[fixture.organizer, holder],
+    ));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PaymentOption, Receipt};
+
+const USDC_PRICE: u64 = 25_000_000;
+
+/// An event with a refund window that takes 0.2 SOL or 25 USDC for its tier.
+fn two_option_event(svm: &mut Svm, usdc: &Pubkey) -> EventFixture {
+    let fixture = create_event(svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetGracePeriod {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetGracePeriod { grace_period_seconds: 3_600 },
+        ),
+        &[fixture.organizer],
+    ));
+    let options = vec![
+        PaymentOption { tier_index: 0, mint: None, price: SOL / 5 },
+        PaymentOption { tier_index: 0, mint: Some(*usdc), price: USDC_PRICE },
+    ];
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAcceptedPayments { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetAcceptedPayments { options },
+        ),
+        &[fixture.organizer],
+    ));
+    fixture
+}
+
+fn buy_with(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, payment_index: u8, usdc: &Pubkey) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.vault = Some(vault_pda(&fixture.event));
+    if payment_index == 1 {
+        accounts.payment_mint = Some(*usdc);
+        accounts.buyer_payment_account = Some(ata(buyer, usdc));
+        accounts.payment_destination = Some(ata(&vault_pda(&fixture.event), usdc));
+    }
+    let mut args = buy_ticket_args(index, 0);
+    args.payment_index = Some(payment_index);
+    svm.send(ix(accounts, args), &[*buyer])
+}
+
+fn grace_refund(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, mint: &Pubkey, usdc: Option<&Pubkey>) -> Result<TxMeta, TxError> {
+    let vault = vault_pda(&fixture.event);
+    svm.send(
+        ix(
+            ticketchain::accounts::RequestGraceRefund {
+                buyer: *buyer,
+                event: fixture.event,
+                vault,
+                receipt: receipt_pda(buyer, mint),
+                ticket_mint: *mint,
+                buyer_token_account: ata(buyer, mint),
+                payment_mint: usdc.copied(),
+                vault_payment_account: usdc.map(|usdc| ata(&vault, usdc)),
+                buyer_payment_account: usdc.map(|usdc| ata(buyer, usdc)),
+                deposit_escrow: None,
+                identity_purchase: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::RequestGraceRefund {},
+        ),
+        &[*buyer],
+    )
+}
+
+#[test]
+fn each_option_is_refunded_in_the_mint_it_was_paid_in() {
+    let mut svm = setup();
+    let usdc = create_mint(&mut svm, &PLATFORM_AUTHORITY, 6);
+    let fixture = two_option_event(&mut svm, &usdc);
+    let vault = vault_pda(&fixture.event);
+    create_token_account(&mut svm, &vault, &usdc, 0);
+    let sol_buyer = svm.wallet(10 * SOL);
+    let usdc_buyer = svm.wallet(10 * SOL);
+    create_token_account(&mut svm, &usdc_buyer, &usdc, 2 * USDC_PRICE);
+
+    assert_err(buy_with(&mut svm, &fixture, &usdc_buyer, 2, &usdc), ErrorCode::InvalidPaymentOption);
+    let vault_lamports = svm.lamports(&vault);
+    assert_ok(buy_with(&mut svm, &fixture, &sol_buyer, 0, &usdc));
+    let sol_ticket = ticket_mint_pda(&fixture.event, 0);
+    assert_eq!(svm.lamports(&vault), vault_lamports + SOL / 5);
+    assert_ok(buy_with(&mut svm, &fixture, &usdc_buyer, 1, &usdc));
+    let usdc_ticket = ticket_mint_pda(&fixture.event, 1);
+    assert_eq!(svm.lamports(&vault), vault_lamports + SOL / 5);
+    assert_eq!(token_balance(&svm, &ata(&vault, &usdc)), USDC_PRICE);
+    let receipt = svm.get::<Receipt>(&receipt_pda(&usdc_buyer, &usdc_ticket));
+    assert_eq!((receipt.payment_index, receipt.paid_lamports), (Some(1), USDC_PRICE));
+
+    // SOL comes back as lamports, USDC as USDC, each for the amount paid
+    let before = svm.lamports(&sol_buyer);
+    assert_ok(grace_refund(&mut svm, &fixture, &sol_buyer, &sol_ticket, None));
+    assert_eq!(svm.lamports(&sol_buyer), before + SOL / 5);
+    assert_err(grace_refund(&mut svm, &fixture, &usdc_buyer, &usdc_ticket, None), ErrorCode::MissingPaymentAccounts);
+    let before = svm.lamports(&usdc_buyer);
+    assert_ok(grace_refund(&mut svm, &fixture, &usdc_buyer, &usdc_ticket, Some(&usdc)));
+    assert_eq!(svm.lamports(&usdc_buyer), before);
+    assert_eq!(token_balance(&svm, &ata(&usdc_buyer, &usdc)), 2 * USDC_PRICE);
+    assert_eq!(token_balance(&svm, &ata(&vault, &usdc)), 0);
+    assert_eq!(svm.lamports(&vault), vault_lamports);
+}
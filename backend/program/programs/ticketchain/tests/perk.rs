@@ -0,0 +1,125 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn perk_pda(event: &Pubkey, index: u32) -> Pubkey {
+    pda(&[b"perk", event.as_ref(), &index.to_le_bytes()])
+}
+
+fn perk_mint_pda(perk: &Pubkey, ticket_mint: &Pubkey) -> Pubkey {
+    pda(&[b"perk_mint", perk.as_ref(), ticket_mint.as_ref()])
+}
+
+fn create_perk(svm: &mut Svm, fixture: &EventFixture, index: u32, claim_until: i64) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CreatePerk {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                perk: perk_pda(&fixture.event, index),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreatePerk { uri: "ipfs://perk".to_string(), claim_until },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn claim_perk(svm: &mut Svm, fixture: &EventFixture, index: u32, holder: &Pubkey, ticket_index: u32) -> Result<TxMeta, TxError> {
+    let perk = perk_pda(&fixture.event, index);
+    let ticket_mint = ticket_mint_pda(&fixture.event, ticket_index);
+    let perk_mint = perk_mint_pda(&perk, &ticket_mint);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimPerk {
+                holder: *holder,
+                event: fixture.event,
+                perk,
+                ticket_mint,
+                holder_token_account: ata(holder, &ticket_mint),
+                perk_claimed: pda(&[b"perk_claimed", perk.as_ref(), ticket_mint.as_ref()]),
+                perk_mint,
+                holder_perk_account: ata(holder, &perk_mint),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::ClaimPerk { _ticket_index: ticket_index },
+        ),
+        &[*holder],
+    )
+}
+
+fn close_perk(svm: &mut Svm, fixture: &EventFixture, index: u32) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ClosePerk {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                perk: perk_pda(&fixture.event, index),
+            },
+            ticketchain::instruction::ClosePerk {},
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn holder_claims_once_per_ticket_even_after_resale() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (holder, buyer) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let claim_until = svm.now() + 3600;
+    assert_ok(create_perk(&mut svm, &fixture, 0, claim_until));
+
+    assert_ok(claim_perk(&mut svm, &fixture, 0, &holder, 0));
+    let perk_mint = perk_mint_pda(&perk_pda(&fixture.event, 0), &mint);
+    assert_eq!(token_balance(&svm, &ata(&holder, &perk_mint)), 1);
+    let perk_state = mint_state(&svm, &perk_mint);
+    assert_eq!((perk_state.supply, perk_state.mint_authority), (1, None.into()));
+
+    assert_ok(transfer_ticket(&mut svm, &holder, &buyer, &mint));
+    assert!(claim_perk(&mut svm, &fixture, 0, &buyer, 0).is_err());
+    assert_eq!(svm.get::<ticketchain::Perk>(&perk_pda(&fixture.event, 0)).claimed, 1);
+}
+
+#[test]
+fn claims_close_at_the_deadline() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let claim_until = svm.now() + 3600;
+    assert_ok(create_perk(&mut svm, &fixture, 0, claim_until));
+    assert_err(close_perk(&mut svm, &fixture, 0), ErrorCode::PerkClaimOpen);
+
+    svm.warp(3601);
+    assert_err(claim_perk(&mut svm, &fixture, 0, &holder, 0), ErrorCode::PerkClaimClosed);
+}
+
+#[test]
+fn closed_perk_address_is_never_reinitialized() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &holder, 0));
+    let claim_until = svm.now() + 3600;
+    assert_ok(create_perk(&mut svm, &fixture, 0, claim_until));
+    assert_ok(claim_perk(&mut svm, &fixture, 0, &holder, 0));
+    svm.warp(3601);
+    assert_ok(close_perk(&mut svm, &fixture, 0));
+    assert!(!svm.exists(&perk_pda(&fixture.event, 0)));
+
+    // The closed index can't be announced again; the next perk takes index 1
+    let claim_until = svm.now() + 3600;
+    assert_err(create_perk(&mut svm, &fixture, 0, claim_until), anchor_lang::error::ErrorCode::ConstraintSeeds);
+    assert_ok(create_perk(&mut svm, &fixture, 1, claim_until));
+    assert_eq!(event(&svm, &fixture).perks_created, 2);
+
+    // A new perk is a new bonus for the same ticket
+    assert_ok(claim_perk(&mut svm, &fixture, 1, &holder, 0));
+}
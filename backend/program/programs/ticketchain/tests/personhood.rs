@@ -0,0 +1,79 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, PersonPurchase, CIVIC_GATEWAY_PROGRAM_ID, PERSONHOOD_CIVIC};
+
+/// A mocked Civic gateway token for `wallet` on `network`, linked to `identity`.
+fn gateway_token(svm: &mut Svm, wallet: &Pubkey, identity: &Pubkey, network: &Pubkey, expire_time: Option<i64>) -> Pubkey {
+    let mut data = vec![0, 0];
+    data.extend_from_slice(wallet.as_ref());
+    data.push(1);
+    data.extend_from_slice(identity.as_ref());
+    data.extend_from_slice(network.as_ref());
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+    data.push(0);
+    match expire_time {
+        Some(expiry) => data.extend([&[1][..], &expiry.to_le_bytes()].concat()),
+        None => data.push(0),
+    }
+    let key = Pubkey::new_unique();
+    svm.set_account(key, Account { lamports: SOL / 100, data, owner: CIVIC_GATEWAY_PROGRAM_ID, executable: false });
+    key
+}
+
+fn person_pda(event: &Pubkey, identity: &Pubkey) -> Pubkey {
+    pda(&[b"person", event.as_ref(), identity.as_ref()])
+}
+
+fn buy_as_person(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, credential: &Pubkey, identity: &Pubkey) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.platform_config = Some(platform_config_pda());
+    accounts.personhood_credential = Some(*credential);
+    accounts.person_purchase = Some(person_pda(&fixture.event, identity));
+    svm.send(ix(accounts, buy_ticket_args(index, 0)), &[*buyer])
+}
+
+#[test]
+fn one_ticket_per_person_across_wallets() {
+    let mut svm = setup();
+    let network = Pubkey::new_unique();
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetPersonhoodProvider { authority: PLATFORM_AUTHORITY, platform_config: platform_config_pda() },
+            ticketchain::instruction::SetPersonhoodProvider { provider: PERSONHOOD_CIVIC, network },
+        ),
+        &[PLATFORM_AUTHORITY],
+    ));
+    let fixture = create_event(&mut svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetOnePerPerson { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetOnePerPerson { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+    let (identity, first, second) = (Pubkey::new_unique(), svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let first_token = gateway_token(&mut svm, &first, &identity, &network, None);
+
+    // The credential must be the buyer's own, from the configured network and unexpired
+    assert_err(buy_ticket(&mut svm, &fixture, &first, 0), ErrorCode::InvalidPersonhoodCredential);
+    assert_err(buy_as_person(&mut svm, &fixture, &second, &first_token, &identity), ErrorCode::InvalidPersonhoodCredential);
+    let elsewhere = gateway_token(&mut svm, &first, &identity, &Pubkey::new_unique(), None);
+    assert_err(buy_as_person(&mut svm, &fixture, &first, &elsewhere, &identity), ErrorCode::InvalidPersonhoodCredential);
+    let now = svm.now();
+    let expired = gateway_token(&mut svm, &first, &identity, &network, Some(now));
+    assert_err(buy_as_person(&mut svm, &fixture, &first, &expired, &identity), ErrorCode::InvalidPersonhoodCredential);
+
+    assert_ok(buy_as_person(&mut svm, &fixture, &first, &first_token, &identity));
+    let record = svm.get::<PersonPurchase>(&person_pda(&fixture.event, &identity));
+    assert_eq!((record.person_id, record.buyer), (identity.to_bytes(), first));
+
+    // A second wallet bound to the same identity is the same person
+    let second_token = gateway_token(&mut svm, &second, &identity, &network, Some(now + 3600));
+    assert_err(buy_as_person(&mut svm, &fixture, &second, &second_token, &identity), ErrorCode::AlreadyPurchasedAsPerson);
+    let other_person = Pubkey::new_unique();
+    let other_token = gateway_token(&mut svm, &second, &other_person, &network, None);
+    assert_ok(buy_as_person(&mut svm, &fixture, &second, &other_token, &other_person));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PlatformConfig, PLATFORM_CONFIG_LEN, PLATFORM_CONFIG_V1_LEN};
+
+/// Rewrites the installed PlatformConfig in its first-deployment layout, ending at bump.
+fn install_v1_config(svm: &mut Svm) {
+    let key = platform_config_pda();
+    let mut account = svm.account(&key).unwrap().clone();
+    account.data.truncate(PLATFORM_CONFIG_V1_LEN);
+    account.data[PLATFORM_CONFIG_V1_LEN - 1] = Pubkey::find_program_address(&[b"platform_config"], &ticketchain::ID).1;
+    account.lamports = Rent::default().minimum_balance(PLATFORM_CONFIG_V1_LEN);
+    svm.set_account(key, account);
+}
+
+fn migrate(svm: &mut Svm, authority: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::MigratePlatformConfig {
+                authority: *authority,
+                platform_config: platform_config_pda(),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::MigratePlatformConfig {},
+        ),
+        &[*authority],
+    )
+}
+
+#[test]
+fn migration_grows_a_first_layout_config_in_place() {
+    let mut svm = setup();
+    let before: PlatformConfig = svm.get(&platform_config_pda());
+    install_v1_config(&mut svm);
+    let stranger = svm.wallet(SOL);
+
+    assert_err(migrate(&mut svm, &stranger), ErrorCode::Unauthorized);
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    assert_ok(migrate(&mut svm, &PLATFORM_AUTHORITY));
+
+    let key = platform_config_pda();
+    assert_eq!(svm.account(&key).unwrap().data.len(), PLATFORM_CONFIG_LEN);
+    assert_eq!(svm.lamports(&key), Rent::default().minimum_balance(PLATFORM_CONFIG_LEN));
+    let after: PlatformConfig = svm.get(&key);
+    assert_eq!((after.authority, after.max_ticket_price, after.bump), (before.authority, before.max_ticket_price, before.bump));
+    assert_eq!(after.messaging_program, None);
+    assert_eq!(after.personhood_provider, ticketchain::PERSONHOOD_CIVIC);
+    assert_eq!(after.personhood_network, Pubkey::default());
+    assert_eq!((after.resale_fees_collected, after.resale_fee_count), (0, 0));
+
+    // Instructions that read the config work again, and a second run is a no-op
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp(1);
+    assert_ok(migrate(&mut svm, &PLATFORM_AUTHORITY));
+    assert_eq!(svm.account(&key).unwrap().data.len(), PLATFORM_CONFIG_LEN);
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{program_option::COption, system_program};
+use common::pnft::{edition_pda, token_record, token_record_pda, TokenRecord};
+use common::*;
+use ticketchain::{ErrorCode, TOKEN_METADATA_PROGRAM_ID};
+
+fn set_pnft_mode(svm: &mut Svm, fixture: &EventFixture, use_pnft: bool) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetPnftMode { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetPnftMode { use_pnft },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+/// An event with ticket metadata whose tickets are pNFTs.
+fn pnft_event(svm: &mut Svm) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.metadata_uri = "ipfs://ticket".to_string();
+    let fixture = create_event_with(svm, args);
+    assert_ok(set_pnft_mode(svm, &fixture, true));
+    fixture
+}
+
+fn buy(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey) -> Result<Pubkey, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    let mint = accounts.ticket_mint;
+    accounts.ticket_metadata = Some(metadata_pda(&mint));
+    accounts.ticket_edition = Some(edition_pda(&mint));
+    accounts.ticket_token_record = Some(token_record_pda(&mint, &ata(buyer, &mint)));
+    accounts.instructions = Some(instructions_sysvar());
+    accounts.token_metadata_program = Some(TOKEN_METADATA_PROGRAM_ID);
+    svm.send(ix(accounts, buy_ticket_args(index, 0)), &[*buyer]).map(|_| mint)
+}
+
+fn list(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let mut accounts = list_accounts(fixture, seller, mint);
+    accounts.ticket_metadata = Some(metadata_pda(mint));
+    accounts.ticket_edition = Some(edition_pda(mint));
+    accounts.seller_token_record = Some(token_record_pda(mint, &ata(seller, mint)));
+    accounts.instructions = Some(instructions_sysvar());
+    accounts.token_metadata_program = Some(TOKEN_METADATA_PROGRAM_ID);
+    svm.send(ix(accounts, list_args(fixture, mint, SOL / 10)), &[*seller])
+}
+
+fn cancel(svm: &mut Svm, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let seller_token_account = ata(seller, mint);
+    svm.send(
+        ix(
+            ticketchain::accounts::CancelListing {
+                seller: *seller,
+                ticket_mint: *mint,
+                listing: listing_pda(mint),
+                seller_token_account,
+                escrow_token_account: pda(&[b"escrow", mint.as_ref()]),
+                ask_book: None,
+                ticket_metadata: Some(metadata_pda(mint)),
+                ticket_edition: Some(edition_pda(mint)),
+                seller_token_record: Some(token_record_pda(mint, &seller_token_account)),
+                instructions: Some(instructions_sysvar()),
+                token_metadata_program: Some(TOKEN_METADATA_PROGRAM_ID),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelListing {},
+        ),
+        &[*seller],
+    )
+}
+
+fn buy_resale(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, seller: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    let mut accounts = buy_resale_accounts(fixture, buyer, seller, mint);
+    accounts.ticket_metadata = Some(metadata_pda(mint));
+    accounts.ticket_edition = Some(edition_pda(mint));
+    accounts.seller_token_account = Some(ata(seller, mint));
+    accounts.seller_token_record = Some(token_record_pda(mint, &ata(seller, mint)));
+    accounts.escrow_token_record = Some(token_record_pda(mint, &accounts.escrow_token_account));
+    accounts.buyer_token_record = Some(token_record_pda(mint, &ata(buyer, mint)));
+    accounts.instructions = Some(instructions_sysvar());
+    accounts.token_metadata_program = Some(TOKEN_METADATA_PROGRAM_ID);
+    svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports: u64::MAX }), &[*buyer])
+}
+
+#[test]
+fn pnft_tickets_stay_in_the_wallet_while_listed() {
+    let mut svm = setup();
+    let fixture = pnft_event(&mut svm);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy(&mut svm, &fixture, &seller));
+    let seller_ata = ata(&seller, &mint);
+    assert_eq!(token_balance(&svm, &seller_ata), 1);
+    assert_eq!(mint_state(&svm, &mint).mint_authority, COption::Some(edition_pda(&mint)));
+    assert_eq!(token_record(&svm, &mint, &seller_ata), Some(TokenRecord { locked: false, delegate: None, locked_address: None }));
+    assert_eq!(ticket_metadata(&svm, &mint).unwrap().2, "ipfs://ticket");
+
+    // Listing locks the ticket in place with the listing as its only delegate
+    let listing = listing_pda(&mint);
+    let locked = TokenRecord { locked: true, delegate: Some(listing), locked_address: Some(listing) };
+    assert_ok(list(&mut svm, &fixture, &seller, &mint));
+    assert_eq!(token_balance(&svm, &seller_ata), 1);
+    assert_eq!(token_record(&svm, &mint, &seller_ata), Some(locked));
+
+    assert_ok(cancel(&mut svm, &seller, &mint));
+    assert_eq!(token_record(&svm, &mint, &seller_ata), Some(TokenRecord { locked: false, delegate: None, locked_address: None }));
+
+    // A sale moves the ticket and a fresh record to the buyer
+    assert_ok(list(&mut svm, &fixture, &seller, &mint));
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_resale(&mut svm, &fixture, &buyer, &seller, &mint));
+    assert_eq!(token_balance(&svm, &seller_ata), 0);
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+    assert_eq!(token_record(&svm, &mint, &ata(&buyer, &mint)).map(|record| record.locked), Some(false));
+    assert!(!svm.exists(&listing));
+}
+
+#[test]
+fn pnft_mode_needs_metadata_and_an_unsold_event() {
+    let mut svm = setup();
+    let bare = create_event(&mut svm);
+    assert_err(set_pnft_mode(&mut svm, &bare, true), ErrorCode::PnftRequiresMetadata);
+
+    let fixture = pnft_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::MissingMetadataAccounts);
+    assert_ok(buy(&mut svm, &fixture, &buyer));
+    assert_err(set_pnft_mode(&mut svm, &fixture, false), ErrorCode::EventHasSales);
+}
+
+#[test]
+fn refunds_burn_the_pnft() {
+    let mut svm = setup();
+    let fixture = pnft_event(&mut svm);
+    let holder = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy(&mut svm, &fixture, &holder));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+
+    let holder_token_account = ata(&holder, &mint);
+    let record = token_record_pda(&mint, &holder_token_account);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                ticket_mint: mint,
+                receipt: receipt_pda(&holder, &mint),
+                holder_token_account,
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: Some(metadata_pda(&mint)),
+                ticket_edition: Some(edition_pda(&mint)),
+                holder_token_record: Some(record),
+                instructions: Some(instructions_sysvar()),
+                token_metadata_program: Some(TOKEN_METADATA_PROGRAM_ID),
+                system_program: Some(system_program::ID),
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 0 },
+        ),
+        &[holder],
+    ));
+    assert_eq!(mint_state(&svm, &mint).supply, 0);
+    assert!(!svm.exists(&record));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+
+fn pre_allocate(svm: &mut Svm, fixture: &EventFixture, count: u8) -> Result<TxMeta, TxError> {
+    let sold = event(svm, fixture).sold;
+    let mints = (sold..sold + count as u32)
+        .map(|i| AccountMeta::new(ticket_mint_pda(&fixture.event, i), false))
+        .collect();
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::PreAllocateTickets {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                allocation: pda(&[b"allocation", fixture.event.as_ref(), &sold.to_le_bytes()]),
+                token_program: TOKEN_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::PreAllocateTickets { count },
+            mints,
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn prefunded_mint_address_does_not_block_pre_allocation() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    // Anyone can send lamports to an upcoming mint address
+    let griefed: Pubkey = ticket_mint_pda(&fixture.event, 1);
+    svm.airdrop(&griefed, 1);
+
+    assert_ok(pre_allocate(&mut svm, &fixture, 3));
+    for i in 0..3 {
+        assert_eq!(svm.account(&ticket_mint_pda(&fixture.event, i)).unwrap().owner, TOKEN_PROGRAM);
+    }
+
+    let buyer = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_eq!(mint, ticket_mint_pda(&fixture.event, 0));
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+}
+
+#[test]
+fn batch_can_be_extended_before_the_next_sale() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    assert_ok(pre_allocate(&mut svm, &fixture, 2));
+    // Same start index: the two existing mints are skipped and three more created
+    assert_ok(pre_allocate(&mut svm, &fixture, 5));
+    assert!(svm.exists(&ticket_mint_pda(&fixture.event, 4)));
+    assert!(!svm.exists(&ticket_mint_pda(&fixture.event, 5)));
+
+    let allocation: ticketchain::TicketAllocation =
+        svm.get(&pda(&[b"allocation", fixture.event.as_ref(), &0u32.to_le_bytes()]));
+    assert_eq!((allocation.event, allocation.start_index), (fixture.event, 0));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::*;
+use ticketchain::ErrorCode;
+
+fn set_max_ticket_price(svm: &mut Svm, max_ticket_price: u64) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetMaxTicketPrice {
+                authority: PLATFORM_AUTHORITY,
+                platform_config: platform_config_pda(),
+            },
+            ticketchain::instruction::SetMaxTicketPrice { max_ticket_price },
+        ),
+        &[PLATFORM_AUTHORITY],
+    ));
+}
+
+fn update_price(svm: &mut Svm, fixture: &EventFixture, price_lamports: u64) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::UpdateEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                platform_config: platform_config_pda(),
+                vault: None,
+                system_program: None,
+            },
+            ticketchain::instruction::UpdateEvent {
+                title: None,
+                venue: None,
+                date_ts: None,
+                price_lamports: Some(price_lamports),
+                tier_name: None,
+                supply: None,
+            },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn create_and_update_respect_the_platform_ceiling() {
+    let mut svm = setup();
+    set_max_ticket_price(&mut svm, SOL);
+
+    let organizer = svm.wallet(10 * SOL);
+    let mut args = EventArgs::new(&svm);
+    args.tiers = vec![tier(2 * SOL, 10)];
+    let instruction = create_event_ix(&svm, &organizer, 1, args);
+    assert_err(svm.send(instruction, &[organizer]), ErrorCode::PriceAboveCeiling);
+
+    let fixture = create_event(&mut svm);
+    assert_err(update_price(&mut svm, &fixture, 2 * SOL), ErrorCode::PriceAboveCeiling);
+    assert_ok(update_price(&mut svm, &fixture, SOL));
+}
+
+#[test]
+fn platform_config_cannot_be_omitted() {
+    let mut svm = setup();
+    set_max_ticket_price(&mut svm, SOL);
+
+    let organizer = svm.wallet(10 * SOL);
+    let mut args = EventArgs::new(&svm);
+    args.tiers = vec![tier(2 * SOL, 10)];
+    let mut instruction = create_event_ix(&svm, &organizer, 1, args);
+    // Where an optional account would take the program id as its "none" placeholder
+    instruction.accounts[2].pubkey = ticketchain::ID;
+    assert!(svm.send(instruction, &[organizer]).is_err());
+
+    // A look-alike config owned by someone else fails its seeds check
+    let fake = svm.wallet(SOL);
+    let mut args = EventArgs::new(&svm);
+    args.tiers = vec![tier(2 * SOL, 10)];
+    let mut instruction = create_event_ix(&svm, &organizer, 1, args);
+    instruction.accounts[2].pubkey = fake;
+    assert!(svm.send(instruction, &[organizer]).is_err());
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::*;
+use ticketchain::TOKEN_METADATA_PROGRAM_ID;
+
+#[test]
+fn buy_ticket_mints_one_ticket_and_pays_the_organizer() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let organizer_before = svm.lamports(&fixture.organizer);
+
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+    assert_eq!(svm.lamports(&fixture.organizer) - organizer_before, SOL / 10);
+    let event = event(&svm, &fixture);
+    assert_eq!(event.sold, 1);
+    assert_eq!(event.tiers[0].sold, 1);
+}
+
+#[test]
+fn design_cid_sets_the_ticket_metadata_uri() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::UpdateTicketDesign { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::UpdateTicketDesign { new_cid: cid.to_string() },
+        ),
+        &[fixture.organizer],
+    ));
+
+    // Without metadata accounts the purchase is rejected once a design is set
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ticketchain::ErrorCode::MissingMetadataAccounts);
+    let mut accounts = buy_ticket_accounts(&fixture, &buyer, 0);
+    accounts.ticket_metadata = Some(metadata_pda(&accounts.ticket_mint));
+    accounts.token_metadata_program = Some(TOKEN_METADATA_PROGRAM_ID);
+    assert_ok(svm.send(ix(accounts, buy_ticket_args(0, 0)), &[buyer]));
+    let (name, _, uri) = ticket_metadata(&svm, &ticket_mint_pda(&fixture.event, 0)).unwrap();
+    assert_eq!(name, "Launch Night #1");
+    assert_eq!(uri, format!("ipfs://{cid}?index=0"));
+}
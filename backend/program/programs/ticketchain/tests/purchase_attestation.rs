@@ -0,0 +1,80 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, MAX_ATTESTATION_AGE_SLOTS};
+
+fn set_attestor(svm: &mut Svm, key: &ed25519_dalek::Keypair) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAttestorKey { authority: PLATFORM_AUTHORITY, platform_config: platform_config_pda() },
+            ticketchain::instruction::SetAttestorKey { attestor_key: Pubkey::new_from_array(key.public.to_bytes()) },
+        ),
+        &[PLATFORM_AUTHORITY],
+    ));
+}
+
+fn attested_event(svm: &mut Svm) -> EventFixture {
+    let fixture = create_event(svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetRequiresAttestation { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetRequiresAttestation { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+    fixture
+}
+
+/// buy_ticket preceded by `attestor`'s signature over `event ‖ buyer ‖ slot`.
+fn buy_attested(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, attestor: &ed25519_dalek::Keypair, slot: u64) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.platform_config = Some(platform_config_pda());
+    accounts.instructions = Some(instructions_sysvar());
+    let message = [fixture.event.as_ref(), buyer.as_ref(), &slot.to_le_bytes()].concat();
+    svm.process(&[ed25519_instruction(attestor, &message), ix(accounts, buy_ticket_args(index, 0))], &[*buyer])
+}
+
+#[test]
+fn purchase_needs_a_recent_attestation() {
+    let mut svm = setup();
+    let attestor = ed25519_keypair(5);
+    set_attestor(&mut svm, &attestor);
+    let fixture = attested_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    svm.warp(1_000);
+    let slot = svm.clock().slot;
+
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::AttestationRequired);
+    assert_err(buy_attested(&mut svm, &fixture, &buyer, &attestor, slot - MAX_ATTESTATION_AGE_SLOTS - 1), ErrorCode::AttestationExpired);
+    assert_err(buy_attested(&mut svm, &fixture, &buyer, &attestor, slot + 1), ErrorCode::AttestationExpired);
+    assert_ok(buy_attested(&mut svm, &fixture, &buyer, &attestor, slot - MAX_ATTESTATION_AGE_SLOTS));
+    assert_ok(buy_attested(&mut svm, &fixture, &buyer, &attestor, slot));
+
+    // Another buyer can't reuse an attestation issued for someone else
+    let other = svm.wallet(10 * SOL);
+    let index = event(&svm, &fixture).sold;
+    let mut accounts = buy_ticket_accounts(&fixture, &other, index);
+    accounts.platform_config = Some(platform_config_pda());
+    accounts.instructions = Some(instructions_sysvar());
+    let message = [fixture.event.as_ref(), buyer.as_ref(), &slot.to_le_bytes()].concat();
+    assert_err(
+        svm.process(&[ed25519_instruction(&attestor, &message), ix(accounts, buy_ticket_args(index, 0))], &[other]),
+        ErrorCode::AttestationRequired,
+    );
+}
+
+#[test]
+fn rotated_attestor_replaces_the_old_key() {
+    let mut svm = setup();
+    let (old, new) = (ed25519_keypair(5), ed25519_keypair(6));
+    set_attestor(&mut svm, &old);
+    let fixture = attested_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    set_attestor(&mut svm, &new);
+
+    let slot = svm.clock().slot;
+    assert_err(buy_attested(&mut svm, &fixture, &buyer, &old, slot), ErrorCode::AttestationRequired);
+    assert_ok(buy_attested(&mut svm, &fixture, &buyer, &new, slot));
+}
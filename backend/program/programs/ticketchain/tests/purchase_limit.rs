@@ -0,0 +1,129 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{BuyerRecord, ErrorCode};
+
+const LIMIT: u16 = 3;
+
+fn limited_event(svm: &mut Svm) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.max_per_wallet = LIMIT;
+    create_event_with(svm, args)
+}
+
+fn buyer_record_pda(fixture: &EventFixture, buyer: &Pubkey) -> Pubkey {
+    pda(&[b"buyer", fixture.event.as_ref(), buyer.as_ref()])
+}
+
+fn buy_counted(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, with_record: bool) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.buyer_record = with_record.then(|| buyer_record_pda(fixture, buyer));
+    svm.send(ix(accounts, buy_ticket_args(index, 0)), &[*buyer])
+}
+
+fn buy_batch(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, quantity: u8) -> Result<TxMeta, TxError> {
+    let first = event(svm, fixture).sold;
+    let key = fixture.event;
+    let mut remaining = Vec::new();
+    for index in first..first + quantity as u32 {
+        let mint = ticket_mint_pda(&key, index);
+        remaining.extend([
+            AccountMeta::new_readonly(ticket_authority_pda(&key, index), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(ata(buyer, &mint), false),
+            AccountMeta::new(receipt_pda(buyer, &mint), false),
+            AccountMeta::new(name_override_pda(&key, index), false),
+        ]);
+    }
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::BuyTickets {
+                buyer: *buyer,
+                organizer: fixture.organizer,
+                event: key,
+                tax_wallet: None,
+                donation_wallet: None,
+                vault: None,
+                rate_limit: pda(&[b"rate_limit", buyer.as_ref()]),
+                buyer_record: Some(buyer_record_pda(fixture, buyer)),
+                organizer_ban: pda(&[b"org_ban", fixture.organizer.as_ref(), buyer.as_ref()]),
+                token_metadata_program: None,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::BuyTickets { quantity, tier_index: 0 },
+            remaining,
+        ),
+        &[*buyer],
+    )
+}
+
+fn close_record(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CloseBuyerRecord {
+                buyer: *buyer,
+                event: fixture.event,
+                buyer_record: buyer_record_pda(fixture, buyer),
+            },
+            ticketchain::instruction::CloseBuyerRecord {},
+        ),
+        &[*buyer],
+    )
+}
+
+#[test]
+fn wallet_buys_up_to_the_limit_and_no_further() {
+    let mut svm = setup();
+    let fixture = limited_event(&mut svm);
+    let (scalper, fan) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+
+    assert_err(buy_counted(&mut svm, &fixture, &scalper, false), ErrorCode::PurchaseLimitReached);
+    for _ in 0..LIMIT {
+        assert_ok(buy_counted(&mut svm, &fixture, &scalper, true));
+    }
+    assert_eq!(svm.get::<BuyerRecord>(&buyer_record_pda(&fixture, &scalper)).tickets_bought, LIMIT);
+    assert_err(buy_counted(&mut svm, &fixture, &scalper, true), ErrorCode::PurchaseLimitReached);
+
+    // Another wallet has its own count
+    assert_ok(buy_counted(&mut svm, &fixture, &fan, true));
+    assert_eq!(svm.get::<BuyerRecord>(&buyer_record_pda(&fixture, &fan)).tickets_bought, 1);
+    assert_eq!(event(&svm, &fixture).sold, LIMIT as u32 + 1);
+}
+
+#[test]
+fn buyer_reclaims_the_record_rent_once_the_event_starts() {
+    let mut svm = setup();
+    let fixture = limited_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_counted(&mut svm, &fixture, &buyer, true));
+    let date_ts = event(&svm, &fixture).date_ts;
+
+    svm.warp_to(date_ts - 1);
+    assert_err(close_record(&mut svm, &fixture, &buyer), ErrorCode::EventNotStarted);
+    svm.warp_to(date_ts);
+    let (rent, before) = (svm.lamports(&buyer_record_pda(&fixture, &buyer)), svm.lamports(&buyer));
+    assert_ok(close_record(&mut svm, &fixture, &buyer));
+    assert!(!svm.exists(&buyer_record_pda(&fixture, &buyer)));
+    assert_eq!(svm.lamports(&buyer), before + rent);
+}
+
+#[test]
+fn batch_counts_every_ticket_against_the_limit() {
+    let mut svm = setup();
+    let fixture = limited_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_counted(&mut svm, &fixture, &buyer, true));
+    assert_ok(buy_counted(&mut svm, &fixture, &buyer, true));
+
+    // A batch that would pass the limit buys nothing; one that lands on it goes through
+    assert_err(buy_batch(&mut svm, &fixture, &buyer, 2), ErrorCode::PurchaseLimitReached);
+    assert_eq!(event(&svm, &fixture).sold, 2);
+    assert_ok(buy_batch(&mut svm, &fixture, &buyer, 1));
+    assert_eq!(svm.get::<BuyerRecord>(&buyer_record_pda(&fixture, &buyer)).tickets_bought, LIMIT);
+    assert_err(buy_counted(&mut svm, &fixture, &buyer, true), ErrorCode::PurchaseLimitReached);
+}
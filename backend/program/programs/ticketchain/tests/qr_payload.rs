@@ -0,0 +1,37 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{generate_verification_payload, verify_qr_payload};
+
+#[test]
+fn payload_round_trips_for_its_event_only() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let other = create_event(&mut svm);
+    let state = event(&svm, &fixture);
+    let (mint, holder, timestamp) = (ticket_mint_pda(&fixture.event, 0), Pubkey::new_unique(), 1_700_000_000);
+
+    let payload = generate_verification_payload(&mint, &state, &holder, timestamp);
+    assert_eq!(payload, generate_verification_payload(&mint, &state, &holder, timestamp));
+    assert_eq!(&payload[0..32], mint.as_ref());
+    assert_eq!(&payload[32..64], holder.as_ref());
+    assert_eq!(i64::from_le_bytes(payload[64..72].try_into().unwrap()), timestamp);
+    assert!(verify_qr_payload(&payload, &fixture.event));
+    assert!(!verify_qr_payload(&payload, &other.event));
+}
+
+#[test]
+fn tampered_payload_fails_verification() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let state = event(&svm, &fixture);
+    let payload = generate_verification_payload(&ticket_mint_pda(&fixture.event, 0), &state, &Pubkey::new_unique(), 42);
+
+    // Changing any field, or the digest itself, breaks the binding
+    for byte in [0, 40, 64, 95] {
+        let mut tampered = payload;
+        tampered[byte] ^= 1;
+        assert!(!verify_qr_payload(&tampered, &fixture.event));
+    }
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use common::*;
+use ticketchain::ErrorCode;
+
+const RANDOMNESS: [u8; 32] = [42; 32];
+const LOCK_SLOT_HASH: [u8; 32] = [9; 32];
+
+fn raffle_pda(event: &Pubkey) -> Pubkey {
+    pda(&[b"raffle", event.as_ref()])
+}
+
+/// An event with `attendees` checked-in tickets and a committed 2-prize raffle.
+fn raffle_fixture(svm: &mut Svm, attendees: u32) -> (EventFixture, Vec<Pubkey>) {
+    let fixture = create_event(svm);
+    let mut holders = Vec::new();
+    for index in 0..attendees {
+        let holder = svm.wallet(10 * SOL);
+        assert_ok(buy_ticket(svm, &fixture, &holder, 0));
+        assert_ok(check_in(svm, &fixture, &holder, index));
+        holders.push(holder);
+    }
+    let date_ts = event(svm, &fixture).date_ts;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateRaffle {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                raffle: raffle_pda(&fixture.event),
+                system_program: anchor_lang::solana_program::system_program::ID,
+            },
+            ticketchain::instruction::CreateRaffle {
+                prize_count: 2,
+                draw_after_ts: date_ts + ticketchain::ATTENDANCE_FINALIZE_SECONDS,
+                commitment: hashv(&[&RANDOMNESS]).to_bytes(),
+            },
+        ),
+        &[fixture.organizer],
+    ));
+    (fixture, holders)
+}
+
+fn draw_time(svm: &Svm, fixture: &EventFixture) -> i64 {
+    event(svm, fixture).date_ts + ticketchain::ATTENDANCE_FINALIZE_SECONDS
+}
+
+fn lock(svm: &mut Svm, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::LockRaffle {
+                event: fixture.event,
+                raffle: raffle_pda(&fixture.event),
+                slot_hashes: anchor_lang::solana_program::sysvar::slot_hashes::ID,
+            },
+            ticketchain::instruction::LockRaffle {},
+        ),
+        &[],
+    )
+}
+
+fn draw(svm: &mut Svm, fixture: &EventFixture, randomness: [u8; 32]) -> Result<TxMeta, TxError> {
+    draw_as(svm, fixture, &fixture.organizer, randomness)
+}
+
+fn draw_as(svm: &mut Svm, fixture: &EventFixture, caller: &Pubkey, randomness: [u8; 32]) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::DrawRaffle {
+                caller: *caller,
+                event: fixture.event,
+                raffle: raffle_pda(&fixture.event),
+                slot_hashes: anchor_lang::solana_program::sysvar::slot_hashes::ID,
+            },
+            ticketchain::instruction::DrawRaffle { randomness },
+        ),
+        &[*caller],
+    )
+}
+
+#[test]
+fn fixed_seed_draws_reproducible_winners_among_locked_entrants() {
+    let mut svm = setup();
+    let (fixture, _) = raffle_fixture(&mut svm, 5);
+    let late = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &late, 0));
+    svm.warp_to(draw_time(&svm, &fixture));
+    assert_ok(lock(&mut svm, &fixture));
+    let lock_slot = svm.clock().slot;
+
+    // Checking in after the lock doesn't make an entrant
+    svm.warp(1);
+    assert_ok(check_in(&mut svm, &fixture, &late, 5));
+
+    svm.set_slot_hashes(&[(svm.clock().slot, [1; 32]), (lock_slot, LOCK_SLOT_HASH)]);
+    let meta = assert_ok(draw(&mut svm, &fixture, RANDOMNESS));
+
+    let seed = hashv(&[&RANDOMNESS, &LOCK_SLOT_HASH]).to_bytes();
+    let expected = ticketchain::draw_winners(&seed, 5, 2);
+    let raffle: ticketchain::Raffle = svm.get(&raffle_pda(&fixture.event));
+    assert_eq!(raffle.entrants, 5);
+    assert_eq!(raffle.winners, expected);
+    assert!(raffle.winners.iter().all(|w| *w < 5));
+    assert_eq!(meta.event::<ticketchain::RaffleDrawn>().winners, expected);
+
+    assert_err(draw(&mut svm, &fixture, RANDOMNESS), ErrorCode::RaffleAlreadyDrawn);
+}
+
+#[test]
+fn draw_requires_lock_matching_reveal_and_lock_slot_hash() {
+    let mut svm = setup();
+    let (fixture, _) = raffle_fixture(&mut svm, 3);
+    assert_err(lock(&mut svm, &fixture), ErrorCode::RaffleNotReady);
+
+    svm.warp_to(draw_time(&svm, &fixture));
+    assert_err(draw(&mut svm, &fixture, RANDOMNESS), ErrorCode::RaffleNotLocked);
+    assert_ok(lock(&mut svm, &fixture));
+    let lock_slot = svm.clock().slot;
+    svm.warp(1);
+    svm.set_slot_hashes(&[(lock_slot + 1, [1; 32]), (lock_slot, LOCK_SLOT_HASH)]);
+    assert_err(lock(&mut svm, &fixture), ErrorCode::RaffleAlreadyLocked);
+
+    assert_err(draw(&mut svm, &fixture, [1; 32]), ErrorCode::RaffleRevealMismatch);
+    // The lock slot has aged out of SlotHashes
+    svm.set_slot_hashes(&[(lock_slot + 1, [1; 32])]);
+    assert_err(draw(&mut svm, &fixture, RANDOMNESS), ErrorCode::SlotHashUnavailable);
+    svm.set_slot_hashes(&[(lock_slot + 1, [1; 32]), (lock_slot, LOCK_SLOT_HASH)]);
+    assert_ok(draw(&mut svm, &fixture, RANDOMNESS));
+}
+
+#[test]
+fn raffle_cannot_be_drawn_before_attendance_is_final() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let date_ts = event(&svm, &fixture).date_ts;
+    let create = |draw_after_ts| {
+        ix(
+            ticketchain::accounts::CreateRaffle {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                raffle: raffle_pda(&fixture.event),
+                system_program: anchor_lang::solana_program::system_program::ID,
+            },
+            ticketchain::instruction::CreateRaffle { prize_count: 1, draw_after_ts, commitment: [1; 32] },
+        )
+    };
+    let finalized_ts = date_ts + ticketchain::ATTENDANCE_FINALIZE_SECONDS;
+    assert_err(svm.send(create(finalized_ts - 1), &[fixture.organizer]), ErrorCode::InvalidDrawTime);
+    assert_ok(svm.send(create(finalized_ts), &[fixture.organizer]));
+}
+
+#[test]
+fn expired_lock_can_be_relocked_and_drawn_by_anyone() {
+    let mut svm = setup();
+    let (fixture, _) = raffle_fixture(&mut svm, 4);
+    let late = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &late, 0));
+    svm.warp_to(draw_time(&svm, &fixture));
+    assert_ok(lock(&mut svm, &fixture));
+    let lock_slot = svm.clock().slot;
+
+    // While the lock slot's hash is available, the organizer's reveal stands
+    svm.warp(1);
+    svm.set_slot_hashes(&[(svm.clock().slot, [1; 32]), (lock_slot, LOCK_SLOT_HASH)]);
+    assert_err(lock(&mut svm, &fixture), ErrorCode::RaffleAlreadyLocked);
+    let stranger = svm.wallet(SOL);
+    assert_err(draw_as(&mut svm, &fixture, &stranger, RANDOMNESS), ErrorCode::InvalidOrganizer);
+
+    // The organizer never reveals and the lock slot ages out
+    svm.warp(600);
+    svm.set_slot_hashes(&[(svm.clock().slot, [1; 32])]);
+    assert_err(draw(&mut svm, &fixture, RANDOMNESS), ErrorCode::SlotHashUnavailable);
+    assert_ok(check_in(&mut svm, &fixture, &late, 4));
+    assert_ok(lock(&mut svm, &fixture));
+    let relock_slot = svm.clock().slot;
+    let raffle: ticketchain::Raffle = svm.get(&raffle_pda(&fixture.event));
+    assert_eq!((raffle.lock_slot, raffle.entrants, raffle.commitment), (relock_slot, 4, [0; 32]));
+
+    // Anyone draws from the new slot's hash; the caller's randomness is ignored
+    svm.warp(1);
+    let relock_hash = [7; 32];
+    svm.set_slot_hashes(&[(svm.clock().slot, [1; 32]), (relock_slot, relock_hash)]);
+    assert_ok(draw_as(&mut svm, &fixture, &stranger, [3; 32]));
+    let seed = hashv(&[&[0u8; 32], &relock_hash]).to_bytes();
+    let raffle: ticketchain::Raffle = svm.get(&raffle_pda(&fixture.event));
+    assert_eq!(raffle.winners, ticketchain::draw_winners(&seed, 4, 2));
+    assert_err(lock(&mut svm, &fixture), ErrorCode::RaffleAlreadyDrawn);
+}
+
+fn claim(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimRaffleWin {
+                holder: *holder,
+                raffle: raffle_pda(&fixture.event),
+                attendance_record: pda(&[b"attendance", mint.as_ref()]),
+            },
+            ticketchain::instruction::ClaimRaffleWin {},
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn winners_claim_once_and_others_cannot() {
+    let mut svm = setup();
+    let (fixture, holders) = raffle_fixture(&mut svm, 5);
+    svm.warp_to(draw_time(&svm, &fixture));
+    assert_ok(lock(&mut svm, &fixture));
+    let lock_slot = svm.clock().slot;
+    svm.warp(1);
+    svm.set_slot_hashes(&[(svm.clock().slot, [1; 32]), (lock_slot, LOCK_SLOT_HASH)]);
+    assert_ok(draw(&mut svm, &fixture, RANDOMNESS));
+    let winners = svm.get::<ticketchain::Raffle>(&raffle_pda(&fixture.event)).winners;
+
+    // Tickets were checked in in order, so attendance index i is ticket i
+    let winner = winners[0];
+    let loser = (0..5).find(|index| !winners.contains(index)).unwrap();
+    assert_err(claim(&mut svm, &fixture, &holders[loser as usize], loser), ErrorCode::NotRaffleWinner);
+    assert_err(claim(&mut svm, &fixture, &holders[loser as usize], winner), ErrorCode::NotTicketHolder);
+
+    assert_ok(claim(&mut svm, &fixture, &holders[winner as usize], winner));
+    assert_eq!(svm.get::<ticketchain::Raffle>(&raffle_pda(&fixture.event)).claimed_mask, 1);
+    svm.warp(1);
+    assert_err(claim(&mut svm, &fixture, &holders[winner as usize], winner), ErrorCode::RaffleAlreadyClaimed);
+}
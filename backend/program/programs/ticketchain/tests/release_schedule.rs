@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use ticketchain::{ErrorCode, ReleaseTranche};
+
+fn set_schedule(svm: &mut Svm, fixture: &EventFixture, release_schedule: Vec<ReleaseTranche>) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetReleaseSchedule { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetReleaseSchedule { release_schedule },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn buy(svm: &mut Svm, fixture: &EventFixture) -> Result<anchor_lang::prelude::Pubkey, TxError> {
+    let buyer = svm.wallet(10 * SOL);
+    buy_ticket(svm, fixture, &buyer, 0)
+}
+
+#[test]
+fn each_tranche_opens_exactly_at_its_release_time() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let first = svm.now() + 100;
+    let second = first + 100;
+    let schedule = vec![ReleaseTranche { release_ts: first, count: 2 }, ReleaseTranche { release_ts: second, count: 3 }];
+    assert_ok(set_schedule(&mut svm, &fixture, schedule));
+
+    svm.warp_to(first - 1);
+    assert_err(buy(&mut svm, &fixture), ErrorCode::TrancheSoldOut);
+    svm.warp_to(first);
+    assert_ok(buy(&mut svm, &fixture));
+    assert_ok(buy(&mut svm, &fixture));
+    assert_err(buy(&mut svm, &fixture), ErrorCode::TrancheSoldOut);
+
+    // The last tranche opens the whole supply, not just its own count
+    svm.warp_to(second - 1);
+    assert_err(buy(&mut svm, &fixture), ErrorCode::TrancheSoldOut);
+    svm.warp_to(second);
+    for _ in 2..10 {
+        assert_ok(buy(&mut svm, &fixture));
+    }
+    assert_eq!(event(&svm, &fixture).sold, 10);
+}
+
+#[test]
+fn early_release_adds_to_the_open_tranches() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let release_ts = svm.now() + 100;
+    assert_ok(set_schedule(&mut svm, &fixture, vec![ReleaseTranche { release_ts, count: 5 }]));
+    assert_err(buy(&mut svm, &fixture), ErrorCode::TrancheSoldOut);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetReleaseSchedule { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::ReleaseNow { count: 1 },
+        ),
+        &[fixture.organizer],
+    ));
+    assert_ok(buy(&mut svm, &fixture));
+    assert_err(buy(&mut svm, &fixture), ErrorCode::TrancheSoldOut);
+
+    // Once a ticket is sold the schedule is fixed
+    assert_err(set_schedule(&mut svm, &fixture, Vec::new()), ErrorCode::EventHasSales);
+}
+
+#[test]
+fn schedule_must_ascend_and_fit_the_supply() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let now = svm.now();
+    let unordered = vec![ReleaseTranche { release_ts: now + 200, count: 1 }, ReleaseTranche { release_ts: now + 100, count: 1 }];
+    assert_err(set_schedule(&mut svm, &fixture, unordered), ErrorCode::InvalidReleaseSchedule);
+    let oversized = vec![ReleaseTranche { release_ts: now + 100, count: 11 }];
+    assert_err(set_schedule(&mut svm, &fixture, oversized), ErrorCode::InvalidReleaseSchedule);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+const BLACKOUT: i64 = 48 * 60 * 60;
+
+fn blackout_event(svm: &mut Svm) -> EventFixture {
+    let fixture = create_event(svm);
+    let set_blackout = |blackout_secs| {
+        ix(
+            ticketchain::accounts::SetResaleBlackout { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetResaleBlackout { blackout_secs },
+        )
+    };
+    assert_err(svm.send(set_blackout(-1), &[fixture.organizer]), ErrorCode::InvalidResaleBlackout);
+    assert_ok(svm.send(set_blackout(BLACKOUT), &[fixture.organizer]));
+    fixture
+}
+
+/// Lists `mint` for `seller`, citing the primary receipt of `buyer` if given.
+fn list(svm: &mut Svm, fixture: &EventFixture, seller: &Pubkey, mint: &Pubkey, buyer: Option<&Pubkey>) -> Result<TxMeta, TxError> {
+    let mut accounts = list_accounts(fixture, seller, mint);
+    accounts.purchase_receipt = buyer.map(|buyer| receipt_pda(buyer, mint));
+    svm.send(ix(accounts, list_args(fixture, mint, SOL / 5)), &[*seller])
+}
+
+#[test]
+fn listing_opens_exactly_when_the_blackout_ends() {
+    let mut svm = setup();
+    let fixture = blackout_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let bought_at = svm.now();
+
+    assert_err(list(&mut svm, &fixture, &buyer, &mint, None), ErrorCode::MissingPurchaseReceipt);
+    svm.warp_to(bought_at + BLACKOUT - 1);
+    assert_err(list(&mut svm, &fixture, &buyer, &mint, Some(&buyer)), ErrorCode::ResaleBlackoutActive);
+    svm.warp_to(bought_at + BLACKOUT);
+    assert_ok(list(&mut svm, &fixture, &buyer, &mint, Some(&buyer)));
+}
+
+#[test]
+fn blackout_cannot_be_dodged_by_listing_under_another_event() {
+    let mut svm = setup();
+    let fixture = blackout_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+
+    // A single-tier event with no blackout, where the receipt would be optional
+    let other = create_event(&mut svm);
+    let instruction = ix(
+        list_accounts(&other, &buyer, &mint),
+        ticketchain::instruction::ListForResale { _ticket_index: 0, price_lamports: SOL / 5 },
+    );
+    assert_err(svm.send(instruction, &[buyer]), anchor_lang::error::ErrorCode::ConstraintSeeds);
+    assert!(!svm.exists(&listing_pda(&mint)));
+}
+
+#[test]
+fn transfer_does_not_restart_the_blackout() {
+    let mut svm = setup();
+    let fixture = blackout_event(&mut svm);
+    let (buyer, friend) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let bought_at = svm.now();
+    svm.warp(BLACKOUT - 60);
+    assert_ok(transfer_ticket(&mut svm, &buyer, &friend, &mint));
+
+    svm.warp_to(bought_at + BLACKOUT - 1);
+    assert_err(list(&mut svm, &fixture, &friend, &mint, Some(&buyer)), ErrorCode::ResaleBlackoutActive);
+    svm.warp_to(bought_at + BLACKOUT);
+    assert_ok(list(&mut svm, &fixture, &friend, &mint, Some(&buyer)));
+}
+
+#[test]
+fn cancelled_event_refunds_tickets_still_in_blackout() {
+    let mut svm = setup();
+    let fixture = blackout_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+
+    // Resale is closed outright, so the holder's exit is the refund
+    assert_err(list(&mut svm, &fixture, &buyer, &mint, Some(&buyer)), ErrorCode::EventCancelled);
+    let before = svm.lamports(&buyer);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: buyer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                ticket_mint: mint,
+                receipt: receipt_pda(&buyer, &mint),
+                holder_token_account: ata(&buyer, &mint),
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 0 },
+        ),
+        &[buyer],
+    ));
+    assert_eq!(svm.lamports(&buyer), before + SOL / 10);
+}
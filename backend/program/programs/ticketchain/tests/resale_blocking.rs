@@ -0,0 +1,65 @@
+mod common;
+
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PLATFORM_WALLET};
+
+#[test]
+fn resale_reopens_once_the_primary_sale_window_closes() {
+    let mut svm = setup();
+    let mut args = EventArgs::new(&svm);
+    let sale_end = svm.now() + 24 * 60 * 60;
+    args.sale_end_ts = Some(sale_end);
+    let fixture = create_event_with(&mut svm, args);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetResaleBlocking { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetResaleBlockedWhilePrimaryAvailable { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mint, SOL / 10));
+    let buyer = svm.wallet(10 * SOL);
+
+    svm.warp_to(sale_end - 1);
+    assert_err(
+        svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL / 10), &[buyer]),
+        ErrorCode::PrimaryStillAvailable,
+    );
+    svm.warp_to(sale_end);
+    assert_ok(svm.send(buy_resale_ix(&fixture, &buyer, &seller, &mint, SOL / 10), &[buyer]));
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+}
+
+#[test]
+fn tickets_of_a_cancelled_event_stop_trading() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let seller = svm.wallet(10 * SOL);
+    let listed = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    let held = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 0));
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &listed, SOL / 10));
+
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(
+        svm.send(buy_resale_ix(&fixture, &buyer, &seller, &listed, SOL / 10), &[buyer]),
+        ErrorCode::EventCancelled,
+    );
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &held, SOL / 10), ErrorCode::EventCancelled);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn set_multiple(svm: &mut Svm, fixture: &EventFixture, max_listing_multiple: u16) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetMaxListingMultiple { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetMaxListingMultiple { max_listing_multiple },
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+fn set_floor(svm: &mut Svm, fixture: &EventFixture, min_resale_lamports: u64) {
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetResaleFloor { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetResaleFloor { min_resale_lamports, dynamic: false },
+        ),
+        &[fixture.organizer],
+    ));
+}
+
+fn seller_with_tickets(svm: &mut Svm, fixture: &EventFixture, count: usize) -> (Pubkey, Vec<Pubkey>) {
+    let seller = svm.wallet(10 * SOL);
+    let mints = (0..count).map(|_| assert_ok(buy_ticket(svm, fixture, &seller, 0))).collect();
+    (seller, mints)
+}
+
+#[test]
+fn listing_may_reach_the_cap_but_not_pass_it() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    set_multiple(&mut svm, &fixture, 150);
+    let (seller, mints) = seller_with_tickets(&mut svm, &fixture, 2);
+    let cap = SOL / 10 * 150 / 100;
+
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &mints[0], cap + 1), ErrorCode::ListingMultipleTooHigh);
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mints[0], cap));
+
+    // Zero lifts the cap for new listings
+    set_multiple(&mut svm, &fixture, 0);
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mints[1], 20 * cap));
+}
+
+#[test]
+fn floor_holds_whether_or_not_a_cap_is_set() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let floor = SOL / 20;
+    set_floor(&mut svm, &fixture, floor);
+    let (seller, mints) = seller_with_tickets(&mut svm, &fixture, 2);
+
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &mints[0], floor - 1), ErrorCode::ResaleBelowDynamicFloor);
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mints[0], floor));
+    set_multiple(&mut svm, &fixture, 100);
+    assert_err(list_ticket(&mut svm, &fixture, &seller, &mints[1], floor - 1), ErrorCode::ResaleBelowDynamicFloor);
+    assert_ok(list_ticket(&mut svm, &fixture, &seller, &mints[1], SOL / 10));
+}
+
+#[test]
+fn ticket_cannot_be_listed_under_another_event() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    set_multiple(&mut svm, &fixture, 150);
+    let (seller, mints) = seller_with_tickets(&mut svm, &fixture, 1);
+    // An uncapped event the seller points the listing at instead
+    let other = create_event(&mut svm);
+
+    let instruction = ix(
+        list_accounts(&other, &seller, &mints[0]),
+        ticketchain::instruction::ListForResale { _ticket_index: 0, price_lamports: 10 * SOL },
+    );
+    assert_err(svm.send(instruction, &[seller]), anchor_lang::error::ErrorCode::ConstraintSeeds);
+    assert!(!svm.exists(&listing_pda(&mints[0])));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+#[test]
+fn dynamic_floor_lifts_for_live_holds_but_not_expired_ones() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let organizer = fixture.organizer;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetResaleFloor { organizer, event: fixture.event },
+            ticketchain::instruction::SetResaleFloor { min_resale_lamports: 0, dynamic: true },
+        ),
+        &[organizer],
+    ));
+    let sponsor = svm.wallet(SOL);
+    let expires_at = svm.now() + 24 * 60 * 60;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateHold {
+                organizer,
+                event: fixture.event,
+                hold: pda(&[b"hold", fixture.event.as_ref(), sponsor.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateHold { beneficiary: sponsor, count: 8, price_lamports: 0, expires_at },
+        ),
+        &[organizer],
+    ));
+    let first = svm.wallet(10 * SOL);
+    let second = svm.wallet(10 * SOL);
+    let first_mint = assert_ok(buy_ticket(&mut svm, &fixture, &first, 0));
+    let second_mint = assert_ok(buy_ticket(&mut svm, &fixture, &second, 0));
+
+    // Sold plus live holds cover the supply: nothing is left on primary sale
+    assert_ok(list_ticket(&mut svm, &fixture, &first, &first_mint, SOL / 20));
+
+    svm.warp_to(expires_at);
+    assert_err(
+        list_ticket(&mut svm, &fixture, &second, &second_mint, SOL / 20),
+        ErrorCode::ResaleBelowDynamicFloor,
+    );
+    assert_ok(list_ticket(&mut svm, &fixture, &second, &second_mint, SOL / 10));
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, PLATFORM_WALLET};
+
+fn template_pda(name: &str) -> Pubkey {
+    pda(&[b"split_template", name.as_bytes()])
+}
+
+fn create_template(svm: &mut Svm, name: &str, artist_bps: u16, seller_bps: u16, platform_bps: u16) -> Result<TxMeta, TxError> {
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::CreateSplitTemplate {
+                authority: PLATFORM_AUTHORITY,
+                platform_config: platform_config_pda(),
+                split_template: template_pda(name),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateSplitTemplate { name: name.to_string(), artist_bps, seller_bps, platform_bps },
+        ),
+        &[PLATFORM_AUTHORITY],
+    )
+}
+
+fn template_event(svm: &mut Svm, name: &str) -> EventFixture {
+    let organizer = svm.wallet(100 * SOL);
+    let args = EventArgs::new(svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CreateEvent {
+                organizer,
+                event: event_pda(&organizer, 1),
+                platform_config: platform_config_pda(),
+                split_template: Some(template_pda(name)),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CreateEvent {
+                nonce: 1,
+                title: args.title,
+                venue: args.venue,
+                date_ts: args.date_ts,
+                tiers: args.tiers,
+                artist_pct: args.artist_pct,
+                sale_start_ts: args.sale_start_ts,
+                sale_end_ts: args.sale_end_ts,
+                max_per_wallet: args.max_per_wallet,
+                metadata_uri: args.metadata_uri,
+            },
+        ),
+        &[organizer],
+    ));
+    EventFixture { organizer, event: event_pda(&organizer, 1), nonce: 1 }
+}
+
+/// Resells a ticket at `price` and returns what the organizer, seller and platform each received.
+fn resale_payouts(svm: &mut Svm, fixture: &EventFixture, price: u64) -> (u64, u64, u64) {
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, fixture, &seller, 0));
+    assert_ok(list_ticket(svm, fixture, &seller, &mint, price));
+    let rent = svm.lamports(&listing_pda(&mint)) + svm.lamports(&pda(&[b"escrow", mint.as_ref()]));
+    let before = [fixture.organizer, seller, PLATFORM_WALLET].map(|key| svm.lamports(&key));
+
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(svm.send(buy_resale_ix(fixture, &buyer, &seller, &mint, price), &[buyer]));
+    let after = [fixture.organizer, seller, PLATFORM_WALLET].map(|key| svm.lamports(&key));
+    (after[0] - before[0], after[1] - before[1] - rent, after[2] - before[2])
+}
+
+#[test]
+fn template_must_sum_to_the_whole_price() {
+    let mut svm = setup();
+    assert_err(create_template(&mut svm, "short", 4000, 4000, 1999), ErrorCode::InvalidSplit);
+    assert_err(create_template(&mut svm, "over", 10_000, 1, 0), ErrorCode::InvalidSplit);
+    assert_ok(create_template(&mut svm, "artist", 10_000, 0, 0));
+    assert_eq!(svm.get::<ticketchain::SplitTemplate>(&template_pda("artist")).artist_bps, 10_000);
+}
+
+#[test]
+fn one_recipient_can_take_the_whole_resale() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let price = SOL / 3;
+    for (name, (artist_bps, seller_bps, platform_bps), expected) in [
+        ("artist", (10_000, 0, 0), (price, 0, 0)),
+        ("seller", (0, 10_000, 0), (0, price, 0)),
+        ("platform", (0, 0, 10_000), (0, 0, price)),
+    ] {
+        assert_ok(create_template(&mut svm, name, artist_bps, seller_bps, platform_bps));
+        let fixture = template_event(&mut svm, name);
+        let state = event(&svm, &fixture);
+        assert_eq!((state.artist_bps, state.seller_bps, state.platform_bps), (artist_bps, seller_bps, platform_bps));
+        assert_eq!(ticketchain::resale_split(&state, price), expected);
+        assert_eq!(resale_payouts(&mut svm, &fixture, price), expected, "{name} template");
+    }
+}
+
+#[test]
+fn zero_artist_royalty_pays_the_organizer_nothing() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let mut args = EventArgs::new(&svm);
+    args.artist_pct = 0;
+    let fixture = create_event_with(&mut svm, args);
+    let price = SOL / 4;
+    assert_eq!(resale_payouts(&mut svm, &fixture, price), (0, price * 4 / 5, price / 5));
+}
+
+#[test]
+fn rounding_dust_goes_to_the_seller() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    // 10% artist and 20% platform of 0.333333337 SOL both round down
+    let price = 333_333_337;
+    let (artist, seller, platform) = (33_333_333, 233_333_337, 66_666_667);
+    assert_eq!((artist, platform), (price / 10, price / 5));
+    assert_eq!(artist + seller + platform, price);
+    assert_eq!(ticketchain::resale_split(&event(&svm, &fixture), price), (artist, seller, platform));
+    assert_eq!(resale_payouts(&mut svm, &fixture, price), (artist, seller, platform));
+}
+
+fn update_template(svm: &mut Svm, authority: &Pubkey, name: &str, split: (u16, u16, u16)) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::UpdateSplitTemplate {
+                authority: *authority,
+                platform_config: platform_config_pda(),
+                split_template: template_pda(name),
+            },
+            ticketchain::instruction::UpdateSplitTemplate {
+                artist_bps: split.0,
+                seller_bps: split.1,
+                platform_bps: split.2,
+            },
+        ),
+        &[*authority],
+    )
+}
+
+#[test]
+fn template_updates_only_reach_events_created_afterwards() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    assert_ok(create_template(&mut svm, "house", 1000, 7000, 2000));
+    let existing = template_event(&mut svm, "house");
+
+    let stranger = svm.wallet(SOL);
+    assert_err(update_template(&mut svm, &stranger, "house", (3000, 5000, 2000)), ErrorCode::Unauthorized);
+    assert_err(update_template(&mut svm, &PLATFORM_AUTHORITY, "house", (3000, 5000, 1000)), ErrorCode::InvalidSplit);
+    assert_ok(update_template(&mut svm, &PLATFORM_AUTHORITY, "house", (3000, 5000, 2000)));
+
+    let price = SOL / 2;
+    assert_eq!(resale_payouts(&mut svm, &existing, price), (price / 10, price * 7 / 10, price / 5));
+    let later = template_event(&mut svm, "house");
+    assert_eq!(resale_payouts(&mut svm, &later, price), (price * 3 / 10, price / 2, price / 5));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, RoyaltyEscrow, DEFAULT_ROYALTY_RELEASE_DELAY_SECONDS, PLATFORM_WALLET};
+
+fn escrow_pda(fixture: &EventFixture) -> Pubkey {
+    pda(&[b"royalty_escrow", fixture.event.as_ref()])
+}
+
+/// A second-hand sale of a fresh ticket, with the artist share sent to the escrow.
+fn escrowed_resale(svm: &mut Svm, fixture: &EventFixture) {
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, fixture, &seller, 0));
+    assert_ok(list_ticket(svm, fixture, &seller, &mint, SOL / 10));
+    let buyer = svm.wallet(10 * SOL);
+    let mut accounts = buy_resale_accounts(fixture, &buyer, &seller, &mint);
+    accounts.royalty_escrow = Some(escrow_pda(fixture));
+    assert_ok(svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports: SOL / 10 }), &[buyer]));
+}
+
+fn claim(svm: &mut Svm, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimEscrowedRoyalties {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                royalty_escrow: escrow_pda(fixture),
+                platform_config: platform_config_pda(),
+            },
+            ticketchain::instruction::ClaimEscrowedRoyalties {},
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn later_deposits_do_not_extend_the_royalty_lock() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetRoyaltyEscrowMode {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                royalty_escrow: escrow_pda(&fixture),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetRoyaltyEscrowMode { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+    let unlock = svm.now() + DEFAULT_ROYALTY_RELEASE_DELAY_SECONDS as i64;
+    escrowed_resale(&mut svm, &fixture);
+    svm.warp(3 * 24 * 60 * 60);
+    escrowed_resale(&mut svm, &fixture);
+    let escrow: RoyaltyEscrow = svm.get(&escrow_pda(&fixture));
+    let owed = escrow.claimable_amount;
+    assert!(owed > 0);
+
+    svm.warp_to(unlock - 1);
+    assert_err(claim(&mut svm, &fixture), ErrorCode::RoyaltyEscrowLocked);
+    svm.warp_to(unlock);
+    let before = svm.lamports(&fixture.organizer);
+    assert_ok(claim(&mut svm, &fixture));
+    assert_eq!(svm.lamports(&fixture.organizer), before + owed);
+
+    // Emptied, so the next deposit starts a fresh lock
+    let escrow: RoyaltyEscrow = svm.get(&escrow_pda(&fixture));
+    assert_eq!(escrow.claimable_amount, 0);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::ErrorCode;
+
+const HOUR: i64 = 60 * 60;
+
+/// An event whose sale opens `start` hours and closes `end` hours from now.
+fn window_event(svm: &mut Svm, start: Option<i64>, end: Option<i64>) -> EventFixture {
+    let now = svm.now();
+    let mut args = EventArgs::new(svm);
+    args.sale_start_ts = start.map(|hours| now + hours * HOUR);
+    args.sale_end_ts = end.map(|hours| now + hours * HOUR);
+    create_event_with(svm, args)
+}
+
+fn update_window(svm: &mut Svm, fixture: &EventFixture, signer: &Pubkey, sale_start_ts: Option<i64>, sale_end_ts: Option<i64>) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::UpdateSaleWindow { organizer: *signer, event: fixture.event },
+            ticketchain::instruction::UpdateSaleWindow { sale_start_ts, sale_end_ts },
+        ),
+        &[*signer],
+    )
+}
+
+#[test]
+fn no_window_sells_until_the_event() {
+    let mut svm = setup();
+    let fixture = window_event(&mut svm, None, None);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(event(&svm, &fixture).date_ts - 1);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+}
+
+#[test]
+fn start_only_opens_at_the_start() {
+    let mut svm = setup();
+    let fixture = window_event(&mut svm, Some(1), None);
+    let buyer = svm.wallet(10 * SOL);
+    let start = event(&svm, &fixture).sale_start_ts.unwrap();
+    svm.warp_to(start - 1);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SaleNotStarted);
+    svm.warp_to(start);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(event(&svm, &fixture).date_ts - 1);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+}
+
+#[test]
+fn end_only_closes_at_the_end() {
+    let mut svm = setup();
+    let fixture = window_event(&mut svm, None, Some(2));
+    let buyer = svm.wallet(10 * SOL);
+    let end = event(&svm, &fixture).sale_end_ts.unwrap();
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(end - 1);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(end);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SaleEnded);
+}
+
+#[test]
+fn start_and_end_bound_the_sale_on_both_sides() {
+    let mut svm = setup();
+    let fixture = window_event(&mut svm, Some(1), Some(2));
+    let buyer = svm.wallet(10 * SOL);
+    let state = event(&svm, &fixture);
+    let (start, end) = (state.sale_start_ts.unwrap(), state.sale_end_ts.unwrap());
+    svm.warp_to(start - 1);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SaleNotStarted);
+    svm.warp_to(start);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(end - 1);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    svm.warp_to(end);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SaleEnded);
+    assert_eq!(event(&svm, &fixture).sold, 2);
+}
+
+#[test]
+fn window_must_open_before_it_closes_and_close_by_the_event() {
+    let mut svm = setup();
+    let organizer = svm.wallet(100 * SOL);
+    let now = svm.now();
+    let mut args = EventArgs::new(&svm);
+    args.sale_start_ts = Some(now + HOUR);
+    args.sale_end_ts = Some(now + HOUR);
+    assert_err(svm.send(create_event_ix(&svm, &organizer, 1, args), &[organizer]), ErrorCode::InvalidSaleWindow);
+    let mut args = EventArgs::new(&svm);
+    args.sale_end_ts = Some(args.date_ts + 1);
+    assert_err(svm.send(create_event_ix(&svm, &organizer, 1, args), &[organizer]), ErrorCode::InvalidSaleWindow);
+}
+
+#[test]
+fn organizer_moves_the_window_until_the_first_sale() {
+    let mut svm = setup();
+    let fixture = window_event(&mut svm, Some(1), None);
+    let (buyer, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let date_ts = event(&svm, &fixture).date_ts;
+
+    assert_err(update_window(&mut svm, &fixture, &stranger, None, None), ErrorCode::InvalidOrganizer);
+    assert_err(update_window(&mut svm, &fixture, &fixture.organizer, None, Some(date_ts + 1)), ErrorCode::InvalidSaleWindow);
+    assert_ok(update_window(&mut svm, &fixture, &fixture.organizer, None, Some(date_ts)));
+    let state = event(&svm, &fixture);
+    assert_eq!((state.sale_start_ts, state.sale_end_ts), (None, Some(date_ts)));
+
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    assert_err(update_window(&mut svm, &fixture, &fixture.organizer, None, None), ErrorCode::EventHasSales);
+}
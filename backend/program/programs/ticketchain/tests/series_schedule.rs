@@ -0,0 +1,242 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::AccountSerialize;
+use common::*;
+use ticketchain::{ErrorCode, POSTPONEMENT_REFUND_SECONDS};
+
+const DAY: i64 = 24 * 60 * 60;
+
+struct Series {
+    key: Pubkey,
+    creator: Pubkey,
+    events: [EventFixture; 3],
+}
+
+/// Three events by different organizers in one pass bundle created by the first.
+fn series_fixture(svm: &mut Svm) -> Series {
+    let events = [create_event(svm), create_event(svm), create_event(svm)];
+    let creator = events[0].organizer;
+    let key = pda(&[b"pass_bundle", creator.as_ref(), &1u64.to_le_bytes()]);
+    let mut remaining: Vec<AccountMeta> = events.iter().map(|e| AccountMeta::new(e.event, false)).collect();
+    remaining.extend(events[1..].iter().map(|e| AccountMeta::new_readonly(e.organizer, true)));
+    let sale_end_ts = svm.now() + 3600;
+    assert_ok(svm.process(
+        &[ix_with_remaining(
+            ticketchain::accounts::CreatePassBundle { creator, bundle: key, system_program: system_program::ID },
+            ticketchain::instruction::CreatePassBundle {
+                nonce: 1,
+                events: events.iter().map(|e| e.event).collect(),
+                shares_bps: vec![3_334, 3_333, 3_333],
+                price_lamports: SOL,
+                supply: 1,
+                sale_end_ts,
+            },
+            remaining,
+        )],
+        &[creator, events[1].organizer, events[2].organizer],
+    ));
+    Series { key, creator, events }
+}
+
+fn shift(svm: &mut Svm, series: &Series, delta_seconds: i64) -> Result<TxMeta, TxError> {
+    let mut remaining: Vec<AccountMeta> = series.events.iter().map(|e| AccountMeta::new(e.event, false)).collect();
+    if delta_seconds > 0 {
+        remaining.extend(series.events.iter().map(|e| AccountMeta::new(vault_pda(&e.event), false)));
+    }
+    remaining.extend(series.events[1..].iter().map(|e| AccountMeta::new(e.organizer, true)));
+    svm.send(
+        ix_with_remaining(
+            ticketchain::accounts::UpdateSeriesSchedule {
+                creator: series.creator,
+                bundle: series.key,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::UpdateSeriesSchedule { delta_seconds },
+            remaining,
+        ),
+        &[series.creator, series.events[1].organizer, series.events[2].organizer],
+    )
+}
+
+fn claim_refund(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, index: u32) -> Result<TxMeta, TxError> {
+    let ticket_mint = ticket_mint_pda(&fixture.event, index);
+    svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: *holder,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                ticket_mint,
+                receipt: receipt_pda(holder, &ticket_mint),
+                holder_token_account: ata(holder, &ticket_mint),
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: index },
+        ),
+        &[*holder],
+    )
+}
+
+#[test]
+fn postponing_a_series_moves_every_date_and_opens_refunds() {
+    let mut svm = setup();
+    let series = series_fixture(&mut svm);
+    let early = svm.wallet(10 * SOL);
+    let late = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &series.events[0], &early, 0));
+    assert_ok(buy_ticket(&mut svm, &series.events[0], &late, 0));
+    assert_ok(buy_ticket(&mut svm, &series.events[2], &early, 0));
+    let dates: Vec<i64> = series.events.iter().map(|e| event(&svm, e).date_ts).collect();
+
+    let now = svm.now();
+    assert_ok(shift(&mut svm, &series, DAY));
+    for (fixture, date) in series.events.iter().zip(&dates) {
+        assert_eq!(event(&svm, fixture).date_ts, date + DAY);
+    }
+    // Events with sales get a funded refund window; the unsold one is left alone
+    let opened = event(&svm, &series.events[0]);
+    assert_eq!(opened.postponement_refund_until, now + POSTPONEMENT_REFUND_SECONDS);
+    assert!(opened.refundable_until >= opened.postponement_refund_until);
+    assert_eq!(event(&svm, &series.events[2]).postponement_refund_until, now + POSTPONEMENT_REFUND_SECONDS);
+    assert_eq!(event(&svm, &series.events[1]).postponement_refund_until, 0);
+    assert!(!svm.exists(&vault_pda(&series.events[1].event)));
+
+    let before = svm.lamports(&early);
+    assert_ok(claim_refund(&mut svm, &series.events[0], &early, 0));
+    assert_eq!(svm.lamports(&early), before + SOL / 10);
+    assert_ok(claim_refund(&mut svm, &series.events[2], &early, 0));
+
+    svm.warp(POSTPONEMENT_REFUND_SECONDS + 1);
+    assert_err(claim_refund(&mut svm, &series.events[0], &late, 1), ErrorCode::EventNotCancelled);
+}
+
+#[test]
+fn only_an_unsold_series_moves_earlier() {
+    let mut svm = setup();
+    let series = series_fixture(&mut svm);
+    let dates: Vec<i64> = series.events.iter().map(|e| event(&svm, e).date_ts).collect();
+
+    // No vaults are needed, and no date may land in the past
+    assert_ok(shift(&mut svm, &series, -60));
+    let into_the_past = -2 * (dates[0] - svm.now());
+    assert_err(shift(&mut svm, &series, into_the_past), ErrorCode::InvalidEventDate);
+
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &series.events[1], &buyer, 0));
+    assert_err(shift(&mut svm, &series, -60), ErrorCode::DateMovedEarlierAfterSales);
+    for (fixture, date) in series.events.iter().zip(&dates) {
+        assert_eq!(event(&svm, fixture).date_ts, date - 60);
+    }
+}
+
+#[test]
+fn cancelled_or_superseded_events_block_the_shift() {
+    let mut svm = setup();
+    let series = series_fixture(&mut svm);
+    let dates: Vec<i64> = series.events.iter().map(|e| event(&svm, e).date_ts).collect();
+
+    // Mark the last event as split into another
+    let mut superseded = event(&svm, &series.events[2]);
+    superseded.split_into[0] = Pubkey::new_unique();
+    let mut account = svm.account(&series.events[2].event).unwrap().clone();
+    let mut data = Vec::new();
+    superseded.try_serialize(&mut data).unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    svm.set_account(series.events[2].event, account);
+    assert_err(shift(&mut svm, &series, DAY), ErrorCode::EventSuperseded);
+
+    let fixture = &series.events[1];
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[fixture.organizer],
+    ));
+    assert_err(shift(&mut svm, &series, -DAY), ErrorCode::EventCancelled);
+    for (fixture, date) in series.events.iter().zip(&dates) {
+        assert_eq!(event(&svm, fixture).date_ts, *date);
+    }
+}
+
+fn update_date(fixture: &EventFixture, date_ts: i64) -> Instruction {
+    ix(
+        ticketchain::accounts::UpdateEvent {
+            organizer: fixture.organizer,
+            event: fixture.event,
+            platform_config: platform_config_pda(),
+            vault: None,
+            system_program: None,
+        },
+        ticketchain::instruction::UpdateEvent {
+            title: None,
+            venue: None,
+            date_ts: Some(date_ts),
+            price_lamports: None,
+            tier_name: None,
+            supply: None,
+        },
+    )
+}
+
+#[test]
+fn unsold_events_move_earlier_but_not_into_the_past() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let now = svm.now();
+    assert_err(svm.send(update_date(&fixture, now), &[fixture.organizer]), ErrorCode::InvalidEventDate);
+    assert_ok(svm.send(update_date(&fixture, now + 60), &[fixture.organizer]));
+    assert_eq!(event(&svm, &fixture).date_ts, now + 60);
+}
+
+#[test]
+fn postponing_one_event_requires_its_vault() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let date_ts = event(&svm, &fixture).date_ts + DAY;
+    let update = |vault: Option<Pubkey>| {
+        ix(
+            ticketchain::accounts::UpdateEvent {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                platform_config: platform_config_pda(),
+                vault,
+                system_program: vault.map(|_| system_program::ID),
+            },
+            ticketchain::instruction::UpdateEvent {
+                title: None,
+                venue: None,
+                date_ts: Some(date_ts),
+                price_lamports: None,
+                tier_name: None,
+                supply: None,
+            },
+        )
+    };
+
+    assert_err(svm.send(update(None), &[fixture.organizer]), ErrorCode::InvalidVault);
+    assert_err(svm.send(update_date(&fixture, date_ts - 2 * DAY), &[fixture.organizer]), ErrorCode::DateMovedEarlierAfterSales);
+    let now = svm.now();
+    assert_ok(svm.send(update(Some(vault_pda(&fixture.event))), &[fixture.organizer]));
+    let postponed = event(&svm, &fixture);
+    assert_eq!((postponed.date_ts, postponed.postponement_refund_until), (date_ts, now + POSTPONEMENT_REFUND_SECONDS));
+    assert_ok(claim_refund(&mut svm, &fixture, &buyer, 0));
+}
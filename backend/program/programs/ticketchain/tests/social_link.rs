@@ -0,0 +1,65 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, MAX_SOCIAL_LINK_LEN};
+
+fn set_link(svm: &mut Svm, fixture: &EventFixture, link: &str) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::UpdateSocialLink { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::UpdateSocialLink { link: link.to_string() },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn set_verified(svm: &mut Svm, fixture: &EventFixture, authority: &Pubkey, verified: bool) -> Result<TxMeta, TxError> {
+    let accounts = ticketchain::accounts::SetOrganizerVerification {
+        authority: *authority,
+        platform_config: platform_config_pda(),
+        event: fixture.event,
+    };
+    let instruction = if verified {
+        ix(accounts, ticketchain::instruction::VerifyOrganizer {})
+    } else {
+        ix(accounts, ticketchain::instruction::RevokeOrganizerVerification {})
+    };
+    svm.send(instruction, &[*authority])
+}
+
+#[test]
+fn platform_authority_verifies_and_revokes_an_organizer() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    let fixture = create_event(&mut svm);
+    // Verification needs a link to vouch for
+    assert_err(set_verified(&mut svm, &fixture, &PLATFORM_AUTHORITY, true), ErrorCode::SocialLinkInvalid);
+    assert_ok(set_link(&mut svm, &fixture, "https://example.com/launch-night"));
+
+    assert_err(set_verified(&mut svm, &fixture, &fixture.organizer, true), ErrorCode::Unauthorized);
+    assert_ok(set_verified(&mut svm, &fixture, &PLATFORM_AUTHORITY, true));
+    assert_eq!(event(&svm, &fixture).verification_ts, svm.now());
+
+    assert_err(set_verified(&mut svm, &fixture, &fixture.organizer, false), ErrorCode::Unauthorized);
+    assert_ok(set_verified(&mut svm, &fixture, &PLATFORM_AUTHORITY, false));
+    assert_eq!(event(&svm, &fixture).verification_ts, 0);
+}
+
+#[test]
+fn link_must_be_https_and_short_and_changing_it_drops_verification() {
+    let mut svm = setup();
+    svm.airdrop(&PLATFORM_AUTHORITY, SOL);
+    let fixture = create_event(&mut svm);
+    assert_err(set_link(&mut svm, &fixture, "http://example.com"), ErrorCode::SocialLinkInvalid);
+    let longest = format!("https://{}", "a".repeat(MAX_SOCIAL_LINK_LEN - "https://".len()));
+    assert_err(set_link(&mut svm, &fixture, &format!("{longest}b")), ErrorCode::SocialLinkInvalid);
+    assert_ok(set_link(&mut svm, &fixture, &longest));
+    assert_eq!(event(&svm, &fixture).social_link, longest);
+
+    assert_ok(set_verified(&mut svm, &fixture, &PLATFORM_AUTHORITY, true));
+    assert_ne!(event(&svm, &fixture).verification_ts, 0);
+    assert_ok(set_link(&mut svm, &fixture, "https://example.com/moved"));
+    let state = event(&svm, &fixture);
+    assert_eq!((state.social_link.as_str(), state.verification_ts), ("https://example.com/moved", 0));
+}
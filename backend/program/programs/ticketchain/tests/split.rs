@@ -0,0 +1,282 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, Receipt, TierConfig};
+
+struct Split {
+    original: EventFixture,
+    shows: [EventFixture; 2],
+}
+
+/// An event with `tiers` and two replacement shows by its organizer: the first with
+/// the same tiers, the second priced at 1 SOL.
+fn split_fixture(svm: &mut Svm, tiers: Vec<TierConfig>) -> Split {
+    let mut args = EventArgs::new(svm);
+    args.tiers = tiers.clone();
+    let original = create_event_with(svm, args);
+    let organizer = original.organizer;
+    let mut same = EventArgs::new(svm);
+    same.tiers = tiers;
+    let mut dearer = EventArgs::new(svm);
+    dearer.tiers = vec![tier(SOL, 10)];
+    let shows = [(2, same), (3, dearer)].map(|(nonce, args)| {
+        let instruction = create_event_ix(svm, &organizer, nonce, args);
+        assert_ok(svm.send(instruction, &[organizer]));
+        EventFixture { organizer, event: event_pda(&organizer, nonce), nonce }
+    });
+    Split { original, shows }
+}
+
+fn split(svm: &mut Svm, split: &Split) -> Result<TxMeta, TxError> {
+    let original = split.original;
+    svm.send(
+        ix(
+            ticketchain::accounts::SplitEvent {
+                organizer: original.organizer,
+                event: original.event,
+                event_a: split.shows[0].event,
+                event_b: split.shows[1].event,
+                vault: vault_pda(&original.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SplitEvent {},
+        ),
+        &[original.organizer],
+    )
+}
+
+/// migrate_ticket accounts for `holder`'s ticket `index` into the next ticket of
+/// `show`, with no deposit escrow or identity.
+fn migrate_accounts(svm: &Svm, split: &Split, holder: &Pubkey, buyer: &Pubkey, index: u32, show: usize) -> ticketchain::accounts::MigrateTicket {
+    let original = split.original.event;
+    let target = split.shows[show].event;
+    let mint = ticket_mint_pda(&original, index);
+    let target_index = svm.get::<ticketchain::Event>(&target).sold;
+    let target_mint = ticket_mint_pda(&target, target_index);
+    ticketchain::accounts::MigrateTicket {
+        holder: *holder,
+        event: original,
+        ticket_mint: mint,
+        ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+        holder_token_account: ata(holder, &mint),
+        receipt: receipt_pda(buyer, &mint),
+        vault: vault_pda(&original),
+        deposit_escrow: None,
+        target_event: target,
+        target_vault: vault_pda(&target),
+        target_ticket_authority: ticket_authority_pda(&target, target_index),
+        target_ticket_mint: target_mint,
+        holder_target_token_account: ata(holder, &target_mint),
+        target_receipt: receipt_pda(holder, &target_mint),
+        identity_purchase: None,
+        target_ticket_metadata: None,
+        token_metadata_program: None,
+        token_program: TOKEN_PROGRAM,
+        associated_token_program: ATA_PROGRAM,
+        system_program: system_program::ID,
+    }
+}
+
+fn migrate_args(index: u32, identity_commitment: Option<[u8; 32]>) -> ticketchain::instruction::MigrateTicket {
+    ticketchain::instruction::MigrateTicket { _ticket_index: index, tier_index: 0, identity_commitment }
+}
+
+fn migrate(svm: &mut Svm, split: &Split, holder: &Pubkey, buyer: &Pubkey, index: u32, show: usize) -> Result<TxMeta, TxError> {
+    let accounts = migrate_accounts(svm, split, holder, buyer, index, show);
+    svm.send(ix(accounts, migrate_args(index, None)), &[*holder])
+}
+
+/// SOL held in `event`'s vault above its rent floor.
+fn escrowed(svm: &Svm, event: &Pubkey) -> u64 {
+    svm.lamports(&vault_pda(event)) - Rent::default().minimum_balance(8 + 32 + 1)
+}
+
+#[test]
+fn migrated_ticket_carries_its_refund_to_the_new_show() {
+    let mut svm = setup();
+    let split_shows = split_fixture(&mut svm, vec![tier(SOL / 10, 10)]);
+    let original = split_shows.original;
+    let buyer = svm.wallet(10 * SOL);
+    let gifter = svm.wallet(10 * SOL);
+    let holder = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &original, &buyer, 0));
+    let gifted = assert_ok(buy_ticket(&mut svm, &original, &gifter, 0));
+    assert_ok(transfer_ticket(&mut svm, &gifter, &holder, &gifted));
+
+    // The reserve is what the holders are owed, funded by the organizer
+    assert_ok(split(&mut svm, &split_shows));
+    assert_eq!(escrowed(&svm, &original.event), 2 * SOL / 10);
+
+    assert_err(migrate(&mut svm, &split_shows, &buyer, &buyer, 0, 1), ErrorCode::TierAbovePaidPrice);
+    assert_ok(migrate(&mut svm, &split_shows, &buyer, &buyer, 0, 0));
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 0);
+    let show = split_shows.shows[0];
+    let new_mint = ticket_mint_pda(&show.event, 0);
+    assert_eq!(token_balance(&svm, &ata(&buyer, &new_mint)), 1);
+    let state = event(&svm, &show);
+    assert_eq!((state.sold, state.tiers[0].sold), (1, 1));
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &new_mint)).paid_lamports, SOL / 10);
+    assert_eq!(escrowed(&svm, &show.event), SOL / 10);
+    assert_eq!(escrowed(&svm, &original.event), SOL / 10);
+
+    // A holder who didn't buy the ticket can still decline both shows
+    let before = svm.lamports(&holder);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimSplitRefund {
+                holder,
+                event: original.event,
+                vault: vault_pda(&original.event),
+                receipt: receipt_pda(&gifter, &gifted),
+                ticket_mint: gifted,
+                holder_token_account: ata(&holder, &gifted),
+                deposit_escrow: None,
+                identity_purchase: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimSplitRefund {},
+        ),
+        &[holder],
+    ));
+    assert_eq!(svm.lamports(&holder), before + SOL / 10);
+    assert_eq!(escrowed(&svm, &original.event), 0);
+
+    // If the new show is cancelled, the migrated ticket is refunded from its vault
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::CancelEvent {
+                organizer: show.organizer,
+                event: show.event,
+                vault: vault_pda(&show.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::CancelEvent {},
+        ),
+        &[show.organizer],
+    ));
+    let before = svm.lamports(&show.organizer);
+    let before_buyer = svm.lamports(&buyer);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::ClaimRefund {
+                holder: buyer,
+                event: show.event,
+                vault: vault_pda(&show.event),
+                ticket_mint: new_mint,
+                receipt: receipt_pda(&buyer, &new_mint),
+                holder_token_account: ata(&buyer, &new_mint),
+                deposit_escrow: None,
+                identity_purchase: None,
+                ticket_metadata: None,
+                ticket_edition: None,
+                holder_token_record: None,
+                instructions: None,
+                token_metadata_program: None,
+                system_program: None,
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::ClaimRefund { _ticket_index: 0 },
+        ),
+        &[buyer],
+    ));
+    assert_eq!(svm.lamports(&buyer), before_buyer + SOL / 10);
+    assert_eq!(svm.lamports(&show.organizer), before);
+}
+
+#[test]
+fn migration_returns_the_attendance_deposit() {
+    let mut svm = setup();
+    let split_shows = split_fixture(&mut svm, vec![tier(0, 10)]);
+    let original = split_shows.original;
+    let escrow = pda(&[b"deposit_escrow", original.event.as_ref()]);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAttendanceDeposit {
+                organizer: original.organizer,
+                event: original.event,
+                deposit_escrow: escrow,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetAttendanceDeposit { deposit_lamports: SOL / 20, forfeit_wallet: None },
+        ),
+        &[original.organizer],
+    ));
+    let buyer = svm.wallet(10 * SOL);
+    let mut accounts = buy_ticket_accounts(&original, &buyer, 0);
+    accounts.deposit_escrow = Some(escrow);
+    assert_ok(svm.send(ix(accounts, buy_ticket_args(0, 0)), &[buyer]));
+    assert_ok(split(&mut svm, &split_shows));
+
+    let mut accounts = migrate_accounts(&svm, &split_shows, &buyer, &buyer, 0, 0);
+    accounts.deposit_escrow = Some(escrow);
+    let before = svm.lamports(&buyer);
+    assert_ok(svm.send(ix(accounts, migrate_args(0, None)), &[buyer]));
+    let new_mint = ticket_mint_pda(&split_shows.shows[0].event, 0);
+    let rent = svm.lamports(&ata(&buyer, &new_mint))
+        + svm.lamports(&new_mint)
+        + svm.lamports(&receipt_pda(&buyer, &new_mint))
+        + svm.lamports(&vault_pda(&split_shows.shows[0].event));
+    assert_eq!(svm.lamports(&buyer), before + SOL / 20 - rent);
+    assert_eq!(event(&svm, &original).deposits_held, 0);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &ticket_mint_pda(&original.event, 0))).deposit_lamports, 0);
+}
+
+#[test]
+fn migrating_into_an_identity_show_takes_the_holder_identity() {
+    let mut svm = setup();
+    let split_shows = split_fixture(&mut svm, vec![tier(SOL / 10, 10)]);
+    let show = split_shows.shows[0];
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetIdentityMode { organizer: show.organizer, event: show.event },
+            ticketchain::instruction::SetIdentityMode { required: true, verify_at_check_in: true },
+        ),
+        &[show.organizer],
+    ));
+    let buyer = svm.wallet(10 * SOL);
+    assert_ok(buy_ticket(&mut svm, &split_shows.original, &buyer, 0));
+    assert_ok(split(&mut svm, &split_shows));
+
+    let id = anchor_lang::solana_program::hash::hashv(&[&[7; 32]]).to_bytes();
+    assert_err(migrate(&mut svm, &split_shows, &buyer, &buyer, 0, 0), ErrorCode::IdentityCommitmentRequired);
+    let mut accounts = migrate_accounts(&svm, &split_shows, &buyer, &buyer, 0, 0);
+    accounts.identity_purchase = Some(pda(&[b"identity", show.event.as_ref(), &id]));
+    assert_ok(svm.send(ix(accounts, migrate_args(0, Some(id))), &[buyer]));
+    let new_mint = ticket_mint_pda(&show.event, 0);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &new_mint)).identity_commitment, id);
+    assert_ok(svm.send(
+        ix(
+            check_in_accounts(&show, &buyer, &buyer, 0),
+            ticketchain::instruction::CheckInWithIdentity { ticket_index: 0, identity_preimage: [7; 32] },
+        ),
+        &[show.organizer, buyer],
+    ));
+}
+
+#[test]
+fn migrated_ticket_takes_the_perks_of_its_new_tier() {
+    let mut svm = setup();
+    let split_shows = split_fixture(&mut svm, vec![tier(SOL / 10, 10)]);
+    let original = split_shows.original;
+    let show = split_shows.shows[0];
+    let buyer = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &original, &buyer, 0));
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &mint)).perks_bitmask, 0);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetTierPerks { organizer: show.organizer, event: show.event },
+            ticketchain::instruction::SetTierPerks { tier_index: 0, perks_bitmask: ticketchain::PERK_FAST_LANE },
+        ),
+        &[show.organizer],
+    ));
+    assert_ok(split(&mut svm, &split_shows));
+
+    assert_ok(migrate(&mut svm, &split_shows, &buyer, &buyer, 0, 0));
+    let new_mint = ticket_mint_pda(&show.event, 0);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &new_mint)).perks_bitmask, ticketchain::PERK_FAST_LANE);
+    let meta = assert_ok(check_in(&mut svm, &show, &buyer, 0));
+    assert_eq!(meta.event::<ticketchain::CheckedIn>().perks_bitmask, ticketchain::PERK_FAST_LANE);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn set_tax(svm: &mut Svm, fixture: &EventFixture, tax_bps: u16, tax_wallet: Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetTaxConfig { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetTaxConfig { tax_bps, tax_wallet },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn tax_must_fit_beside_the_donation_share() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let wallet = svm.wallet(SOL);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetDonationConfig { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetDonationConfig { donation_bps: 4_000, donation_wallet: wallet },
+        ),
+        &[fixture.organizer],
+    ));
+    assert_err(set_tax(&mut svm, &fixture, 6_001, wallet), ErrorCode::InvalidTaxConfig);
+    assert_err(set_tax(&mut svm, &fixture, 500, Pubkey::default()), ErrorCode::InvalidTaxConfig);
+    assert_ok(set_tax(&mut svm, &fixture, 6_000, wallet));
+}
+
+#[test]
+fn tax_is_split_off_each_sale_to_the_lamport() {
+    let mut svm = setup();
+    let price = SOL + 7;
+    let mut args = EventArgs::new(&svm);
+    args.tiers = vec![tier(price, 10)];
+    let fixture = create_event_with(&mut svm, args);
+    let tax_wallet = svm.wallet(SOL);
+    assert_ok(set_tax(&mut svm, &fixture, 725, tax_wallet));
+
+    // 725 bps of 1_000_000_007 is 72_500_000.5; the remainder stays with the organizer
+    let tax = 72_500_000;
+    let buyer = svm.wallet(10 * SOL);
+    let (organizer_before, tax_before) = (svm.lamports(&fixture.organizer), svm.lamports(&tax_wallet));
+    let mut accounts = buy_ticket_accounts(&fixture, &buyer, 0);
+    accounts.tax_wallet = Some(tax_wallet);
+    assert_ok(svm.send(ix(accounts, buy_ticket_args(0, 0)), &[buyer]));
+    assert_eq!(svm.lamports(&tax_wallet), tax_before + tax);
+    assert_eq!(svm.lamports(&fixture.organizer), organizer_before + price - tax);
+    assert_eq!(event(&svm, &fixture).tax_collected, tax);
+
+    // The wallet is required while tax applies, and the rate is fixed after a sale
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::InvalidTaxWallet);
+    assert_err(set_tax(&mut svm, &fixture, 0, tax_wallet), ErrorCode::EventHasSales);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{BuyTicketOptions, CheckedIn, ErrorCode, TICKET_NOTE_LOCK_SECONDS};
+
+fn note(text: &str) -> [u8; 32] {
+    let mut note = [0; 32];
+    note[..text.len()].copy_from_slice(text.as_bytes());
+    note
+}
+
+fn buy_with_note(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, version: u8, text: &str) -> Result<Pubkey, TxError> {
+    let index = event(svm, fixture).sold;
+    let options = BuyTicketOptions { note: Some(note(text)), ..Default::default() };
+    svm.send(
+        ix(buy_ticket_accounts(fixture, buyer, index), ticketchain::instruction::BuyTicketV2 { ticket_index: index, version, options }),
+        &[*buyer],
+    )?;
+    Ok(ticket_mint_pda(&fixture.event, index))
+}
+
+fn set_note_ix(holder: &Pubkey, buyer: &Pubkey, fixture: &EventFixture, mint: &Pubkey, text: &str) -> anchor_lang::solana_program::instruction::Instruction {
+    ix(
+        ticketchain::accounts::SetTicketNote {
+            holder: *holder,
+            event: fixture.event,
+            ticket_mint: *mint,
+            receipt: receipt_pda(buyer, mint),
+            holder_token_account: ata(holder, mint),
+        },
+        ticketchain::instruction::SetTicketNote { note: note(text) },
+    )
+}
+
+fn check_in_note(svm: &mut Svm, fixture: &EventFixture, holder: &Pubkey, buyer: &Pubkey, index: u32) -> (Option<[u8; 32]>, Vec<u8>) {
+    let meta = assert_ok(svm.send(
+        ix(check_in_accounts(fixture, holder, buyer, index), ticketchain::instruction::CheckIn { ticket_index: index }),
+        &[fixture.organizer, *holder],
+    ));
+    (meta.event::<CheckedIn>().note, meta.return_data.expect("check-in return data")[2..].to_vec())
+}
+
+#[test]
+fn note_is_a_fixed_32_bytes_and_needs_version_2() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(buy_with_note(&mut svm, &fixture, &buyer, 1, "L tee"), ErrorCode::UnsupportedVersion);
+    let mint = assert_ok(buy_with_note(&mut svm, &fixture, &buyer, 2, "L tee"));
+
+    // A note one byte short doesn't decode
+    let mut short = set_note_ix(&buyer, &buyer, &fixture, &mint, "M tee");
+    short.data.pop();
+    assert_err(svm.process(&[short], &[buyer]), anchor_lang::error::ErrorCode::InstructionDidNotDeserialize);
+    let (seen, returned) = check_in_note(&mut svm, &fixture, &buyer, &buyer, 0);
+    assert_eq!(seen, Some(note("L tee")));
+    assert_eq!(returned, note("L tee"));
+}
+
+#[test]
+fn transfer_clears_the_note_until_the_new_holder_writes_one() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (buyer, friend, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_with_note(&mut svm, &fixture, &buyer, 2, "vegan meal"));
+    let kept = assert_ok(buy_with_note(&mut svm, &fixture, &buyer, 2, "XL tee"));
+    assert_ok(transfer_ticket(&mut svm, &buyer, &friend, &mint));
+    assert_ok(transfer_ticket(&mut svm, &buyer, &friend, &kept));
+
+    // Only the current holder can write; the old holder no longer can
+    assert_err(svm.send(set_note_ix(&buyer, &buyer, &fixture, &mint, "steak"), &[buyer]), ErrorCode::NotTicketHolder);
+    assert!(svm.send(set_note_ix(&stranger, &buyer, &fixture, &mint, "steak"), &[stranger]).is_err());
+    let date_ts = event(&svm, &fixture).date_ts;
+    svm.warp_to(date_ts - TICKET_NOTE_LOCK_SECONDS - 1);
+    assert_ok(svm.send(set_note_ix(&friend, &buyer, &fixture, &mint, "fish"), &[friend]));
+    svm.warp_to(date_ts - TICKET_NOTE_LOCK_SECONDS);
+    assert_err(svm.send(set_note_ix(&friend, &buyer, &fixture, &mint, "steak"), &[friend]), ErrorCode::TicketNoteLocked);
+
+    svm.warp_to(date_ts);
+    assert_eq!(check_in_note(&mut svm, &fixture, &friend, &buyer, 0), (Some(note("fish")), note("fish").to_vec()));
+    assert_eq!(check_in_note(&mut svm, &fixture, &friend, &buyer, 1), (None, vec![0; 32]));
+}
+
+#[test]
+fn event_can_keep_notes_across_transfers() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetNotesSurviveTransfer { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetNotesSurviveTransfer { enabled: true },
+        ),
+        &[fixture.organizer],
+    ));
+    let (buyer, friend) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_with_note(&mut svm, &fixture, &buyer, 2, "vegan meal"));
+    assert_ok(transfer_ticket(&mut svm, &buyer, &friend, &mint));
+    assert_eq!(check_in_note(&mut svm, &fixture, &friend, &buyer, 0).0, Some(note("vegan meal")));
+}
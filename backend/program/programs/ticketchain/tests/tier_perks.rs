@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use ticketchain::{CheckedIn, ErrorCode, Receipt, PERK_BACKSTAGE, PERK_FAST_LANE};
+
+fn set_tier_perks(svm: &mut Svm, fixture: &EventFixture, tier_index: u8, perks_bitmask: u16) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::SetTierPerks { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetTierPerks { tier_index, perks_bitmask },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+#[test]
+fn tier_perks_reach_the_door_with_the_ticket() {
+    let mut svm = setup();
+    let mut args = EventArgs::new(&svm);
+    args.tiers = vec![tier(SOL / 10, 10), tier(SOL / 2, 10)];
+    let fixture = create_event_with(&mut svm, args);
+    let vip_bits = PERK_FAST_LANE | PERK_BACKSTAGE;
+    assert_err(set_tier_perks(&mut svm, &fixture, 2, vip_bits), ErrorCode::InvalidTierIndex);
+    assert_ok(set_tier_perks(&mut svm, &fixture, 1, vip_bits));
+
+    let (vip, general) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let vip_ticket = assert_ok(buy_ticket(&mut svm, &fixture, &vip, 1));
+    let general_ticket = assert_ok(buy_ticket(&mut svm, &fixture, &general, 0));
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&vip, &vip_ticket)).perks_bitmask, vip_bits);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&general, &general_ticket)).perks_bitmask, 0);
+
+    // Later tier changes don't rewrite tickets already sold
+    assert_ok(set_tier_perks(&mut svm, &fixture, 1, PERK_FAST_LANE));
+    let meta = assert_ok(check_in(&mut svm, &fixture, &vip, 0));
+    assert_eq!(meta.event::<CheckedIn>().perks_bitmask, vip_bits);
+    assert_eq!(meta.return_data.expect("perks returned")[..2], vip_bits.to_le_bytes());
+    let meta = assert_ok(check_in(&mut svm, &fixture, &general, 1));
+    assert_eq!(meta.event::<CheckedIn>().perks_bitmask, 0);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{Concession, ErrorCode, PaymentOption, Receipt, PERK_BACKSTAGE};
+
+/// A 0.1 SOL GA tier and a 1 SOL VIP tier.
+fn two_tier_event(svm: &mut Svm) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.tiers = vec![tier(SOL / 10, 10), tier(SOL, 10)];
+    create_event_with(svm, args)
+}
+
+fn buy_with(svm: &mut Svm, fixture: &EventFixture, buyer: &anchor_lang::prelude::Pubkey, tier_index: u8, category: Option<u8>, payment_index: Option<u8>) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut args = buy_ticket_args(index, tier_index);
+    args.category = category;
+    args.payment_index = payment_index;
+    svm.send(ix(buy_ticket_accounts(fixture, buyer, index), args), &[*buyer])
+}
+
+#[test]
+fn concessions_and_payment_options_apply_to_their_own_tier() {
+    let mut svm = setup();
+    let fixture = two_tier_event(&mut svm);
+    let child = Concession { name: "child".into(), tier_index: 0, price_lamports: SOL / 20, max_count: 5, sold: 0, verified_only: false };
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::SetConcessions { organizer: fixture.organizer, event: fixture.event },
+                ticketchain::instruction::SetConcessions { concessions: vec![Concession { tier_index: 2, ..child.clone() }] },
+            ),
+            &[fixture.organizer],
+        ),
+        ErrorCode::InvalidTierIndex,
+    );
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetConcessions { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetConcessions { concessions: vec![child] },
+        ),
+        &[fixture.organizer],
+    ));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAcceptedPayments { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetAcceptedPayments {
+                options: vec![PaymentOption { tier_index: 1, mint: None, price: SOL / 2 }],
+            },
+        ),
+        &[fixture.organizer],
+    ));
+    let buyer = svm.wallet(10 * SOL);
+
+    // The GA child price can't buy a VIP seat, nor the VIP option a GA seat
+    assert_err(buy_with(&mut svm, &fixture, &buyer, 1, Some(0), None), ErrorCode::InvalidConcession);
+    assert_err(buy_with(&mut svm, &fixture, &buyer, 0, None, Some(0)), ErrorCode::InvalidPaymentOption);
+    let before = svm.lamports(&fixture.organizer);
+    assert_ok(buy_with(&mut svm, &fixture, &buyer, 0, Some(0), None));
+    assert_ok(buy_with(&mut svm, &fixture, &buyer, 1, None, Some(0)));
+    assert_eq!(svm.lamports(&fixture.organizer), before + SOL / 20 + SOL / 2);
+}
+
+#[test]
+fn resale_caps_use_the_ticket_tier_price() {
+    let mut svm = setup();
+    let fixture = two_tier_event(&mut svm);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetMaxListingMultiple { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetMaxListingMultiple { max_listing_multiple: 150 },
+        ),
+        &[fixture.organizer],
+    ));
+    let seller = svm.wallet(10 * SOL);
+    let vip = assert_ok(buy_ticket(&mut svm, &fixture, &seller, 1));
+    let list = |svm: &mut Svm, price_lamports: u64, with_receipt: bool| {
+        let mut accounts = list_accounts(&fixture, &seller, &vip);
+        accounts.purchase_receipt = with_receipt.then(|| receipt_pda(&seller, &vip));
+        svm.send(ix(accounts, list_args(&fixture, &vip, price_lamports)), &[seller])
+    };
+
+    assert_err(list(&mut svm, SOL, false), ErrorCode::TierReceiptRequired);
+    assert_err(list(&mut svm, 3 * SOL / 2 + 1, true), ErrorCode::ListingMultipleTooHigh);
+    // Well above the GA cap, within the VIP one
+    assert_ok(list(&mut svm, 3 * SOL / 2, true));
+    assert_eq!(svm.get::<ticketchain::Listing>(&listing_pda(&vip)).tier_index, 1);
+}
+
+#[test]
+fn holds_reserve_ga_seats_and_perks_follow_the_tier() {
+    let mut svm = setup();
+    let fixture = two_tier_event(&mut svm);
+    let hold = |svm: &mut Svm, beneficiary: anchor_lang::prelude::Pubkey, count: u32| {
+        let expires_at = svm.now() + 24 * 60 * 60;
+        svm.send(
+            ix(
+                ticketchain::accounts::CreateHold {
+                    organizer: fixture.organizer,
+                    event: fixture.event,
+                    hold: pda(&[b"hold", fixture.event.as_ref(), beneficiary.as_ref()]),
+                    system_program: system_program::ID,
+                },
+                ticketchain::instruction::CreateHold { beneficiary, count, price_lamports: 0, expires_at },
+            ),
+            &[fixture.organizer],
+        )
+    };
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetTierPerks { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetTierPerks { tier_index: 1, perks_bitmask: PERK_BACKSTAGE },
+        ),
+        &[fixture.organizer],
+    ));
+
+    // Holding every GA seat leaves GA sold out while VIP still sells
+    let sponsor = svm.wallet(SOL);
+    assert_ok(hold(&mut svm, sponsor, 10 - 1));
+    let buyer = svm.wallet(10 * SOL);
+    let ga = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 0));
+    let late = svm.wallet(SOL);
+    assert_err(hold(&mut svm, late, 1), ErrorCode::InsufficientSupply);
+    assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::TierSoldOut);
+    let vip = assert_ok(buy_ticket(&mut svm, &fixture, &buyer, 1));
+
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &vip)).perks_bitmask, PERK_BACKSTAGE);
+    assert_eq!(svm.get::<Receipt>(&receipt_pda(&buyer, &ga)).perks_bitmask, 0);
+}
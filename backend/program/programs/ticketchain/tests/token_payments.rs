@@ -0,0 +1,65 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, PaymentOption};
+
+/// A token-only event priced at `price` base units of `mint`.
+fn token_event(svm: &mut Svm, mint: &Pubkey, price: u64) -> EventFixture {
+    let fixture = create_event(svm);
+    let organizer = fixture.organizer;
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAcceptedPayments { organizer, event: fixture.event },
+            ticketchain::instruction::SetAcceptedPayments {
+                options: vec![PaymentOption { tier_index: 0, mint: Some(*mint), price }],
+            },
+        ),
+        &[organizer],
+    ));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetTokenOnly { organizer, event: fixture.event },
+            ticketchain::instruction::SetTokenOnly { enabled: true },
+        ),
+        &[organizer],
+    ));
+    fixture
+}
+
+fn buy_for_tokens(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, mint: &Pubkey, destination: Pubkey) -> Result<TxMeta, TxError> {
+    let index = event(svm, fixture).sold;
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.payment_mint = Some(*mint);
+    accounts.buyer_payment_account = Some(ata(buyer, mint));
+    accounts.payment_destination = Some(destination);
+    let mut args = buy_ticket_args(index, 0);
+    args.payment_index = Some(0);
+    svm.send(ix(accounts, args), &[*buyer])
+}
+
+#[test]
+fn six_and_nine_decimal_tokens_pay_the_organizer_in_base_units() {
+    for decimals in [6, 9] {
+        let mut svm = setup();
+        let mint = create_mint(&mut svm, &PLATFORM_AUTHORITY, decimals);
+        let price = 25 * 10u64.pow(decimals as u32);
+        let fixture = token_event(&mut svm, &mint, price);
+        create_token_account(&mut svm, &fixture.organizer, &mint, 0);
+        let (buyer, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL));
+        create_token_account(&mut svm, &buyer, &mint, 2 * price);
+        create_token_account(&mut svm, &stranger, &mint, 0);
+
+        assert_err(buy_ticket(&mut svm, &fixture, &buyer, 0), ErrorCode::SolPaymentDisabled);
+        assert_err(
+            buy_for_tokens(&mut svm, &fixture, &buyer, &mint, ata(&stranger, &mint)),
+            ErrorCode::InvalidPaymentAccount,
+        );
+        let organizer_lamports = svm.lamports(&fixture.organizer);
+        assert_ok(buy_for_tokens(&mut svm, &fixture, &buyer, &mint, ata(&fixture.organizer, &mint)));
+        assert_eq!(token_balance(&svm, &ata(&fixture.organizer, &mint)), price, "{decimals} decimals");
+        assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), price);
+        assert_eq!(svm.lamports(&fixture.organizer), organizer_lamports);
+        assert_eq!(token_balance(&svm, &ata(&buyer, &ticket_mint_pda(&fixture.event, 0))), 1);
+    }
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{rent::Rent, system_program};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use common::*;
+use ticketchain::{ErrorCode, PaymentOption};
+
+const PRICE: u64 = 25_000_000;
+
+fn ata_2022(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::ID)
+}
+
+/// Rewrites `mint` as a Token-2022 mint whose TransferFee extension withholds `bps` of
+/// every transfer, uncapped.
+fn set_transfer_fee(svm: &mut Svm, mint: &Pubkey, bps: u16) {
+    let base = StateWithExtensions::<Mint>::unpack(&svm.account(mint).unwrap().data).unwrap().base;
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    let fee = TransferFee { epoch: 0.into(), maximum_fee: u64::MAX.into(), transfer_fee_basis_points: bps.into() };
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = base;
+    state.pack_base();
+    state.init_account_type().unwrap();
+    let lamports = Rent::default().minimum_balance(len);
+    svm.set_account(*mint, Account { lamports, data, owner: spl_token_2022::ID, executable: false });
+}
+
+/// A Token-2022 account for a transfer-fee mint, at the owner's associated address.
+fn fee_token_account(svm: &mut Svm, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+    let key = ata_2022(owner, mint);
+    let len = ExtensionType::try_calculate_account_len::<TokenAccount>(&[ExtensionType::TransferFeeAmount]).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferFeeAmount>(true).unwrap();
+    state.base = TokenAccount { mint: *mint, owner: *owner, amount, state: AccountState::Initialized, ..Default::default() };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    let lamports = Rent::default().minimum_balance(len);
+    svm.set_account(key, Account { lamports, data, owner: spl_token_2022::ID, executable: false });
+    key
+}
+
+/// buy_ticket with Token-2022 as the token program, paying with `payment` if given.
+fn buy_2022(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, payment: Option<&Pubkey>) -> Result<Pubkey, TxError> {
+    let index = event(svm, fixture).sold;
+    let mint = ticket_mint_pda(&fixture.event, index);
+    let vault = vault_pda(&fixture.event);
+    let mut accounts = buy_ticket_accounts(fixture, buyer, index);
+    accounts.buyer_token_account = ata_2022(buyer, &mint);
+    accounts.token_program = spl_token_2022::ID;
+    let mut args = buy_ticket_args(index, 0);
+    if let Some(payment) = payment {
+        accounts.vault = Some(vault);
+        accounts.payment_mint = Some(*payment);
+        accounts.buyer_payment_account = Some(ata_2022(buyer, payment));
+        accounts.payment_destination = Some(ata_2022(&vault, payment));
+        args.payment_index = Some(0);
+    }
+    svm.send(ix(accounts, args), &[*buyer])?;
+    Ok(mint)
+}
+
+#[test]
+fn fee_payment_token_is_rejected_at_purchase_and_refund() {
+    let mut svm = setup();
+    let token = create_mint(&mut svm, &PLATFORM_AUTHORITY, 6);
+    set_transfer_fee(&mut svm, &token, 100);
+    let fixture = create_event(&mut svm);
+    let vault = vault_pda(&fixture.event);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetGracePeriod {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetGracePeriod { grace_period_seconds: 3_600 },
+        ),
+        &[fixture.organizer],
+    ));
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::SetAcceptedPayments { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::SetAcceptedPayments {
+                options: vec![PaymentOption { tier_index: 0, mint: Some(token), price: PRICE }],
+            },
+        ),
+        &[fixture.organizer],
+    ));
+    let buyer = svm.wallet(10 * SOL);
+    fee_token_account(&mut svm, &buyer, &token, PRICE);
+    fee_token_account(&mut svm, &vault, &token, 0);
+
+    // The vault would receive less than the price
+    assert_err(buy_2022(&mut svm, &fixture, &buyer, Some(&token)), ErrorCode::UnexpectedTransferFee);
+    set_transfer_fee(&mut svm, &token, 0);
+    let mint = assert_ok(buy_2022(&mut svm, &fixture, &buyer, Some(&token)));
+    assert_eq!(token_balance(&svm, &ata_2022(&vault, &token)), PRICE);
+
+    // A fee switched on afterwards can't shortchange the refund either
+    set_transfer_fee(&mut svm, &token, 100);
+    let refund = ix(
+        ticketchain::accounts::RequestGraceRefund {
+            buyer,
+            event: fixture.event,
+            vault,
+            receipt: receipt_pda(&buyer, &mint),
+            ticket_mint: mint,
+            buyer_token_account: ata_2022(&buyer, &mint),
+            payment_mint: Some(token),
+            vault_payment_account: Some(ata_2022(&vault, &token)),
+            buyer_payment_account: Some(ata_2022(&buyer, &token)),
+            deposit_escrow: None,
+            identity_purchase: None,
+            token_program: spl_token_2022::ID,
+        },
+        ticketchain::instruction::RequestGraceRefund {},
+    );
+    assert_err(svm.send(refund.clone(), &[buyer]), ErrorCode::UnexpectedTransferFee);
+    assert_eq!(token_balance(&svm, &ata_2022(&vault, &token)), PRICE);
+    set_transfer_fee(&mut svm, &token, 0);
+    assert_ok(svm.send(refund, &[buyer]));
+    assert_eq!(token_balance(&svm, &ata_2022(&buyer, &token)), PRICE);
+}
+
+#[test]
+fn fee_ticket_cannot_enter_or_leave_the_resale_escrow() {
+    let mut svm = setup();
+    svm.airdrop(&ticketchain::PLATFORM_WALLET, SOL);
+    let fixture = create_event(&mut svm);
+    let (seller, buyer) = (svm.wallet(10 * SOL), svm.wallet(10 * SOL));
+    let unlisted = assert_ok(buy_2022(&mut svm, &fixture, &seller, None));
+    let listed = assert_ok(buy_2022(&mut svm, &fixture, &seller, None));
+    let list = |mint: &Pubkey| {
+        let mut accounts = list_accounts(&fixture, &seller, mint);
+        accounts.seller_token_account = ata_2022(&seller, mint);
+        accounts.token_program = spl_token_2022::ID;
+        ix(accounts, list_args(&fixture, mint, SOL / 5))
+    };
+
+    // Any fee on a one-token transfer takes the whole ticket
+    set_transfer_fee(&mut svm, &unlisted, 1);
+    assert_err(svm.send(list(&unlisted), &[seller]), ErrorCode::UnexpectedTransferFee);
+    assert_eq!(token_balance(&svm, &ata_2022(&seller, &unlisted)), 1);
+
+    // A fee enabled while the ticket sits in escrow blocks both the sale and the cancel
+    assert_ok(svm.send(list(&listed), &[seller]));
+    set_transfer_fee(&mut svm, &listed, 1);
+    let mut accounts = buy_resale_accounts(&fixture, &buyer, &seller, &listed);
+    accounts.buyer_token_account = ata_2022(&buyer, &listed);
+    accounts.token_program = spl_token_2022::ID;
+    let resale = ix(accounts, ticketchain::instruction::BuyResale { max_lamports: u64::MAX });
+    assert_err(svm.send(resale.clone(), &[buyer]), ErrorCode::UnexpectedTransferFee);
+    let cancel = ix(
+        ticketchain::accounts::CancelListing {
+            seller,
+            ticket_mint: listed,
+            listing: listing_pda(&listed),
+            seller_token_account: ata_2022(&seller, &listed),
+            escrow_token_account: pda(&[b"escrow", listed.as_ref()]),
+            ask_book: None,
+            ticket_metadata: None,
+            ticket_edition: None,
+            seller_token_record: None,
+            instructions: None,
+            token_metadata_program: None,
+            token_program: spl_token_2022::ID,
+            associated_token_program: ATA_PROGRAM,
+            system_program: system_program::ID,
+        },
+        ticketchain::instruction::CancelListing {},
+    );
+    assert_err(svm.send(cancel, &[seller]), ErrorCode::UnexpectedTransferFee);
+
+    // A zero fee configuration moves the ticket as usual
+    set_transfer_fee(&mut svm, &listed, 0);
+    assert_ok(svm.send(resale, &[buyer]));
+    assert_eq!(token_balance(&svm, &ata_2022(&buyer, &listed)), 1);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{ErrorCode, GiftNote, MAX_GIFT_MESSAGE_LEN};
+
+fn offer(svm: &mut Svm, sender: &Pubkey, mint: &Pubkey, recipient: &Pubkey) -> Result<TxMeta, TxError> {
+    offer_with_message(svm, sender, mint, recipient, None)
+}
+
+fn gift_note_pda(mint: &Pubkey, recipient: &Pubkey) -> Pubkey {
+    pda(&[b"gift_note", mint.as_ref(), recipient.as_ref()])
+}
+
+fn offer_with_message(svm: &mut Svm, sender: &Pubkey, mint: &Pubkey, recipient: &Pubkey, message: Option<String>) -> Result<TxMeta, TxError> {
+    let expires_at = svm.now() + 3600;
+    let gift_note = message.as_ref().map(|_| gift_note_pda(mint, recipient));
+    svm.send(
+        ix(
+            ticketchain::accounts::OfferTransfer {
+                sender: *sender,
+                ticket_mint: *mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                transfer_offer: pda(&[b"transfer_offer", mint.as_ref()]),
+                sender_token_account: ata(sender, mint),
+                escrow_token_account: pda(&[b"transfer_escrow", mint.as_ref()]),
+                gift_note,
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::OfferTransfer { recipient: *recipient, expires_at, message },
+        ),
+        &[*sender],
+    )
+}
+
+fn accept(svm: &mut Svm, sender: &Pubkey, mint: &Pubkey, recipient: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::AcceptTransfer {
+                recipient: *recipient,
+                sender: *sender,
+                ticket_mint: *mint,
+                ticket_flag: pda(&[b"ticket_flag", mint.as_ref()]),
+                transfer_offer: pda(&[b"transfer_offer", mint.as_ref()]),
+                escrow_token_account: pda(&[b"transfer_escrow", mint.as_ref()]),
+                recipient_token_account: ata(recipient, mint),
+                token_program: TOKEN_PROGRAM,
+                associated_token_program: ATA_PROGRAM,
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::AcceptTransfer {},
+        ),
+        &[*recipient],
+    )
+}
+
+fn cancel(svm: &mut Svm, caller: &Pubkey, sender: &Pubkey, mint: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CancelTransfer {
+                caller: *caller,
+                sender: *sender,
+                ticket_mint: *mint,
+                transfer_offer: pda(&[b"transfer_offer", mint.as_ref()]),
+                sender_token_account: ata(sender, mint),
+                escrow_token_account: pda(&[b"transfer_escrow", mint.as_ref()]),
+                token_program: TOKEN_PROGRAM,
+            },
+            ticketchain::instruction::CancelTransfer {},
+        ),
+        &[*caller],
+    )
+}
+
+#[test]
+fn flagged_ticket_cannot_be_offered() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+    assert_ok(flag_ticket(&mut svm, &fixture, 0));
+
+    assert_err(offer(&mut svm, &sender, &mint, &recipient), ErrorCode::TicketFlagged);
+}
+
+#[test]
+fn ticket_flagged_while_offered_cannot_be_accepted() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+    assert_ok(offer(&mut svm, &sender, &mint, &recipient));
+    assert_ok(flag_ticket(&mut svm, &fixture, 0));
+
+    assert_err(accept(&mut svm, &sender, &mint, &recipient), ErrorCode::TicketFlagged);
+    assert!(!svm.exists(&ata(&recipient, &mint)));
+}
+
+#[test]
+fn unflagged_offer_is_accepted() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+    assert_ok(offer(&mut svm, &sender, &mint, &recipient));
+
+    assert_ok(accept(&mut svm, &sender, &mint, &recipient));
+    assert_eq!(token_balance(&svm, &ata(&recipient, &mint)), 1);
+}
+
+#[test]
+fn only_the_named_recipient_accepts_before_expiry() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let interloper = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+    assert_ok(offer(&mut svm, &sender, &mint, &recipient));
+    assert_eq!(token_balance(&svm, &ata(&sender, &mint)), 0);
+
+    assert_err(accept(&mut svm, &sender, &mint, &interloper), ErrorCode::InvalidRecipient);
+    svm.warp(3600);
+    assert_err(accept(&mut svm, &sender, &mint, &recipient), ErrorCode::TransferOfferExpired);
+}
+
+#[test]
+fn sender_cancels_any_time_and_anyone_sweeps_after_expiry() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let cranker = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+
+    assert_ok(offer(&mut svm, &sender, &mint, &recipient));
+    assert_ok(cancel(&mut svm, &sender, &sender, &mint));
+    assert_eq!(token_balance(&svm, &ata(&sender, &mint)), 1);
+    assert!(!svm.exists(&pda(&[b"transfer_offer", mint.as_ref()])));
+    assert!(!svm.exists(&pda(&[b"transfer_escrow", mint.as_ref()])));
+
+    assert_ok(offer(&mut svm, &sender, &mint, &recipient));
+    assert_err(cancel(&mut svm, &cranker, &sender, &mint), ErrorCode::TransferOfferActive);
+    svm.warp(3600);
+    let before = svm.lamports(&sender);
+    let rent = svm.lamports(&pda(&[b"transfer_offer", mint.as_ref()])) + svm.lamports(&pda(&[b"transfer_escrow", mint.as_ref()]));
+    assert_ok(cancel(&mut svm, &cranker, &sender, &mint));
+    assert_eq!(token_balance(&svm, &ata(&sender, &mint)), 1);
+    assert_eq!(svm.lamports(&sender), before + rent);
+}
+
+#[test]
+fn gift_message_round_trips_at_the_maximum_length() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let sender = svm.wallet(10 * SOL);
+    let recipient = svm.wallet(SOL);
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+
+    // Two-byte characters fill the limit in bytes, not characters
+    let message = "é".repeat(MAX_GIFT_MESSAGE_LEN / 2);
+    let too_long = format!("{message}!");
+    assert_err(offer_with_message(&mut svm, &sender, &mint, &recipient, Some(too_long)), ErrorCode::GiftMessageTooLong);
+    assert_ok(offer_with_message(&mut svm, &sender, &mint, &recipient, Some(message.clone())));
+    assert_ok(accept(&mut svm, &sender, &mint, &recipient));
+
+    let note = svm.get::<GiftNote>(&gift_note_pda(&mint, &recipient));
+    assert_eq!((note.sender, note.recipient, note.ticket_mint), (sender, recipient, mint));
+    assert_eq!(note.message, message);
+}
+
+fn close_note(svm: &mut Svm, caller: &Pubkey, sender: &Pubkey, note: &Pubkey) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::CloseGiftNote { caller: *caller, sender: *sender, gift_note: *note },
+            ticketchain::instruction::CloseGiftNote {},
+        ),
+        &[*caller],
+    )
+}
+
+#[test]
+fn recipient_closes_the_gift_note_and_the_sender_gets_the_rent() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let (sender, recipient, stranger) = (svm.wallet(10 * SOL), svm.wallet(SOL), svm.wallet(SOL));
+    let mint = assert_ok(buy_ticket(&mut svm, &fixture, &sender, 0));
+    assert_ok(offer_with_message(&mut svm, &sender, &mint, &recipient, Some("Enjoy the show".to_string())));
+    assert_ok(accept(&mut svm, &sender, &mint, &recipient));
+    let note = gift_note_pda(&mint, &recipient);
+
+    assert_err(close_note(&mut svm, &stranger, &sender, &note), ErrorCode::Unauthorized);
+    let (rent, before) = (svm.lamports(&note), svm.lamports(&sender));
+    let recipient_before = svm.lamports(&recipient);
+    assert_ok(close_note(&mut svm, &recipient, &sender, &note));
+    assert!(!svm.exists(&note));
+    assert_eq!(svm.lamports(&sender), before + rent);
+    assert_eq!(svm.lamports(&recipient), recipient_before);
+}
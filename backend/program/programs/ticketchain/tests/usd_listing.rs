@@ -0,0 +1,80 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use ticketchain::{ErrorCode, PLATFORM_WALLET, PYTH_RECEIVER_PROGRAM_ID, SOL_USD_FEED_ID};
+
+/// Writes a fully verified SOL/USD PriceUpdateV2 at `price` (exponent -8) published now.
+fn install_feed(svm: &mut Svm, key: Pubkey, price: i64, conf: u64) {
+    let mut data = vec![34, 241, 35, 99, 157, 126, 244, 205];
+    data.extend_from_slice(&[0; 32]);
+    data.push(1);
+    data.extend_from_slice(&SOL_USD_FEED_ID);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&svm.now().to_le_bytes());
+    data.resize(134, 0);
+    let lamports = anchor_lang::solana_program::rent::Rent::default().minimum_balance(data.len());
+    svm.set_account(key, Account { lamports, data, owner: PYTH_RECEIVER_PROGRAM_ID, executable: false });
+}
+
+/// A ticket listed at $15, with the feed at $150/SOL: 0.1 SOL.
+fn usd_listing(svm: &mut Svm) -> (EventFixture, Pubkey, Pubkey, Pubkey) {
+    let fixture = create_event(svm);
+    svm.airdrop(&PLATFORM_WALLET, SOL);
+    let feed = Pubkey::new_unique();
+    install_feed(svm, feed, 150_00000000, 0);
+    let seller = svm.wallet(10 * SOL);
+    let mint = assert_ok(buy_ticket(svm, &fixture, &seller, 0));
+    let mut accounts = list_accounts(&fixture, &seller, &mint);
+    accounts.price_feed = Some(feed);
+    let args =
+        ticketchain::instruction::ListForResaleUsd { _ticket_index: ticket_index(&fixture.event, &mint), usd_price_cents: 1500 };
+    assert_ok(svm.send(ix(accounts, args), &[seller]));
+    (fixture, seller, mint, feed)
+}
+
+fn buy(svm: &mut Svm, fixture: &EventFixture, buyer: &Pubkey, seller: &Pubkey, mint: &Pubkey, feed: Pubkey, max_lamports: u64) -> Result<TxMeta, TxError> {
+    let mut accounts = buy_resale_accounts(fixture, buyer, seller, mint);
+    accounts.price_feed = Some(feed);
+    svm.send(ix(accounts, ticketchain::instruction::BuyResale { max_lamports }), &[*buyer])
+}
+
+#[test]
+fn usd_listing_settles_at_the_feed_price() {
+    let mut svm = setup();
+    let (fixture, seller, mint, feed) = usd_listing(&mut svm);
+    let listing: ticketchain::Listing = svm.get(&listing_pda(&mint));
+    assert_eq!((listing.usd_price_cents, listing.price_lamports, listing.price_feed), (1500, SOL / 10, feed));
+
+    // SOL rises to $300: the same $15 now costs 0.05 SOL
+    install_feed(&mut svm, feed, 300_00000000, 0);
+    let buyer = svm.wallet(10 * SOL);
+    assert_err(buy(&mut svm, &fixture, &buyer, &seller, &mint, feed, SOL / 20 - 1), ErrorCode::PriceAboveMax);
+    let before = svm.lamports(&seller);
+    assert_ok(buy(&mut svm, &fixture, &buyer, &seller, &mint, feed, SOL / 20));
+    assert_eq!(token_balance(&svm, &ata(&buyer, &mint)), 1);
+    assert!(svm.lamports(&seller) > before);
+}
+
+#[test]
+fn stale_or_uncertain_feed_is_rejected() {
+    let mut svm = setup();
+    let (fixture, seller, mint, feed) = usd_listing(&mut svm);
+    let buyer = svm.wallet(10 * SOL);
+
+    svm.warp(ticketchain::MAX_PRICE_FEED_AGE_SECONDS + 1);
+    assert_err(buy(&mut svm, &fixture, &buyer, &seller, &mint, feed, SOL), ErrorCode::StalePriceFeed);
+
+    // Confidence wider than 1% of the price
+    install_feed(&mut svm, feed, 150_00000000, 2_00000000);
+    assert_err(buy(&mut svm, &fixture, &buyer, &seller, &mint, feed, SOL), ErrorCode::PriceConfidenceTooWide);
+
+    // Only the listing's feed is accepted
+    let other = Pubkey::new_unique();
+    install_feed(&mut svm, other, 150_00000000, 0);
+    assert_err(buy(&mut svm, &fixture, &buyer, &seller, &mint, other, SOL), ErrorCode::InvalidPriceFeed);
+    install_feed(&mut svm, feed, 150_00000000, 0);
+    assert_ok(buy(&mut svm, &fixture, &buyer, &seller, &mint, feed, SOL / 10));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::ErrorCode;
+
+fn withdraw(svm: &mut Svm, fixture: &EventFixture) -> u64 {
+    let before = svm.lamports(&fixture.organizer);
+    assert_ok(svm.send(
+        ix(
+            ticketchain::accounts::WithdrawProceeds {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+            },
+            ticketchain::instruction::WithdrawProceeds {},
+        ),
+        &[fixture.organizer],
+    ));
+    svm.lamports(&fixture.organizer) - before
+}
+
+#[test]
+fn proceeds_vest_linearly_from_the_cliff() {
+    let mut svm = setup();
+    let fixture = create_event(&mut svm);
+    let cliff_ts = svm.now() + 1_000;
+    let set_vesting = |duration_seconds| {
+        ix(
+            ticketchain::accounts::SetVestingSchedule {
+                organizer: fixture.organizer,
+                event: fixture.event,
+                vault: vault_pda(&fixture.event),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::SetVestingSchedule { cliff_ts, duration_seconds },
+        )
+    };
+    assert_err(svm.send(set_vesting(-1), &[fixture.organizer]), ErrorCode::InvalidVestingSchedule);
+    assert_ok(svm.send(set_vesting(1_000), &[fixture.organizer]));
+    for index in 0..4 {
+        let buyer = svm.wallet(10 * SOL);
+        let mut accounts = buy_ticket_accounts(&fixture, &buyer, index);
+        accounts.vault = Some(vault_pda(&fixture.event));
+        assert_ok(svm.send(ix(accounts, buy_ticket_args(index, 0)), &[buyer]));
+    }
+
+    // 0.4 SOL is vaulted: nothing before or at the cliff, a quarter at 250s, all at 1000s
+    svm.warp_to(cliff_ts - 1);
+    assert_eq!(withdraw(&mut svm, &fixture), 0);
+    svm.warp_to(cliff_ts);
+    assert_eq!(withdraw(&mut svm, &fixture), 0);
+    svm.warp_to(cliff_ts + 250);
+    assert_eq!(withdraw(&mut svm, &fixture), SOL / 10);
+    svm.warp_to(cliff_ts + 500);
+    assert_eq!(withdraw(&mut svm, &fixture), SOL / 10);
+    svm.warp_to(cliff_ts + 1_000);
+    assert_eq!(withdraw(&mut svm, &fixture), 2 * SOL / 10);
+    svm.warp_to(cliff_ts + 5_000);
+    assert_eq!(withdraw(&mut svm, &fixture), 0);
+    assert_eq!(event(&svm, &fixture).proceeds_withdrawn, 4 * SOL / 10);
+    assert_err(svm.send(set_vesting(0), &[fixture.organizer]), ErrorCode::EventHasSales);
+}
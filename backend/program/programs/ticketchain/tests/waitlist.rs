@@ -0,0 +1,129 @@
+mod common;
+
+use anchor_lang::solana_program::system_program;
+use common::*;
+use ticketchain::{AutoSupplyIncrease, ErrorCode, MAX_AUTO_INCREMENTS};
+
+fn configure(svm: &mut Svm, fixture: &EventFixture, enabled: bool, increment: u32, capacity: u32) -> Result<TxMeta, TxError> {
+    svm.send(
+        ix(
+            ticketchain::accounts::ConfigureAutoSupply { organizer: fixture.organizer, event: fixture.event },
+            ticketchain::instruction::ConfigureAutoSupply {
+                auto_increase_supply: enabled,
+                demand_increment: increment,
+                capacity,
+            },
+        ),
+        &[fixture.organizer],
+    )
+}
+
+fn join(svm: &mut Svm, fixture: &EventFixture) -> Result<TxMeta, TxError> {
+    let wallet = svm.wallet(SOL);
+    svm.send(
+        ix(
+            ticketchain::accounts::JoinWaitlist {
+                wallet,
+                event: fixture.event,
+                waitlist_entry: pda(&[b"waitlist", fixture.event.as_ref(), wallet.as_ref()]),
+                system_program: system_program::ID,
+            },
+            ticketchain::instruction::JoinWaitlist {},
+        ),
+        &[wallet],
+    )
+}
+
+fn sell_out(svm: &mut Svm, fixture: &EventFixture) {
+    while event(svm, fixture).sold < event(svm, fixture).supply {
+        let buyer = svm.wallet(SOL);
+        assert_ok(buy_ticket(svm, fixture, &buyer, 0));
+    }
+}
+
+fn event_with_supply(svm: &mut Svm, supply: u32) -> EventFixture {
+    let mut args = EventArgs::new(svm);
+    args.tiers = vec![tier(SOL / 10, supply)];
+    create_event_with(svm, args)
+}
+
+#[test]
+fn auto_supply_config_is_organizer_only_and_validated() {
+    let mut svm = setup();
+    let fixture = event_with_supply(&mut svm, 2);
+    let stranger = svm.wallet(SOL);
+    assert_err(
+        svm.send(
+            ix(
+                ticketchain::accounts::ConfigureAutoSupply { organizer: stranger, event: fixture.event },
+                ticketchain::instruction::ConfigureAutoSupply {
+                    auto_increase_supply: true,
+                    demand_increment: 1,
+                    capacity: 4,
+                },
+            ),
+            &[stranger],
+        ),
+        ErrorCode::InvalidOrganizer,
+    );
+    assert_err(configure(&mut svm, &fixture, true, 1, 1), ErrorCode::InvalidCapacity);
+    assert_err(configure(&mut svm, &fixture, true, 0, 4), ErrorCode::InvalidDemandIncrement);
+    assert_ok(configure(&mut svm, &fixture, true, 1, 4));
+}
+
+#[test]
+fn waitlist_demand_grows_supply_up_to_capacity() {
+    let mut svm = setup();
+    let fixture = event_with_supply(&mut svm, 2);
+    assert_ok(configure(&mut svm, &fixture, true, 3, 7));
+    assert_err(join(&mut svm, &fixture), ErrorCode::NotSoldOut);
+    sell_out(&mut svm, &fixture);
+
+    // Supply grows on the join after the waitlist reaches the threshold
+    for _ in 0..2 {
+        assert!(assert_ok(join(&mut svm, &fixture)).events::<AutoSupplyIncrease>().is_empty());
+    }
+    let increase = assert_ok(join(&mut svm, &fixture)).event::<AutoSupplyIncrease>();
+    assert_eq!((increase.event, increase.new_supply, increase.trigger_waitlist_size), (fixture.event, 5, 2));
+    let state = event(&svm, &fixture);
+    assert_eq!((state.supply, state.tiers[0].supply, state.waitlist_head), (5, 5, 3));
+    assert_err(join(&mut svm, &fixture), ErrorCode::NotSoldOut);
+
+    // The next increase is clamped to the capacity
+    sell_out(&mut svm, &fixture);
+    for _ in 0..2 {
+        assert_ok(join(&mut svm, &fixture));
+    }
+    assert_eq!(assert_ok(join(&mut svm, &fixture)).event::<AutoSupplyIncrease>().new_supply, 7);
+    let state = event(&svm, &fixture);
+    assert_eq!((state.supply, state.tiers[0].supply, state.auto_increments), (7, 7, 2));
+
+    // At capacity, demand no longer grows supply
+    sell_out(&mut svm, &fixture);
+    for _ in 0..8 {
+        assert_ok(join(&mut svm, &fixture));
+    }
+    assert_eq!(event(&svm, &fixture).supply, 7);
+}
+
+#[test]
+fn auto_supply_stops_after_the_maximum_increments() {
+    let mut svm = setup();
+    let fixture = event_with_supply(&mut svm, 1);
+    assert_ok(configure(&mut svm, &fixture, true, 1, 100));
+
+    for round in 1..=MAX_AUTO_INCREMENTS as u32 {
+        sell_out(&mut svm, &fixture);
+        while event(&svm, &fixture).supply == round {
+            assert_ok(join(&mut svm, &fixture));
+        }
+    }
+    let state = event(&svm, &fixture);
+    assert_eq!((state.supply, state.auto_increments), (1 + MAX_AUTO_INCREMENTS as u32, MAX_AUTO_INCREMENTS));
+
+    sell_out(&mut svm, &fixture);
+    for _ in 0..2 * state.supply {
+        assert!(assert_ok(join(&mut svm, &fixture)).events::<AutoSupplyIncrease>().is_empty());
+    }
+    assert_eq!(event(&svm, &fixture).supply, state.supply);
+}